@@ -14,9 +14,13 @@
 
 #![warn(missing_docs)]
 
+pub use self::transition::*;
+
 use np_graphics::PixelMap;
 use np_graphics::PixelMapMut;
 
+mod transition;
+
 /// How to draw the image of a layer.
 ///
 /// The output of this function is what will appear in the composited image,
@@ -0,0 +1,105 @@
+use np_graphics::Interpolate;
+
+/// Animated change of a value from one value to another.
+///
+/// Animated draw functions receive the current time,
+/// and can use transitions to find the values to draw with.
+/// Any value that implements [`Interpolate`] can be animated,
+/// including colors, vectors, and numbers.
+#[derive(Clone, Copy, Debug)]
+pub struct Transition<T>
+{
+    /// The value at the start of the transition.
+    pub from: T,
+
+    /// The value at the end of the transition.
+    pub to: T,
+
+    /// The time at which the transition starts.
+    pub start: f64,
+
+    /// How long the transition takes.
+    pub duration: f64,
+
+    /// How the progress of the transition changes over time.
+    ///
+    /// This maps the fraction of the duration that has passed
+    /// to the fraction of the way from `from` to `to`.
+    /// See [`linear`] and [`ease_in_out`].
+    pub easing: fn(f64) -> f64,
+}
+
+impl<T> Transition<T>
+    where T: Copy + Interpolate
+{
+    /// Create a transition with linear easing.
+    pub fn new(from: T, to: T, start: f64, duration: f64) -> Self
+    {
+        Self{from, to, start, duration, easing: linear}
+    }
+
+    /// The value at the given time.
+    ///
+    /// Before the transition starts, this is `from`.
+    /// After the transition ends, this is `to`.
+    pub fn value(&self, time: f64) -> T
+    {
+        if time < self.start {
+            return self.from;
+        }
+        if self.is_finished(time) {
+            return self.to;
+        }
+        let progress = (time - self.start) / self.duration;
+        self.from.interpolate(self.to, (self.easing)(progress))
+    }
+
+    /// Whether the transition has ended at the given time.
+    ///
+    /// Animated draw functions can return the negation of this.
+    pub fn is_finished(&self, time: f64) -> bool
+    {
+        time >= self.start + self.duration
+    }
+}
+
+/// Easing that progresses at a constant rate.
+pub fn linear(t: f64) -> f64
+{
+    t
+}
+
+/// Easing that starts slow, speeds up, and ends slow.
+///
+/// This is the cubic smoothstep function.
+pub fn ease_in_out(t: f64) -> f64
+{
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use np_graphics::Color;
+
+    #[test]
+    fn test_value()
+    {
+        let transition = Transition::new(10.0, 20.0, 1.0, 2.0);
+        assert_eq!(transition.value(0.0), 10.0);
+        assert_eq!(transition.value(2.0), 15.0);
+        assert_eq!(transition.value(5.0), 20.0);
+        assert!(!transition.is_finished(2.9));
+        assert!(transition.is_finished(3.0));
+
+        let transition = Transition{easing: ease_in_out, ..transition};
+        assert!(transition.value(1.5) < 12.5);
+        assert_eq!(transition.value(2.0), 15.0);
+
+        let instant = Transition::new(Color::BLACK, Color::WHITE, 1.0, 0.0);
+        assert_eq!(instant.value(0.5), Color::BLACK);
+        assert_eq!(instant.value(1.0), Color::WHITE);
+    }
+}
@@ -2,6 +2,8 @@ use crate::BlendMode;
 use crate::Format;
 use crate::Paint;
 use crate::PixelMapMut;
+use crate::Region;
+use crate::Vector;
 use crate::blitters::*;
 
 /// Specialized code for drawing pixels onto a pixel map.
@@ -32,6 +34,22 @@ pub trait Blitter
         length: u32,
     );
 
+    /// Draw a line segment starting at `start`
+    /// and extending `length` pixels to the right,
+    /// which the shape being drawn covers only partially.
+    ///
+    /// The coverage ranges from 0 (nothing) to 255 (the whole pixel).
+    /// Drawing with full coverage has the same effect
+    /// as [`Blitter::horizontal`].
+    fn horizontal_coverage(
+        &self,
+        map: &mut PixelMapMut<Self::Pixel>,
+        start_x: u32,
+        start_y: u32,
+        length: u32,
+        coverage: u8,
+    );
+
     /// Draw a filled rectangle starting at `start`
     /// and extending `extent` pixels to the bottom right.
     ///
@@ -52,50 +70,258 @@ pub trait Blitter
         }
     }
 
-    /// Draw a line segment starting at `start` and ending at `end`.
+    /// Draw each rectangle of a region.
+    ///
+    /// The parts of the region with negative coordinates
+    /// cannot be drawn and are skipped.
+    /// The provided implementation calls [`Blitter::rectangle`]
+    /// for each rectangle in the region.
+    fn region(&self, map: &mut PixelMapMut<Self::Pixel>, region: &Region)
+    {
+        for rect in region.rects() {
+            let x0 = i32::max(rect.x0, 0);
+            let y0 = i32::max(rect.y0, 0);
+            if rect.x1 <= x0 || rect.y1 <= y0 { continue; }
+            self.rectangle(
+                map,
+                x0 as u32,
+                y0 as u32,
+                (rect.x1 - x0) as u32,
+                (rect.y1 - y0) as u32,
+            );
+        }
+    }
+
+    /// Draw a line segment starting at `start` and ending at `end`,
+    /// including both end points.
     ///
-    /// The provided implementation uses Bresenham’s line algorithm,
-    /// calling [`Blitter::horizontal`] with length 1 for each pixel.
+    /// The coordinates may lie outside of the pixel map.
+    /// Only the pixels of the line segment within the pixel map are visited,
+    /// so the time this takes does not depend on
+    /// how far the line segment extends beyond the pixel map.
+    ///
+    /// The provided implementation draws one pixel
+    /// for each coordinate along the major axis of the line segment,
+    /// like Bresenham’s line algorithm does.
+    /// The pixel drawn is the one nearest to the ideal line,
+    /// rounding halves towards positive infinity.
+    /// Because this is computed for each pixel separately
+    /// rather than incrementally from the start,
+    /// the pixels drawn do not depend on which part of the line segment
+    /// is within the pixel map, which matters for tiles.
+    /// It calls [`Blitter::horizontal`] with length 1 for each pixel.
     fn line_segment(
         &self,
         map: &mut PixelMapMut<Self::Pixel>,
-        start_x: u32,
-        start_y: u32,
-        end_x: u32,
-        end_y: u32
+        start_x: i32,
+        start_y: i32,
+        end_x: i32,
+        end_y: i32,
     )
     {
-        // Bresenham’s line algorithm.
-
-        let mut x0 = start_x as i64;
-        let mut y0 = start_y as i64;
-        let x1 = end_x as i64;
-        let y1 = end_y as i64;
-
-        let dx = i64::abs(x1 - x0);
-        let sx = if x0 < x1 { 1 } else { -1 };
-        let dy = -i64::abs(y1 - y0);
-        let sy = if y0 < y1 { 1 } else { -1 };
-        let mut err = dx + dy;
-
-        loop {
-            self.horizontal(map, x0 as u32, y0 as u32, 1);
-            if x0 == x1 && y0 == y1 {
-                break;
-            }
-            let e2 = 2 * err;
-            if e2 >= dy {
-                err += dy;
-                x0 += sx;
-            }
-            if e2 <= dx {
-                err += dx;
-                y0 += sy;
+        let (origin_x, origin_y) = map.origin();
+        let (extent_x, extent_y) = map.extent();
+        let bounds_x = (origin_x as i64, origin_x as i64 + extent_x as i64 - 1);
+        let bounds_y = (origin_y as i64, origin_y as i64 + extent_y as i64 - 1);
+
+        let (x0, y0) = (start_x as i64, start_y as i64);
+        let (x1, y1) = (end_x as i64, end_y as i64);
+
+        if i64::abs(x1 - x0) >= i64::abs(y1 - y0) {
+            line_pixels((x0, y0), (x1, y1), bounds_x, bounds_y, |x, y| {
+                self.horizontal(map, x as u32, y as u32, 1);
+            });
+        } else {
+            line_pixels((y0, x0), (y1, x1), bounds_y, bounds_x, |y, x| {
+                self.horizontal(map, x as u32, y as u32, 1);
+            });
+        }
+    }
+
+    /// Draw an anti-aliased line segment
+    /// starting at `start` and ending at `end`
+    /// with the given width, in pixels.
+    ///
+    /// Unlike [`Blitter::line_segment`], the end points may be fractional;
+    /// the center of the pixel with coordinates (x, y) is at (x + ½, y + ½).
+    /// The line segment is drawn as a rectangle with butt caps:
+    /// it does not extend beyond its end points.
+    /// Pixels on its edges are drawn with partial coverage,
+    /// which approximates how much of the pixel the rectangle covers
+    /// from the distance between the pixel center and the edges.
+    /// Line segments thinner than a pixel are drawn
+    /// with their width as their maximum coverage,
+    /// which makes them look lighter rather than jagged.
+    ///
+    /// Like with [`Blitter::line_segment`], the coordinates
+    /// may lie outside of the pixel map, only the pixels within it
+    /// are visited, and the coverage of each pixel does not depend
+    /// on which part of the line segment is within the pixel map.
+    /// If any coordinate or the width is NaN or infinite,
+    /// or if the width is not positive, nothing is drawn.
+    /// Neither is anything drawn for line segments of length zero.
+    ///
+    /// The provided implementation calls [`Blitter::horizontal_coverage`]
+    /// with length 1 for each pixel with non-zero coverage.
+    fn antialiased_line_segment(
+        &self,
+        map: &mut PixelMapMut<Self::Pixel>,
+        start: Vector,
+        end: Vector,
+        width: f64,
+    )
+    {
+        let finite = [start.x, start.y, end.x, end.y, width].iter().all(|c| c.is_finite());
+        if !finite || width <= 0.0 {
+            return;
+        }
+
+        let length = (end - start).length();
+        if !(length > 0.0 && length.is_finite()) {
+            return;
+        }
+        let direction = Vector{x: (end.x - start.x) / length, y: (end.y - start.y) / length};
+        let half_width = width / 2.0;
+        let max_coverage = f64::min(width, 1.0);
+        let max_along = f64::min(length, 1.0);
+
+        // Approximate the area of the pixel within the rectangle
+        // as the product of the coverage across and along the line segment.
+        let coverage = |x: i64, y: i64| -> u8 {
+            let center = Vector{x: x as f64 + 0.5, y: y as f64 + 0.5};
+            let offset = center - start;
+            let along = direction.dot(offset);
+            let across = direction.cross(offset).abs();
+            let across = (half_width + 0.5 - across).clamp(0.0, max_coverage);
+            let along = f64::min(along + 0.5, length - along + 0.5);
+            let along = along.clamp(0.0, max_along);
+            (across * along * 255.0).round() as u8
+        };
+
+        let (origin_x, origin_y) = map.origin();
+        let (extent_x, extent_y) = map.extent();
+        let bounds_x = (origin_x as i64, origin_x as i64 + extent_x as i64 - 1);
+        let bounds_y = (origin_y as i64, origin_y as i64 + extent_y as i64 - 1);
+
+        let mut draw = |x: i64, y: i64| {
+            let coverage = coverage(x, y);
+            if coverage > 0 {
+                self.horizontal_coverage(map, x as u32, y as u32, 1, coverage);
             }
+        };
+
+        // Walk along the major axis, and across the line segment
+        // for each major coordinate, like for aliased line segments.
+        let reach = half_width + 0.5;
+        if direction.x.abs() >= direction.y.abs() {
+            let (u0, v0, u1, v1) = (start.x, start.y, end.x, end.y);
+            let slant = 1.0 / direction.x.abs();
+            line_band(u0, v0, u1, v1, reach, slant, bounds_x, bounds_y, &mut draw);
+        } else {
+            let (u0, v0, u1, v1) = (start.y, start.x, end.y, end.x);
+            let slant = 1.0 / direction.y.abs();
+            line_band(u0, v0, u1, v1, reach, slant, bounds_y, bounds_x, |u, v| draw(v, u));
         }
     }
 }
 
+/// Visit the pixels of a line segment within the given bounds.
+///
+/// The coordinates are given as pairs of major and minor coordinates,
+/// and the bounds are inclusive.
+/// For each major coordinate from `p0` to `p1`,
+/// the nearest minor coordinate on the line is computed.
+fn line_pixels(
+    (u0, v0): (i64, i64),
+    (u1, v1): (i64, i64),
+    bounds_u: (i64, i64),
+    bounds_v: (i64, i64),
+    mut f: impl FnMut(i64, i64),
+)
+{
+    let (du, dv) = (u1 - u0, v1 - v0);
+
+    // The minor coordinate at a major coordinate, rounded to nearest.
+    // The products may not fit in 64 bits for extreme coordinates.
+    let v_at = |u: i64| -> i64 {
+        if du == 0 { return v0; }
+        let (n, d) = ((u - u0) as i128 * dv as i128, du as i128);
+        let (n, d) = if d < 0 { (-n, -d) } else { (n, d) };
+        v0 + (2 * n + d).div_euclid(2 * d) as i64
+    };
+
+    // Restrict the major coordinates to the bounds.
+    let lo = i64::max(i64::min(u0, u1), bounds_u.0);
+    let hi = i64::min(i64::max(u0, u1), bounds_u.1);
+    if lo > hi { return; }
+
+    // The minor coordinate is monotonic in the major coordinate,
+    // so the major coordinates for which it is within the bounds
+    // form a range, which can be found by bisection.
+    let (lo, hi) = if v_at(lo) <= v_at(hi) {
+        let lo = partition_point(lo, hi, |u| v_at(u) < bounds_v.0);
+        let hi = partition_point(lo, hi, |u| v_at(u) <= bounds_v.1) - 1;
+        (lo, hi)
+    } else {
+        let lo = partition_point(lo, hi, |u| v_at(u) > bounds_v.1);
+        let hi = partition_point(lo, hi, |u| v_at(u) >= bounds_v.0) - 1;
+        (lo, hi)
+    };
+
+    for u in lo ..= hi {
+        f(u, v_at(u));
+    }
+}
+
+/// Visit the pixels whose centers may lie within
+/// a band around a line segment, within the given bounds.
+///
+/// The coordinates are given as major and minor coordinates.
+/// `reach` is the distance from the line segment to the edges of the band,
+/// and `slant` is how much longer this distance is
+/// along the minor axis than perpendicular to the line segment.
+#[allow(clippy::too_many_arguments)]
+fn line_band(
+    u0: f64, v0: f64,
+    u1: f64, v1: f64,
+    reach: f64,
+    slant: f64,
+    bounds_u: (i64, i64),
+    bounds_v: (i64, i64),
+    mut f: impl FnMut(i64, i64),
+)
+{
+    // Conversions from f64 to i64 saturate,
+    // so far away coordinates are fine.
+    let lo = i64::max(f64::floor(f64::min(u0, u1) - reach) as i64, bounds_u.0);
+    let hi = i64::min(f64::ceil(f64::max(u0, u1) + reach) as i64, bounds_u.1);
+
+    let slope = (v1 - v0) / (u1 - u0);
+    let half_band = reach * slant + 0.5;
+    for u in lo ..= hi {
+        let v = v0 + (u as f64 + 0.5 - u0) * slope - 0.5;
+        let v_lo = i64::max(f64::floor(v - half_band) as i64, bounds_v.0);
+        let v_hi = i64::min(f64::ceil(v + half_band) as i64, bounds_v.1);
+        for v in v_lo ..= v_hi {
+            f(u, v);
+        }
+    }
+}
+
+/// The first integer in `lo ..= hi` for which `pred` is false,
+/// or `hi + 1` if there is none.
+///
+/// `pred` must be true for some prefix of the range and false afterwards.
+fn partition_point(mut lo: i64, mut hi: i64, pred: impl Fn(i64) -> bool) -> i64
+{
+    hi += 1;
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) { lo = mid + 1; } else { hi = mid; }
+    }
+    lo
+}
+
 /// Common implementation for [`create_blitter`] and [`with_blitter`].
 ///
 /// I couldn’t find another way to share this code
@@ -110,12 +336,12 @@ macro_rules! select_blitter
             let Paint{blend_mode, pixel} = $paint;
             match blend_mode {
                 BlendMode::Source =>
-                    $f(BlendSourceBlitter::new(pixel)),
+                    $f(BlendSourceBlitter::new(format, pixel)),
                 BlendMode::SourceOver =>
                     if !format.is_visible(pixel) {
                         $f(BlendDestinationBlitter::new())
                     } else if format.is_opaque(pixel) {
-                        $f(BlendSourceBlitter::new(pixel))
+                        $f(BlendSourceBlitter::new(format, pixel))
                     } else {
                         $f(BlendSourceOverBlitter::new(format, pixel))
                     },
@@ -154,3 +380,83 @@ pub fn with_blitter<F, R>(
 {
     select_blitter!(format, paint, move |mut b| f(&mut b))
 }
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use crate::PixelMap;
+    use crate::formats::Bgra8888;
+
+    use proptest::prop_assert_eq;
+    use proptest::proptest;
+
+    const PAINT: Paint<[u8; 4]> =
+        Paint{blend_mode: BlendMode::SourceOver, pixel: [0, 0, 0, 255]};
+
+    fn alphas(pixel_map: &PixelMap<[u8; 4]>) -> Vec<u8>
+    {
+        pixel_map.pixels().iter().map(|pixel| pixel[3]).collect()
+    }
+
+    fn antialiased(start: Vector, end: Vector, width: f64) -> Vec<u8>
+    {
+        let mut pixel_map = PixelMap::new([0; 4], 4, 3).unwrap();
+        with_blitter(Bgra8888, PAINT, |blitter| {
+            blitter.antialiased_line_segment(&mut pixel_map.as_mut(), start, end, width);
+        });
+        alphas(&pixel_map)
+    }
+
+    #[test]
+    fn test_antialiased_line_segment()
+    {
+        let start = Vector{x: 0.0, y: 1.5};
+        let end = Vector{x: 4.0, y: 1.5};
+
+        assert_eq!(
+            antialiased(start, end, 1.0),
+            [0, 0, 0, 0, 255, 255, 255, 255, 0, 0, 0, 0],
+        );
+        assert_eq!(
+            antialiased(start, end, 2.0),
+            [128, 128, 128, 128, 255, 255, 255, 255, 128, 128, 128, 128],
+        );
+        assert_eq!(
+            antialiased(start, Vector{x: 2.0, y: 1.5}, 0.5),
+            [0, 0, 0, 0, 128, 128, 0, 0, 0, 0, 0, 0],
+        );
+
+        let nan = Vector{x: f64::NAN, y: 0.0};
+        assert_eq!(antialiased(start, nan, 1.0), [0; 12]);
+        assert_eq!(antialiased(start, end, f64::INFINITY), [0; 12]);
+        assert_eq!(antialiased(start, start, 1.0), [0; 12]);
+    }
+
+    proptest! {
+
+        #[test]
+        fn test_antialiased_line_segment_tiles(
+            (x0, y0) in (-20.0 .. 40.0, -20.0 .. 40.0),
+            (x1, y1) in (-20.0 .. 40.0, -20.0 .. 40.0),
+            width in 0.1 .. 4.0,
+            tile in 1u32 .. 10,
+        )
+        {
+            let start = Vector{x: x0, y: y0};
+            let end = Vector{x: x1, y: y1};
+
+            let mut single = PixelMap::new([0; 4], 20, 20).unwrap();
+            let mut tiled = PixelMap::new([0; 4], 20, 20).unwrap();
+            with_blitter(Bgra8888, PAINT, |blitter| {
+                blitter.antialiased_line_segment(&mut single.as_mut(), start, end, width);
+                for mut tile in tiled.as_mut().tiles(tile, tile) {
+                    blitter.antialiased_line_segment(&mut tile, start, end, width);
+                }
+            });
+            prop_assert_eq!(alphas(&single), alphas(&tiled));
+        }
+
+    }
+}
@@ -1,5 +1,6 @@
 use crate::Blitter;
 use crate::PixelMapMut;
+use crate::Vector;
 
 use std::marker::PhantomData;
 
@@ -35,6 +36,17 @@ impl<T> Blitter for BlendDestinationBlitter<T>
     {
     }
 
+    fn horizontal_coverage(
+        &self,
+        _map: &mut PixelMapMut<T>,
+        _start_x: u32,
+        _start_y: u32,
+        _length: u32,
+        _coverage: u8,
+    )
+    {
+    }
+
     fn rectangle(
         &self,
         _map: &mut PixelMapMut<Self::Pixel>,
@@ -45,4 +57,14 @@ impl<T> Blitter for BlendDestinationBlitter<T>
     )
     {
     }
+
+    fn antialiased_line_segment(
+        &self,
+        _map: &mut PixelMapMut<Self::Pixel>,
+        _start: Vector,
+        _end: Vector,
+        _width: f64,
+    )
+    {
+    }
 }
@@ -1,31 +1,38 @@
 use crate::Blitter;
+use crate::Format;
 use crate::PixelMapMut;
 
 /// Blitter that implements the [`Source`] blend mode.
 ///
 /// [`Source`]: `crate::BlendMode::Source`
-pub struct BlendSourceBlitter<T>
+pub struct BlendSourceBlitter<F>
+    where F: Format
 {
-    pixel: T,
+    format: F,
+    pixel: F::Pixel,
 }
 
-impl<T> BlendSourceBlitter<T>
+impl<F> BlendSourceBlitter<F>
+    where F: Format
 {
     /// Create a new blitter.
-    pub fn new(pixel: T) -> Self
+    ///
+    /// The format is only used for pixels
+    /// that are partially covered by the shape being drawn.
+    pub fn new(format: F, pixel: F::Pixel) -> Self
     {
-        Self{pixel}
+        Self{format, pixel}
     }
 }
 
-impl<T> Blitter for BlendSourceBlitter<T>
-    where T: Copy
+impl<F> Blitter for BlendSourceBlitter<F>
+    where F: Format
 {
-    type Pixel = T;
+    type Pixel = F::Pixel;
 
     fn horizontal(
         &self,
-        map: &mut PixelMapMut<T>,
+        map: &mut PixelMapMut<F::Pixel>,
         start_x: u32,
         start_y: u32,
         length: u32,
@@ -34,4 +41,17 @@ impl<T> Blitter for BlendSourceBlitter<T>
         let dest = map.horizontal_mut(start_x, start_y, length);
         dest.fill(self.pixel);
     }
+
+    fn horizontal_coverage(
+        &self,
+        map: &mut PixelMapMut<F::Pixel>,
+        start_x: u32,
+        start_y: u32,
+        length: u32,
+        coverage: u8,
+    )
+    {
+        let dest = map.horizontal_mut(start_x, start_y, length);
+        self.format.blend_source_coverage(dest, self.pixel, coverage);
+    }
 }
@@ -38,4 +38,17 @@ impl<F> Blitter for BlendSourceOverBlitter<F>
         let dest = map.horizontal_mut(start_x, start_y, length);
         self.format.blend_source_over(dest, self.pixel);
     }
+
+    fn horizontal_coverage(
+        &self,
+        map: &mut PixelMapMut<F::Pixel>,
+        start_x: u32,
+        start_y: u32,
+        length: u32,
+        coverage: u8,
+    )
+    {
+        let dest = map.horizontal_mut(start_x, start_y, length);
+        self.format.blend_source_over_coverage(dest, self.pixel, coverage);
+    }
 }
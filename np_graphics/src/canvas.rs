@@ -1,13 +1,34 @@
+use crate::BlendMode;
+use crate::Bounds;
+use crate::Color;
 use crate::Format;
+use crate::Homography;
 use crate::Matrix;
+use crate::NinePatch;
 use crate::Paint;
+use crate::PixelMap;
 use crate::PixelMapMut;
+use crate::Rect;
+use crate::Sampling;
 use crate::Vector;
+use crate::image::draw_image;
+use crate::nine_patch::draw_nine_patch;
+use crate::path::FillRule;
 use crate::path::Instruction;
-use crate::path::bezier_cubic;
-use crate::path::bezier_quadratic;
+use crate::path::flatten;
+use crate::path::flatten_instruction;
+use crate::scanline::fill_contours;
+use crate::scanline::shade_contours;
+use crate::sdf::DistanceField;
+use crate::sdf::Distances;
+use crate::sdf::Style;
+use crate::sdf::draw_distance_field;
+use crate::tessellate::Mesh;
+use crate::tessellate::draw_mesh;
 use crate::with_blitter;
 
+use std::slice;
+
 /// High-level interface for drawing shapes.
 pub trait Canvas
 {
@@ -41,17 +62,105 @@ pub trait Canvas
         path: P,
         paint: Paint<Self::Pixel>,
     );
+
+    /// Begin drawing into a layer.
+    ///
+    /// Until the matching call to [`Canvas::pop_layer`],
+    /// drawing commands draw into the layer rather than onto the canvas.
+    /// The layer starts out transparent,
+    /// and is clipped to the given bounds, which are not transformed.
+    /// When it is popped, the layer is drawn onto the canvas as a unit,
+    /// with its alpha scaled by `opacity` and using the given blend mode.
+    /// Overlapping shapes within a layer are thus blended with each other
+    /// before the opacity is applied, rather than with the canvas.
+    ///
+    /// Layers can be nested.
+    fn push_layer(&mut self, bounds: Bounds, opacity: f32, blend_mode: BlendMode);
+
+    /// Finish drawing into the most recently pushed layer,
+    /// and draw it onto what lies beneath.
+    ///
+    /// If no layer is pushed, this does nothing.
+    fn pop_layer(&mut self);
 }
 
 /// Canvas that draws onto a pixel map.
 ///
 /// The canvas operations will automatically construct suitable blitters.
 /// Only the format and the pixel map are configurable.
+///
+/// Shapes are clipped against the bounds of the pixel map
+/// in floating point before they are rasterized,
+/// so coordinates may be negative or lie far outside the pixel map.
+///
+/// A rectangle covers the pixels whose centers lie within it,
+/// including the top and left edges but excluding the bottom and right edges.
+/// If any transformed coordinate of a rectangle is NaN, nothing is drawn.
+/// Transformed coordinates that are infinite, because they overflowed,
+/// extend to the edge of the pixel map.
+/// Infinite coordinates given to the canvas become NaN when transformed,
+/// as they are multiplied by the zeros of the matrix.
+///
+/// Paths are divided into line segments.
+/// Line segments with a NaN or infinite end point are not drawn,
+/// but the rest of the path is.
+/// The end points of the other line segments are rounded
+/// towards negative infinity to find the pixels they start and end in.
+///
+/// Layers are drawn into offscreen pixel buffers that cover
+/// the pixels whose centers lie within the bounds of the layer.
+/// These buffers are kept in [`Layers`] for reuse;
+/// use [`PixelMapCanvas::with_layers`] and [`PixelMapCanvas::into_layers`]
+/// to reuse them across frames.
+/// Layers that are still pushed when the canvas is dropped
+/// are discarded without being drawn.
 pub struct PixelMapCanvas<'a, F>
     where F: Format
 {
     format: F,
     pixel_map: PixelMapMut<'a, F::Pixel>,
+    layers: Layers<F::Pixel>,
+}
+
+/// Offscreen pixel buffers for the layers of a [`PixelMapCanvas`].
+///
+/// The buffers of popped layers are kept to be used for new layers,
+/// so that drawing layers does not allocate once the buffers are large enough.
+pub struct Layers<T>
+{
+    // The layers that are pushed, innermost last.
+    active: Vec<Layer<T>>,
+
+    // The buffers of layers that were popped.
+    spare: Vec<Vec<T>>,
+}
+
+struct Layer<T>
+{
+    pixels: Vec<T>,
+    origin_x: u32,
+    origin_y: u32,
+    extent_x: u32,
+    extent_y: u32,
+    coverage: u8,
+    blend_mode: BlendMode,
+}
+
+impl<T> Layers<T>
+{
+    /// Create an empty set of layers, without allocating.
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+}
+
+impl<T> Default for Layers<T>
+{
+    fn default() -> Self
+    {
+        Self{active: Vec::new(), spare: Vec::new()}
+    }
 }
 
 impl<'a, F> PixelMapCanvas<'a, F>
@@ -60,10 +169,226 @@ impl<'a, F> PixelMapCanvas<'a, F>
     /// Create a new canvas.
     pub fn new(format: F, pixel_map: PixelMapMut<'a, F::Pixel>) -> Self
     {
-        Self{format, pixel_map}
+        Self::with_layers(format, pixel_map, Layers::new())
+    }
+
+    /// Create a new canvas that reuses the buffers of the given layers.
+    ///
+    /// Layers that were still pushed onto the other canvas are discarded.
+    pub fn with_layers(
+        format: F,
+        pixel_map: PixelMapMut<'a, F::Pixel>,
+        mut layers: Layers<F::Pixel>,
+    ) -> Self
+    {
+        let Layers{active, spare} = &mut layers;
+        spare.extend(active.drain(..).map(|layer| layer.pixels));
+        Self{format, pixel_map, layers}
+    }
+
+    /// Draw the layers that are still pushed, and stop drawing.
+    ///
+    /// This returns the buffers of the layers,
+    /// for reuse with [`PixelMapCanvas::with_layers`].
+    pub fn into_layers(mut self) -> Layers<F::Pixel>
+    {
+        while !self.layers.active.is_empty() {
+            self.pop_layer();
+        }
+        self.layers
+    }
+
+    /// Fill a path transformed with the given matrix.
+    ///
+    /// Unlike [`Canvas::path`], which draws only the outline for now,
+    /// this draws every pixel whose center is inside the path
+    /// according to the fill rule, using a scanline rasterizer.
+    /// Contours are implicitly closed, as with [`flatten`].
+    /// Pixels are not anti-aliased, and are drawn at most once,
+    /// even where contours overlap.
+    pub fn fill_path<P>(
+        &mut self,
+        matrix: Matrix,
+        path: P,
+        fill_rule: FillRule,
+        paint: Paint<F::Pixel>,
+    )
+        where P: IntoIterator<Item=Instruction>
+    {
+        let path = path.into_iter().map(|instruction| matrix * instruction);
+        let contours = flatten(path, CURVE_TOLERANCE);
+        let (format, mut pixel_map) = self.target();
+        fill_contours(format, &mut pixel_map, &contours, fill_rule, paint);
+    }
+
+    /// Fill a path transformed with the given matrix,
+    /// with a color for each pixel given by the shader.
+    ///
+    /// This draws the same pixels as [`PixelMapCanvas::fill_path`].
+    /// The shader is called with the center of each pixel,
+    /// mapped back with the inverse of the matrix
+    /// so that it is in the coordinates of the path,
+    /// and the color it returns is drawn with the given blend mode.
+    /// This is how to fill paths with gradients.
+    /// If the matrix is not invertible, nothing is drawn.
+    pub fn shade_path<P, S>(
+        &mut self,
+        matrix: Matrix,
+        path: P,
+        fill_rule: FillRule,
+        blend_mode: BlendMode,
+        mut shader: S,
+    )
+        where P: IntoIterator<Item=Instruction>,
+              S: FnMut(Vector) -> Color,
+    {
+        let inverse = match Homography::from(matrix).inverse() {
+            Some(inverse) => inverse,
+            None => return,
+        };
+        let path = path.into_iter().map(|instruction| matrix * instruction);
+        let contours = flatten(path, CURVE_TOLERANCE);
+        let (format, mut pixel_map) = self.target();
+        let shader = |center| {
+            let color = inverse.transform(center).map_or(Color::TRANSPARENT, &mut shader);
+            color.to_pixel(format)
+        };
+        shade_contours(format, &mut pixel_map, &contours, fill_rule, blend_mode, shader);
+    }
+
+    /// Draw a triangle mesh, such as one made by a [`Tessellator`].
+    ///
+    /// A pixel is drawn if its center is inside a triangle.
+    /// Pixel centers exactly on an edge shared by two triangles
+    /// belong to only one of them, as in [`PixelMapCanvas::fill_path`],
+    /// so meshes of triangles that do not overlap draw each pixel at most once.
+    /// The paint is drawn with the coverage interpolated
+    /// from the vertices of the triangle.
+    ///
+    /// # Panics
+    ///
+    /// If an index is out of range of the vertices,
+    /// this method panics.
+    ///
+    /// [`Tessellator`]: `crate::tessellate::Tessellator`
+    pub fn mesh(&mut self, mesh: &Mesh, paint: Paint<F::Pixel>)
+    {
+        let (format, mut pixel_map) = self.target();
+        draw_mesh(format, &mut pixel_map, mesh, paint);
+    }
+
+    /// Draw a signed distance field transformed with the given matrix.
+    ///
+    /// The matrix maps the coordinates of the shape
+    /// the field was generated from onto coordinates within the pixel map,
+    /// like the matrix given to [`Canvas::path`].
+    /// For each pixel of the pixel map within the field,
+    /// the distance at the pixel center is sampled from the field,
+    /// and converted to pixels of the pixel map assuming uniform scaling.
+    /// See [`Style`] for the effects that are drawn.
+    pub fn distance_field<T>(
+        &mut self,
+        matrix: Matrix,
+        field: &DistanceField<T>,
+        style: Style<F::Pixel>,
+    )
+        where T: Distances
+    {
+        let (format, mut pixel_map) = self.target();
+        draw_distance_field(format, &mut pixel_map, matrix, field, style);
+    }
+
+    /// The format and the pixel map that drawing commands draw onto.
+    ///
+    /// This is the pixel map of the innermost layer,
+    /// or that of the canvas if no layer is pushed.
+    fn target(&mut self) -> (&F, PixelMapMut<'_, F::Pixel>)
+    {
+        let target = match self.layers.active.last_mut() {
+            None => self.pixel_map.reborrow(),
+            Some(layer) => {
+                let pixel_map = PixelMapMut::new(
+                    &mut layer.pixels,
+                    layer.extent_x,
+                    layer.extent_x,
+                    layer.extent_y,
+                ).expect("Layer has as many pixels as its extent");
+                pixel_map.with_origin(layer.origin_x, layer.origin_y)
+            },
+        };
+        (&self.format, target)
+    }
+
+    /// Draw an image transformed with the given homography.
+    ///
+    /// The homography maps coordinates within the image,
+    /// where the image spans from (0, 0) to its extent,
+    /// onto coordinates within the pixel map.
+    /// Use [`Homography::from_quad_to_quad`] to map
+    /// the corners of the image onto arbitrary points.
+    ///
+    /// For each pixel of the pixel map, the pixel center
+    /// is mapped back onto the image with the inverse homography,
+    /// which makes the texture mapping perspective-correct.
+    /// Pixels for which that point lies behind the viewer
+    /// or outside of the image are left alone.
+    /// If the homography is not invertible, nothing is drawn.
+    pub fn image(
+        &mut self,
+        homography: Homography,
+        image: &PixelMap<F::Pixel>,
+        sampling: Sampling,
+        blend_mode: BlendMode,
+    )
+    {
+        let (format, mut pixel_map) = self.target();
+        draw_image(
+            format,
+            &mut pixel_map,
+            homography,
+            image,
+            sampling,
+            blend_mode,
+        );
+    }
+
+    /// Draw a nine-patch into the given destination rectangle.
+    ///
+    /// See [`NinePatch`] for how the image is divided.
+    /// The destination rectangle is in pixel coordinates,
+    /// and may lie partially or entirely outside of the pixel map.
+    pub fn nine_patch(
+        &mut self,
+        image: &PixelMap<F::Pixel>,
+        nine_patch: &NinePatch,
+        dest: Rect,
+        blend_mode: BlendMode,
+    )
+    {
+        let (format, mut pixel_map) = self.target();
+        draw_nine_patch(
+            format,
+            &mut pixel_map,
+            image,
+            nine_patch,
+            dest,
+            blend_mode,
+        );
     }
 }
 
+/// Half the size of the window that line segments are clipped to
+/// before their end points are converted to integers.
+///
+/// This is comfortably within the range of `i32`,
+/// and does not depend on the pixel map,
+/// so that tiles draw the same pixels as the whole pixel map.
+const LINE_WINDOW: f64 = (1 << 30) as f64;
+
+/// Maximum distance between a curve and the line segments
+/// that approximate it, in pixels.
+const CURVE_TOLERANCE: f64 = 0.25;
+
 impl<'a, F> Canvas for PixelMapCanvas<'a, F>
     where F: Format
 {
@@ -79,33 +404,25 @@ impl<'a, F> Canvas for PixelMapCanvas<'a, F>
     {
         // TODO: Draw rotated and skewed rectangles correctly.
 
-        // Transform start and extent.
-        let mut t_start = matrix * start;
-        let mut t_extent = matrix * (start + extent) - t_start;
-
-        // Make sure start comes before end.
-        if t_extent.x < 0.0 {
-            t_start.x += t_extent.x;
-            t_extent.x = -t_extent.x;
-        }
-        if t_extent.y < 0.0 {
-            t_start.y += t_extent.y;
-            t_extent.y = -t_extent.y;
+        // Transform the corners.
+        let p0 = matrix * start;
+        let p1 = matrix * (start + extent);
+        if [p0.x, p0.y, p1.x, p1.y].iter().any(|c| c.is_nan()) {
+            return;
         }
 
-        // Borrow fields separately.
-        let format = &self.format;
-        let pixel_map = &mut self.pixel_map;
+        // Find the pixel centers within the rectangle.
+        let (format, mut pixel_map) = self.target();
+        let (origin_x, origin_y) = pixel_map.origin();
+        let (extent_x, extent_y) = pixel_map.extent();
+        let (start_x, extent_x) = pixel_span(
+            f64::min(p0.x, p1.x), f64::max(p0.x, p1.x), origin_x, extent_x);
+        let (start_y, extent_y) = pixel_span(
+            f64::min(p0.y, p1.y), f64::max(p0.y, p1.y), origin_y, extent_y);
 
         // Draw rectangle using blitter.
         with_blitter(format, paint, |blitter| {
-            blitter.rectangle(
-                pixel_map,
-                t_start.x as u32,
-                t_start.y as u32,
-                t_extent.x as u32,
-                t_extent.y as u32,
-            );
+            blitter.rectangle(&mut pixel_map, start_x, start_y, extent_x, extent_y);
         });
     }
 
@@ -118,29 +435,48 @@ impl<'a, F> Canvas for PixelMapCanvas<'a, F>
     {
         // TODO: Fill the path instead of drawing the outline only. :P
 
-        // Borrow fields separately.
-        let format = &self.format;
-        let pixel_map = &mut self.pixel_map;
+        // Line segments that miss this are entirely outside of the pixel map.
+        // The margin accounts for rounding the end points.
+        let (format, mut pixel_map) = self.target();
+        let (origin_x, origin_y) = pixel_map.origin();
+        let (extent_x, extent_y) = pixel_map.extent();
+        let visible = Bounds{
+            min: Vector{x: origin_x as f64 - 2.0, y: origin_y as f64 - 2.0},
+            max: Vector{
+                x: origin_x as f64 + extent_x as f64 + 2.0,
+                y: origin_y as f64 + extent_y as f64 + 2.0,
+            },
+        };
+        let window = Bounds{
+            min: Vector{x: -LINE_WINDOW, y: -LINE_WINDOW},
+            max: Vector{x: LINE_WINDOW, y: LINE_WINDOW},
+        };
 
         // Draw using the blitter for this paint.
         with_blitter(format, paint, |blitter| {
 
-            // The Bézier curves will be divided into line segments.
+            // The Bézier curves are divided into line segments.
             // The line segments are drawn using the blitter.
             let mut line_segment = |p0: Vector, p1: Vector| {
-                blitter.line_segment(
-                    pixel_map,
-                    p0.x as u32,
-                    p0.y as u32,
-                    p1.x as u32,
-                    p1.y as u32,
-                );
+                if visible.clip_line_segment(p0, p1).is_none() {
+                    return;
+                }
+                if let Some((p0, p1)) = window.clip_line_segment(p0, p1) {
+                    blitter.line_segment(
+                        &mut pixel_map,
+                        p0.x.floor() as i32,
+                        p0.y.floor() as i32,
+                        p1.x.floor() as i32,
+                        p1.y.floor() as i32,
+                    );
+                }
             };
 
             // Keep track of the p0 point across iterations.
-            // After each iteraiton, this is moved to
-            // the end point of each Bézier curve.
+            // After each iteration, this is moved to
+            // the end point of each instruction.
             let mut p0 = Vector{x: 0.0, y: 0.0};
+            let mut points = Vec::new();
 
             // Perform each instruction.
             for instruction in path {
@@ -150,31 +486,245 @@ impl<'a, F> Canvas for PixelMapCanvas<'a, F>
                 // with Bézier control points.
                 let instruction = matrix * instruction;
 
-                match instruction {
-                    Instruction::Move(to) => {
-                        p0 = to;
-                    },
-                    Instruction::Linear(p1) => {
+                points.clear();
+                flatten_instruction(p0, instruction, CURVE_TOLERANCE, &mut points);
+                for &p1 in &points {
+                    if !matches!(instruction, Instruction::Move(..)) {
                         line_segment(p0, p1);
-                        p0 = p1;
-                    },
-                    Instruction::Quadratic(p1, p2) => {
-                        // TODO: Smarter heuristics to generate line segments.
-                        let half = bezier_quadratic(p0, p1, p2, 0.5);
-                        line_segment(p0, half);
-                        line_segment(half, p2);
-                        p0 = p2;
-                    },
-                    Instruction::Cubic(p1, p2, p3) => {
-                        // TODO: Smarter heuristics to generate line segments.
-                        let half = bezier_cubic(p0, p1, p2, p3, 0.5);
-                        line_segment(p0, half);
-                        line_segment(half, p3);
-                        p0 = p3;
-                    },
+                    }
+                    p0 = p1;
+                }
+            }
+
+        });
+    }
+
+    fn push_layer(&mut self, bounds: Bounds, opacity: f32, blend_mode: BlendMode)
+    {
+        // The layer covers the pixel centers within its bounds,
+        // clipped to what lies beneath so that it can be drawn back.
+        let (_, pixel_map) = self.target();
+        let (origin_x, origin_y) = pixel_map.origin();
+        let (extent_x, extent_y) = pixel_map.extent();
+        let ((origin_x, extent_x), (origin_y, extent_y)) =
+            if bounds.is_empty() {
+                ((origin_x, 0), (origin_y, 0))
+            } else {
+                (
+                    pixel_span(bounds.min.x, bounds.max.x, origin_x, extent_x),
+                    pixel_span(bounds.min.y, bounds.max.y, origin_y, extent_y),
+                )
+            };
+
+        let transparent = self.format.from_rgba8888([0; 4]);
+        let mut pixels = self.layers.spare.pop().unwrap_or_default();
+        pixels.clear();
+        pixels.resize(extent_x as usize * extent_y as usize, transparent);
+
+        // Conversions from f32 to u8 saturate, and turn NaN into zero.
+        let coverage = (opacity * 255.0).round() as u8;
+
+        self.layers.active.push(Layer{
+            pixels,
+            origin_x,
+            origin_y,
+            extent_x,
+            extent_y,
+            coverage,
+            blend_mode,
+        });
+    }
+
+    fn pop_layer(&mut self)
+    {
+        let layer = match self.layers.active.pop() {
+            Some(layer) => layer,
+            None => return,
+        };
+
+        let (format, mut pixel_map) = self.target();
+        let rows = layer.pixels.chunks_exact(layer.extent_x.max(1) as usize);
+        for (y, row) in (layer.origin_y ..).zip(rows) {
+            let dest = pixel_map.horizontal_mut(layer.origin_x, y, layer.extent_x);
+            for (dest, &source) in dest.iter_mut().zip(row) {
+                let dest = slice::from_mut(dest);
+                match layer.blend_mode {
+                    BlendMode::Source =>
+                        format.blend_source_coverage(dest, source, layer.coverage),
+                    BlendMode::SourceOver =>
+                        format.blend_source_over_coverage(dest, source, layer.coverage),
+                    BlendMode::Destination =>
+                        (),
                 }
             }
+        }
+
+        self.layers.spare.push(layer.pixels);
+    }
+}
+
+/// The pixels whose centers lie within `min .. max`
+/// and within the `extent` pixels starting at `origin`,
+/// as their start and their number.
+pub(crate) fn pixel_span(min: f64, max: f64, origin: u32, extent: u32) -> (u32, u32)
+{
+    // Clip against the pixels, then find the pixel centers within.
+    // The clipping does not change which pixels are selected,
+    // as the edges of the pixels lie between pixel centers.
+    let (lower, upper) = (origin as f64, origin as f64 + extent as f64);
+    let p0 = f64::ceil(min.clamp(lower, upper) - 0.5) as u32;
+    let p1 = f64::ceil(max.clamp(lower, upper) - 0.5) as u32;
+    (p0, p1 - p0)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use crate::BlendMode;
+    use crate::PixelMap;
+    use crate::formats::Bgra8888;
+
+    const PAINT: Paint<[u8; 4]> =
+        Paint{blend_mode: BlendMode::Source, pixel: [0xFF; 4]};
+
+    fn draw(f: impl FnOnce(&mut PixelMapCanvas<Bgra8888>)) -> Vec<bool>
+    {
+        let mut pixel_map = PixelMap::new([0; 4], 4, 4).unwrap();
+        f(&mut PixelMapCanvas::new(Bgra8888, pixel_map.as_mut()));
+        pixel_map.pixels().iter().map(|&pixel| pixel == [0xFF; 4]).collect()
+    }
+
+    fn pixels(rows: [&str; 4]) -> Vec<bool>
+    {
+        rows.iter().flat_map(|row| row.chars().map(|c| c == '#')).collect()
+    }
+
+    fn line(p0: Vector, p1: Vector) -> Vec<Instruction>
+    {
+        vec![Instruction::Move(p0), Instruction::Linear(p1)]
+    }
+
+    #[test]
+    fn test_rectangle_clipping()
+    {
+        let result = draw(|canvas| {
+            let start = Vector{x: -10.0, y: -10.0};
+            canvas.rectangle(Matrix::IDENTITY, start, Vector{x: 12.0, y: 11.6}, PAINT);
+        });
+        assert_eq!(result, pixels(["##..", "##..", "....", "...."]));
+
+        let result = draw(|canvas| {
+            let start = Vector{x: -1e300, y: 2.6};
+            let extent = Vector{x: 2e300, y: 1e300};
+            canvas.rectangle(Matrix::from_scale(1e10, 1.0), start, extent, PAINT);
+        });
+        assert_eq!(result, pixels(["....", "....", "....", "####"]));
+
+        let result = draw(|canvas| {
+            let start = Vector{x: 0.0, y: f64::NAN};
+            canvas.rectangle(Matrix::IDENTITY, start, Vector{x: 4.0, y: 4.0}, PAINT);
+        });
+        assert_eq!(result, pixels(["....", "....", "....", "...."]));
+    }
+
+    #[test]
+    fn test_line_segment_clipping()
+    {
+        // Steep line from far above the pixel map,
+        // which must not be distorted by saturating at zero.
+        let result = draw(|canvas| {
+            let path = line(Vector{x: 1.0, y: -1e9}, Vector{x: 1.0, y: 2.0});
+            canvas.path(Matrix::IDENTITY, path, PAINT);
+        });
+        assert_eq!(result, pixels([".#..", ".#..", ".#..", "...."]));
+
+        // Diagonal line through the pixel map with far away end points.
+        let result = draw(|canvas| {
+            let path = line(Vector{x: -1e12, y: -1e12}, Vector{x: 1e12, y: 1e12});
+            canvas.path(Matrix::IDENTITY, path, PAINT);
+        });
+        assert_eq!(result, pixels(["#...", ".#..", "..#.", "...#"]));
+
+        // Segments with non-finite end points are skipped,
+        // and the rest of the path is drawn.
+        let result = draw(|canvas| {
+            let path = vec![
+                Instruction::Move(Vector{x: 0.0, y: 0.0}),
+                Instruction::Linear(Vector{x: f64::NAN, y: 0.0}),
+                Instruction::Linear(Vector{x: f64::INFINITY, y: 1.0}),
+                Instruction::Move(Vector{x: 0.0, y: 3.0}),
+                Instruction::Linear(Vector{x: 3.0, y: 3.0}),
+            ];
+            canvas.path(Matrix::IDENTITY, path, PAINT);
+        });
+        assert_eq!(result, pixels(["....", "....", "....", "####"]));
+    }
+
+    #[test]
+    fn test_layer_opacity()
+    {
+        let mut pixel_map = PixelMap::new([0xFF; 4], 4, 1).unwrap();
+        let mut canvas = PixelMapCanvas::new(Bgra8888, pixel_map.as_mut());
+        let bounds = Bounds{min: Vector{x: 0.0, y: 0.0}, max: Vector{x: 3.0, y: 4.0}};
+        let red = Paint{blend_mode: BlendMode::SourceOver, pixel: [0, 0, 0xFF, 0xFF]};
+        canvas.push_layer(bounds, 0.5, BlendMode::SourceOver);
+        canvas.rectangle(Matrix::IDENTITY, Vector{x: 0.0, y: 0.0}, Vector{x: 2.0, y: 1.0}, red);
+        canvas.rectangle(Matrix::IDENTITY, Vector{x: 1.0, y: 0.0}, Vector{x: 3.0, y: 1.0}, red);
+        canvas.pop_layer();
+        canvas.pop_layer();
+        let layers = canvas.into_layers();
+
+        // The overlapping rectangles are blended as a unit,
+        // and the layer clips the second rectangle.
+        let pixels = pixel_map.pixels();
+        assert_eq!(pixels[0], pixels[1]);
+        assert_eq!(pixels[1], pixels[2]);
+        let [b, g, r, a] = pixels[0];
+        assert!((b as i32 - 0x80).abs() <= 1 && b == g && r == 0xFF && a == 0xFF, "{:?}", pixels[0]);
+        assert_eq!(pixels[3], [0xFF; 4]);
+
+        // The buffer of the layer is kept for reuse.
+        assert!(layers.active.is_empty());
+        assert_eq!(layers.spare.len(), 1);
+        assert!(layers.spare[0].capacity() >= 3);
+    }
+
+    #[test]
+    fn test_layer_blend_modes()
+    {
+        let bounds = Bounds{min: Vector{x: 0.0, y: 0.0}, max: Vector{x: 2.0, y: 4.0}};
+
+        // Source replaces everything within the layer,
+        // including pixels that nothing was drawn onto.
+        let result = draw(|canvas| {
+            canvas.rectangle(Matrix::IDENTITY, Vector{x: 0.0, y: 0.0}, Vector{x: 4.0, y: 4.0}, PAINT);
+            canvas.push_layer(bounds, 1.0, BlendMode::Source);
+            canvas.pop_layer();
+        });
+        assert_eq!(result, pixels(["..##", "..##", "..##", "..##"]));
+
+        // Destination draws nothing, and layers that are not popped
+        // are drawn by into_layers.
+        let mut pixel_map = PixelMap::new([0; 4], 4, 4).unwrap();
+        let mut canvas = PixelMapCanvas::new(Bgra8888, pixel_map.as_mut());
+        canvas.push_layer(bounds, 1.0, BlendMode::Destination);
+        canvas.rectangle(Matrix::IDENTITY, Vector{x: 0.0, y: 0.0}, Vector{x: 4.0, y: 4.0}, PAINT);
+        canvas.push_layer(bounds, 1.0, BlendMode::SourceOver);
+        let layers = canvas.into_layers();
+        assert!(pixel_map.pixels().iter().all(|&pixel| pixel == [0; 4]));
+        assert_eq!(layers.spare.len(), 2);
 
+        // Empty and NaN bounds clip everything.
+        let result = draw(|canvas| {
+            let nan = Bounds{max: Vector{x: f64::NAN, y: 4.0}, ..bounds};
+            canvas.push_layer(nan, 1.0, BlendMode::SourceOver);
+            canvas.rectangle(Matrix::IDENTITY, Vector{x: 0.0, y: 0.0}, Vector{x: 4.0, y: 4.0}, PAINT);
+            canvas.pop_layer();
+            canvas.push_layer(Bounds::EMPTY, 1.0, BlendMode::Source);
+            canvas.pop_layer();
         });
+        assert_eq!(result, pixels(["....", "....", "....", "...."]));
     }
 }
@@ -0,0 +1,86 @@
+use crate::Format;
+
+/// Color with an alpha component, in the sRGB color space.
+///
+/// Each component is a floating-point number,
+/// which is normally between zero and one.
+/// The color components are encoded with the sRGB transfer function,
+/// like the colors in CSS and most images,
+/// and are not premultiplied by alpha.
+/// Components outside the normal range are allowed;
+/// they represent colors outside of the sRGB gamut,
+/// which conversions to other color spaces preserve.
+/// They are clamped when the color is converted to pixels.
+///
+/// Colors convert to and from other color spaces with [`From`],
+/// such as [`Hsl`] and [`Oklab`], and parse from CSS syntax with
+/// [`str::parse`]. Use [`Color::mix`] for mixing colors.
+///
+/// [`Hsl`]: `crate::Hsl`
+/// [`Oklab`]: `crate::Oklab`
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color
+{
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color
+{
+    /// Fully transparent black.
+    pub const TRANSPARENT: Self = Self::new(0.0, 0.0, 0.0, 0.0);
+
+    /// Opaque black.
+    pub const BLACK: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+
+    /// Opaque white.
+    pub const WHITE: Self = Self::new(1.0, 1.0, 1.0, 1.0);
+
+    /// Create a color from its components.
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self
+    {
+        Self{r, g, b, a}
+    }
+
+    /// Create a color from 8-bit red, green, blue, and alpha components.
+    pub fn from_rgba8888(rgba: [u8; 4]) -> Self
+    {
+        let [r, g, b, a] = rgba;
+        let component = |c: u8| c as f32 / 255.0;
+        Self{r: component(r), g: component(g), b: component(b), a: component(a)}
+    }
+
+    /// Convert the color to 8-bit red, green, blue, and alpha components.
+    ///
+    /// Components are clamped to the normal range and rounded.
+    /// NaN components become zero.
+    pub fn to_rgba8888(self) -> [u8; 4]
+    {
+        // Conversions from f32 to u8 saturate, and turn NaN into zero.
+        let component = |c: f32| (c * 255.0).round() as u8;
+        [component(self.r), component(self.g), component(self.b), component(self.a)]
+    }
+
+    /// Convert the color to a pixel in the given format.
+    pub fn to_pixel<F>(self, format: F) -> F::Pixel
+        where F: Format
+    {
+        format.from_rgba8888(self.to_rgba8888())
+    }
+
+    /// Convert a pixel in the given format to a color.
+    pub fn from_pixel<F>(format: F, pixel: F::Pixel) -> Self
+        where F: Format
+    {
+        Self::from_rgba8888(format.to_rgba8888(pixel))
+    }
+
+    /// The same color with a different alpha component.
+    pub fn with_alpha(self, a: f32) -> Self
+    {
+        Self{a, ..self}
+    }
+}
@@ -0,0 +1,259 @@
+use crate::Color;
+use crate::Hsl;
+use crate::Hsv;
+use crate::LinearRgb;
+use crate::Oklab;
+use crate::Oklch;
+use crate::color::spaces::normalize_hue;
+use crate::float::total_cmp;
+
+/// Saturation or chroma below which a color counts as gray.
+///
+/// Conversions give grays a tiny chroma due to rounding errors.
+const ACHROMATIC: f32 = 1e-4;
+
+/// Color space in which colors are mixed.
+///
+/// The same two colors mix into different colors in different spaces.
+/// [`Oklab`] gives the most even results, and is a good default.
+/// [`Srgb`] is what most other software does,
+/// and [`LinearSrgb`] is what happens to light.
+/// The spaces with a hue take the shorter way around the color wheel.
+///
+/// [`Oklab`]: `ColorSpace::Oklab`
+/// [`Srgb`]: `ColorSpace::Srgb`
+/// [`LinearSrgb`]: `ColorSpace::LinearSrgb`
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ColorSpace
+{
+    Srgb,
+    LinearSrgb,
+    Hsl,
+    Hsv,
+    Oklab,
+    Oklch,
+}
+
+impl ColorSpace
+{
+    /// The components of a color in this color space.
+    ///
+    /// The alpha component comes last.
+    fn components(self, color: Color) -> [f32; 4]
+    {
+        match self {
+            Self::Srgb => {
+                let Color{r, g, b, a} = color;
+                [r, g, b, a]
+            },
+            Self::LinearSrgb => {
+                let LinearRgb{r, g, b, a} = color.into();
+                [r, g, b, a]
+            },
+            Self::Hsl => {
+                let Hsl{h, s, l, a} = color.into();
+                [h, s, l, a]
+            },
+            Self::Hsv => {
+                let Hsv{h, s, v, a} = color.into();
+                [h, s, v, a]
+            },
+            Self::Oklab => {
+                let Oklab{l, a, b, alpha} = color.into();
+                [l, a, b, alpha]
+            },
+            Self::Oklch => {
+                let Oklch{l, c, h, alpha} = color.into();
+                [l, c, h, alpha]
+            },
+        }
+    }
+
+    /// The color with the given components in this color space.
+    fn color(self, [c0, c1, c2, a]: [f32; 4]) -> Color
+    {
+        match self {
+            Self::Srgb => Color{r: c0, g: c1, b: c2, a},
+            Self::LinearSrgb => LinearRgb{r: c0, g: c1, b: c2, a}.into(),
+            Self::Hsl => Hsl{h: c0, s: c1, l: c2, a}.into(),
+            Self::Hsv => Hsv{h: c0, s: c1, v: c2, a}.into(),
+            Self::Oklab => Oklab{l: c0, a: c1, b: c2, alpha: a}.into(),
+            Self::Oklch => Oklch{l: c0, c: c1, h: c2, alpha: a}.into(),
+        }
+    }
+
+    /// The index of the hue component, and of the component
+    /// which is zero when the hue is meaningless.
+    fn hue(self) -> Option<(usize, usize)>
+    {
+        match self {
+            Self::Hsl | Self::Hsv => Some((0, 1)),
+            Self::Oklch => Some((2, 1)),
+            _ => None,
+        }
+    }
+}
+
+impl Color
+{
+    /// Mix two colors in the given color space.
+    ///
+    /// The result is `self` if `t` is zero,
+    /// and `other` if `t` is one.
+    /// Values of `t` outside of this range extrapolate.
+    ///
+    /// This follows the rules of CSS:
+    /// the components are premultiplied by alpha while they are mixed,
+    /// so that transparent colors do not affect the color of the result,
+    /// and the hue of a gray (which has no meaningful hue)
+    /// is taken to be the hue of the other color.
+    pub fn mix(self, other: Self, t: f32, space: ColorSpace) -> Self
+    {
+        let mut a = space.components(self);
+        let mut b = space.components(other);
+        let hue = space.hue();
+
+        if let Some((hue, chroma)) = hue {
+            if a[chroma].abs() < ACHROMATIC { a[hue] = b[hue]; }
+            if b[chroma].abs() < ACHROMATIC { b[hue] = a[hue]; }
+            // Take the shorter way around.
+            let delta = b[hue] - a[hue];
+            if delta > 180.0 { a[hue] += 360.0; }
+            if delta < -180.0 { b[hue] += 360.0; }
+        }
+
+        let lerp = |x: f32, y: f32| x + (y - x) * t;
+        let alpha = lerp(a[3], b[3]);
+        let mut result = [0.0, 0.0, 0.0, alpha];
+        for i in 0 .. 3 {
+            result[i] =
+                if hue.map(|(hue, _)| hue) == Some(i) {
+                    normalize_hue(lerp(a[i], b[i]))
+                } else if alpha != 0.0 {
+                    lerp(a[i] * a[3], b[i] * b[3]) / alpha
+                } else {
+                    lerp(a[i], b[i])
+                };
+        }
+        space.color(result)
+    }
+}
+
+/// Colors at positions along a line, with colors mixed in between.
+///
+/// This is the color ramp of a gradient.
+/// Positions are typically between zero and one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient
+{
+    space: ColorSpace,
+    stops: Vec<(f32, Color)>,
+}
+
+impl Gradient
+{
+    /// Create a gradient from colors at positions.
+    ///
+    /// The stops are sorted by position.
+    /// Stops at the same position keep their order,
+    /// which makes for a sharp transition between them.
+    /// Stops at NaN positions are discarded.
+    pub fn new(space: ColorSpace, stops: impl IntoIterator<Item=(f32, Color)>) -> Self
+    {
+        let mut stops: Vec<_> =
+            stops.into_iter()
+            .filter(|(position, _)| !position.is_nan())
+            .collect();
+        stops.sort_by(|a, b| total_cmp(&a.0, &b.0));
+        Self{space, stops}
+    }
+
+    /// The color at a position.
+    ///
+    /// Before the first stop and after the last stop,
+    /// the color of that stop is used.
+    /// If there are no stops, the color is transparent.
+    pub fn at(&self, position: f32) -> Color
+    {
+        let index = self.stops.partition_point(|&(p, _)| p <= position);
+        match (self.stops.get(index.wrapping_sub(1)), self.stops.get(index)) {
+            (None, None) => Color::TRANSPARENT,
+            (Some(&(_, color)), None) | (None, Some(&(_, color))) => color,
+            (Some(&(p0, c0)), Some(&(p1, c1))) =>
+                c0.mix(c1, (position - p0) / (p1 - p0), self.space),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const SPACES: [ColorSpace; 6] = [
+        ColorSpace::Srgb, ColorSpace::LinearSrgb, ColorSpace::Hsl,
+        ColorSpace::Hsv, ColorSpace::Oklab, ColorSpace::Oklch,
+    ];
+
+    fn rgba8888(s: &str) -> [u8; 4]
+    {
+        s.parse::<Color>().unwrap().to_rgba8888()
+    }
+
+    #[test]
+    fn test_mix_endpoints()
+    {
+        let a: Color = "#36c".parse().unwrap();
+        let b: Color = "rgb(200 40 10 / 0.5)".parse().unwrap();
+        for &space in SPACES.iter() {
+            assert_eq!(a.mix(b, 0.0, space).to_rgba8888(), a.to_rgba8888());
+            assert_eq!(a.mix(b, 1.0, space).to_rgba8888(), b.to_rgba8888());
+        }
+    }
+
+    #[test]
+    fn test_mix_transparent()
+    {
+        // Premultiplied mixing keeps the color of the opaque endpoint.
+        let red = Color::new(1.0, 0.0, 0.0, 1.0);
+        for &space in SPACES.iter() {
+            let [r, g, b, a] = red.mix(Color::TRANSPARENT, 0.5, space).to_rgba8888();
+            assert_eq!([r, g, b], [255, 0, 0], "{:?}", space);
+            assert_eq!(a, 128);
+        }
+    }
+
+    #[test]
+    fn test_mix_hue()
+    {
+        // From red (0°) to magenta (300°) the shorter way passes 330°.
+        let red = Color::new(1.0, 0.0, 0.0, 1.0);
+        let magenta = Color::new(1.0, 0.0, 1.0, 1.0);
+        let mixed = red.mix(magenta, 0.5, ColorSpace::Hsl);
+        assert_eq!(mixed.to_rgba8888(), rgba8888("hsl(330 100% 50%)"));
+
+        // Gray takes on the hue of the other color.
+        let gray = Color::new(0.5, 0.5, 0.5, 1.0);
+        let mixed = gray.mix(magenta, 0.5, ColorSpace::Hsl);
+        assert_eq!(Hsl::from(mixed).h.round(), 300.0);
+    }
+
+    #[test]
+    fn test_gradient()
+    {
+        let black = Color::BLACK;
+        let white = Color::WHITE;
+        let gradient = Gradient::new(ColorSpace::Srgb, vec![(1.0, white), (0.0, black)]);
+        assert_eq!(gradient.at(-1.0), black);
+        assert_eq!(gradient.at(0.5).to_rgba8888(), [128, 128, 128, 255]);
+        assert_eq!(gradient.at(2.0), white);
+
+        let gradient = Gradient::new(ColorSpace::Oklab, vec![(0.5, black), (0.5, white)]);
+        assert_eq!(gradient.at(0.25), black);
+        assert_eq!(gradient.at(0.5), white);
+
+        let gradient = Gradient::new(ColorSpace::Oklab, vec![]);
+        assert_eq!(gradient.at(0.5), Color::TRANSPARENT);
+    }
+}
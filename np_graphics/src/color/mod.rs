@@ -0,0 +1,10 @@
+pub use self::color::*;
+pub use self::mix::*;
+pub use self::parse::*;
+pub use self::spaces::*;
+
+#[allow(clippy::module_inception)]
+mod color;
+mod mix;
+mod parse;
+mod spaces;
@@ -0,0 +1,233 @@
+use crate::Color;
+use crate::Hsl;
+
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Why a string could not be parsed as a CSS color.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseColorError
+{
+    /// The string is not a hex color or a function call.
+    InvalidSyntax,
+
+    /// The hex color has an invalid digit or number of digits.
+    InvalidHex,
+
+    /// The function is not one of `rgb`, `rgba`, `hsl`, or `hsla`.
+    UnknownFunction,
+
+    /// The function has the wrong number of arguments,
+    /// or an argument is not a valid number, percentage, or angle.
+    InvalidArguments,
+}
+
+impl fmt::Display for ParseColorError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self {
+            Self::InvalidSyntax =>
+                write!(f, "Invalid color syntax"),
+            Self::InvalidHex =>
+                write!(f, "Invalid hex color"),
+            Self::UnknownFunction =>
+                write!(f, "Unknown color function"),
+            Self::InvalidArguments =>
+                write!(f, "Invalid color function arguments"),
+        }
+    }
+}
+
+impl error::Error for ParseColorError
+{
+}
+
+/// Parse a color from CSS syntax.
+///
+/// The following syntaxes are supported, case-insensitively:
+///
+///  - Hex colors with 3, 4, 6, or 8 digits, such as `#f80` and `#ff880080`.
+///  - `rgb()` and `rgba()`, with components as numbers from 0 to 255
+///    or percentages, such as `rgb(255 136 0 / 50%)`
+///    and `rgba(100%, 53%, 0%, 0.5)`.
+///  - `hsl()` and `hsla()`, with the hue as a number of degrees
+///    or an angle in `deg`, `rad`, `grad`, or `turn`,
+///    such as `hsl(32deg 100% 50%)` and `hsla(0.1turn, 100%, 50%, 0.5)`.
+///  - The keyword `transparent`.
+///
+/// Both the comma-separated and the space-separated syntax are supported,
+/// the latter with an optional alpha component after a slash.
+/// Like in CSS, components outside their range are clamped.
+impl FromStr for Color
+{
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let s = s.trim().to_ascii_lowercase();
+
+        if s == "transparent" {
+            return Ok(Self::TRANSPARENT);
+        }
+
+        if let Some(hex) = s.strip_prefix('#') {
+            return parse_hex(hex);
+        }
+
+        let open = s.find('(').ok_or(ParseColorError::InvalidSyntax)?;
+        let arguments = s[open + 1 ..].strip_suffix(')')
+            .ok_or(ParseColorError::InvalidSyntax)?;
+        let arguments = split_arguments(arguments)?;
+        match s[.. open].trim_end() {
+            "rgb" | "rgba" => parse_rgb(&arguments),
+            "hsl" | "hsla" => parse_hsl(&arguments),
+            _ => Err(ParseColorError::UnknownFunction),
+        }
+    }
+}
+
+fn parse_hex(hex: &str) -> Result<Color, ParseColorError>
+{
+    let digits: Vec<u8> =
+        hex.chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<_>>()
+        .ok_or(ParseColorError::InvalidHex)?;
+
+    let rgba = match *digits.as_slice() {
+        [r, g, b] => [r * 17, g * 17, b * 17, 255],
+        [r, g, b, a] => [r * 17, g * 17, b * 17, a * 17],
+        [r1, r2, g1, g2, b1, b2] => [r1 << 4 | r2, g1 << 4 | g2, b1 << 4 | b2, 255],
+        [r1, r2, g1, g2, b1, b2, a1, a2] =>
+            [r1 << 4 | r2, g1 << 4 | g2, b1 << 4 | b2, a1 << 4 | a2],
+        _ => return Err(ParseColorError::InvalidHex),
+    };
+    Ok(Color::from_rgba8888(rgba))
+}
+
+/// Split the arguments of a color function,
+/// using either the comma-separated or the space-separated syntax.
+///
+/// The alpha component, if any, is always the fourth argument.
+fn split_arguments(arguments: &str) -> Result<Vec<&str>, ParseColorError>
+{
+    let arguments: Vec<&str> =
+        if arguments.contains(',') {
+            arguments.split(',').map(str::trim).collect()
+        } else {
+            let (components, alpha) = match arguments.split_once('/') {
+                Some((components, alpha)) => (components, Some(alpha.trim())),
+                None => (arguments, None),
+            };
+            let mut arguments: Vec<&str> = components.split_whitespace().collect();
+            if arguments.len() != 3 && alpha.is_some() {
+                return Err(ParseColorError::InvalidArguments);
+            }
+            arguments.extend(alpha);
+            arguments
+        };
+    if arguments.len() != 3 && arguments.len() != 4 {
+        return Err(ParseColorError::InvalidArguments);
+    }
+    Ok(arguments)
+}
+
+/// Parse a number or a percentage,
+/// where `scale` is the number that corresponds to 100%.
+fn parse_number(s: &str, scale: f32) -> Result<f32, ParseColorError>
+{
+    let (number, factor) = match s.strip_suffix('%') {
+        Some(number) => (number, scale / 100.0),
+        None => (s, 1.0),
+    };
+    let number: f32 = number.parse().map_err(|_| ParseColorError::InvalidArguments)?;
+    if !number.is_finite() {
+        return Err(ParseColorError::InvalidArguments);
+    }
+    Ok(number * factor)
+}
+
+/// Parse an angle in degrees.
+fn parse_angle(s: &str) -> Result<f32, ParseColorError>
+{
+    // "grad" ends in "rad", so it must be tried first.
+    let units = [
+        ("deg", 1.0),
+        ("grad", 0.9),
+        ("rad", 180.0 / std::f32::consts::PI),
+        ("turn", 360.0),
+    ];
+    let (number, factor) =
+        units.iter()
+        .find_map(|&(unit, factor)| Some((s.strip_suffix(unit)?, factor)))
+        .unwrap_or((s, 1.0));
+    parse_number(number, 1.0).map(|number| number * factor)
+}
+
+fn parse_alpha(arguments: &[&str]) -> Result<f32, ParseColorError>
+{
+    match arguments.get(3) {
+        Some(alpha) => Ok(parse_number(alpha, 1.0)?.clamp(0.0, 1.0)),
+        None => Ok(1.0),
+    }
+}
+
+fn parse_rgb(arguments: &[&str]) -> Result<Color, ParseColorError>
+{
+    let mut rgb = [0.0; 3];
+    for (c, argument) in rgb.iter_mut().zip(arguments) {
+        *c = parse_number(argument, 255.0)?.clamp(0.0, 255.0) / 255.0;
+    }
+    let [r, g, b] = rgb;
+    Ok(Color{r, g, b, a: parse_alpha(arguments)?})
+}
+
+fn parse_hsl(arguments: &[&str]) -> Result<Color, ParseColorError>
+{
+    let h = parse_angle(arguments[0])?;
+    let s = parse_number(arguments[1], 100.0)?.clamp(0.0, 100.0) / 100.0;
+    let l = parse_number(arguments[2], 100.0)?.clamp(0.0, 100.0) / 100.0;
+    Ok(Hsl{h, s, l, a: parse_alpha(arguments)?}.into())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_parse()
+    {
+        let parse = |s: &str| s.parse::<Color>().map(Color::to_rgba8888);
+        assert_eq!(parse("#f80"),      Ok([255, 136, 0, 255]));
+        assert_eq!(parse("#F808"),     Ok([255, 136, 0, 136]));
+        assert_eq!(parse("#ff8800"),   Ok([255, 136, 0, 255]));
+        assert_eq!(parse("#ff880080"), Ok([255, 136, 0, 128]));
+        assert_eq!(parse(" transparent "), Ok([0, 0, 0, 0]));
+        assert_eq!(parse("rgb(255, 136, 0)"), Ok([255, 136, 0, 255]));
+        assert_eq!(parse("RGBA(100%, 0%, 300, 0.5)"), Ok([255, 0, 255, 128]));
+        assert_eq!(parse("rgb(255 136 0 / 50%)"), Ok([255, 136, 0, 128]));
+        assert_eq!(parse("hsl(0, 100%, 50%)"), Ok([255, 0, 0, 255]));
+        assert_eq!(parse("hsl(120deg 100% 25%)"), Ok([0, 128, 0, 255]));
+        assert_eq!(parse("hsla(0.5turn, 100%, 50%, 0)"), Ok([0, 255, 255, 0]));
+        assert_eq!(parse("hsl(400grad 0% 100%)"), Ok([255, 255, 255, 255]));
+        assert_eq!(parse("hsl(3.14159rad 100% 50%)"), Ok([0, 255, 255, 255]));
+    }
+
+    #[test]
+    fn test_parse_errors()
+    {
+        let parse = |s: &str| s.parse::<Color>();
+        assert_eq!(parse("red"),            Err(ParseColorError::InvalidSyntax));
+        assert_eq!(parse("rgb(1, 2, 3"),    Err(ParseColorError::InvalidSyntax));
+        assert_eq!(parse("#ff"),            Err(ParseColorError::InvalidHex));
+        assert_eq!(parse("#ggg"),           Err(ParseColorError::InvalidHex));
+        assert_eq!(parse("lab(1, 2, 3)"),   Err(ParseColorError::UnknownFunction));
+        assert_eq!(parse("rgb(1, 2)"),      Err(ParseColorError::InvalidArguments));
+        assert_eq!(parse("rgb(1 2 / 3)"),   Err(ParseColorError::InvalidArguments));
+        assert_eq!(parse("rgb(1, x, 3)"),   Err(ParseColorError::InvalidArguments));
+        assert_eq!(parse("rgb(1, inf, 3)"), Err(ParseColorError::InvalidArguments));
+    }
+}
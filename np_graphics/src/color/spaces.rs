@@ -0,0 +1,364 @@
+use crate::Color;
+
+/// Color in the linear sRGB color space.
+///
+/// This has the same primaries as [`Color`],
+/// but without the sRGB transfer function,
+/// so the components are proportional to light intensity.
+/// Physically correct blending happens in this color space.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LinearRgb
+{
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+/// Color in the HSL color space, as used by CSS.
+///
+/// The hue is in degrees from 0 up to but excluding 360,
+/// and the saturation and lightness range from zero to one.
+/// The hue of grays is zero.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hsl
+{
+    pub h: f32,
+    pub s: f32,
+    pub l: f32,
+    pub a: f32,
+}
+
+/// Color in the HSV color space, also known as HSB.
+///
+/// The hue is in degrees from 0 up to but excluding 360,
+/// and the saturation and value range from zero to one.
+/// The hue of grays is zero.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hsv
+{
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+    pub a: f32,
+}
+
+/// Color in the Oklab color space.
+///
+/// Oklab is a perceptual color space:
+/// equal distances correspond to roughly equal perceived differences,
+/// which makes it good for interpolation.
+/// The lightness ranges from zero (black) to one (white),
+/// and `a` and `b` are the green–red and blue–yellow axes.
+/// The alpha component is called `alpha` to avoid confusion with `a`.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Oklab
+{
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+/// Color in the Oklch color space.
+///
+/// This is Oklab in polar coordinates:
+/// the chroma is the distance from gray,
+/// and the hue is the angle in degrees from 0 up to but excluding 360.
+/// The hue of grays is zero.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Oklch
+{
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+    pub alpha: f32,
+}
+
+/// Decode a component with the sRGB transfer function.
+///
+/// Negative components are mirrored, to support colors out of gamut.
+fn srgb_to_linear(c: f32) -> f32
+{
+    let abs = c.abs();
+    let linear =
+        if abs <= 0.04045 { abs / 12.92 }
+        else { ((abs + 0.055) / 1.055).powf(2.4) };
+    linear.copysign(c)
+}
+
+/// Encode a component with the sRGB transfer function.
+fn linear_to_srgb(c: f32) -> f32
+{
+    let abs = c.abs();
+    let srgb =
+        if abs <= 0.0031308 { abs * 12.92 }
+        else { 1.055 * abs.powf(1.0 / 2.4) - 0.055 };
+    srgb.copysign(c)
+}
+
+/// The hue in degrees of a color with the given components,
+/// shared by the HSL and HSV conversions.
+fn hue(r: f32, g: f32, b: f32, max: f32, delta: f32) -> f32
+{
+    if delta == 0.0 {
+        return 0.0;
+    }
+    let h =
+        if max == r { (g - b) / delta }
+        else if max == g { (b - r) / delta + 2.0 }
+        else { (r - g) / delta + 4.0 };
+    normalize_hue(h * 60.0)
+}
+
+/// Wrap a hue in degrees into the range from 0 up to but excluding 360.
+pub(crate) fn normalize_hue(h: f32) -> f32
+{
+    let h = h.rem_euclid(360.0);
+    // rem_euclid may round up to exactly 360.
+    if h >= 360.0 { 0.0 } else { h }
+}
+
+/// How far the red, green, and blue components of a hue
+/// are from the maximum, between zero (at maximum) and one (at minimum),
+/// for a fully saturated color.
+fn hue_to_rgb(h: f32) -> [f32; 3]
+{
+    let h = normalize_hue(h) / 60.0;
+    let component = |n: f32| {
+        let k = (n + h) % 6.0;
+        f32::min(k, 4.0 - k).clamp(0.0, 1.0)
+    };
+    [component(5.0), component(3.0), component(1.0)]
+}
+
+impl From<Color> for LinearRgb
+{
+    fn from(color: Color) -> Self
+    {
+        let Color{r, g, b, a} = color;
+        Self{r: srgb_to_linear(r), g: srgb_to_linear(g), b: srgb_to_linear(b), a}
+    }
+}
+
+impl From<LinearRgb> for Color
+{
+    fn from(color: LinearRgb) -> Self
+    {
+        let LinearRgb{r, g, b, a} = color;
+        Self{r: linear_to_srgb(r), g: linear_to_srgb(g), b: linear_to_srgb(b), a}
+    }
+}
+
+impl From<Color> for Hsl
+{
+    fn from(color: Color) -> Self
+    {
+        let Color{r, g, b, a} = color;
+        let max = f32::max(r, f32::max(g, b));
+        let min = f32::min(r, f32::min(g, b));
+        let delta = max - min;
+        let l = (max + min) / 2.0;
+        let s =
+            if delta == 0.0 { 0.0 }
+            else { delta / (1.0 - (2.0 * l - 1.0).abs()) };
+        Self{h: hue(r, g, b, max, delta), s, l, a}
+    }
+}
+
+impl From<Hsl> for Color
+{
+    fn from(color: Hsl) -> Self
+    {
+        let Hsl{h, s, l, a} = color;
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let [r, g, b] = hue_to_rgb(h);
+        let component = |c| l + chroma * (0.5 - c);
+        Self{r: component(r), g: component(g), b: component(b), a}
+    }
+}
+
+impl From<Color> for Hsv
+{
+    fn from(color: Color) -> Self
+    {
+        let Color{r, g, b, a} = color;
+        let max = f32::max(r, f32::max(g, b));
+        let min = f32::min(r, f32::min(g, b));
+        let delta = max - min;
+        let s = if max == 0.0 { 0.0 } else { delta / max };
+        Self{h: hue(r, g, b, max, delta), s, v: max, a}
+    }
+}
+
+impl From<Hsv> for Color
+{
+    fn from(color: Hsv) -> Self
+    {
+        let Hsv{h, s, v, a} = color;
+        let [r, g, b] = hue_to_rgb(h);
+        let component = |c| v - v * s * c;
+        Self{r: component(r), g: component(g), b: component(b), a}
+    }
+}
+
+// Coefficients as published with Oklab.
+#[allow(clippy::excessive_precision)]
+impl From<LinearRgb> for Oklab
+{
+    fn from(color: LinearRgb) -> Self
+    {
+        let LinearRgb{r, g, b, a: alpha} = color;
+        let l = 0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b;
+        let m = 0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b;
+        let s = 0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b;
+        let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+        Self{
+            l: 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            a: 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            b: 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+            alpha,
+        }
+    }
+}
+
+// Coefficients as published with Oklab.
+#[allow(clippy::excessive_precision)]
+impl From<Oklab> for LinearRgb
+{
+    fn from(color: Oklab) -> Self
+    {
+        let Oklab{l, a, b, alpha} = color;
+        let l_ = l + 0.3963377774 * a + 0.2158037573 * b;
+        let m_ = l - 0.1055613458 * a - 0.0638541728 * b;
+        let s_ = l - 0.0894841775 * a - 1.2914855480 * b;
+        let (l, m, s) = (l_ * l_ * l_, m_ * m_ * m_, s_ * s_ * s_);
+        Self{
+            r:  4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+            g: -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+            b: -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+            a: alpha,
+        }
+    }
+}
+
+impl From<Color> for Oklab
+{
+    fn from(color: Color) -> Self
+    {
+        LinearRgb::from(color).into()
+    }
+}
+
+impl From<Oklab> for Color
+{
+    fn from(color: Oklab) -> Self
+    {
+        LinearRgb::from(color).into()
+    }
+}
+
+impl From<Oklab> for Oklch
+{
+    fn from(color: Oklab) -> Self
+    {
+        let Oklab{l, a, b, alpha} = color;
+        let c = f32::hypot(a, b);
+        // Rounding errors give grays a tiny chroma with an arbitrary hue.
+        let h = if c < 1e-4 { 0.0 } else { normalize_hue(b.atan2(a).to_degrees()) };
+        Self{l, c, h, alpha}
+    }
+}
+
+impl From<Oklch> for Oklab
+{
+    fn from(color: Oklch) -> Self
+    {
+        let Oklch{l, c, h, alpha} = color;
+        let (sin, cos) = h.to_radians().sin_cos();
+        Self{l, a: c * cos, b: c * sin, alpha}
+    }
+}
+
+impl From<Color> for Oklch
+{
+    fn from(color: Color) -> Self
+    {
+        Oklab::from(color).into()
+    }
+}
+
+impl From<Oklch> for Color
+{
+    fn from(color: Oklch) -> Self
+    {
+        Oklab::from(color).into()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn assert_close(a: [f32; 4], b: [f32; 4])
+    {
+        for (a, b) in a.iter().zip(&b) {
+            assert!((a - b).abs() < 1e-3, "{:?} != {:?}", a, b);
+        }
+    }
+
+    fn rgba(Color{r, g, b, a}: Color) -> [f32; 4]
+    {
+        [r, g, b, a]
+    }
+
+    #[test]
+    fn test_known_values()
+    {
+        let red = Color::new(1.0, 0.0, 0.0, 1.0);
+        assert_eq!(Hsl::from(red), Hsl{h: 0.0, s: 1.0, l: 0.5, a: 1.0});
+        assert_eq!(Hsv::from(red), Hsv{h: 0.0, s: 1.0, v: 1.0, a: 1.0});
+
+        let Hsl{h, ..} = Color::new(0.0, 0.0, 1.0, 1.0).into();
+        assert_eq!(h, 240.0);
+
+        let Oklab{l, a, b, ..} = Color::WHITE.into();
+        assert_close([l, a, b, 1.0], [1.0, 0.0, 0.0, 1.0]);
+
+        let Oklab{l, a, b, ..} = red.into();
+        assert_close([l, a, b, 1.0], [0.628, 0.2249, 0.1258, 1.0]);
+
+        let LinearRgb{r, ..} = Color::new(0.5, 0.0, 0.0, 1.0).into();
+        assert!((r - 0.2140).abs() < 1e-3);
+
+        let Oklch{c, h, ..} = Color::new(0.5, 0.5, 0.5, 1.0).into();
+        assert!(c < 1e-4);
+        assert_eq!(h, 0.0);
+    }
+
+    #[test]
+    fn test_round_trips()
+    {
+        let colors = [
+            Color::new(0.2, 0.4, 0.6, 1.0),
+            Color::new(0.9, 0.1, 0.3, 0.5),
+            Color::new(0.0, 1.0, 0.5, 0.0),
+            Color::new(0.7, 0.7, 0.1, 1.0),
+            Color::WHITE,
+            Color::BLACK,
+        ];
+        for &color in colors.iter() {
+            assert_close(rgba(Hsl::from(color).into()), rgba(color));
+            assert_close(rgba(Hsv::from(color).into()), rgba(color));
+            assert_close(rgba(LinearRgb::from(color).into()), rgba(color));
+            assert_close(rgba(Oklab::from(color).into()), rgba(color));
+            assert_close(rgba(Oklch::from(color).into()), rgba(color));
+        }
+    }
+}
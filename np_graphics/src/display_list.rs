@@ -0,0 +1,895 @@
+//! Recording drawing commands for later replay.
+//!
+//! A [`RecordingCanvas`] does not draw anything itself.
+//! Instead it records the drawing commands it receives
+//! into a [`DisplayList`], which can be replayed onto any other canvas.
+//! Display lists also have a stable byte encoding,
+//! so drawing commands can be sent to other processes
+//! rather than the pixels they would produce.
+
+use crate::BlendMode;
+use crate::Bounds;
+use crate::Canvas;
+use crate::Matrix;
+use crate::Paint;
+use crate::PathBuf;
+use crate::Vector;
+use crate::path::Instruction;
+use crate::path::Instructions;
+use crate::path::Operands;
+use crate::path::Word;
+use crate::path;
+
+use std::convert::TryInto;
+use std::error;
+use std::fmt;
+use std::ops::Range;
+
+/// Canvas that records drawing commands into a display list.
+pub struct RecordingCanvas<T>
+{
+    display_list: DisplayList<T>,
+}
+
+impl<T> RecordingCanvas<T>
+{
+    /// Create a canvas with an empty display list.
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// The commands recorded so far.
+    pub fn display_list(&self) -> &DisplayList<T>
+    {
+        &self.display_list
+    }
+
+    /// Stop recording and return the recorded commands.
+    pub fn into_display_list(self) -> DisplayList<T>
+    {
+        self.display_list
+    }
+}
+
+impl<T> Default for RecordingCanvas<T>
+{
+    fn default() -> Self
+    {
+        Self{display_list: DisplayList::new()}
+    }
+}
+
+impl<T> Canvas for RecordingCanvas<T>
+    where T: Copy
+{
+    type Pixel = T;
+
+    fn rectangle(
+        &mut self,
+        matrix: Matrix,
+        start: Vector,
+        extent: Vector,
+        paint: Paint<T>,
+    )
+    {
+        self.display_list.push_rectangle(matrix, start, extent, paint);
+    }
+
+    fn path<P: IntoIterator<Item=Instruction>>(
+        &mut self,
+        matrix: Matrix,
+        path: P,
+        paint: Paint<T>,
+    )
+    {
+        self.display_list.push_path(matrix, path, paint);
+    }
+
+    fn push_layer(&mut self, bounds: Bounds, opacity: f32, blend_mode: BlendMode)
+    {
+        self.display_list.push_layer(bounds, opacity, blend_mode);
+    }
+
+    fn pop_layer(&mut self)
+    {
+        self.display_list.pop_layer();
+    }
+}
+
+/// Sequence of recorded drawing commands.
+///
+/// The path instructions of all commands are stored
+/// in a single buffer of packed instructions,
+/// so recording a path does not allocate once the buffer is large enough.
+/// Each command also stores the bounding box of what it draws,
+/// which is used for culling when replaying.
+/// Commands within a layer are clipped to the bounds of the layer,
+/// and so are their bounding boxes.
+/// The commands that push and pop a layer
+/// both have the bounding box of the layer,
+/// so that culling never separates them.
+pub struct DisplayList<T>
+{
+    commands: Vec<Command<T>>,
+    words: Vec<Word>,
+
+    // The bounding boxes of the layers that are pushed, innermost last.
+    layers: Vec<Bounds>,
+}
+
+enum Command<T>
+{
+    Draw{
+        matrix: Matrix,
+        paint: Paint<T>,
+        bounds: Bounds,
+        shape: Shape,
+    },
+    PushLayer{
+        bounds: Bounds,
+        opacity: f32,
+        blend_mode: BlendMode,
+    },
+    PopLayer{
+        bounds: Bounds,
+    },
+}
+
+impl<T> Command<T>
+{
+    fn bounds(&self) -> Bounds
+    {
+        match *self {
+            Self::Draw{bounds, ..} => bounds,
+            Self::PushLayer{bounds, ..} => bounds,
+            Self::PopLayer{bounds} => bounds,
+        }
+    }
+}
+
+enum Shape
+{
+    Rectangle(Vector, Vector),
+    Path(Range<usize>),
+}
+
+impl<T> DisplayList<T>
+{
+    /// Create an empty display list.
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// The number of commands in the display list.
+    pub fn len(&self) -> usize
+    {
+        self.commands.len()
+    }
+
+    /// Whether the display list has no commands.
+    pub fn is_empty(&self) -> bool
+    {
+        self.commands.is_empty()
+    }
+
+    /// Remove all commands, keeping the allocated memory for reuse.
+    pub fn clear(&mut self)
+    {
+        self.commands.clear();
+        self.words.clear();
+        self.layers.clear();
+    }
+
+    /// The bounding box of everything drawn by the display list.
+    ///
+    /// This is in the coordinate system of the canvas,
+    /// that is, with each command’s matrix applied.
+    pub fn bounds(&self) -> Bounds
+    {
+        self.commands.iter()
+            .map(Command::bounds)
+            .fold(Bounds::EMPTY, Bounds::union)
+    }
+
+    /// The bounding box of the command at the given index.
+    ///
+    /// # Panics
+    ///
+    /// If there is no command at the given index,
+    /// this function panics.
+    pub fn command_bounds(&self, index: usize) -> Bounds
+    {
+        self.commands[index].bounds()
+    }
+
+    /// Record a [`Canvas::rectangle`] command.
+    pub fn push_rectangle(
+        &mut self,
+        matrix: Matrix,
+        start: Vector,
+        extent: Vector,
+        paint: Paint<T>,
+    )
+    {
+        let end = start + extent;
+        let corners = [
+            start,
+            Vector{x: end.x, y: start.y},
+            end,
+            Vector{x: start.x, y: end.y},
+        ];
+        let bounds = Bounds::from_points(corners.iter().map(|&p| matrix * p));
+        let bounds = self.clip_to_layer(bounds);
+        let shape = Shape::Rectangle(start, extent);
+        self.commands.push(Command::Draw{matrix, paint, bounds, shape});
+    }
+
+    /// Record a [`Canvas::path`] command.
+    pub fn push_path<P>(&mut self, matrix: Matrix, path: P, paint: Paint<T>)
+        where P: IntoIterator<Item=Instruction>
+    {
+        let start = self.words.len();
+
+        // Bézier curves lie within the convex hull of their control points,
+        // so the bounding box of the control points contains the path.
+        // Paths that do not begin with a move begin at the origin.
+        let mut bounds = Bounds::EMPTY;
+        for instruction in path {
+            let first = self.words.len() == start;
+            if first && !matches!(instruction, Instruction::Move(..)) {
+                bounds = bounds.include(matrix * Vector{x: 0.0, y: 0.0});
+            }
+            instruction.to_words(&mut self.words);
+            bounds = match matrix * instruction {
+                Instruction::Move(p) => bounds.include(p),
+                Instruction::Linear(p1) => bounds.include(p1),
+                Instruction::Quadratic(p1, p2) =>
+                    bounds.include(p1).include(p2),
+                Instruction::Cubic(p1, p2, p3) =>
+                    bounds.include(p1).include(p2).include(p3),
+            };
+        }
+
+        let bounds = self.clip_to_layer(bounds);
+        let shape = Shape::Path(start .. self.words.len());
+        self.commands.push(Command::Draw{matrix, paint, bounds, shape});
+    }
+
+    /// Record a [`Canvas::push_layer`] command.
+    pub fn push_layer(&mut self, bounds: Bounds, opacity: f32, blend_mode: BlendMode)
+    {
+        let bounds = self.clip_to_layer(bounds);
+        self.layers.push(bounds);
+        self.commands.push(Command::PushLayer{bounds, opacity, blend_mode});
+    }
+
+    /// Record a [`Canvas::pop_layer`] command.
+    ///
+    /// If no layer is pushed, nothing is recorded.
+    pub fn pop_layer(&mut self)
+    {
+        if let Some(bounds) = self.layers.pop() {
+            self.commands.push(Command::PopLayer{bounds});
+        }
+    }
+
+    /// Clip the bounding box of a command to the innermost layer.
+    fn clip_to_layer(&self, bounds: Bounds) -> Bounds
+    {
+        match self.layers.last() {
+            None => bounds,
+            // Bounding boxes that are not finite may still draw,
+            // but not outside of the layer.
+            Some(&layer) if !bounds.is_finite() => layer,
+            Some(&layer) => bounds.intersection(layer),
+        }
+    }
+}
+
+impl<T> Default for DisplayList<T>
+{
+    fn default() -> Self
+    {
+        Self{commands: Vec::new(), words: Vec::new(), layers: Vec::new()}
+    }
+}
+
+impl<T> DisplayList<T>
+    where T: Copy
+{
+    /// Perform all commands on the given canvas, in order.
+    pub fn replay<C>(&self, canvas: &mut C)
+        where C: Canvas<Pixel=T>
+    {
+        for command in &self.commands {
+            self.replay_command(command, canvas);
+        }
+    }
+
+    /// Perform the commands that draw within the given bounding box.
+    ///
+    /// Commands whose bounding boxes do not intersect `bounds`
+    /// are skipped, because they cannot affect any pixels within it.
+    /// This includes commands with NaN coordinates.
+    pub fn replay_culled<C>(&self, canvas: &mut C, bounds: Bounds)
+        where C: Canvas<Pixel=T>
+    {
+        for command in &self.commands {
+            if command.bounds().intersects(bounds) {
+                self.replay_command(command, canvas);
+            }
+        }
+    }
+
+    /// Perform the command at the given index.
+    ///
+    /// # Panics
+    ///
+    /// If there is no command at the given index,
+    /// this function panics.
+    pub fn replay_one<C>(&self, index: usize, canvas: &mut C)
+        where C: Canvas<Pixel=T>
+    {
+        self.replay_command(&self.commands[index], canvas);
+    }
+
+    fn replay_command<C>(&self, command: &Command<T>, canvas: &mut C)
+        where C: Canvas<Pixel=T>
+    {
+        match *command {
+            Command::Draw{matrix, paint, shape: Shape::Rectangle(start, extent), ..} =>
+                canvas.rectangle(matrix, start, extent, paint),
+            Command::Draw{matrix, paint, shape: Shape::Path(ref range), ..} =>
+                canvas.path(matrix, self.path(range.clone()), paint),
+            Command::PushLayer{bounds, opacity, blend_mode} =>
+                canvas.push_layer(bounds, opacity, blend_mode),
+            Command::PopLayer{..} =>
+                canvas.pop_layer(),
+        }
+    }
+
+    fn path(&self, range: Range<usize>) -> Instructions<'_>
+    {
+        // SAFETY: Commands only refer to ranges of words
+        // SAFETY: that were written by Instruction::to_words,
+        // SAFETY: or checked by PathBuf::from_words.
+        unsafe { Instructions::from_words_unchecked(&self.words[range]) }
+    }
+}
+
+/// Pixel types that can be stored in the byte encoding of a display list.
+pub trait PixelBytes: Copy
+{
+    /// The number of bytes in the encoding of each pixel.
+    const SIZE: usize;
+
+    /// Append the encoding of the pixel to a buffer.
+    fn write_bytes(self, buf: &mut Vec<u8>);
+
+    /// Decode a pixel from exactly [`PixelBytes::SIZE`] bytes.
+    fn read_bytes(bytes: &[u8]) -> Self;
+}
+
+impl<const N: usize> PixelBytes for [u8; N]
+{
+    const SIZE: usize = N;
+
+    fn write_bytes(self, buf: &mut Vec<u8>)
+    {
+        buf.extend_from_slice(&self);
+    }
+
+    fn read_bytes(bytes: &[u8]) -> Self
+    {
+        bytes.try_into().unwrap()
+    }
+}
+
+impl<T> DisplayList<T>
+    where T: PixelBytes
+{
+    /// The magic number at the start of the byte encoding.
+    pub const BYTES_MAGIC: [u8; 4] = *b"NPdl";
+
+    /// The version of the byte encoding produced by [`DisplayList::to_bytes`].
+    ///
+    /// Version 2 added layers.
+    /// Encodings of version 1 are still decoded,
+    /// as they are valid encodings of version 2.
+    pub const BYTES_VERSION: u32 = 2;
+
+    /// Encode the display list as a sequence of bytes.
+    ///
+    /// Like the encoding of [`PathBuf::to_bytes`],
+    /// this encoding is stable and all integers are little-endian.
+    /// It begins with the magic number [`DisplayList::BYTES_MAGIC`],
+    /// the version number [`DisplayList::BYTES_VERSION`] as a `u32`,
+    /// the pixel size in bytes as a `u32`,
+    /// and the number of commands as a `u32`.
+    /// Each command then begins with the kind of command as a `u8`:
+    /// 0 for rectangles, 1 for paths,
+    /// 2 for pushing a layer, and 3 for popping a layer.
+    /// Rectangles and paths continue with the following fields:
+    ///
+    ///  1. The six components of the matrix as `f64`s,
+    ///     in the order they are declared in [`Matrix`].
+    ///  2. The blend mode as a `u8`:
+    ///     0 for [`Source`], 1 for [`SourceOver`],
+    ///     and 2 for [`Destination`].
+    ///  3. The pixel, as encoded by [`PixelBytes`].
+    ///  4. For rectangles, the start and extent as four `f64`s.
+    ///     For paths, the number of words as a `u32`,
+    ///     followed by the words as `u64`s,
+    ///     as described for [`PathBuf::to_bytes`].
+    ///
+    /// Pushing a layer continues with the bounds
+    /// as four `f64`s (the minimum and then the maximum),
+    /// the opacity as an `f32`, and the blend mode as a `u8`.
+    /// Popping a layer has no further fields.
+    ///
+    /// [`Source`]: `BlendMode::Source`
+    /// [`SourceOver`]: `BlendMode::SourceOver`
+    /// [`Destination`]: `BlendMode::Destination`
+    pub fn to_bytes(&self) -> Vec<u8>
+    {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&Self::BYTES_MAGIC);
+        buf.extend_from_slice(&Self::BYTES_VERSION.to_le_bytes());
+        buf.extend_from_slice(&(T::SIZE as u32).to_le_bytes());
+        buf.extend_from_slice(&(self.commands.len() as u32).to_le_bytes());
+
+        let write_f64 = |buf: &mut Vec<u8>, value: f64| {
+            buf.extend_from_slice(&value.to_le_bytes());
+        };
+
+        for command in &self.commands {
+            match *command {
+                Command::Draw{matrix, paint, ref shape, ..} => {
+                    let Matrix{a, b, c, d, tx, ty} = matrix;
+                    let kind = match shape {
+                        Shape::Rectangle(..) => 0,
+                        Shape::Path(..) => 1,
+                    };
+                    buf.push(kind);
+                    for &component in &[a, b, c, d, tx, ty] {
+                        write_f64(&mut buf, component);
+                    }
+                    buf.push(encode_blend_mode(paint.blend_mode));
+                    paint.pixel.write_bytes(&mut buf);
+                    match *shape {
+                        Shape::Rectangle(start, extent) => {
+                            for &value in &[start.x, start.y, extent.x, extent.y] {
+                                write_f64(&mut buf, value);
+                            }
+                        },
+                        Shape::Path(ref range) => {
+                            let words = &self.words[range.clone()];
+                            buf.extend_from_slice(&(words.len() as u32).to_le_bytes());
+                            for word in words {
+                                buf.extend_from_slice(&word.to_bits().to_le_bytes());
+                            }
+                        },
+                    }
+                },
+                Command::PushLayer{bounds, opacity, blend_mode} => {
+                    buf.push(2);
+                    let Bounds{min, max} = bounds;
+                    for &value in &[min.x, min.y, max.x, max.y] {
+                        write_f64(&mut buf, value);
+                    }
+                    buf.extend_from_slice(&opacity.to_le_bytes());
+                    buf.push(encode_blend_mode(blend_mode));
+                },
+                Command::PopLayer{..} => {
+                    buf.push(3);
+                },
+            }
+        }
+
+        buf
+    }
+
+    /// Decode a display list from the encoding produced by [`to_bytes`].
+    ///
+    /// The path instructions are checked as with [`PathBuf::from_words`],
+    /// accepting any operands.
+    /// Commands that pop a layer when no layer is pushed are ignored,
+    /// like they are when recording.
+    /// If the encoding is invalid, this function returns [`Err`].
+    ///
+    /// [`to_bytes`]: `DisplayList::to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError>
+    {
+        let mut reader = Reader{bytes, offset: 0};
+
+        if reader.take(4)? != Self::BYTES_MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+        let version = reader.u32()?;
+        if version != 1 && version != Self::BYTES_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        let pixel_size = reader.u32()?;
+        if pixel_size as usize != T::SIZE {
+            return Err(DecodeError::PixelSizeMismatch(pixel_size));
+        }
+        let count = reader.u32()?;
+
+        let mut this = Self::new();
+        for _ in 0 .. count {
+            let offset = reader.offset;
+            let kind = reader.u8()?;
+
+            match kind {
+                0 | 1 => (),
+                2 => {
+                    let min = Vector{x: reader.f64()?, y: reader.f64()?};
+                    let max = Vector{x: reader.f64()?, y: reader.f64()?};
+                    let opacity = reader.f32()?;
+                    let blend_mode = decode_blend_mode(reader.u8()?, offset)?;
+                    this.push_layer(Bounds{min, max}, opacity, blend_mode);
+                    continue;
+                },
+                3 => {
+                    this.pop_layer();
+                    continue;
+                },
+                _ => return Err(DecodeError::InvalidCommand(offset)),
+            }
+
+            let mut matrix = [0.0; 6];
+            for component in &mut matrix {
+                *component = reader.f64()?;
+            }
+            let [a, b, c, d, tx, ty] = matrix;
+            let matrix = Matrix{a, b, c, d, tx, ty};
+
+            let blend_mode = decode_blend_mode(reader.u8()?, offset)?;
+            let pixel = T::read_bytes(reader.take(T::SIZE)?);
+            let paint = Paint{blend_mode, pixel};
+
+            if kind == 0 {
+                let start = Vector{x: reader.f64()?, y: reader.f64()?};
+                let extent = Vector{x: reader.f64()?, y: reader.f64()?};
+                this.push_rectangle(matrix, start, extent, paint);
+            } else {
+                let len = reader.u32()?;
+                let words =
+                    (0 .. len)
+                    .map(|_| reader.u64().map(Word::from_bits))
+                    .collect::<Result<_, _>>()?;
+                let path = PathBuf::from_words(words, Operands::Any)
+                    .map_err(DecodeError::Path)?;
+                this.push_path(matrix, path.instructions(), paint);
+            }
+        }
+
+        if reader.offset != bytes.len() {
+            return Err(DecodeError::TrailingBytes);
+        }
+
+        Ok(this)
+    }
+}
+
+fn encode_blend_mode(blend_mode: BlendMode) -> u8
+{
+    match blend_mode {
+        BlendMode::Source => 0,
+        BlendMode::SourceOver => 1,
+        BlendMode::Destination => 2,
+    }
+}
+
+fn decode_blend_mode(byte: u8, offset: usize) -> Result<BlendMode, DecodeError>
+{
+    match byte {
+        0 => Ok(BlendMode::Source),
+        1 => Ok(BlendMode::SourceOver),
+        2 => Ok(BlendMode::Destination),
+        _ => Err(DecodeError::InvalidBlendMode(offset)),
+    }
+}
+
+struct Reader<'a>
+{
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a>
+{
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError>
+    {
+        let bytes = self.bytes.get(self.offset .. self.offset + len)
+            .ok_or(DecodeError::Truncated)?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError>
+    {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError>
+    {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, DecodeError>
+    {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodeError>
+    {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn f64(&mut self) -> Result<f64, DecodeError>
+    {
+        self.u64().map(f64::from_bits)
+    }
+}
+
+/// Why a display list could not be decoded.
+///
+/// Variants that carry an offset refer to the byte offset
+/// of the command in which the problem was found.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DecodeError
+{
+    /// The encoding does not start with the expected magic number.
+    BadMagic,
+
+    /// The encoding has a version this crate does not understand.
+    UnsupportedVersion(u32),
+
+    /// The encoding was made for pixels of a different size.
+    PixelSizeMismatch(u32),
+
+    /// The encoding ends in the middle of a field.
+    Truncated,
+
+    /// There are bytes after the last command.
+    TrailingBytes,
+
+    /// A command is of an unknown kind.
+    InvalidCommand(usize),
+
+    /// A command has an unknown blend mode.
+    InvalidBlendMode(usize),
+
+    /// The instructions of a path command are invalid.
+    Path(path::DecodeError),
+}
+
+impl fmt::Display for DecodeError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self {
+            Self::BadMagic =>
+                write!(f, "Bad magic number"),
+            Self::UnsupportedVersion(version) =>
+                write!(f, "Unsupported version: {}", version),
+            Self::PixelSizeMismatch(size) =>
+                write!(f, "Pixel size mismatch: {}", size),
+            Self::Truncated =>
+                write!(f, "Unexpected end of display list"),
+            Self::TrailingBytes =>
+                write!(f, "Trailing bytes after last command"),
+            Self::InvalidCommand(offset) =>
+                write!(f, "Invalid command at byte {}", offset),
+            Self::InvalidBlendMode(offset) =>
+                write!(f, "Invalid blend mode at byte {}", offset),
+            Self::Path(inner) =>
+                write!(f, "Invalid path: {}", inner),
+        }
+    }
+}
+
+impl error::Error for DecodeError
+{
+    fn source(&self) -> Option<&(dyn error::Error + 'static)>
+    {
+        match self {
+            Self::Path(inner) => Some(inner),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    /// Canvas that logs the commands it receives.
+    struct LogCanvas(Vec<String>);
+
+    impl Canvas for LogCanvas
+    {
+        type Pixel = [u8; 4];
+
+        fn rectangle(
+            &mut self,
+            matrix: Matrix,
+            start: Vector,
+            extent: Vector,
+            paint: Paint<[u8; 4]>,
+        )
+        {
+            let entry = format!("rectangle {:?} {:?} {:?} {:?} {:?}",
+                                matrix, start, extent,
+                                paint.blend_mode, paint.pixel);
+            self.0.push(entry);
+        }
+
+        fn path<P: IntoIterator<Item=Instruction>>(
+            &mut self,
+            matrix: Matrix,
+            path: P,
+            paint: Paint<[u8; 4]>,
+        )
+        {
+            let path: Vec<_> = path.into_iter().collect();
+            let entry = format!("path {:?} {:?} {:?} {:?}",
+                                matrix, path,
+                                paint.blend_mode, paint.pixel);
+            self.0.push(entry);
+        }
+
+        fn push_layer(&mut self, bounds: Bounds, opacity: f32, blend_mode: BlendMode)
+        {
+            let entry = format!("push_layer {:?} {:?} {:?}",
+                                bounds, opacity, blend_mode);
+            self.0.push(entry);
+        }
+
+        fn pop_layer(&mut self)
+        {
+            self.0.push("pop_layer".to_owned());
+        }
+    }
+
+    fn example() -> RecordingCanvas<[u8; 4]>
+    {
+        let paint = Paint{blend_mode: BlendMode::SourceOver, pixel: [1, 2, 3, 4]};
+        let mut canvas = RecordingCanvas::new();
+        canvas.rectangle(
+            Matrix::from_translate(10.0, 10.0),
+            Vector{x: 0.0, y: 0.0},
+            Vector{x: 5.0, y: 5.0},
+            paint,
+        );
+        canvas.path(
+            Matrix::from_scale(2.0, 2.0),
+            vec![
+                Instruction::Move(Vector{x: 100.0, y: 100.0}),
+                Instruction::Quadratic(
+                    Vector{x: 110.0, y: 120.0},
+                    Vector{x: 120.0, y: 100.0},
+                ),
+            ],
+            Paint{blend_mode: BlendMode::Source, ..paint},
+        );
+        canvas
+    }
+
+    fn replayed(display_list: &DisplayList<[u8; 4]>, bounds: Option<Bounds>)
+        -> Vec<String>
+    {
+        let mut canvas = LogCanvas(Vec::new());
+        match bounds {
+            None => display_list.replay(&mut canvas),
+            Some(bounds) => display_list.replay_culled(&mut canvas, bounds),
+        }
+        canvas.0
+    }
+
+    #[test]
+    fn test_replay()
+    {
+        let mut direct = LogCanvas(Vec::new());
+        let recording = example();
+        recording.display_list().replay(&mut direct);
+        assert_eq!(direct.0.len(), 2);
+        assert!(direct.0[0].starts_with("rectangle"));
+        assert!(direct.0[1].starts_with("path"));
+    }
+
+    #[test]
+    fn test_culling()
+    {
+        let display_list = example().into_display_list();
+        let bounds = |x0, y0, x1, y1| Some(Bounds{
+            min: Vector{x: x0, y: y0},
+            max: Vector{x: x1, y: y1},
+        });
+        assert_eq!(replayed(&display_list, bounds(0.0, 0.0, 20.0, 20.0)).len(), 1);
+        assert_eq!(replayed(&display_list, bounds(11.0, 11.0, 12.0, 12.0)).len(), 1);
+        assert_eq!(replayed(&display_list, bounds(16.0, 16.0, 150.0, 150.0)).len(), 0);
+        assert_eq!(replayed(&display_list, bounds(230.0, 230.0, 300.0, 300.0)).len(), 1);
+        assert_eq!(replayed(&display_list, bounds(0.0, 0.0, 300.0, 300.0)).len(), 2);
+    }
+
+    #[test]
+    fn test_bytes_roundtrip()
+    {
+        let display_list = example().into_display_list();
+        let bytes = display_list.to_bytes();
+        let decoded = DisplayList::<[u8; 4]>::from_bytes(&bytes).unwrap();
+        assert_eq!(replayed(&decoded, None), replayed(&display_list, None));
+        assert_eq!(decoded.bounds(), display_list.bounds());
+
+        let result = DisplayList::<[u8; 4]>::from_bytes(&bytes[.. bytes.len() - 1]);
+        assert_eq!(result.err(), Some(DecodeError::Truncated));
+
+        let result = DisplayList::<[u8; 3]>::from_bytes(&bytes);
+        assert_eq!(result.err(), Some(DecodeError::PixelSizeMismatch(4)));
+
+        let mut corrupt = bytes.clone();
+        corrupt[16] = 7;
+        let result = DisplayList::<[u8; 4]>::from_bytes(&corrupt);
+        assert_eq!(result.err(), Some(DecodeError::InvalidCommand(16)));
+
+        // Version 1 had no layers, but is otherwise the same.
+        let mut version_1 = bytes.clone();
+        version_1[4] = 1;
+        let decoded = DisplayList::<[u8; 4]>::from_bytes(&version_1).unwrap();
+        assert_eq!(replayed(&decoded, None), replayed(&display_list, None));
+    }
+
+    #[test]
+    fn test_layers()
+    {
+        let bounds = |x0, y0, x1, y1| Bounds{
+            min: Vector{x: x0, y: y0},
+            max: Vector{x: x1, y: y1},
+        };
+
+        let mut canvas = example();
+        canvas.pop_layer();
+        canvas.push_layer(bounds(0.0, 0.0, 12.0, 12.0), 0.5, BlendMode::SourceOver);
+        canvas.push_layer(bounds(-5.0, 11.0, 20.0, 20.0), 1.0, BlendMode::Source);
+        canvas.rectangle(
+            Matrix::IDENTITY,
+            Vector{x: 5.0, y: 5.0},
+            Vector{x: 10.0, y: 10.0},
+            Paint{blend_mode: BlendMode::SourceOver, pixel: [0; 4]},
+        );
+        canvas.path(
+            Matrix::IDENTITY,
+            vec![Instruction::Linear(Vector{x: f64::NAN, y: 0.0})],
+            Paint{blend_mode: BlendMode::SourceOver, pixel: [0; 4]},
+        );
+        canvas.pop_layer();
+        canvas.pop_layer();
+        let display_list = canvas.into_display_list();
+
+        // Stray pops are not recorded,
+        // and bounds are clipped to the enclosing layers.
+        assert_eq!(display_list.len(), 8);
+        let inner = bounds(0.0, 11.0, 12.0, 12.0);
+        assert_eq!(display_list.command_bounds(2), bounds(0.0, 0.0, 12.0, 12.0));
+        assert_eq!(display_list.command_bounds(3), inner);
+        assert_eq!(display_list.command_bounds(4), bounds(5.0, 11.0, 12.0, 12.0));
+        assert_eq!(display_list.command_bounds(5), inner);
+        assert_eq!(display_list.command_bounds(6), inner);
+        assert_eq!(display_list.command_bounds(7), bounds(0.0, 0.0, 12.0, 12.0));
+
+        // Culling keeps pushes and pops together.
+        let culled = replayed(&display_list, Some(bounds(0.0, 0.0, 1.0, 1.0)));
+        assert_eq!(culled.len(), 2);
+        assert!(culled[0].starts_with("push_layer"));
+        assert_eq!(culled[1], "pop_layer");
+
+        let bytes = display_list.to_bytes();
+        let decoded = DisplayList::<[u8; 4]>::from_bytes(&bytes).unwrap();
+        assert_eq!(replayed(&decoded, None), replayed(&display_list, None));
+    }
+}
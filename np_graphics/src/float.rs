@@ -0,0 +1,13 @@
+use std::cmp::Ordering;
+
+/// Compare numbers such that sorting them never fails.
+///
+/// NaN is equal to itself and greater than every other number.
+/// This stands in for `f64::total_cmp`, which is newer than the toolchain of the development shell.
+pub(crate) fn total_cmp<T>(a: &T, b: &T) -> Ordering
+    where T: PartialOrd
+{
+    #[allow(clippy::eq_op)]
+    let (a_is_nan, b_is_nan) = (a != a, b != b);
+    a.partial_cmp(b).unwrap_or_else(|| a_is_nan.cmp(&b_is_nan))
+}
@@ -40,6 +40,55 @@ pub trait Format
     ///
     /// [`SourceOver`]: `crate::BlendMode::SourceOver`
     fn blend_source_over(&self, dest: &mut [Self::Pixel], source: Self::Pixel);
+
+    /// Blend `source` onto each pixel in `dest`
+    /// using the formula for [`Source`],
+    /// for a shape that covers only part of each pixel.
+    ///
+    /// The coverage ranges from 0 (nothing) to 255 (the whole pixel).
+    /// The result is the destination pixel and the blended pixel
+    /// mixed according to the coverage,
+    /// as if each color component were premultiplied by alpha.
+    /// With full coverage this is the same as the [`Source`] formula.
+    ///
+    /// [`Source`]: `crate::BlendMode::Source`
+    fn blend_source_coverage(
+        &self,
+        dest: &mut [Self::Pixel],
+        source: Self::Pixel,
+        coverage: u8,
+    );
+
+    /// Blend `source` over each pixel in `dest`
+    /// using the formula for [`SourceOver`],
+    /// for a shape that covers only part of each pixel.
+    ///
+    /// The coverage ranges from 0 (nothing) to 255 (the whole pixel),
+    /// and scales the alpha component of the source pixel.
+    ///
+    /// [`SourceOver`]: `crate::BlendMode::SourceOver`
+    fn blend_source_over_coverage(
+        &self,
+        dest: &mut [Self::Pixel],
+        source: Self::Pixel,
+        coverage: u8,
+    );
+
+    /// The color and alpha of the given pixel,
+    /// as 8-bit red, green, blue, and alpha components.
+    ///
+    /// The color components are not premultiplied by alpha.
+    /// This is used by canvases that do not write pixels,
+    /// but need to describe colors in some other way.
+    fn to_rgba8888(&self, pixel: Self::Pixel) -> [u8; 4];
+
+    /// The pixel closest to the given
+    /// 8-bit red, green, blue, and alpha components.
+    ///
+    /// This is the inverse of [`Format::to_rgba8888`],
+    /// and is used to draw colors described in some other way.
+    #[allow(clippy::wrong_self_convention)]
+    fn from_rgba8888(&self, rgba: [u8; 4]) -> Self::Pixel;
 }
 
 impl<'a, F> Format for &'a F
@@ -61,4 +110,34 @@ impl<'a, F> Format for &'a F
     {
         (**self).blend_source_over(dest, source)
     }
+
+    fn blend_source_coverage(
+        &self,
+        dest: &mut [Self::Pixel],
+        source: Self::Pixel,
+        coverage: u8,
+    )
+    {
+        (**self).blend_source_coverage(dest, source, coverage)
+    }
+
+    fn blend_source_over_coverage(
+        &self,
+        dest: &mut [Self::Pixel],
+        source: Self::Pixel,
+        coverage: u8,
+    )
+    {
+        (**self).blend_source_over_coverage(dest, source, coverage)
+    }
+
+    fn to_rgba8888(&self, pixel: Self::Pixel) -> [u8; 4]
+    {
+        (**self).to_rgba8888(pixel)
+    }
+
+    fn from_rgba8888(&self, rgba: [u8; 4]) -> Self::Pixel
+    {
+        (**self).from_rgba8888(rgba)
+    }
 }
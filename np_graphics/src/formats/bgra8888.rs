@@ -46,6 +46,67 @@ impl Format for Bgra8888
 
         }
     }
+
+    fn blend_source_coverage(
+        &self,
+        dest: &mut [Self::Pixel],
+        source: Self::Pixel,
+        coverage: u8,
+    )
+    {
+        let ws = coverage as u32;
+        let wd = 255 - ws;
+        for dest in dest.iter_mut() {
+
+            let [bd, gd, rd, ad] = *dest;
+            let [bs, gs, rs, az] = source;
+
+            let [bd, gd, rd, ad] = [bd as u32, gd as u32, rd as u32, ad as u32];
+            let [bs, gs, rs, az] = [bs as u32, gs as u32, rs as u32, az as u32];
+
+            let ar = (ws * az + wd * ad) / 255;
+            if ar == 0 {
+                *dest = [0, 0, 0, 0];
+                continue;
+            }
+
+            let br = (ws * az * bs + wd * ad * bd) / 255 / ar;
+            let gr = (ws * az * gs + wd * ad * gd) / 255 / ar;
+            let rr = (ws * az * rs + wd * ad * rd) / 255 / ar;
+
+            *dest = [
+                u32::min(br, 255) as u8,
+                u32::min(gr, 255) as u8,
+                u32::min(rr, 255) as u8,
+                ar as u8,
+            ];
+
+        }
+    }
+
+    fn blend_source_over_coverage(
+        &self,
+        dest: &mut [Self::Pixel],
+        source: Self::Pixel,
+        coverage: u8,
+    )
+    {
+        let [bs, gs, rs, az] = source;
+        let az = (az as u32 * coverage as u32 + 127) / 255;
+        self.blend_source_over(dest, [bs, gs, rs, az as u8]);
+    }
+
+    fn to_rgba8888(&self, pixel: Self::Pixel) -> [u8; 4]
+    {
+        let [b, g, r, a] = pixel;
+        [r, g, b, a]
+    }
+
+    fn from_rgba8888(&self, rgba: [u8; 4]) -> Self::Pixel
+    {
+        let [r, g, b, a] = rgba;
+        [b, g, r, a]
+    }
 }
 
 #[cfg(test)]
@@ -75,6 +136,20 @@ mod tests
         }
     }
 
+    #[test]
+    fn test_blend_source_coverage_examples()
+    {
+        // Half coverage of opaque red over opaque blue.
+        let mut dest = [0xFF, 0x00, 0x00, 0xFF];
+        Bgra8888.blend_source_coverage(slice::from_mut(&mut dest), [0x00, 0x00, 0xFF, 0xFF], 128);
+        assert_eq!(dest, [0x7F, 0x00, 0x80, 0xFF]);
+
+        // Half coverage of opaque red over transparent.
+        let mut dest = [0x00, 0x00, 0x00, 0x00];
+        Bgra8888.blend_source_coverage(slice::from_mut(&mut dest), [0x00, 0x00, 0xFF, 0xFF], 128);
+        assert_eq!(dest, [0x00, 0x00, 0xFF, 0x80]);
+    }
+
     proptest! {
 
         #[test]
@@ -108,5 +183,29 @@ mod tests
             assert_eq!(dest, source);
         }
 
+        #[test]
+        fn test_blend_coverage_extremes(
+            dest   in 0x00_00_00_00u32 .. 0xFF_FF_FF_FFu32,
+            source in 0x00_00_00_00u32 .. 0xFF_FF_FF_FFu32,
+        )
+        {
+            let orig_dest = u32::to_be_bytes(dest);
+            let source    = u32::to_be_bytes(source);
+
+            // With full coverage, the blend modes apply as usual.
+            let mut dest = orig_dest;
+            Bgra8888.blend_source_coverage(slice::from_mut(&mut dest), source, 255);
+            assert_eq!(dest, if source[3] == 0 { [0; 4] } else { source });
+            let (mut dest, mut expected) = (orig_dest, orig_dest);
+            Bgra8888.blend_source_over_coverage(slice::from_mut(&mut dest), source, 255);
+            Bgra8888.blend_source_over(slice::from_mut(&mut expected), source);
+            assert_eq!(dest, expected);
+
+            // Without coverage, the destination is left alone.
+            let mut dest = orig_dest;
+            Bgra8888.blend_source_coverage(slice::from_mut(&mut dest), source, 0);
+            assert_eq!(dest, if orig_dest[3] == 0 { [0; 4] } else { orig_dest });
+        }
+
     }
 }
@@ -0,0 +1,258 @@
+use crate::BlendMode;
+use crate::Bounds;
+use crate::Format;
+use crate::Homography;
+use crate::PixelMap;
+use crate::PixelMapMut;
+use crate::Vector;
+
+use std::slice;
+
+/// How to find the color of an image at a point between pixel centers.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Sampling
+{
+    /// Use the pixel that contains the point.
+    ///
+    /// This is fast and keeps edges sharp,
+    /// but looks blocky when images are magnified.
+    Nearest,
+
+    /// Interpolate between the four pixels
+    /// whose centers are nearest to the point.
+    ///
+    /// Pixels are interpolated using [`Format::blend_source_coverage`],
+    /// so transparent pixels do not bleed their color.
+    /// Beyond the outermost pixel centers,
+    /// the outermost pixels are repeated.
+    Bilinear,
+}
+
+/// Draw an image onto a pixel map, transformed with a homography.
+///
+/// This implements [`PixelMapCanvas::image`].
+///
+/// [`PixelMapCanvas::image`]: `crate::PixelMapCanvas::image`
+pub(crate) fn draw_image<F>(
+    format: &F,
+    pixel_map: &mut PixelMapMut<F::Pixel>,
+    homography: Homography,
+    image: &PixelMap<F::Pixel>,
+    sampling: Sampling,
+    blend_mode: BlendMode,
+)
+    where F: Format
+{
+    let (image_x, image_y) = image.extent();
+    if image_x == 0 || image_y == 0 || matches!(blend_mode, BlendMode::Destination) {
+        return;
+    }
+    let inverse = match homography.inverse() {
+        Some(inverse) => inverse,
+        None => return,
+    };
+
+    // If the whole image is in front of the viewer,
+    // it is within the bounding box of its transformed corners.
+    // Otherwise it extends infinitely far, and all pixels are visited.
+    let (origin_x, origin_y) = pixel_map.origin();
+    let (extent_x, extent_y) = pixel_map.extent();
+    let corner = |x: u32, y: u32| homography.transform(Vector{x: x as f64, y: y as f64});
+    let corners = [corner(0, 0), corner(image_x, 0), corner(image_x, image_y), corner(0, image_y)];
+    let (mut x0, mut y0) = (origin_x as f64, origin_y as f64);
+    let (mut x1, mut y1) = (x0 + extent_x as f64, y0 + extent_y as f64);
+    if corners.iter().all(Option::is_some) {
+        let bounds = Bounds::from_points(corners.iter().flatten().copied());
+        if bounds.is_empty() { return; }
+        x0 = f64::max(x0, bounds.min.x.floor());
+        y0 = f64::max(y0, bounds.min.y.floor());
+        x1 = f64::min(x1, bounds.max.x.ceil());
+        y1 = f64::min(y1, bounds.max.y.ceil());
+    }
+    if !(x0 < x1 && y0 < y1) {
+        return;
+    }
+    let (x0, y0, x1, y1) = (x0 as u32, y0 as u32, x1 as u32, y1 as u32);
+
+    for y in y0 .. y1 {
+        let row = pixel_map.horizontal_mut(x0, y, x1 - x0);
+        for (x, dest) in (x0 ..).zip(row) {
+
+            // Map the pixel center back onto the image.
+            // Pixels for which this ends up behind the viewer
+            // do not show the image; without this check,
+            // they would show a mirrored image.
+            let center = Vector{x: x as f64 + 0.5, y: y as f64 + 0.5};
+            let point = match inverse.transform(center) {
+                Some(point) => point,
+                None => continue,
+            };
+            let inside =
+                point.x >= 0.0 && point.x < image_x as f64 &&
+                point.y >= 0.0 && point.y < image_y as f64;
+            if !inside {
+                continue;
+            }
+
+            let source = match sampling {
+                Sampling::Nearest => pixel(image, point.x as u32, point.y as u32),
+                Sampling::Bilinear => bilinear(format, image, point),
+            };
+
+            blend_pixel(format, dest, source, blend_mode);
+
+        }
+    }
+}
+
+/// Blend a single pixel that varies from pixel to pixel,
+/// as is the case when drawing images.
+pub(crate) fn blend_pixel<F>(
+    format: &F,
+    dest: &mut F::Pixel,
+    source: F::Pixel,
+    blend_mode: BlendMode,
+)
+    where F: Format
+{
+    match blend_mode {
+        BlendMode::Source =>
+            *dest = source,
+        BlendMode::SourceOver =>
+            format.blend_source_over(slice::from_mut(dest), source),
+        BlendMode::Destination =>
+            (),
+    }
+}
+
+pub(crate) fn pixel<T>(image: &PixelMap<T>, x: u32, y: u32) -> T
+    where T: Copy
+{
+    let (extent_x, _) = image.extent();
+    image.pixels()[x as usize + y as usize * extent_x as usize]
+}
+
+fn bilinear<F>(format: &F, image: &PixelMap<F::Pixel>, point: Vector) -> F::Pixel
+    where F: Format
+{
+    let (extent_x, extent_y) = image.extent();
+
+    // Coordinates of the nearest pixel centers and the weights between them.
+    let neighbors = |c: f64, extent: u32| {
+        let c = c - 0.5;
+        let floor = c.floor();
+        let weight = ((c - floor) * 255.0).round() as u8;
+        let c0 = (floor as i64).clamp(0, extent as i64 - 1) as u32;
+        let c1 = (floor as i64 + 1).clamp(0, extent as i64 - 1) as u32;
+        (c0, c1, weight)
+    };
+    let (x0, x1, wx) = neighbors(point.x, extent_x);
+    let (y0, y1, wy) = neighbors(point.y, extent_y);
+
+    let lerp = |mut a: F::Pixel, b: F::Pixel, weight: u8| {
+        format.blend_source_coverage(slice::from_mut(&mut a), b, weight);
+        a
+    };
+    let top = lerp(pixel(image, x0, y0), pixel(image, x1, y0), wx);
+    let bottom = lerp(pixel(image, x0, y1), pixel(image, x1, y1), wx);
+    lerp(top, bottom, wy)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use crate::Matrix;
+    use crate::PixelMapCanvas;
+    use crate::formats::Bgra8888;
+
+    /// Image whose pixels contain their own coordinates.
+    fn image() -> PixelMap<[u8; 4]>
+    {
+        let mut image = PixelMap::new([0; 4], 4, 4).unwrap();
+        let mut map = image.as_mut();
+        for y in 0 .. 4 {
+            for x in 0 .. 4 {
+                map.horizontal_mut(x, y, 1)[0] = [x as u8, y as u8, 0, 255];
+            }
+        }
+        image
+    }
+
+    fn draw(homography: Homography, sampling: Sampling) -> PixelMap<[u8; 4]>
+    {
+        let mut pixel_map = PixelMap::new([0; 4], 8, 8).unwrap();
+        let mut canvas = PixelMapCanvas::new(Bgra8888, pixel_map.as_mut());
+        canvas.image(homography, &image(), sampling, BlendMode::SourceOver);
+        pixel_map
+    }
+
+    #[test]
+    fn test_translate_and_scale()
+    {
+        let matrix = Matrix::from_translate(2.0, 1.0);
+        let result = draw(matrix.into(), Sampling::Nearest);
+        let pixels = result.pixels();
+        assert_eq!(pixels[2 + 8], [0, 0, 0, 255]);
+        assert_eq!(pixels[5 + 4 * 8], [3, 3, 0, 255]);
+        assert_eq!(pixels[1 + 8], [0; 4]);
+        assert_eq!(pixels[6 + 8], [0; 4]);
+
+        // Magnified, bilinear sampling interpolates between pixel centers.
+        let matrix = Matrix::from_scale(2.0, 2.0);
+        let result = draw(matrix.into(), Sampling::Bilinear);
+        let pixels = result.pixels();
+        assert_eq!(pixels[0], [0, 0, 0, 255]);
+        assert_eq!(pixels[2 + 2 * 8][3], 255);
+        assert_eq!(pixels[7 + 7 * 8], [3, 3, 0, 255]);
+    }
+
+    #[test]
+    fn test_perspective()
+    {
+        let from = [
+            Vector{x: 0.0, y: 0.0}, Vector{x: 4.0, y: 0.0},
+            Vector{x: 4.0, y: 4.0}, Vector{x: 0.0, y: 4.0},
+        ];
+        let to = [
+            Vector{x: 0.0, y: 0.0}, Vector{x: 8.0, y: 2.0},
+            Vector{x: 8.0, y: 6.0}, Vector{x: 0.0, y: 8.0},
+        ];
+        let homography = Homography::from_quad_to_quad(from, to).unwrap();
+        let result = draw(homography, Sampling::Nearest);
+        let pixels = result.pixels();
+
+        // The corners of the image end up at the corners of the quad.
+        assert_eq!(pixels[0], [0, 0, 0, 255]);
+        assert_eq!(pixels[7 + 2 * 8], [3, 0, 0, 255]);
+        assert_eq!(pixels[7 + 5 * 8], [3, 3, 0, 255]);
+        assert_eq!(pixels[7 * 8], [0, 3, 0, 255]);
+
+        // Pixels outside of the quad are left alone.
+        assert_eq!(pixels[7], [0; 4]);
+        assert_eq!(pixels[7 + 7 * 8], [0; 4]);
+    }
+
+    #[test]
+    fn test_behind_viewer()
+    {
+        // This maps every point to itself, but behind the viewer,
+        // so that nothing must be drawn.
+        let homography = -1.0 * Homography::IDENTITY;
+        let result = draw(homography, Sampling::Nearest);
+        assert!(result.pixels().iter().all(|&pixel| pixel == [0; 4]));
+
+        // Only the half of the pixel map with x < 4 is in front of the viewer.
+        // Without the check, the other half would show a mirrored image.
+        let homography = Homography{px: -0.25, ..Homography::IDENTITY};
+        let result = draw(homography.inverse().unwrap(), Sampling::Nearest);
+        let pixels = result.pixels();
+        assert_eq!(pixels[0], [0, 0, 0, 255]);
+        for y in 0 .. 8 {
+            for x in 4 .. 8 {
+                assert_eq!(pixels[x + y * 8], [0; 4]);
+            }
+        }
+    }
+}
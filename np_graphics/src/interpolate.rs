@@ -0,0 +1,55 @@
+use crate::Color;
+use crate::ColorSpace;
+use crate::Vector;
+
+/// Values that change smoothly from one to another.
+///
+/// Animations use this to find the values between their key frames.
+pub trait Interpolate
+{
+    /// The value a fraction `t` of the way from `self` to `other`.
+    ///
+    /// The result is `self` if `t` is zero, and `other` if `t` is one.
+    /// Values of `t` outside of this range extrapolate,
+    /// which is useful for easing functions that overshoot.
+    fn interpolate(self, other: Self, t: f64) -> Self;
+}
+
+impl Interpolate for f32
+{
+    fn interpolate(self, other: Self, t: f64) -> Self
+    {
+        self + (other - self) * t as f32
+    }
+}
+
+impl Interpolate for f64
+{
+    fn interpolate(self, other: Self, t: f64) -> Self
+    {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Vector
+{
+    fn interpolate(self, other: Self, t: f64) -> Self
+    {
+        Vector{
+            x: self.x.interpolate(other.x, t),
+            y: self.y.interpolate(other.y, t),
+        }
+    }
+}
+
+/// Colors are mixed in the [`Oklab`] color space.
+/// Use [`Color::mix`] to mix them in another color space.
+///
+/// [`Oklab`]: `ColorSpace::Oklab`
+impl Interpolate for Color
+{
+    fn interpolate(self, other: Self, t: f64) -> Self
+    {
+        self.mix(other, t as f32, ColorSpace::Oklab)
+    }
+}
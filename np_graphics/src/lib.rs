@@ -4,19 +4,40 @@
 
 pub use self::blitter::*;
 pub use self::canvas::*;
+pub use self::color::*;
+pub use self::display_list::DisplayList;
+pub use self::display_list::RecordingCanvas;
 pub use self::format::*;
+pub use self::image::*;
+pub use self::interpolate::*;
 pub use self::linalg::*;
+pub use self::nine_patch::*;
 pub use self::paint::*;
 pub use self::path::PathBuf;
 pub use self::pixel::*;
+pub use self::region::*;
+pub use self::svg_canvas::*;
+pub use self::tiled::*;
 
 pub mod blitters;
+pub mod display_list;
 pub mod formats;
 pub mod path;
+pub mod sdf;
+pub mod tessellate;
 
 mod blitter;
 mod canvas;
+mod color;
+mod float;
 mod format;
+mod image;
+mod interpolate;
 mod linalg;
+mod nine_patch;
 mod paint;
 mod pixel;
+mod region;
+mod scanline;
+mod svg_canvas;
+mod tiled;
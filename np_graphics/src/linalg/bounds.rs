@@ -0,0 +1,199 @@
+use crate::Vector;
+
+/// Axis-aligned bounding box.
+///
+/// The bounding box contains all points between
+/// `min` and `max` inclusive, in both dimensions.
+/// If `min` is greater than `max` in either dimension,
+/// the bounding box is empty.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds
+{
+    pub min: Vector,
+    pub max: Vector,
+}
+
+impl Bounds
+{
+    /// The bounding box that contains no points.
+    ///
+    /// This is the identity for [`Bounds::union`].
+    pub const EMPTY: Self = Self{
+        min: Vector{x: f64::INFINITY, y: f64::INFINITY},
+        max: Vector{x: f64::NEG_INFINITY, y: f64::NEG_INFINITY},
+    };
+
+    /// The smallest bounding box that contains all the given points.
+    pub fn from_points<I>(points: I) -> Self
+        where I: IntoIterator<Item=Vector>
+    {
+        points.into_iter().fold(Self::EMPTY, Self::include)
+    }
+
+    /// The smallest bounding box that contains
+    /// both this bounding box and the given point.
+    ///
+    /// If the point has a NaN coordinate,
+    /// the bounding box has NaN coordinates as well,
+    /// which makes it contain no points and intersect nothing.
+    pub fn include(self, point: Vector) -> Self
+    {
+        let min = Vector{x: nan_min(self.min.x, point.x), y: nan_min(self.min.y, point.y)};
+        let max = Vector{x: nan_max(self.max.x, point.x), y: nan_max(self.max.y, point.y)};
+        Self{min, max}
+    }
+
+    /// The smallest bounding box that contains both bounding boxes.
+    pub fn union(self, other: Self) -> Self
+    {
+        if other.is_empty() { return self; }
+        self.include(other.min).include(other.max)
+    }
+
+    /// The bounding box of the points in both bounding boxes.
+    ///
+    /// If the bounding boxes do not intersect, the result is empty.
+    pub fn intersection(self, other: Self) -> Self
+    {
+        let min = Vector{x: nan_max(self.min.x, other.min.x), y: nan_max(self.min.y, other.min.y)};
+        let max = Vector{x: nan_min(self.max.x, other.max.x), y: nan_min(self.max.y, other.max.y)};
+        Self{min, max}
+    }
+
+    /// Whether all coordinates are neither NaN nor infinite.
+    ///
+    /// This is not the case for [`Bounds::EMPTY`].
+    pub fn is_finite(self) -> bool
+    {
+        self.min.x.is_finite() && self.min.y.is_finite() &&
+        self.max.x.is_finite() && self.max.y.is_finite()
+    }
+
+    /// Whether the bounding box contains no points.
+    pub fn is_empty(self) -> bool
+    {
+        !(self.min.x <= self.max.x && self.min.y <= self.max.y)
+    }
+
+    /// Whether the bounding boxes have any points in common.
+    pub fn intersects(self, other: Self) -> bool
+    {
+        self.min.x <= other.max.x && other.min.x <= self.max.x &&
+        self.min.y <= other.max.y && other.min.y <= self.max.y
+    }
+
+    /// The part of a line segment within the bounding box.
+    ///
+    /// This uses the Liang–Barsky algorithm.
+    /// If the line segment misses the bounding box,
+    /// this function returns [`None`].
+    /// It also returns [`None`] if either end point
+    /// has a NaN or infinite coordinate,
+    /// as the direction of such a line segment is not meaningful.
+    pub fn clip_line_segment(self, p0: Vector, p1: Vector) -> Option<(Vector, Vector)>
+    {
+        let finite = [p0.x, p0.y, p1.x, p1.y].iter().all(|c| c.is_finite());
+        if !finite || self.is_empty() {
+            return None;
+        }
+
+        let d = p1 - p0;
+        let (mut t0, mut t1) = (0.0f64, 1.0f64);
+
+        // For each edge, the line segment enters or leaves
+        // the half-plane inside of it at some t.
+        let edges = [
+            (-d.x, p0.x - self.min.x),
+            ( d.x, self.max.x - p0.x),
+            (-d.y, p0.y - self.min.y),
+            ( d.y, self.max.y - p0.y),
+        ];
+        for &(p, q) in edges.iter() {
+            if p == 0.0 {
+                // Parallel to the edge; inside or outside entirely.
+                if q < 0.0 { return None; }
+            } else {
+                let t = q / p;
+                if t.is_nan() { return None; }
+                if p < 0.0 { t0 = f64::max(t0, t); }
+                else { t1 = f64::min(t1, t); }
+            }
+        }
+
+        if t0 > t1 {
+            return None;
+        }
+
+        // Clamp to the bounding box, as p0 + t·d may be off by rounding.
+        let at = |t: f64| {
+            let v = p0 + t * d;
+            Vector{
+                x: v.x.clamp(self.min.x, self.max.x),
+                y: v.y.clamp(self.min.y, self.max.y),
+            }
+        };
+        let (q0, q1) = (at(t0), at(t1));
+        Some((q0, q1))
+    }
+}
+
+// Unlike f64::min and f64::max, these propagate NaN.
+// A bounding box around a NaN point should not pretend to be useful.
+
+fn nan_min(a: f64, b: f64) -> f64
+{
+    if a.is_nan() || b.is_nan() { f64::NAN } else { f64::min(a, b) }
+}
+
+fn nan_max(a: f64, b: f64) -> f64
+{
+    if a.is_nan() || b.is_nan() { f64::NAN } else { f64::max(a, b) }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn v(x: f64, y: f64) -> Vector
+    {
+        Vector{x, y}
+    }
+
+    #[test]
+    fn test_clip_line_segment()
+    {
+        let bounds = Bounds{min: v(0.0, 0.0), max: v(10.0, 10.0)};
+        assert_eq!(
+            bounds.clip_line_segment(v(-10.0, 5.0), v(20.0, 5.0)),
+            Some((v(0.0, 5.0), v(10.0, 5.0))),
+        );
+        assert_eq!(
+            bounds.clip_line_segment(v(-5.0, -5.0), v(5.0, 5.0)),
+            Some((v(0.0, 0.0), v(5.0, 5.0))),
+        );
+        assert_eq!(
+            bounds.clip_line_segment(v(2.0, 3.0), v(4.0, 5.0)),
+            Some((v(2.0, 3.0), v(4.0, 5.0))),
+        );
+        assert_eq!(bounds.clip_line_segment(v(-5.0, 6.0), v(6.0, 20.0)), None);
+        assert_eq!(bounds.clip_line_segment(v(11.0, 0.0), v(11.0, 10.0)), None);
+        assert_eq!(bounds.clip_line_segment(v(f64::NAN, 0.0), v(1.0, 1.0)), None);
+        assert_eq!(bounds.clip_line_segment(v(f64::INFINITY, 5.0), v(1.0, 5.0)), None);
+        assert_eq!(Bounds::EMPTY.clip_line_segment(v(0.0, 0.0), v(1.0, 1.0)), None);
+    }
+
+    #[test]
+    fn test_intersection()
+    {
+        let a = Bounds{min: v(0.0, 0.0), max: v(10.0, 10.0)};
+        let b = Bounds{min: v(5.0, -5.0), max: v(20.0, 8.0)};
+        assert_eq!(a.intersection(b), Bounds{min: v(5.0, 0.0), max: v(10.0, 8.0)});
+        assert!(a.intersection(Bounds{min: v(11.0, 0.0), max: v(12.0, 1.0)}).is_empty());
+        assert!(a.intersection(Bounds::EMPTY).is_empty());
+        assert!(a.intersection(Bounds::EMPTY.include(v(f64::NAN, 0.0))).is_empty());
+        assert!(a.is_finite());
+        assert!(!Bounds::EMPTY.is_finite());
+    }
+}
@@ -0,0 +1,289 @@
+use crate::Matrix;
+use crate::Vector;
+
+use std::ops;
+
+/// Projective transformation matrix.
+///
+/// Projective transformation matrices (_homographies_)
+/// can do anything affine transformation matrices can do,
+/// and in addition can apply perspective,
+/// such as that of a rectangle rotating around a vertical axis.
+/// Points are transformed like with [`Matrix`],
+/// after which they are divided by the resulting \\( w \\) component.
+///
+/// \\(
+/// \begin{bmatrix}
+/// a   & b   & p_x \\\\
+/// c   & d   & p_y \\\\
+/// t_x & t_y & w
+/// \end{bmatrix}
+/// \\)
+///
+/// Points for which the \\( w \\) component is not positive
+/// lie behind the viewer, and have no meaningful position.
+/// Hence, unlike in some treatments of homographies,
+/// multiplying every component of the matrix by a negative number
+/// does not give the same transformation.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Homography
+{
+    pub a:  f64,
+    pub b:  f64,
+    pub px: f64,
+    pub c:  f64,
+    pub d:  f64,
+    pub py: f64,
+    pub tx: f64,
+    pub ty: f64,
+    pub w:  f64,
+}
+
+impl Homography
+{
+    /// The identity matrix, which provides no transformations.
+    pub const IDENTITY: Self = Self{
+        a:  1.0, b:  0.0, px: 0.0,
+        c:  0.0, d:  1.0, py: 0.0,
+        tx: 0.0, ty: 0.0, w:  1.0,
+    };
+
+    /// A matrix which maps the corners of one quadrilateral
+    /// onto the corners of another quadrilateral.
+    ///
+    /// The corners must be given in the same order for both,
+    /// either clockwise or counterclockwise.
+    /// Points within `from` are mapped to points within `to`,
+    /// with a positive \\( w \\) component, if both are convex.
+    /// If three corners of either quadrilateral lie on a line,
+    /// there is no such matrix and this function returns [`None`].
+    pub fn from_quad_to_quad(from: [Vector; 4], to: [Vector; 4]) -> Option<Self>
+    {
+        let center = 0.25 * (from[0] + from[1] + from[2] + from[3]);
+        let from = Self::from_square_to_quad(from)?;
+        let to = Self::from_square_to_quad(to)?;
+        let mut result = to * from.inverse()?;
+
+        // The inverse may have flipped the sign of every component,
+        // which would put the quadrilateral behind the viewer.
+        if result.transform_homogeneous(center).2 < 0.0 {
+            result = -1.0 * result;
+        }
+
+        result.is_finite().then(|| result)
+    }
+
+    /// A matrix which maps the unit square onto a quadrilateral.
+    ///
+    /// The corners (0, 0), (1, 0), (1, 1), and (0, 1)
+    /// are mapped to the corners of the quadrilateral in that order.
+    /// This uses the formulae from Heckbert’s
+    /// _Fundamentals of Texture Mapping and Image Warping_.
+    fn from_square_to_quad([p0, p1, p2, p3]: [Vector; 4]) -> Option<Self>
+    {
+        let s = p0 - p1 + p2 - p3;
+        let (px, py) =
+            if s.x == 0.0 && s.y == 0.0 {
+                (0.0, 0.0)
+            } else {
+                let d1 = p1 - p2;
+                let d2 = p3 - p2;
+                let den = d1.cross(d2);
+                if den == 0.0 { return None; }
+                (s.cross(d2) / den, d1.cross(s) / den)
+            };
+
+        let result = Self{
+            a:  p1.x - p0.x + px * p1.x,
+            b:  p1.y - p0.y + px * p1.y,
+            px,
+            c:  p3.x - p0.x + py * p3.x,
+            d:  p3.y - p0.y + py * p3.y,
+            py,
+            tx: p0.x,
+            ty: p0.y,
+            w:  1.0,
+        };
+        (result.determinant() != 0.0).then(|| result)
+    }
+
+    /// The inverse of the matrix, which undoes its transformation.
+    ///
+    /// If the matrix is singular, this function returns [`None`].
+    pub fn inverse(self) -> Option<Self>
+    {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let Self{a, b, px, c, d, py, tx, ty, w} = self;
+        let adjugate = Self{
+            a:  d * w - py * ty,
+            b:  px * ty - b * w,
+            px: b * py - px * d,
+            c:  py * tx - c * w,
+            d:  a * w - px * tx,
+            py: px * c - a * py,
+            tx: c * ty - d * tx,
+            ty: b * tx - a * ty,
+            w:  a * d - b * c,
+        };
+        Some((1.0 / det) * adjugate)
+    }
+
+    /// The determinant of the matrix.
+    pub fn determinant(self) -> f64
+    {
+        let Self{a, b, px, c, d, py, tx, ty, w} = self;
+        a * (d * w - py * ty) - b * (c * w - py * tx) + px * (c * ty - d * tx)
+    }
+
+    /// Transform a point, returning its homogeneous coordinates.
+    ///
+    /// The components are returned before
+    /// they are divided by the \\( w \\) component,
+    /// which is the third component.
+    pub fn transform_homogeneous(self, point: Vector) -> (f64, f64, f64)
+    {
+        let Vector{x, y} = point;
+        (
+            self.a  * x + self.c  * y + self.tx,
+            self.b  * x + self.d  * y + self.ty,
+            self.px * x + self.py * y + self.w,
+        )
+    }
+
+    /// Transform a point.
+    ///
+    /// If the \\( w \\) component of the transformed point
+    /// is not positive (or is NaN), the point lies behind the viewer,
+    /// and this function returns [`None`].
+    pub fn transform(self, point: Vector) -> Option<Vector>
+    {
+        let (x, y, w) = self.transform_homogeneous(point);
+        (w > 0.0).then(|| Vector{x: x / w, y: y / w})
+    }
+
+    fn is_finite(self) -> bool
+    {
+        let Self{a, b, px, c, d, py, tx, ty, w} = self;
+        [a, b, px, c, d, py, tx, ty, w].iter().all(|x| x.is_finite())
+    }
+}
+
+impl From<Matrix> for Homography
+{
+    fn from(matrix: Matrix) -> Self
+    {
+        let Matrix{a, b, c, d, tx, ty} = matrix;
+        Self{a, b, px: 0.0, c, d, py: 0.0, tx, ty, w: 1.0}
+    }
+}
+
+impl ops::Mul<Homography> for Homography
+{
+    type Output = Homography;
+
+    /// Combine two transformations,
+    /// such that `rhs` is applied first, like with [`Matrix`].
+    fn mul(self, rhs: Homography) -> Self::Output
+    {
+        let (l, r) = (rhs, self);
+        Homography{
+            a:  l.a  * r.a + l.b  * r.c + l.px * r.tx,
+            b:  l.a  * r.b + l.b  * r.d + l.px * r.ty,
+            px: l.a  * r.px + l.b  * r.py + l.px * r.w,
+            c:  l.c  * r.a + l.d  * r.c + l.py * r.tx,
+            d:  l.c  * r.b + l.d  * r.d + l.py * r.ty,
+            py: l.c  * r.px + l.d  * r.py + l.py * r.w,
+            tx: l.tx * r.a + l.ty * r.c + l.w  * r.tx,
+            ty: l.tx * r.b + l.ty * r.d + l.w  * r.ty,
+            w:  l.tx * r.px + l.ty * r.py + l.w  * r.w,
+        }
+    }
+}
+
+impl ops::Mul<Homography> for f64
+{
+    type Output = Homography;
+
+    fn mul(self, rhs: Homography) -> Self::Output
+    {
+        Homography{
+            a:  self * rhs.a,  b:  self * rhs.b,  px: self * rhs.px,
+            c:  self * rhs.c,  d:  self * rhs.d,  py: self * rhs.py,
+            tx: self * rhs.tx, ty: self * rhs.ty, w:  self * rhs.w,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use proptest::prop_assert;
+    use proptest::proptest;
+
+    fn v(x: f64, y: f64) -> Vector
+    {
+        Vector{x, y}
+    }
+
+    fn close(a: Vector, b: Vector) -> bool
+    {
+        (a - b).length() < 1e-6
+    }
+
+    #[test]
+    fn test_from_quad_to_quad()
+    {
+        let from = [v(0.0, 0.0), v(4.0, 0.0), v(4.0, 3.0), v(0.0, 3.0)];
+        let to = [v(1.0, 1.0), v(9.0, 2.0), v(8.0, 7.0), v(2.0, 9.0)];
+        let h = Homography::from_quad_to_quad(from, to).unwrap();
+        for (&p, &q) in from.iter().zip(&to) {
+            assert!(close(h.transform(p).unwrap(), q));
+        }
+
+        // The flipped matrix maps to the same points, but behind the viewer.
+        assert_eq!((-1.0 * h).transform(from[0]), None);
+
+        let line = [v(0.0, 0.0), v(1.0, 1.0), v(2.0, 2.0), v(0.0, 3.0)];
+        assert_eq!(Homography::from_quad_to_quad(line, to), None);
+    }
+
+    #[test]
+    fn test_from_matrix()
+    {
+        let matrix = Matrix::from_translate(3.0, -2.0) * Matrix::from_rotate(0.5);
+        let h = Homography::from(matrix);
+        let p = v(1.5, 2.5);
+        assert!(close(h.transform(p).unwrap(), matrix * p));
+    }
+
+    proptest! {
+
+        #[test]
+        fn test_inverse_and_composition(
+            coords in proptest::array::uniform8(-1.0 .. 1.0f64),
+            (x, y) in (-1.0 .. 1.0f64, -1.0 .. 1.0f64),
+        )
+        {
+            // Perturb the corners of a square, keeping it convex.
+            let square = [v(0.0, 0.0), v(4.0, 0.0), v(4.0, 4.0), v(0.0, 4.0)];
+            let mut quad = square;
+            for (i, corner) in quad.iter_mut().enumerate() {
+                *corner = *corner + v(coords[2 * i], coords[2 * i + 1]);
+            }
+            let h = Homography::from_quad_to_quad(square, quad).unwrap();
+            let inverse = h.inverse().unwrap();
+
+            let p = v(2.0 + x, 2.0 + y);
+            let q = h.transform(p).unwrap();
+            prop_assert!(close(inverse.transform(q).unwrap(), p));
+            prop_assert!(close((inverse * h).transform(p).unwrap(), p));
+        }
+
+    }
+}
@@ -1,5 +1,9 @@
+pub use self::bounds::*;
+pub use self::homography::*;
 pub use self::matrix::*;
 pub use self::vector::*;
 
+mod bounds;
+mod homography;
 mod matrix;
 mod vector;
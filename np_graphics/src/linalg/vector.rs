@@ -20,6 +20,37 @@ pub struct Vector
     pub y: f64,
 }
 
+impl Vector
+{
+    /// The dot product of two vectors.
+    pub fn dot(self, other: Self) -> f64
+    {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// The z component of the cross product of two vectors,
+    /// treating them as three-dimensional vectors with \\( z = 0 \\).
+    ///
+    /// The sign of the result tells on which side of `self` `other` lies.
+    pub fn cross(self, other: Self) -> f64
+    {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// The Euclidean length of the vector.
+    pub fn length(self) -> f64
+    {
+        f64::hypot(self.x, self.y)
+    }
+
+    /// The vector rotated a quarter turn,
+    /// from the positive x axis towards the positive y axis.
+    pub fn perpendicular(self) -> Self
+    {
+        Self{x: -self.y, y: self.x}
+    }
+}
+
 impl ops::Add<Vector> for Vector
 {
     type Output = Vector;
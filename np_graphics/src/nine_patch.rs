@@ -0,0 +1,428 @@
+use crate::BlendMode;
+use crate::Format;
+use crate::PixelMap;
+use crate::PixelMapMut;
+use crate::Rect;
+use crate::image::blend_pixel;
+use crate::image::pixel;
+
+use std::error;
+use std::fmt;
+
+/// How the edges and center of a nine-patch fill their space.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PatchFill
+{
+    /// Scale the part of the image to fit, using the nearest pixels.
+    Stretch,
+
+    /// Repeat the part of the image, starting at the top left.
+    Tile,
+}
+
+/// Image that can be drawn at any size without distorting its corners.
+///
+/// The part of the image to draw, `source`, is divided into nine patches
+/// by the rows and columns of `center`.
+/// When drawn into a destination rectangle,
+/// the corners are drawn as is, the top and bottom edges
+/// fill the space horizontally, the left and right edges
+/// fill the space vertically, and the center fills the rest.
+/// This is useful for skinning window frames and buttons.
+///
+/// If the destination rectangle is too small for the corners,
+/// the corners and edges are scaled down to fit, and the center is not drawn.
+/// The same happens if the center has no rows or columns.
+///
+/// The rectangles are given in pixel coordinates within the image.
+/// They must lie within the image, and `center` and `content`
+/// must lie within `source`; otherwise, nothing is drawn.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NinePatch
+{
+    /// The part of the image to draw.
+    pub source: Rect,
+
+    /// The part of the source that fills the middle of the destination.
+    pub center: Rect,
+
+    /// The part of the source in which content is placed.
+    ///
+    /// This is not used for drawing,
+    /// but see [`NinePatch::content_rect`].
+    pub content: Rect,
+
+    /// How the edges fill their space.
+    pub edges: PatchFill,
+
+    /// How the center fills its space.
+    pub fill: PatchFill,
+}
+
+/// Why the border of an Android-style nine-patch image is invalid.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NinePatchError
+{
+    /// The image is too small to have a border and any content.
+    TooSmall,
+
+    /// A pixel of the border is neither transparent nor opaque black.
+    ///
+    /// The variant carries the coordinates of the pixel.
+    InvalidBorderPixel(u32, u32),
+
+    /// The top or left border does not mark any pixels to stretch.
+    MissingStretchRegion,
+}
+
+impl fmt::Display for NinePatchError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self {
+            Self::TooSmall =>
+                write!(f, "Nine-patch image is too small"),
+            Self::InvalidBorderPixel(x, y) =>
+                write!(f, "Invalid nine-patch border pixel at ({}, {})", x, y),
+            Self::MissingStretchRegion =>
+                write!(f, "Nine-patch border has no stretch region"),
+        }
+    }
+}
+
+impl error::Error for NinePatchError
+{
+}
+
+impl NinePatch
+{
+    /// Create a nine-patch from the part of an image to draw
+    /// and the part of it that fills the middle.
+    ///
+    /// The content is placed within the center,
+    /// and the edges and center are stretched.
+    pub fn new(source: Rect, center: Rect) -> Self
+    {
+        Self{
+            source,
+            center,
+            content: center,
+            edges: PatchFill::Stretch,
+            fill: PatchFill::Stretch,
+        }
+    }
+
+    /// Create a nine-patch from an image with an Android-style border.
+    ///
+    /// The outermost rows and columns of the image are not drawn,
+    /// but mark which pixels are stretched with opaque black pixels:
+    /// the top row marks the columns of the center,
+    /// and the left column marks the rows of the center.
+    /// The bottom row and right column optionally mark
+    /// the columns and rows of the content;
+    /// if they mark nothing, the content is placed within the center.
+    /// Other border pixels must be fully transparent,
+    /// and the corner pixels are ignored.
+    ///
+    /// Android allows several stretch regions per axis.
+    /// Nine-patches have only one, so if a row or column
+    /// marks several, the pixels between them are marked as well.
+    pub fn from_android_border<F>(format: F, image: &PixelMap<F::Pixel>)
+        -> Result<Self, NinePatchError>
+        where F: Format
+    {
+        let (extent_x, extent_y) = image.extent();
+        if extent_x < 3 || extent_y < 3 {
+            return Err(NinePatchError::TooSmall);
+        }
+
+        // Find the range of marked pixels in a row or column of the border,
+        // skipping the corners, given the coordinates of each of its pixels.
+        let marked = |len: u32, coordinates: &dyn Fn(u32) -> (u32, u32)| {
+            let mut range: Option<(i32, i32)> = None;
+            for i in 1 .. len - 1 {
+                let (x, y) = coordinates(i);
+                match format.to_rgba8888(pixel(image, x, y)) {
+                    [0, 0, 0, 255] => {
+                        let (start, _) = range.unwrap_or((i as i32, 0));
+                        range = Some((start, i as i32 + 1));
+                    },
+                    [_, _, _, 0] => (),
+                    _ => return Err(NinePatchError::InvalidBorderPixel(x, y)),
+                }
+            }
+            Ok(range)
+        };
+
+        let (last_x, last_y) = (extent_x - 1, extent_y - 1);
+        let top = marked(extent_x, &|x| (x, 0))?;
+        let left = marked(extent_y, &|y| (0, y))?;
+        let bottom = marked(extent_x, &|x| (x, last_y))?;
+        let right = marked(extent_y, &|y| (last_x, y))?;
+
+        let (x0, x1) = top.ok_or(NinePatchError::MissingStretchRegion)?;
+        let (y0, y1) = left.ok_or(NinePatchError::MissingStretchRegion)?;
+        let center = Rect{x0, y0, x1, y1};
+        let content = Rect{
+            x0: bottom.map_or(x0, |range| range.0),
+            y0: right.map_or(y0, |range| range.0),
+            x1: bottom.map_or(x1, |range| range.1),
+            y1: right.map_or(y1, |range| range.1),
+        };
+
+        let source = Rect{x0: 1, y0: 1, x1: last_x as i32, y1: last_y as i32};
+        Ok(Self{content, ..Self::new(source, center)})
+    }
+
+    /// Where content goes when the nine-patch is drawn
+    /// into the given destination rectangle.
+    ///
+    /// The distances between the content and the edges of the source
+    /// are the same as those within the destination rectangle.
+    pub fn content_rect(&self, dest: Rect) -> Rect
+    {
+        Rect{
+            x0: dest.x0 + (self.content.x0 - self.source.x0),
+            y0: dest.y0 + (self.content.y0 - self.source.y0),
+            x1: dest.x1 - (self.source.x1 - self.content.x1),
+            y1: dest.y1 - (self.source.y1 - self.content.y1),
+        }
+    }
+
+    fn is_valid<T>(&self, image: &PixelMap<T>) -> bool
+    {
+        let (extent_x, extent_y) = image.extent();
+        let within = |inner: Rect, outer: Rect| {
+            outer.x0 <= inner.x0 && inner.x0 <= inner.x1 && inner.x1 <= outer.x1 &&
+            outer.y0 <= inner.y0 && inner.y0 <= inner.y1 && inner.y1 <= outer.y1
+        };
+        let image = Rect{x0: 0, y0: 0, x1: extent_x as i32, y1: extent_y as i32};
+        !self.source.is_empty() &&
+        within(self.source, image) &&
+        within(self.center, self.source) &&
+        within(self.content, self.source)
+    }
+}
+
+/// Draw a nine-patch into a rectangle of a pixel map.
+///
+/// This implements [`PixelMapCanvas::nine_patch`].
+///
+/// [`PixelMapCanvas::nine_patch`]: `crate::PixelMapCanvas::nine_patch`
+pub(crate) fn draw_nine_patch<F>(
+    format: &F,
+    pixel_map: &mut PixelMapMut<F::Pixel>,
+    image: &PixelMap<F::Pixel>,
+    nine_patch: &NinePatch,
+    dest: Rect,
+    blend_mode: BlendMode,
+)
+    where F: Format
+{
+    if !nine_patch.is_valid(image) || matches!(blend_mode, BlendMode::Destination) {
+        return;
+    }
+
+    // Only visit the part of the destination within the pixel map.
+    let (origin_x, origin_y) = pixel_map.origin();
+    let (extent_x, extent_y) = pixel_map.extent();
+    let x0 = i64::max(dest.x0 as i64, origin_x as i64);
+    let y0 = i64::max(dest.y0 as i64, origin_y as i64);
+    let x1 = i64::min(dest.x1 as i64, origin_x as i64 + extent_x as i64);
+    let y1 = i64::min(dest.y1 as i64, origin_y as i64 + extent_y as i64);
+    if x0 >= x1 || y0 >= y1 {
+        return;
+    }
+
+    // Both axes are mapped independently,
+    // so the source columns can be computed once for all rows.
+    let NinePatch{source, center, edges, fill, ..} = *nine_patch;
+    let axis_x = Axis{
+        dest: (dest.x0 as i64, dest.x1 as i64),
+        source: (source.x0 as i64, source.x1 as i64),
+        center: (center.x0 as i64, center.x1 as i64),
+    };
+    let axis_y = Axis{
+        dest: (dest.y0 as i64, dest.y1 as i64),
+        source: (source.y0 as i64, source.y1 as i64),
+        center: (center.y0 as i64, center.y1 as i64),
+    };
+    let columns: Vec<_> = (x0 .. x1).map(|x| axis_x.map(x, edges, fill)).collect();
+
+    for y in y0 .. y1 {
+        let (row_edge, row_center, row_in_middle) = axis_y.map(y, edges, fill);
+        let row = pixel_map.horizontal_mut(x0 as u32, y as u32, (x1 - x0) as u32);
+        for (dest, &(column_edge, column_center, column_in_middle)) in row.iter_mut().zip(&columns) {
+            // The top and bottom edges fill horizontally like the edges,
+            // and the left and right edges fill vertically like the edges.
+            let source_x = if row_in_middle { column_center } else { column_edge };
+            let source_y = if column_in_middle { row_center } else { row_edge };
+            blend_pixel(format, dest, pixel(image, source_x, source_y), blend_mode);
+        }
+    }
+}
+
+/// Ranges along one axis of a nine-patch.
+struct Axis
+{
+    dest: (i64, i64),
+    source: (i64, i64),
+    center: (i64, i64),
+}
+
+impl Axis
+{
+    /// The source coordinate for a destination coordinate.
+    ///
+    /// Returns the source coordinate for when the pixel is in an edge,
+    /// the source coordinate for when the pixel is in the center,
+    /// and whether the coordinate is within the middle of the axis.
+    /// Only the latter differ, in how they fill the middle.
+    fn map(&self, d: i64, edges: PatchFill, fill: PatchFill) -> (u32, u32, bool)
+    {
+        let (dest_len, d) = (self.dest.1 - self.dest.0, d - self.dest.0);
+        let start = self.center.0 - self.source.0;
+        let end = self.source.1 - self.center.1;
+        let fixed = start + end;
+        let middle = self.center.1 - self.center.0;
+
+        let fixed_source =
+            if dest_len <= fixed || middle == 0 {
+                // Scale the corners down, sampling at pixel centers.
+                let f = ((2 * d + 1) * fixed) / (2 * dest_len);
+                if f < start { self.source.0 + f } else { self.center.1 + (f - start) }
+            } else if d < start {
+                self.source.0 + d
+            } else if d >= dest_len - end {
+                self.source.1 - (dest_len - d)
+            } else {
+                let m = d - start;
+                let middle_source = |mode| match mode {
+                    PatchFill::Stretch =>
+                        self.center.0 + ((2 * m + 1) * middle) / (2 * (dest_len - fixed)),
+                    PatchFill::Tile =>
+                        self.center.0 + m % middle,
+                };
+                return (middle_source(edges) as u32, middle_source(fill) as u32, true);
+            };
+        (fixed_source as u32, fixed_source as u32, false)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use crate::PixelMapCanvas;
+    use crate::formats::Bgra8888;
+
+    const B: [u8; 4] = [0, 0, 0, 255];
+    const T: [u8; 4] = [0, 0, 0, 0];
+
+    fn rect(x0: i32, y0: i32, x1: i32, y1: i32) -> Rect
+    {
+        Rect{x0, y0, x1, y1}
+    }
+
+    /// Image whose pixels contain their own coordinates.
+    fn image(extent_x: u32, extent_y: u32) -> PixelMap<[u8; 4]>
+    {
+        let mut image = PixelMap::new([0; 4], extent_x, extent_y).unwrap();
+        let mut map = image.as_mut();
+        for y in 0 .. extent_y {
+            for x in 0 .. extent_x {
+                map.horizontal_mut(x, y, 1)[0] = [x as u8, y as u8, 1, 255];
+            }
+        }
+        image
+    }
+
+    /// Draw a nine-patch and return the source coordinates of each pixel.
+    fn draw(nine_patch: &NinePatch, dest: Rect, extent: u32) -> Vec<Vec<(u8, u8)>>
+    {
+        let image = image(4, 4);
+        let mut pixel_map = PixelMap::new([0; 4], extent, extent).unwrap();
+        let mut canvas = PixelMapCanvas::new(Bgra8888, pixel_map.as_mut());
+        canvas.nine_patch(&image, nine_patch, dest, BlendMode::Source);
+        pixel_map.pixels()
+            .chunks(extent as usize)
+            .map(|row| row.iter().map(|p| (p[0], p[1])).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_stretch_and_tile()
+    {
+        // Corners of one pixel, and a center of two by two pixels.
+        let mut nine_patch = NinePatch::new(rect(0, 0, 4, 4), rect(1, 1, 3, 3));
+        let result = draw(&nine_patch, rect(0, 0, 6, 6), 6);
+        let columns: Vec<u8> = result[0].iter().map(|p| p.0).collect();
+        assert_eq!(columns, [0, 1, 1, 2, 2, 3]);
+        let rows: Vec<u8> = result.iter().map(|row| row[5].1).collect();
+        assert_eq!(rows, [0, 1, 1, 2, 2, 3]);
+
+        nine_patch.fill = PatchFill::Tile;
+        let result = draw(&nine_patch, rect(0, 0, 6, 6), 6);
+        let center: Vec<u8> = result[2].iter().map(|p| p.0).collect();
+        assert_eq!(center, [0, 1, 2, 1, 2, 3]);
+        let edge: Vec<u8> = result[0].iter().map(|p| p.0).collect();
+        assert_eq!(edge, [0, 1, 1, 2, 2, 3]);
+    }
+
+    #[test]
+    fn test_small_and_clipped()
+    {
+        // Too small for the corners; only the corners are drawn.
+        let nine_patch = NinePatch::new(rect(0, 0, 4, 4), rect(1, 1, 3, 3));
+        let result = draw(&nine_patch, rect(0, 0, 2, 2), 2);
+        assert_eq!(result, [[(0, 0), (3, 0)], [(0, 3), (3, 3)]]);
+
+        // Partially outside of the pixel map,
+        // drawn the same as the visible part of a larger pixel map.
+        let dest = rect(-3, -2, 5, 5);
+        let clipped = draw(&nine_patch, dest, 4);
+        let moved = draw(&nine_patch, rect(0, 0, 8, 7), 8);
+        for y in 0 .. 4 {
+            assert_eq!(clipped[y][.. 4], moved[y + 2][3 .. 7]);
+        }
+    }
+
+    #[test]
+    fn test_android_border()
+    {
+        let mut image = PixelMap::new(T, 6, 5).unwrap();
+        {
+            let mut map = image.as_mut();
+            map.horizontal_mut(2, 0, 2).fill(B); // Stretch columns.
+            map.horizontal_mut(0, 2, 1).fill(B); // Stretch rows.
+            map.horizontal_mut(1, 4, 3).fill(B); // Content columns.
+            map.horizontal_mut(1, 1, 4).fill([9, 9, 9, 255]);
+        }
+        let nine_patch = NinePatch::from_android_border(Bgra8888, &image).unwrap();
+        assert_eq!(nine_patch.source, rect(1, 1, 5, 4));
+        assert_eq!(nine_patch.center, rect(2, 2, 4, 3));
+        assert_eq!(nine_patch.content, rect(1, 2, 4, 3));
+        assert_eq!(
+            nine_patch.content_rect(rect(10, 10, 30, 20)),
+            rect(10, 11, 29, 19),
+        );
+
+        image.as_mut().horizontal_mut(5, 2, 1).fill([0, 0, 255, 255]);
+        assert_eq!(
+            NinePatch::from_android_border(Bgra8888, &image),
+            Err(NinePatchError::InvalidBorderPixel(5, 2)),
+        );
+
+        let blank = PixelMap::new(T, 6, 5).unwrap();
+        assert_eq!(
+            NinePatch::from_android_border(Bgra8888, &blank),
+            Err(NinePatchError::MissingStretchRegion),
+        );
+        let tiny = PixelMap::new(T, 2, 5).unwrap();
+        assert_eq!(
+            NinePatch::from_android_border(Bgra8888, &tiny),
+            Err(NinePatchError::TooSmall),
+        );
+    }
+}
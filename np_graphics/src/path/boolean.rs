@@ -0,0 +1,472 @@
+use crate::PathBuf;
+use crate::Vector;
+use crate::float::total_cmp;
+use crate::path::FillRule;
+use crate::path::edges;
+use crate::path::flatten;
+use crate::path::winding_number;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+/// Which boolean operation to perform on two paths.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BooleanOp
+{
+    /// Points inside either path.
+    Union,
+
+    /// Points inside both paths.
+    Intersection,
+
+    /// Points inside the first path but not inside the second path.
+    Difference,
+
+    /// Points inside exactly one of the paths.
+    Xor,
+}
+
+impl BooleanOp
+{
+    /// Whether a point is inside the result,
+    /// given whether it is inside either path.
+    pub fn apply(self, in_a: bool, in_b: bool) -> bool
+    {
+        match self {
+            Self::Union        => in_a || in_b,
+            Self::Intersection => in_a && in_b,
+            Self::Difference   => in_a && !in_b,
+            Self::Xor          => in_a != in_b,
+        }
+    }
+}
+
+/// Points inside either path; see [`boolean`].
+pub fn union(a: &PathBuf, b: &PathBuf, fill_rule: FillRule, tolerance: f64)
+    -> PathBuf
+{
+    boolean(BooleanOp::Union, a, b, fill_rule, tolerance)
+}
+
+/// Points inside both paths; see [`boolean`].
+pub fn intersection(a: &PathBuf, b: &PathBuf, fill_rule: FillRule, tolerance: f64)
+    -> PathBuf
+{
+    boolean(BooleanOp::Intersection, a, b, fill_rule, tolerance)
+}
+
+/// Points inside `a` but not inside `b`; see [`boolean`].
+pub fn difference(a: &PathBuf, b: &PathBuf, fill_rule: FillRule, tolerance: f64)
+    -> PathBuf
+{
+    boolean(BooleanOp::Difference, a, b, fill_rule, tolerance)
+}
+
+/// Points inside exactly one of the paths; see [`boolean`].
+pub fn xor(a: &PathBuf, b: &PathBuf, fill_rule: FillRule, tolerance: f64)
+    -> PathBuf
+{
+    boolean(BooleanOp::Xor, a, b, fill_rule, tolerance)
+}
+
+/// Combine the regions filled by two paths.
+///
+/// The paths are first flattened with the given tolerance,
+/// and `fill_rule` decides which points are inside each path.
+/// The paths may intersect themselves and each other arbitrarily.
+///
+/// The resulting path consists of closed polygonal contours
+/// made up of only [`Move`] and [`Linear`] instructions.
+/// The contours do not cross each other,
+/// and the result is inside on the left of each contour,
+/// so the winding number of every point is either zero or one
+/// and both fill rules fill the same region.
+/// Holes therefore run in the opposite direction of their surroundings.
+///
+/// The algorithm splits every edge at every intersection,
+/// then keeps those pieces of edges that separate
+/// the inside of the result from the outside.
+/// Only edges whose horizontal extents overlap are tested for intersection,
+/// but classifying the pieces still takes quadratic time in the number of edges,
+/// which is fine for user interface shapes,
+/// but do not throw entire maps at it.
+///
+/// [`Move`]: `crate::path::Instruction::Move`
+/// [`Linear`]: `crate::path::Instruction::Linear`
+pub fn boolean(
+    op: BooleanOp,
+    a: &PathBuf,
+    b: &PathBuf,
+    fill_rule: FillRule,
+    tolerance: f64,
+) -> PathBuf
+{
+    let a = flatten(a.instructions(), tolerance);
+    let b = flatten(b.instructions(), tolerance);
+
+    // Collect the edges of both paths.
+    // Zero-length edges do not bound anything.
+    let segments: Vec<(Vector, Vector)> =
+        a.iter().chain(&b)
+        .flat_map(|contour| edges(contour))
+        .map(|(&p, &q)| (p, q))
+        .filter(|(p, q)| p != q)
+        .collect();
+
+    // Points at which to split each edge, with parameters along the edge.
+    let mut splits: Vec<Vec<(f64, Vector)>> =
+        segments.iter()
+        .map(|&(p, q)| vec![(0.0, p), (1.0, q)])
+        .collect();
+
+    // Only edges whose horizontal extents overlap can intersect,
+    // so visit the edges from left to right and stop
+    // at the first edge that starts right of the current one.
+    // The margin covers intersections snapped to nearby endpoints.
+    let scale = bounds_scale(&segments);
+    let margin = scale * 1e-6;
+    let extent = |(p, q): (Vector, Vector)| (f64::min(p.x, q.x), f64::max(p.x, q.x));
+    let mut order: Vec<usize> = (0 .. segments.len()).collect();
+    order.sort_by(|&i, &j| total_cmp(&extent(segments[i]).0, &extent(segments[j]).0));
+    for (k, &i) in order.iter().enumerate() {
+        let right = extent(segments[i]).1 + margin;
+        for &j in &order[k + 1 ..] {
+            if extent(segments[j]).0 > right { break; }
+            intersect(segments[i], segments[j], |t, u, point| {
+                splits[i].push((t, point));
+                splits[j].push((u, point));
+            });
+        }
+    }
+
+    // Split the edges into pieces that only touch at their endpoints.
+    // Pieces that coincide, possibly in opposite directions,
+    // bound the same regions and need only be considered once.
+    let mut seen = HashSet::new();
+    let mut pieces = Vec::new();
+    for mut split in splits {
+        split.sort_by(|x, y| total_cmp(&x.0, &y.0));
+        split.dedup_by(|x, y| x.1 == y.1);
+        for window in split.windows(2) {
+            let (p, q) = (window[0].1, window[1].1);
+            let (kp, kq) = (key(p), key(q));
+            if seen.insert((kp.min(kq), kp.max(kq))) {
+                pieces.push((p, q));
+            }
+        }
+    }
+
+    // Keep the pieces that have the inside of the result on one side
+    // and the outside of the result on the other side.
+    // Orient them so that the inside is on the left.
+    let is_inside = |point| {
+        let in_a = fill_rule.is_inside(winding_number(&a, point));
+        let in_b = fill_rule.is_inside(winding_number(&b, point));
+        op.apply(in_a, in_b)
+    };
+    let mut kept = Vec::new();
+    for (p, q) in pieces {
+        let direction = q - p;
+        let length = direction.length();
+        let offset = f64::min(length, scale) * 1e-6 / length;
+        let normal = offset * direction.perpendicular();
+        let middle = 0.5 * (p + q);
+        let left = is_inside(middle + normal);
+        let right = is_inside(middle - normal);
+        match (left, right) {
+            (true, false) => kept.push((p, q)),
+            (false, true) => kept.push((q, p)),
+            _ => (),
+        }
+    }
+
+    link(&kept)
+}
+
+/// Find the intersections of two line segments.
+///
+/// For each intersection, `f` is called with
+/// the parameters along both segments and the point of intersection.
+/// Intersections near endpoints are snapped to those endpoints,
+/// so that the same point is reported for both segments.
+/// Collinear overlapping segments intersect at
+/// the endpoints of either that lie on the other.
+fn intersect(
+    (p0, p1): (Vector, Vector),
+    (q0, q1): (Vector, Vector),
+    mut f: impl FnMut(f64, f64, Vector),
+)
+{
+    const EPSILON: f64 = 1e-9;
+
+    let r = p1 - p0;
+    let s = q1 - q0;
+    let denominator = r.cross(s);
+    let pq = q0 - p0;
+
+    if denominator.abs() <= EPSILON * r.length() * s.length() {
+
+        // Parallel segments only intersect if they are collinear.
+        if pq.cross(r).abs() > EPSILON * r.length() * pq.length() {
+            return;
+        }
+
+        // Report the endpoints of each segment that lie within the other.
+        let rr = r.dot(r);
+        let ss = s.dot(s);
+        for &q in &[q0, q1] {
+            let t = (q - p0).dot(r) / rr;
+            if 0.0 < t && t < 1.0 {
+                f(t, if q == q0 { 0.0 } else { 1.0 }, q);
+            }
+        }
+        for &p in &[p0, p1] {
+            let u = (p - q0).dot(s) / ss;
+            if 0.0 < u && u < 1.0 {
+                f(if p == p0 { 0.0 } else { 1.0 }, u, p);
+            }
+        }
+
+    } else {
+
+        let t = pq.cross(s) / denominator;
+        let u = pq.cross(r) / denominator;
+        if !(-EPSILON ..= 1.0 + EPSILON).contains(&t) { return; }
+        if !(-EPSILON ..= 1.0 + EPSILON).contains(&u) { return; }
+
+        let snap = |t: f64| if t < EPSILON { Some(0.0) }
+                            else if t > 1.0 - EPSILON { Some(1.0) }
+                            else { None };
+
+        let (t, u, point) = match (snap(t), snap(u)) {
+            (Some(t), _) => (t, u, if t == 0.0 { p0 } else { p1 }),
+            (None, Some(u)) => (t, u, if u == 0.0 { q0 } else { q1 }),
+            (None, None) => (t, u, p0 + t * r),
+        };
+        f(t, u, point);
+
+    }
+}
+
+/// Join oriented edges into closed contours.
+fn link(edges: &[(Vector, Vector)]) -> PathBuf
+{
+    let mut outgoing: HashMap<_, Vec<usize>> = HashMap::new();
+    for (i, (p, _)) in edges.iter().enumerate() {
+        outgoing.entry(key(*p)).or_default().push(i);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut path = PathBuf::new();
+
+    for first in 0 .. edges.len() {
+        if used[first] { continue; }
+
+        let mut contour = vec![edges[first].0];
+        let mut current = first;
+        loop {
+            used[current] = true;
+            let end = edges[current].1;
+            if key(end) == key(edges[first].0) { break; }
+            contour.push(end);
+            let next = outgoing.get(&key(end))
+                .and_then(|next| next.iter().find(|&&i| !used[i]));
+            match next {
+                Some(&next) => current = next,
+                None => break,
+            }
+        }
+
+        simplify(&mut contour);
+        if contour.len() < 3 { continue; }
+
+        path.push_move(contour[0]);
+        for &point in &contour[1 ..] {
+            path.push_linear(point);
+        }
+        path.push_linear(contour[0]);
+    }
+
+    path
+}
+
+/// Remove points from a closed contour that lie
+/// on the straight line through their neighbors.
+fn simplify(contour: &mut Vec<Vector>)
+{
+    let mut i = 0;
+    while contour.len() >= 3 && i < contour.len() {
+        let n = contour.len();
+        let prev = contour[(i + n - 1) % n];
+        let next = contour[(i + 1) % n];
+        let point = contour[i];
+        let d0 = point - prev;
+        let d1 = next - point;
+        let collinear = d0.cross(d1).abs() <= 1e-12 * d0.length() * d1.length();
+        if collinear && d0.dot(d1) >= 0.0 {
+            contour.remove(i);
+            i = i.saturating_sub(1);
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Size of the bounding box of the segments,
+/// used for scaling tolerances.
+fn bounds_scale(segments: &[(Vector, Vector)]) -> f64
+{
+    let mut min = Vector{x: f64::INFINITY, y: f64::INFINITY};
+    let mut max = Vector{x: f64::NEG_INFINITY, y: f64::NEG_INFINITY};
+    for &(p, _) in segments {
+        min = Vector{x: min.x.min(p.x), y: min.y.min(p.y)};
+        max = Vector{x: max.x.max(p.x), y: max.y.max(p.y)};
+    }
+    f64::max((max - min).length(), 1.0)
+}
+
+/// Key for looking up points by their exact value.
+fn key(point: Vector) -> (u64, u64)
+{
+    // Adding zero turns negative zero into positive zero.
+    ((point.x + 0.0).to_bits(), (point.y + 0.0).to_bits())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use crate::path::signed_area;
+
+    const TOLERANCE: f64 = 0.01;
+
+    fn polygon(points: &[(f64, f64)]) -> PathBuf
+    {
+        let mut path = PathBuf::new();
+        path.push_move(Vector{x: points[0].0, y: points[0].1});
+        for &(x, y) in &points[1 ..] {
+            path.push_linear(Vector{x, y});
+        }
+        path
+    }
+
+    fn square(x: f64, y: f64, size: f64) -> PathBuf
+    {
+        polygon(&[(x, y), (x + size, y), (x + size, y + size), (x, y + size)])
+    }
+
+    fn area(path: &PathBuf) -> f64
+    {
+        signed_area(&flatten(path.instructions(), TOLERANCE))
+    }
+
+    fn contains(path: &PathBuf, x: f64, y: f64) -> bool
+    {
+        let contours = flatten(path.instructions(), TOLERANCE);
+        FillRule::NonZero.is_inside(winding_number(&contours, Vector{x, y}))
+    }
+
+    fn assert_close(actual: f64, expected: f64)
+    {
+        assert!((actual - expected).abs() < 1e-6, "{} ≉ {}", actual, expected);
+    }
+
+    #[test]
+    fn test_overlapping_squares()
+    {
+        let a = square(0.0, 0.0, 10.0);
+        let b = square(5.0, 5.0, 10.0);
+        let rule = FillRule::NonZero;
+
+        let result = union(&a, &b, rule, TOLERANCE);
+        assert_close(area(&result), 175.0);
+        assert!(contains(&result, 2.0, 2.0));
+        assert!(contains(&result, 12.0, 12.0));
+        assert!(!contains(&result, 12.0, 2.0));
+
+        let result = intersection(&a, &b, rule, TOLERANCE);
+        assert_close(area(&result), 25.0);
+        assert!(contains(&result, 7.0, 7.0));
+        assert!(!contains(&result, 2.0, 2.0));
+
+        let result = difference(&a, &b, rule, TOLERANCE);
+        assert_close(area(&result), 75.0);
+        assert!(contains(&result, 2.0, 2.0));
+        assert!(!contains(&result, 7.0, 7.0));
+
+        let result = xor(&a, &b, rule, TOLERANCE);
+        assert_close(area(&result), 150.0);
+        assert!(contains(&result, 12.0, 12.0));
+        assert!(!contains(&result, 7.0, 7.0));
+    }
+
+    #[test]
+    fn test_shared_edge()
+    {
+        let a = square(0.0, 0.0, 10.0);
+        let b = square(10.0, 0.0, 10.0);
+        let result = union(&a, &b, FillRule::NonZero, TOLERANCE);
+        assert_close(area(&result), 200.0);
+        assert!(contains(&result, 10.0, 5.0));
+        assert_eq!(result.instructions().count(), 5);
+    }
+
+    #[test]
+    fn test_hole()
+    {
+        let a = square(0.0, 0.0, 10.0);
+        let b = square(2.0, 2.0, 6.0);
+        let result = difference(&a, &b, FillRule::EvenOdd, TOLERANCE);
+        assert_close(area(&result), 64.0);
+        assert!(contains(&result, 1.0, 1.0));
+        assert!(!contains(&result, 5.0, 5.0));
+    }
+
+    #[test]
+    fn test_self_intersecting_fill_rules()
+    {
+        // A pentagram, whose center has winding number two.
+        let star = polygon(&[
+            (  0.0, -10.0),
+            (  5.9,   8.1),
+            ( -9.5,  -3.1),
+            (  9.5,  -3.1),
+            ( -5.9,   8.1),
+        ]);
+        let empty = PathBuf::new();
+
+        let non_zero = union(&star, &empty, FillRule::NonZero, TOLERANCE);
+        let even_odd = union(&star, &empty, FillRule::EvenOdd, TOLERANCE);
+        assert!(contains(&non_zero, 0.0, 0.0));
+        assert!(!contains(&even_odd, 0.0, 0.0));
+        assert!(contains(&even_odd, 0.0, -7.0));
+
+        let center = intersection(&star, &star, FillRule::NonZero, TOLERANCE);
+        let points = xor(&non_zero, &even_odd, FillRule::NonZero, TOLERANCE);
+        assert_close(area(&non_zero) - area(&even_odd), area(&points));
+        assert!(area(&center) > area(&even_odd));
+    }
+
+    #[test]
+    fn test_curves()
+    {
+        // A circle approximated with four cubic Bézier curves.
+        let k = 0.552284749831 * 10.0;
+        let v = |x, y| Vector{x, y};
+        let mut circle = PathBuf::new();
+        circle.push_move(v(10.0, 0.0));
+        circle.push_cubic(v(10.0, k), v(k, 10.0), v(0.0, 10.0));
+        circle.push_cubic(v(-k, 10.0), v(-10.0, k), v(-10.0, 0.0));
+        circle.push_cubic(v(-10.0, -k), v(-k, -10.0), v(0.0, -10.0));
+        circle.push_cubic(v(k, -10.0), v(10.0, -k), v(10.0, 0.0));
+
+        let quarter = square(0.0, 0.0, 20.0);
+        let result = intersection(&circle, &quarter, FillRule::NonZero, TOLERANCE);
+        let expected = std::f64::consts::PI * 100.0 / 4.0;
+        assert!((area(&result) - expected).abs() < 0.5);
+        assert!(contains(&result, 5.0, 5.0));
+        assert!(!contains(&result, 9.0, 9.0));
+        assert!(!contains(&result, -5.0, 5.0));
+    }
+}
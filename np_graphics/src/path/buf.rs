@@ -1,7 +1,12 @@
 use crate::Vector;
+use crate::path::DecodeError;
 use crate::path::Instruction;
+use crate::path::Opcode;
+use crate::path::Operands;
 use crate::path::Word;
 
+use std::convert::TryInto;
+
 use std::fmt;
 use std::iter::FromIterator;
 
@@ -107,6 +112,50 @@ impl PathBuf
         Self{words}
     }
 
+    /// Create a path from a sequence of packed instructions,
+    /// checking that the sequence is valid.
+    ///
+    /// Each opcode is checked to be a known opcode
+    /// and to be followed by the correct number of operands.
+    /// If `operands` is [`Operands::Finite`],
+    /// each operand is also checked to be finite.
+    /// If any check fails, this function returns [`Err`].
+    ///
+    /// ```
+    /// # use np_graphics::PathBuf;
+    /// # use np_graphics::path::DecodeError;
+    /// # use np_graphics::path::Operands;
+    /// # use np_graphics::path::Word;
+    /// let words = vec![Word::from_bits(0), Word{operand: 1.0}];
+    /// let result = PathBuf::from_words(words, Operands::Any);
+    /// assert_eq!(result.unwrap_err(), DecodeError::TruncatedInstruction(0));
+    /// ```
+    pub fn from_words(words: Vec<Word>, operands: Operands)
+        -> Result<Self, DecodeError>
+    {
+        let mut index = 0;
+        while index < words.len() {
+            let opcode = Opcode::from_bits(words[index].to_bits())
+                .ok_or(DecodeError::InvalidOpcode(index))?;
+            let end = index + 1 + opcode.operand_count();
+            if end > words.len() {
+                return Err(DecodeError::TruncatedInstruction(index));
+            }
+            if operands == Operands::Finite {
+                let operand_words = words[index + 1 .. end].iter();
+                for (i, word) in (index + 1 ..).zip(operand_words) {
+                    // SAFETY: Every bit pattern is a valid f64.
+                    let operand = unsafe { word.operand };
+                    if !operand.is_finite() {
+                        return Err(DecodeError::NonFiniteOperand(i));
+                    }
+                }
+            }
+            index = end;
+        }
+        Ok(Self{words})
+    }
+
     /// The packed encoding for the sequence of instructions.
     ///
     /// See the documentation for [`Word`] for
@@ -124,6 +173,85 @@ impl PathBuf
     {
         self.words
     }
+
+    /// Encode the path as a sequence of bytes.
+    ///
+    /// The byte encoding is stable across versions of this crate
+    /// and across machines, so it is suitable for sending paths
+    /// to other processes and for storing them in caches.
+    /// It consists of the following fields, all little-endian:
+    ///
+    /// | Size           | Field                                     |
+    /// | -------------- | ----------------------------------------- |
+    /// | 4 bytes        | Magic number [`PathBuf::BYTES_MAGIC`].    |
+    /// | 4 bytes        | Version number [`PathBuf::BYTES_VERSION`].|
+    /// | 8 bytes × _n_  | The words of the packed encoding.         |
+    ///
+    /// Each word is stored as its bit pattern;
+    /// see [`Word::to_bits`] and [`Opcode`].
+    ///
+    /// ```
+    /// # use np_graphics::PathBuf;
+    /// # use np_graphics::Vector;
+    /// # use np_graphics::path::Operands;
+    /// let mut path = PathBuf::new();
+    /// path.push_move(Vector{x: 1.0, y: 2.0});
+    /// path.push_linear(Vector{x: 3.0, y: 4.0});
+    ///
+    /// let bytes = path.to_bytes();
+    /// let decoded = PathBuf::from_bytes(&bytes, Operands::Finite).unwrap();
+    /// assert!(decoded.instructions().eq(path.instructions()));
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8>
+    {
+        let mut bytes = Vec::with_capacity(8 + 8 * self.words.len());
+        bytes.extend_from_slice(&Self::BYTES_MAGIC);
+        bytes.extend_from_slice(&Self::BYTES_VERSION.to_le_bytes());
+        for word in &self.words {
+            bytes.extend_from_slice(&word.to_bits().to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decode a path from the byte encoding produced by [`to_bytes`].
+    ///
+    /// The decoded words are checked as with [`from_words`].
+    /// If the header is wrong or any check fails,
+    /// this function returns [`Err`].
+    ///
+    /// [`from_words`]: `PathBuf::from_words`
+    /// [`to_bytes`]: `PathBuf::to_bytes`
+    pub fn from_bytes(bytes: &[u8], operands: Operands)
+        -> Result<Self, DecodeError>
+    {
+        if bytes.len() < 8 || bytes[0 .. 4] != Self::BYTES_MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+
+        let version = u32::from_le_bytes(bytes[4 .. 8].try_into().unwrap());
+        if version != Self::BYTES_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+
+        let body = &bytes[8 ..];
+        if body.len() % 8 != 0 {
+            return Err(DecodeError::TrailingBytes);
+        }
+
+        let words =
+            body.chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .map(Word::from_bits)
+            .collect();
+
+        Self::from_words(words, operands)
+    }
+
+    /// The magic number at the start of the byte encoding.
+    pub const BYTES_MAGIC: [u8; 4] = *b"NPpb";
+
+    /// The version of the byte encoding produced by [`PathBuf::to_bytes`].
+    pub const BYTES_VERSION: u32 = 1;
 }
 
 impl Extend<Instruction> for PathBuf
@@ -195,3 +323,68 @@ impl<'a> Iterator for Instructions<'a>
         }
     }
 }
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn example() -> PathBuf
+    {
+        let p1 = Vector{x:  50.0, y: 100.0};
+        let p2 = Vector{x: 150.0, y: 200.0};
+        let p3 = Vector{x: 250.0, y: 300.0};
+        let mut path = PathBuf::new();
+        path.push_move(p1);
+        path.push_linear(p2);
+        path.push_quadratic(p2, p3);
+        path.push_cubic(p1, p2, p3);
+        path
+    }
+
+    #[test]
+    fn test_bytes_roundtrip()
+    {
+        let path = example();
+        let bytes = path.to_bytes();
+        assert_eq!(bytes.len(), 8 + 8 * path.words().len());
+        let decoded = PathBuf::from_bytes(&bytes, Operands::Finite).unwrap();
+        assert!(decoded.instructions().eq(path.instructions()));
+    }
+
+    #[test]
+    fn test_bytes_header()
+    {
+        let mut bytes = example().to_bytes();
+
+        let result = PathBuf::from_bytes(&bytes[.. 4], Operands::Any);
+        assert_eq!(result.unwrap_err(), DecodeError::BadMagic);
+
+        let result = PathBuf::from_bytes(&bytes[.. bytes.len() - 1], Operands::Any);
+        assert_eq!(result.unwrap_err(), DecodeError::TrailingBytes);
+
+        bytes[4] = 2;
+        let result = PathBuf::from_bytes(&bytes, Operands::Any);
+        assert_eq!(result.unwrap_err(), DecodeError::UnsupportedVersion(2));
+    }
+
+    #[test]
+    fn test_from_words_invalid()
+    {
+        let mut words = example().into_words();
+        words[3] = Word::from_bits(4);
+        let result = PathBuf::from_words(words, Operands::Any);
+        assert_eq!(result.unwrap_err(), DecodeError::InvalidOpcode(3));
+
+        let mut words = example().into_words();
+        words.pop();
+        let result = PathBuf::from_words(words, Operands::Any);
+        assert_eq!(result.unwrap_err(), DecodeError::TruncatedInstruction(11));
+
+        let mut words = example().into_words();
+        words[4] = Word{operand: f64::NAN};
+        let result = PathBuf::from_words(words.clone(), Operands::Finite);
+        assert_eq!(result.unwrap_err(), DecodeError::NonFiniteOperand(4));
+        assert!(PathBuf::from_words(words, Operands::Any).is_ok());
+    }
+}
@@ -0,0 +1,69 @@
+use std::error;
+use std::fmt;
+
+/// Which operands are accepted when decoding a path.
+///
+/// Operands are allowed to be any floating-point number,
+/// but drawing routines have little use for NaN and infinity.
+/// Paths received from untrusted sources may ask for finite operands.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operands
+{
+    /// Accept any operand, including NaN and infinity.
+    Any,
+
+    /// Reject NaN and infinite operands.
+    Finite,
+}
+
+/// Why a sequence of packed instructions could not be decoded.
+///
+/// Each variant carries the index of the offending word,
+/// counted from the first word of the sequence.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DecodeError
+{
+    /// The byte encoding does not start with the expected magic number.
+    BadMagic,
+
+    /// The byte encoding has a version this crate does not understand.
+    UnsupportedVersion(u32),
+
+    /// The byte encoding does not consist of whole words.
+    TrailingBytes,
+
+    /// A word in opcode position does not encode an opcode.
+    InvalidOpcode(usize),
+
+    /// An instruction is missing some of its operands.
+    TruncatedInstruction(usize),
+
+    /// An operand is NaN or infinite,
+    /// and finite operands were requested.
+    NonFiniteOperand(usize),
+}
+
+impl fmt::Display for DecodeError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self {
+            Self::BadMagic =>
+                write!(f, "Bad magic number"),
+            Self::UnsupportedVersion(version) =>
+                write!(f, "Unsupported version: {}", version),
+            Self::TrailingBytes =>
+                write!(f, "Trailing bytes after last word"),
+            Self::InvalidOpcode(index) =>
+                write!(f, "Invalid opcode at word {}", index),
+            Self::TruncatedInstruction(index) =>
+                write!(f, "Truncated instruction at word {}", index),
+            Self::NonFiniteOperand(index) =>
+                write!(f, "Non-finite operand at word {}", index),
+        }
+    }
+}
+
+impl error::Error for DecodeError
+{
+}
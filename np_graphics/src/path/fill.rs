@@ -0,0 +1,83 @@
+use crate::Vector;
+
+/// How to decide which points are inside a path.
+///
+/// Both rules are defined in terms of the _winding number_ of a point,
+/// which is the number of times the contours of the path
+/// wind around the point, counting each direction with opposite sign.
+/// See [`winding_number`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FillRule
+{
+    /// Points with a non-zero winding number are inside the path.
+    NonZero,
+
+    /// Points with an odd winding number are inside the path.
+    EvenOdd,
+}
+
+impl FillRule
+{
+    /// Whether a point with the given winding number is inside the path.
+    pub fn is_inside(self, winding_number: i32) -> bool
+    {
+        match self {
+            Self::NonZero => winding_number != 0,
+            Self::EvenOdd => winding_number % 2 != 0,
+        }
+    }
+}
+
+/// The winding number of a point with respect to a flattened path.
+///
+/// The contours are implicitly closed, as returned by [`flatten`].
+/// Contours that run counterclockwise in a y-up coordinate system
+/// (clockwise on screen, where y points down)
+/// contribute positively to the winding number.
+/// Points exactly on the boundary are considered inside
+/// the contours to their left and below them.
+///
+/// [`flatten`]: `crate::path::flatten`
+pub fn winding_number(contours: &[Vec<Vector>], point: Vector) -> i32
+{
+    let mut winding_number = 0;
+    for contour in contours {
+        for (&a, &b) in edges(contour) {
+            let side = (b - a).cross(point - a);
+            if a.y <= point.y && point.y < b.y && side > 0.0 {
+                winding_number += 1;
+            }
+            if b.y <= point.y && point.y < a.y && side < 0.0 {
+                winding_number -= 1;
+            }
+        }
+    }
+    winding_number
+}
+
+/// The area enclosed by a flattened path, counting orientation.
+///
+/// Contours with a positive winding direction (see [`winding_number`])
+/// have positive area, and contours with the opposite direction
+/// have negative area.
+/// For paths without overlapping contours,
+/// such as those returned by the boolean operations,
+/// the absolute value is the area of the filled region.
+pub fn signed_area(contours: &[Vec<Vector>]) -> f64
+{
+    let mut area = 0.0;
+    for contour in contours {
+        for (&a, &b) in edges(contour) {
+            area += a.cross(b);
+        }
+    }
+    area / 2.0
+}
+
+/// Iterator over the edges of an implicitly closed contour.
+pub (crate) fn edges(contour: &[Vector])
+    -> impl Iterator<Item=(&Vector, &Vector)>
+{
+    let next = contour.iter().skip(1).chain(contour.first());
+    contour.iter().zip(next)
+}
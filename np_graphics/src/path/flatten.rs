@@ -0,0 +1,109 @@
+use crate::Vector;
+use crate::path::Instruction;
+use crate::path::bezier_cubic;
+use crate::path::bezier_quadratic;
+
+/// Maximum number of line segments a single curve is divided into.
+///
+/// This bounds the work done for huge or degenerate curves,
+/// for which the tolerance would otherwise ask for absurd numbers.
+const MAX_SEGMENTS: f64 = 1024.0;
+
+/// Approximate a path with line segments.
+///
+/// Each contour of the path becomes a sequence of points,
+/// connected by line segments, and implicitly closed.
+/// A contour begins at each [`Move`] instruction;
+/// if the path does not begin with one, the first contour
+/// begins at the origin, as is the case for [`Canvas::path`].
+/// Contours with fewer than two points are discarded.
+///
+/// Curves are divided into line segments such that
+/// no point on the curve is further than `tolerance` units
+/// away from the line segments that approximate it.
+///
+/// [`Move`]: `Instruction::Move`
+/// [`Canvas::path`]: `crate::Canvas::path`
+pub fn flatten<P>(path: P, tolerance: f64) -> Vec<Vec<Vector>>
+    where P: IntoIterator<Item=Instruction>
+{
+    let mut contours = Vec::new();
+    let mut contour = vec![Vector{x: 0.0, y: 0.0}];
+
+    for instruction in path {
+        match instruction {
+            Instruction::Move(to) => {
+                finish_contour(&mut contours, &mut contour);
+                contour.push(to);
+            },
+            _ => {
+                let p0 = *contour.last().unwrap();
+                flatten_instruction(p0, instruction, tolerance, &mut contour);
+            },
+        }
+    }
+
+    finish_contour(&mut contours, &mut contour);
+    contours
+}
+
+/// Approximate a single instruction starting at `p0` with line segments.
+///
+/// The end points of the line segments, excluding `p0`,
+/// are appended to `points`.
+/// Move instructions append only the point moved to.
+pub(crate) fn flatten_instruction(
+    p0: Vector,
+    instruction: Instruction,
+    tolerance: f64,
+    points: &mut Vec<Vector>,
+)
+{
+    match instruction {
+        Instruction::Move(to) => {
+            points.push(to);
+        },
+        Instruction::Linear(p1) => {
+            points.push(p1);
+        },
+        Instruction::Quadratic(p1, p2) => {
+            let dd = (p0 - 2.0 * p1 + p2).length();
+            let n = segment_count(dd / 8.0, tolerance);
+            for i in 1 ..= n {
+                let t = i as f64 / n as f64;
+                points.push(bezier_quadratic(p0, p1, p2, t));
+            }
+        },
+        Instruction::Cubic(p1, p2, p3) => {
+            let dd = f64::max(
+                (p0 - 2.0 * p1 + p2).length(),
+                (p1 - 2.0 * p2 + p3).length(),
+            );
+            let n = segment_count(dd * 3.0 / 4.0, tolerance);
+            for i in 1 ..= n {
+                let t = i as f64 / n as f64;
+                points.push(bezier_cubic(p0, p1, p2, p3, t));
+            }
+        },
+    }
+}
+
+/// Number of line segments needed for a curve
+/// whose deviation from a single line segment is at most `deviation`.
+///
+/// The deviation of a curve divided into \\( n \\) segments
+/// decreases with \\( n^2 \\), hence the square root.
+fn segment_count(deviation: f64, tolerance: f64) -> usize
+{
+    let n = f64::sqrt(deviation / tolerance).ceil();
+    // NaN compares false, and falls back to a single segment.
+    if n >= 1.0 { f64::min(n, MAX_SEGMENTS) as usize } else { 1 }
+}
+
+fn finish_contour(contours: &mut Vec<Vec<Vector>>, contour: &mut Vec<Vector>)
+{
+    if contour.len() >= 2 {
+        contours.push(contour.clone());
+    }
+    contour.clear();
+}
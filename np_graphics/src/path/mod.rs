@@ -1,11 +1,23 @@
 //! Utilities for working with paths.
 
 pub use self::bezier::*;
+pub use self::boolean::*;
 pub use self::buf::*;
+pub use self::decode::*;
+pub use self::fill::*;
+pub use self::flatten::*;
 pub use self::instruction::*;
+pub use self::offset::*;
+pub use self::simplify::*;
 pub use self::word::*;
 
 mod bezier;
+mod boolean;
 mod buf;
+mod decode;
+mod fill;
+mod flatten;
 mod instruction;
+mod offset;
+mod simplify;
 mod word;
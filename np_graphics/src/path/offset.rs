@@ -0,0 +1,168 @@
+use crate::PathBuf;
+use crate::Vector;
+use crate::path::BooleanOp;
+use crate::path::FillRule;
+use crate::path::boolean;
+use crate::path::flatten;
+use crate::path::signed_area;
+
+use std::f64::consts::PI;
+
+/// Grow or shrink the region filled by a path.
+///
+/// With a positive distance, the result contains every point
+/// within `distance` units of the filled region.
+/// With a negative distance, the result contains every point
+/// of the filled region that is more than `-distance` units
+/// away from its boundary.
+/// Corners are rounded when growing,
+/// and so are the corners of holes when shrinking.
+///
+/// The result is computed as the union or difference
+/// of the region with the area swept by a disc
+/// moving along the boundary of the region,
+/// so it has the same properties as the result of [`boolean`],
+/// and is flattened with the given tolerance.
+/// If `distance` is zero, NaN, or infinite,
+/// the region is returned without offsetting.
+pub fn offset(path: &PathBuf, distance: f64, fill_rule: FillRule, tolerance: f64)
+    -> PathBuf
+{
+    // Normalize the region so that its winding numbers
+    // are zero or one, and the non-zero fill rule applies.
+    let empty = PathBuf::new();
+    let region = boolean(BooleanOp::Union, path, &empty, fill_rule, tolerance);
+
+    if distance == 0.0 || !distance.is_finite() {
+        return region;
+    }
+
+    let radius = distance.abs();
+    let direction = |a: Vector, b: Vector| (1.0 / (b - a).length()) * (b - a);
+
+    let mut sweep = PathBuf::new();
+    for mut contour in flatten(region.instructions(), tolerance) {
+        contour.dedup();
+        if contour.first() == contour.last() { contour.pop(); }
+        let n = contour.len();
+        for i in 0 .. n {
+            let (previous, a, b) = (contour[(i + n - 1) % n], contour[i], contour[(i + 1) % n]);
+            let normal = radius * direction(a, b).perpendicular();
+            push_positive(&mut sweep, &[a - normal, b - normal, b + normal, a + normal]);
+
+            // Between the rectangles swept along the edges,
+            // the disc only sweeps a wedge on the side the contour turns away from.
+            let (d0, d1) = (direction(previous, a), direction(a, b));
+            let side = if d0.cross(d1) > 0.0 { -radius } else { radius };
+            let wedge = wedge(a, side * d0.perpendicular(), side * d1.perpendicular(), tolerance);
+            if !wedge.is_empty() {
+                push_positive(&mut sweep, &wedge);
+            }
+        }
+    }
+
+    let op = if distance > 0.0 { BooleanOp::Union } else { BooleanOp::Difference };
+    boolean(op, &region, &sweep, FillRule::NonZero, tolerance)
+}
+
+/// Polygon approximating a disc around the origin.
+///
+/// The edges of the polygon touch the disc, so the polygon contains it,
+/// and the corners deviate from the disc by at most `tolerance`.
+pub(crate) fn disc(radius: f64, tolerance: f64) -> Vec<Vector>
+{
+    let tolerance = f64::min(tolerance, radius / 2.0);
+    let step = 2.0 * f64::acos(1.0 - tolerance / radius);
+    let n = f64::clamp((2.0 * PI / step).ceil(), 8.0, 256.0) as usize;
+    let circumradius = radius / f64::cos(PI / n as f64);
+    (0 .. n)
+        .map(|i| 2.0 * PI * i as f64 / n as f64)
+        .map(|theta| Vector{x: theta.cos(), y: theta.sin()})
+        .map(|p| circumradius * p)
+        .collect()
+}
+
+/// Polygon approximating the sector of a disc around `center`
+/// from `center + start` to `center + end`, turning the short way.
+///
+/// Like with [`disc`], the polygon contains the sector,
+/// and its corners deviate from the disc by at most `tolerance`.
+/// The polygon has only as many corners as the angle needs,
+/// and is empty if the angle is zero.
+fn wedge(center: Vector, start: Vector, end: Vector, tolerance: f64) -> Vec<Vector>
+{
+    let radius = start.length();
+    let angle = f64::atan2(start.cross(end), start.dot(end));
+    let step = 2.0 * f64::acos(radius / (radius + tolerance));
+    let n = (angle.abs() / step).ceil().min(256.0) as usize;
+    if n == 0 {
+        return Vec::new();
+    }
+
+    // The corners lie where the tangents at n + 1 equally spaced points meet.
+    let rotate = |p: Vector, theta: f64| {
+        let (sin, cos) = theta.sin_cos();
+        Vector{x: cos * p.x - sin * p.y, y: sin * p.x + cos * p.y}
+    };
+    let half_step = angle / (2 * n) as f64;
+    let corners = (0 .. n)
+        .map(|i| rotate(start, (2 * i + 1) as f64 * half_step))
+        .map(|p| center + (1.0 / half_step.cos()) * p);
+    let mut wedge = vec![center, center + start];
+    wedge.extend(corners);
+    wedge.push(center + end);
+    wedge
+}
+
+/// Append a polygon with a positive winding direction.
+pub(crate) fn push_positive(path: &mut PathBuf, polygon: &[Vector])
+{
+    let area = signed_area(&[polygon.to_vec()]);
+    let mut points: Vec<Vector> = polygon.to_vec();
+    if area < 0.0 { points.reverse(); }
+    path.push_move(points[0]);
+    for &point in &points[1 ..] {
+        path.push_linear(point);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const TOLERANCE: f64 = 0.001;
+
+    fn square(x: f64, y: f64, size: f64) -> PathBuf
+    {
+        let mut path = PathBuf::new();
+        path.push_move(Vector{x, y});
+        path.push_linear(Vector{x: x + size, y});
+        path.push_linear(Vector{x: x + size, y: y + size});
+        path.push_linear(Vector{x, y: y + size});
+        path
+    }
+
+    fn area(path: &PathBuf) -> f64
+    {
+        signed_area(&flatten(path.instructions(), TOLERANCE))
+    }
+
+    #[test]
+    fn test_grow()
+    {
+        let result = offset(&square(0.0, 0.0, 10.0), 1.0, FillRule::NonZero, TOLERANCE);
+        let expected = 100.0 + 4.0 * 10.0 + PI;
+        assert!((area(&result) - expected).abs() < 0.05, "{}", area(&result));
+    }
+
+    #[test]
+    fn test_shrink()
+    {
+        let result = offset(&square(0.0, 0.0, 10.0), -1.0, FillRule::NonZero, TOLERANCE);
+        assert!((area(&result) - 64.0).abs() < 0.05, "{}", area(&result));
+
+        let result = offset(&square(0.0, 0.0, 10.0), -6.0, FillRule::NonZero, TOLERANCE);
+        assert_eq!(result.instructions().count(), 0);
+    }
+}
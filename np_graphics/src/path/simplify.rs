@@ -0,0 +1,180 @@
+use crate::PathBuf;
+use crate::Vector;
+
+/// Reduce the number of points in a polyline.
+///
+/// This implements the Ramer–Douglas–Peucker algorithm.
+/// The first and last points are always kept,
+/// and every removed point lies within `epsilon` units
+/// of the line segment that replaces it.
+/// To simplify a closed contour, such as those returned by [`flatten`],
+/// append its first point before calling this function.
+///
+/// ```
+/// # use np_graphics::Vector;
+/// # use np_graphics::path::simplify;
+/// let points = [
+///     Vector{x: 0.0, y: 0.0},
+///     Vector{x: 1.0, y: 0.1},
+///     Vector{x: 2.0, y: 0.0},
+/// ];
+/// assert_eq!(simplify(&points, 0.5), [points[0], points[2]]);
+/// assert_eq!(simplify(&points, 0.05), points);
+/// ```
+///
+/// [`flatten`]: `crate::path::flatten`
+pub fn simplify(points: &[Vector], epsilon: f64) -> Vec<Vector>
+{
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    // Ranges of points still to be simplified, inclusive.
+    // Using an explicit stack avoids deep recursion on long polylines.
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let (a, b) = (points[first], points[last]);
+
+        let mut farthest = first;
+        let mut max_distance = 0.0;
+        for (i, &point) in points.iter().enumerate().take(last).skip(first + 1) {
+            let distance = segment_distance(a, b, point);
+            if distance > max_distance {
+                farthest = i;
+                max_distance = distance;
+            }
+        }
+
+        if max_distance > epsilon {
+            keep[farthest] = true;
+            stack.push((first, farthest));
+            stack.push((farthest, last));
+        }
+    }
+
+    points.iter()
+        .zip(keep)
+        .filter(|&(_, keep)| keep)
+        .map(|(&point, _)| point)
+        .collect()
+}
+
+/// Distance from a point to a line segment.
+fn segment_distance(a: Vector, b: Vector, point: Vector) -> f64
+{
+    let ab = b - a;
+    let length_squared = ab.dot(ab);
+    if length_squared == 0.0 {
+        return (point - a).length();
+    }
+    let t = f64::clamp((point - a).dot(ab) / length_squared, 0.0, 1.0);
+    (point - (a + t * ab)).length()
+}
+
+/// Smooth curve through a sequence of points.
+///
+/// The curve is a uniform Catmull–Rom spline,
+/// converted into cubic Bézier curves, one between each pair of points.
+/// The tangent at each point is parallel to the line
+/// through its neighbors.
+/// If `closed` is true, the curve continues from
+/// the last point back to the first point.
+/// Otherwise the end points are their own outer neighbors.
+///
+/// The path begins with a [`Move`] to the first point.
+/// If there are no points, the path is empty.
+///
+/// [`Move`]: `crate::path::Instruction::Move`
+pub fn catmull_rom(points: &[Vector], closed: bool) -> PathBuf
+{
+    let mut path = PathBuf::new();
+    let n = points.len();
+    if n == 0 {
+        return path;
+    }
+
+    let point = |i: isize| {
+        if closed {
+            points[i.rem_euclid(n as isize) as usize]
+        } else {
+            points[i.clamp(0, n as isize - 1) as usize]
+        }
+    };
+
+    path.push_move(points[0]);
+    let segments = if closed { n } else { n - 1 };
+    for i in 0 .. segments as isize {
+        let (p0, p1, p2, p3) = (point(i - 1), point(i), point(i + 1), point(i + 2));
+        let c1 = p1 + (1.0 / 6.0) * (p2 - p0);
+        let c2 = p2 - (1.0 / 6.0) * (p3 - p1);
+        path.push_cubic(c1, c2, p2);
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use crate::path::Instruction;
+
+    #[test]
+    fn test_simplify_circle()
+    {
+        let points: Vec<Vector> =
+            (0 ..= 1000)
+            .map(|i| i as f64 / 1000.0 * std::f64::consts::PI)
+            .map(|theta| Vector{x: theta.cos(), y: theta.sin()})
+            .collect();
+        let simplified = simplify(&points, 0.01);
+        assert!(simplified.len() < 20);
+        assert_eq!(simplified.first(), points.first());
+        assert_eq!(simplified.last(), points.last());
+        for &point in &points {
+            let distance =
+                simplified.windows(2)
+                .map(|w| segment_distance(w[0], w[1], point))
+                .fold(f64::INFINITY, f64::min);
+            assert!(distance <= 0.01);
+        }
+    }
+
+    #[test]
+    fn test_catmull_rom_interpolates()
+    {
+        let points = [
+            Vector{x: 0.0, y: 0.0},
+            Vector{x: 1.0, y: 2.0},
+            Vector{x: 3.0, y: 1.0},
+        ];
+
+        let ends: Vec<Vector> =
+            catmull_rom(&points, false).instructions()
+            .map(|instruction| match instruction {
+                Instruction::Move(p) => p,
+                Instruction::Cubic(_, _, p) => p,
+                _ => panic!("Unexpected instruction"),
+            })
+            .collect();
+        assert_eq!(ends, points);
+
+        let closed = catmull_rom(&points, true);
+        assert_eq!(closed.instructions().count(), 4);
+        match closed.instructions().last() {
+            Some(Instruction::Cubic(c1, c2, p)) => {
+                assert_eq!(p, points[0]);
+                // The tangent at the first point is parallel to
+                // the line through its neighbors, the last and second points.
+                let tangent = points[1] - points[2];
+                assert!((points[0] - c2).cross(tangent).abs() < 1e-12);
+                assert!((c1 - points[2]).cross(points[0] - points[1]).abs() < 1e-12);
+            },
+            _ => panic!("Expected cubic Bézier curve"),
+        }
+    }
+}
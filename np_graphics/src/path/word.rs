@@ -21,7 +21,12 @@ pub union Word
 
 /// What kind of path drawing instruction
 /// is encoded in a packed encoding.
-#[derive(Clone, Copy)]
+///
+/// The discriminants are part of the byte encoding
+/// produced by [`PathBuf::to_bytes`], so they must never change.
+///
+/// [`PathBuf::to_bytes`]: `crate::PathBuf::to_bytes`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(u64)]
 pub enum Opcode
 {
@@ -30,14 +35,14 @@ pub enum Opcode
     /// Followed by two operands \\( x \\) and \\( y \\).
     ///
     /// [`Move`]: `crate::path::Instruction::Move`
-    Move,
+    Move = 0,
 
     /// The opcode begins a [`Linear`] instruction.
     ///
     /// Followed by two operands \\( p_{0_x} \\) and \\( p_{0_y} \\).
     ///
     /// [`Linear`]: `crate::path::Instruction::Linear`
-    Linear,
+    Linear = 1,
 
     /// The opcode begins a [`Quadratic`] instruction.
     ///
@@ -46,7 +51,7 @@ pub enum Opcode
     /// \\( p_{1_x} \\), and \\( p_{1_y} \\).
     ///
     /// [`Quadratic`]: `crate::path::Instruction::Quadratic`
-    Quadratic,
+    Quadratic = 2,
 
     /// The opcode begins a [`Cubic`] instruction.
     ///
@@ -56,5 +61,58 @@ pub enum Opcode
     /// \\( p_{2_x} \\), and \\( p_{2_y} \\).
     ///
     /// [`Cubic`]: `crate::path::Instruction::Cubic`
-    Cubic,
+    Cubic = 3,
+}
+
+impl Word
+{
+    /// The bit pattern of the word.
+    ///
+    /// Every bit pattern is a valid operand,
+    /// so this is safe to call regardless of
+    /// whether the word is an opcode or an operand.
+    pub fn to_bits(self) -> u64
+    {
+        // SAFETY: Every bit pattern is a valid f64.
+        unsafe { self.operand }.to_bits()
+    }
+
+    /// Create a word from its bit pattern.
+    ///
+    /// The word may be read as an operand,
+    /// but reading it as an opcode is only valid
+    /// if [`Opcode::from_bits`] accepts the bit pattern.
+    pub fn from_bits(bits: u64) -> Self
+    {
+        Self{operand: f64::from_bits(bits)}
+    }
+}
+
+impl Opcode
+{
+    /// The opcode with the given bit pattern.
+    ///
+    /// If the bit pattern does not encode an opcode,
+    /// this function returns [`None`].
+    pub fn from_bits(bits: u64) -> Option<Self>
+    {
+        match bits {
+            0 => Some(Self::Move),
+            1 => Some(Self::Linear),
+            2 => Some(Self::Quadratic),
+            3 => Some(Self::Cubic),
+            _ => None,
+        }
+    }
+
+    /// The number of operands that follow the opcode.
+    pub fn operand_count(self) -> usize
+    {
+        match self {
+            Self::Move      => 2,
+            Self::Linear    => 2,
+            Self::Quadratic => 4,
+            Self::Cubic     => 6,
+        }
+    }
 }
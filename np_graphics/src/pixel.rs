@@ -27,6 +27,31 @@ impl<T> PixelMap<T>
 
 impl<T> PixelMap<T>
 {
+    /// Create a pixel map from its pixels, given row by row.
+    ///
+    /// If the number of pixels does not match the width and height,
+    /// this function returns [`None`].
+    pub fn from_pixels(pixels: Vec<T>, extent_x: u32, extent_y: u32) -> Option<Self>
+    {
+        let num_pixels = u32::checked_mul(extent_x, extent_y)? as usize;
+        if pixels.len() != num_pixels {
+            return None;
+        }
+        Some(Self{pixels, extent_x, extent_y})
+    }
+
+    /// The width and height of the pixel map.
+    pub fn extent(&self) -> (u32, u32)
+    {
+        (self.extent_x, self.extent_y)
+    }
+
+    /// The pixels of the pixel map, row by row.
+    pub fn pixels(&self) -> &[T]
+    {
+        &self.pixels
+    }
+
     /// Borrow the entire pixel map.
     pub fn as_mut(&mut self) -> PixelMapMut<T>
     {
@@ -34,6 +59,8 @@ impl<T> PixelMap<T>
             _slice: PhantomData,
             pixels: self.pixels.as_mut_ptr(),
             pitch: self.extent_x,
+            origin_x: 0,
+            origin_y: 0,
             extent_x: self.extent_x,
             extent_y: self.extent_y,
         }
@@ -41,11 +68,19 @@ impl<T> PixelMap<T>
 }
 
 /// Mutable reference to a 2D array of pixels.
+///
+/// The pixel map may be a tile of a larger pixel map,
+/// as returned by [`PixelMapMut::tiles`].
+/// Tiles keep using the coordinates of the larger pixel map,
+/// so drawing onto a tile draws exactly those pixels
+/// that drawing onto the larger pixel map would draw within the tile.
 pub struct PixelMapMut<'a, T>
 {
     _slice: PhantomData<&'a mut [T]>,
     pixels: *mut T,
     pitch: u32,
+    origin_x: u32,
+    origin_y: u32,
     extent_x: u32,
     extent_y: u32,
 }
@@ -86,7 +121,15 @@ impl<'a, T> PixelMapMut<'a, T>
 
         // Construct slice blitter.
         let pixels = pixels.as_mut_ptr();
-        Some(Self{_slice: PhantomData, pixels, pitch, extent_x, extent_y})
+        Some(Self{
+            _slice: PhantomData,
+            pixels,
+            pitch,
+            origin_x: 0,
+            origin_y: 0,
+            extent_x,
+            extent_y,
+        })
     }
 }
 
@@ -98,15 +141,56 @@ impl<'a, T> PixelMapMut<'a, T>
         (self.extent_x, self.extent_y)
     }
 
+    /// The coordinates of the top left pixel of the pixel map.
+    ///
+    /// This is zero, except for tiles returned by [`PixelMapMut::tiles`],
+    /// where it is the position of the tile within the larger pixel map.
+    pub fn origin(&self) -> (u32, u32)
+    {
+        (self.origin_x, self.origin_y)
+    }
+
+    /// Move the pixel map to the given coordinates.
+    ///
+    /// The top left pixel will have these coordinates,
+    /// as with tiles of a larger pixel map.
+    pub(crate) fn with_origin(self, origin_x: u32, origin_y: u32) -> Self
+    {
+        Self{origin_x, origin_y, ..self}
+    }
+
+    /// Borrow the pixel map for a shorter lifetime.
+    pub(crate) fn reborrow(&mut self) -> PixelMapMut<'_, T>
+    {
+        PixelMapMut{_slice: PhantomData, ..*self}
+    }
+
     /// Slice of a line segment starting at `start`
     /// and extending `length` pixels to the right.
     ///
+    /// The coordinates are those of the larger pixel map,
+    /// as returned by [`origin`](Self::origin),
+    /// so the top left pixel of this pixel map is at its origin.
     /// The returned slice may be shorter than `length`
-    /// if the line segment is (partially) out of bounds.
+    /// if the line segment is (partially) out of bounds,
+    /// including when it starts left of or above the origin.
     /// This function does not panic on out of bounds conditions.
     pub fn horizontal_mut(&mut self, start_x: u32, start_y: u32, length: u32)
         -> &mut [T]
     {
+        // Translate the starting vertex to offsets within the pixel map,
+        // cutting off the part of the line segment left of the origin.
+        if start_y < self.origin_y { return &mut []; }
+        let (start_x, length) =
+            if start_x < self.origin_x {
+                let skip = self.origin_x - start_x;
+                if skip >= length { return &mut []; }
+                (0, length - skip)
+            } else {
+                (start_x - self.origin_x, length)
+            };
+        let start_y = start_y - self.origin_y;
+
         // Check that the starting vertex is in bounds.
         if start_x >= self.extent_x { return &mut []; }
         if start_y >= self.extent_y { return &mut []; }
@@ -115,8 +199,8 @@ impl<'a, T> PixelMapMut<'a, T>
         let length = u32::min(length, self.extent_x - start_x);
 
         // Compute the start and end offsets of the vertices.
-        let start_index = start_x + start_y * self.pitch;
-        let end_index = start_index + length;
+        let start_index = start_x as usize + start_y as usize * self.pitch as usize;
+        let end_index = start_index + length as usize;
 
         // SAFETY: The indices are in bounds as per the above checks.
         unsafe {
@@ -127,9 +211,43 @@ impl<'a, T> PixelMapMut<'a, T>
                 start_index,
             );
             slice::from_raw_parts_mut(
-                self.pixels.add(start_index as usize),
-                end_index as usize - start_index as usize,
+                self.pixels.add(start_index),
+                end_index - start_index,
             )
         }
     }
+
+    /// Divide the pixel map into disjoint tiles.
+    ///
+    /// The tiles are at most `tile_extent_x` by `tile_extent_y` pixels;
+    /// those at the right and bottom edges may be smaller.
+    /// They are returned row by row, from the top left.
+    /// Because the tiles do not overlap,
+    /// they can be drawn onto by different threads at the same time.
+    ///
+    /// # Panics
+    ///
+    /// If either tile extent is zero, this function panics.
+    pub fn tiles(self, tile_extent_x: u32, tile_extent_y: u32) -> Vec<Self>
+    {
+        assert!(tile_extent_x > 0 && tile_extent_y > 0, "Empty tiles");
+
+        let mut tiles = Vec::new();
+        for y in (0 .. self.extent_y).step_by(tile_extent_y as usize) {
+            for x in (0 .. self.extent_x).step_by(tile_extent_x as usize) {
+                let offset = x as usize + y as usize * self.pitch as usize;
+                tiles.push(Self{
+                    _slice: PhantomData,
+                    // SAFETY: The offset is in bounds as x and y are.
+                    pixels: unsafe { self.pixels.add(offset) },
+                    pitch: self.pitch,
+                    origin_x: self.origin_x + x,
+                    origin_y: self.origin_y + y,
+                    extent_x: u32::min(tile_extent_x, self.extent_x - x),
+                    extent_y: u32::min(tile_extent_y, self.extent_y - y),
+                });
+            }
+        }
+        tiles
+    }
 }
@@ -0,0 +1,346 @@
+use crate::path::BooleanOp;
+
+use std::cmp::Ordering;
+
+/// Rectangle with integer coordinates.
+///
+/// The rectangle contains the pixels with
+/// x coordinates from `x0` up to but excluding `x1`, and
+/// y coordinates from `y0` up to but excluding `y1`.
+/// If `x0 >= x1` or `y0 >= y1`, the rectangle is empty.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Rect
+{
+    pub x0: i32,
+    pub y0: i32,
+    pub x1: i32,
+    pub y1: i32,
+}
+
+impl Rect
+{
+    /// Whether the rectangle contains no pixels.
+    pub fn is_empty(self) -> bool
+    {
+        self.x0 >= self.x1 || self.y0 >= self.y1
+    }
+}
+
+/// Set of pixels, stored as disjoint rectangles.
+///
+/// Regions are useful for keeping track of which parts
+/// of an image need to be redrawn (_damage tracking_).
+///
+/// The rectangles are stored in y-x banded form:
+/// the region is divided into horizontal bands,
+/// and each band is divided into horizontal spans.
+/// Bands do not overlap and are sorted from top to bottom.
+/// Spans within a band do not overlap or touch,
+/// and are sorted from left to right.
+/// Vertically adjacent bands with identical spans are merged.
+/// This form is unique, so regions can be compared for equality.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Region
+{
+    bands: Vec<Band>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Band
+{
+    y0: i32,
+    y1: i32,
+    spans: Vec<(i32, i32)>,
+}
+
+impl Region
+{
+    /// The region that contains no pixels.
+    pub fn new() -> Self
+    {
+        Self{bands: Vec::new()}
+    }
+
+    /// The region that contains the pixels of a rectangle.
+    pub fn from_rect(rect: Rect) -> Self
+    {
+        if rect.is_empty() {
+            return Self::new();
+        }
+        let band = Band{y0: rect.y0, y1: rect.y1, spans: vec![(rect.x0, rect.x1)]};
+        Self{bands: vec![band]}
+    }
+
+    /// Whether the region contains no pixels.
+    pub fn is_empty(&self) -> bool
+    {
+        self.bands.is_empty()
+    }
+
+    /// Whether the region contains the given pixel.
+    pub fn contains(&self, x: i32, y: i32) -> bool
+    {
+        self.band_at(y).map_or(false, |band| {
+            band.spans.iter().any(|&(x0, x1)| x0 <= x && x < x1)
+        })
+    }
+
+    /// The smallest rectangle that contains the region.
+    ///
+    /// If the region is empty, this function returns [`None`].
+    pub fn bounds(&self) -> Option<Rect>
+    {
+        let first = self.bands.first()?;
+        let last = self.bands.last()?;
+        let x0 = self.bands.iter().map(|band| band.spans[0].0).min()?;
+        let x1 = self.bands.iter().map(|band| band.spans[band.spans.len() - 1].1).max()?;
+        Some(Rect{x0, y0: first.y0, x1, y1: last.y1})
+    }
+
+    /// Iterator over the disjoint rectangles that make up the region.
+    ///
+    /// The rectangles are yielded band by band from top to bottom,
+    /// and from left to right within each band.
+    pub fn rects(&self) -> impl '_ + Iterator<Item=Rect>
+    {
+        self.bands.iter().flat_map(|band| {
+            band.spans.iter().map(move |&(x0, x1)| {
+                Rect{x0, y0: band.y0, x1, y1: band.y1}
+            })
+        })
+    }
+
+    /// Move the region by the given distance.
+    ///
+    /// # Panics
+    ///
+    /// If any coordinate would overflow, this function panics.
+    pub fn translate(&mut self, dx: i32, dy: i32)
+    {
+        for band in &mut self.bands {
+            band.y0 = band.y0.checked_add(dy).expect("Region overflow");
+            band.y1 = band.y1.checked_add(dy).expect("Region overflow");
+            for span in &mut band.spans {
+                span.0 = span.0.checked_add(dx).expect("Region overflow");
+                span.1 = span.1.checked_add(dx).expect("Region overflow");
+            }
+        }
+    }
+
+    /// The pixels in either region.
+    pub fn union(&self, other: &Self) -> Self
+    {
+        self.combine(other, BooleanOp::Union)
+    }
+
+    /// The pixels in both regions.
+    pub fn intersection(&self, other: &Self) -> Self
+    {
+        self.combine(other, BooleanOp::Intersection)
+    }
+
+    /// The pixels in this region but not in the other region.
+    pub fn subtract(&self, other: &Self) -> Self
+    {
+        self.combine(other, BooleanOp::Difference)
+    }
+
+    /// The pixels in exactly one of the regions.
+    pub fn xor(&self, other: &Self) -> Self
+    {
+        self.combine(other, BooleanOp::Xor)
+    }
+
+    /// Add the pixels of a rectangle to the region.
+    pub fn union_rect(&mut self, rect: Rect)
+    {
+        *self = self.union(&Self::from_rect(rect));
+    }
+
+    fn band_at(&self, y: i32) -> Option<&Band>
+    {
+        let index = self.bands.binary_search_by(|band| {
+            if band.y1 <= y { Ordering::Less }
+            else if band.y0 > y { Ordering::Greater }
+            else { Ordering::Equal }
+        });
+        index.ok().map(|index| &self.bands[index])
+    }
+
+    /// Apply a boolean operation to each horizontal strip
+    /// in which neither region changes.
+    fn combine(&self, other: &Self, op: BooleanOp) -> Self
+    {
+        let mut ys: Vec<i32> = Vec::new();
+        for band in self.bands.iter().chain(&other.bands) {
+            ys.extend_from_slice(&[band.y0, band.y1]);
+        }
+        ys.sort_unstable();
+        ys.dedup();
+
+        let mut result = Self::new();
+        let mut spans = Vec::new();
+        for window in ys.windows(2) {
+            let (y0, y1) = (window[0], window[1]);
+            let a = self.band_at(y0).map_or(&[][..], |band| &band.spans);
+            let b = other.band_at(y0).map_or(&[][..], |band| &band.spans);
+            combine_spans(a, b, op, &mut spans);
+            result.push_band(y0, y1, &mut spans);
+        }
+        result
+    }
+
+    /// Append a band, merging it with the last band if possible.
+    fn push_band(&mut self, y0: i32, y1: i32, spans: &mut Vec<(i32, i32)>)
+    {
+        if spans.is_empty() {
+            return;
+        }
+        if let Some(last) = self.bands.last_mut() {
+            if last.y1 == y0 && last.spans == *spans {
+                last.y1 = y1;
+                spans.clear();
+                return;
+            }
+        }
+        let spans = std::mem::take(spans);
+        self.bands.push(Band{y0, y1, spans});
+    }
+}
+
+/// Apply a boolean operation to two sorted sequences of disjoint spans.
+///
+/// The result is written to `out`, which is cleared first.
+/// Touching spans in the result are merged.
+fn combine_spans(
+    a: &[(i32, i32)],
+    b: &[(i32, i32)],
+    op: BooleanOp,
+    out: &mut Vec<(i32, i32)>,
+)
+{
+    out.clear();
+
+    let (mut i, mut j) = (0, 0);
+    let (mut in_a, mut in_b) = (false, false);
+    let mut start = None;
+
+    // Walk along the span endpoints of both sequences in order.
+    loop {
+        let next_a = a.get(i / 2).map(|span| if i % 2 == 0 { span.0 } else { span.1 });
+        let next_b = b.get(j / 2).map(|span| if j % 2 == 0 { span.0 } else { span.1 });
+        let x = match (next_a, next_b) {
+            (None, None) => break,
+            (Some(x), None) | (None, Some(x)) => x,
+            (Some(xa), Some(xb)) => i32::min(xa, xb),
+        };
+        if next_a == Some(x) { in_a = !in_a; i += 1; }
+        if next_b == Some(x) { in_b = !in_b; j += 1; }
+
+        let inside = op.apply(in_a, in_b);
+        match (start, inside) {
+            (None, true) => start = Some(x),
+            (Some(x0), false) => {
+                start = None;
+                match out.last_mut() {
+                    Some(last) if last.1 == x0 => last.1 = x,
+                    _ => out.push((x0, x)),
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use proptest::collection::vec;
+    use proptest::prop_assert_eq;
+    use proptest::proptest;
+
+    const SIZE: i32 = 16;
+
+    fn rect(x0: i32, y0: i32, x1: i32, y1: i32) -> Rect
+    {
+        Rect{x0, y0, x1, y1}
+    }
+
+    fn region(rects: &[(i32, i32, i32, i32)]) -> Region
+    {
+        let mut region = Region::new();
+        for &(x0, y0, x1, y1) in rects {
+            region.union_rect(rect(x0, y0, x1, y1));
+        }
+        region
+    }
+
+    fn pixels(region: &Region) -> Vec<bool>
+    {
+        let mut pixels = vec![false; (SIZE * SIZE) as usize];
+        for rect in region.rects() {
+            for y in rect.y0 .. rect.y1 {
+                for x in rect.x0 .. rect.x1 {
+                    let pixel = &mut pixels[(x + y * SIZE) as usize];
+                    assert!(!*pixel, "Rectangles overlap");
+                    *pixel = true;
+                }
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn test_coalesce()
+    {
+        let region = region(&[(0, 0, 4, 4), (4, 0, 8, 4), (0, 4, 8, 8)]);
+        assert_eq!(region.rects().collect::<Vec<_>>(), [rect(0, 0, 8, 8)]);
+    }
+
+    #[test]
+    fn test_bounds_translate()
+    {
+        let mut region = region(&[(1, 2, 3, 4), (5, 0, 6, 1)]);
+        assert_eq!(region.bounds(), Some(rect(1, 0, 6, 4)));
+        region.translate(-1, 10);
+        assert_eq!(region.bounds(), Some(rect(0, 10, 5, 14)));
+        assert!(region.contains(0, 12));
+        assert!(!region.contains(0, 1));
+        assert_eq!(Region::new().bounds(), None);
+    }
+
+    proptest! {
+
+        #[test]
+        fn test_ops_match_pixels(
+            a in vec((0 .. SIZE, 0 .. SIZE, 0 .. SIZE, 0 .. SIZE), 0 .. 6),
+            b in vec((0 .. SIZE, 0 .. SIZE, 0 .. SIZE, 0 .. SIZE), 0 .. 6),
+        )
+        {
+            let (a, b) = (region(&a), region(&b));
+            let (pa, pb) = (pixels(&a), pixels(&b));
+            type Op = fn(&Region, &Region) -> Region;
+            let ops: [(Op, BooleanOp); 4] = [
+                (Region::union, BooleanOp::Union),
+                (Region::intersection, BooleanOp::Intersection),
+                (Region::subtract, BooleanOp::Difference),
+                (Region::xor, BooleanOp::Xor),
+            ];
+            for &(f, op) in &ops {
+                let expected: Vec<bool> =
+                    pa.iter().zip(&pb)
+                    .map(|(&in_a, &in_b)| op.apply(in_a, in_b))
+                    .collect();
+                let result = f(&a, &b);
+                prop_assert_eq!(pixels(&result), expected);
+                // The banded form is unique.
+                let rebuilt = Region::new().union(&result);
+                prop_assert_eq!(rebuilt, result);
+            }
+        }
+
+    }
+}
@@ -0,0 +1,228 @@
+use crate::BlendMode;
+use crate::Format;
+use crate::Paint;
+use crate::PixelMapMut;
+use crate::Vector;
+use crate::canvas::pixel_span;
+use crate::float::total_cmp;
+use crate::path::FillRule;
+use crate::path::edges;
+use crate::with_blitter;
+
+use std::slice;
+
+/// Fill flattened contours onto a pixel map.
+///
+/// This implements [`PixelMapCanvas::fill_path`].
+/// A pixel is drawn if its center is inside the contours
+/// according to the fill rule, as decided by [`winding_number`],
+/// which makes every pixel depend only on its own coordinates.
+/// Edges with NaN or infinite end points are skipped.
+///
+/// [`PixelMapCanvas::fill_path`]: `crate::PixelMapCanvas::fill_path`
+/// [`winding_number`]: `crate::path::winding_number`
+pub(crate) fn fill_contours<F>(
+    format: &F,
+    pixel_map: &mut PixelMapMut<F::Pixel>,
+    contours: &[Vec<Vector>],
+    fill_rule: FillRule,
+    paint: Paint<F::Pixel>,
+)
+    where F: Format
+{
+    let (origin, extent) = (pixel_map.origin(), pixel_map.extent());
+    with_blitter(format, paint, |blitter| {
+        fill_spans(origin, extent, contours, fill_rule, |start_x, y, length| {
+            blitter.horizontal(pixel_map, start_x, y, length);
+        });
+    });
+}
+
+/// Fill flattened contours onto a pixel map,
+/// with the pixel for each pixel center given by the shader.
+///
+/// This implements [`PixelMapCanvas::shade_path`],
+/// and draws the same pixels as [`fill_contours`].
+///
+/// [`PixelMapCanvas::shade_path`]: `crate::PixelMapCanvas::shade_path`
+pub(crate) fn shade_contours<F, S>(
+    format: &F,
+    pixel_map: &mut PixelMapMut<F::Pixel>,
+    contours: &[Vec<Vector>],
+    fill_rule: FillRule,
+    blend_mode: BlendMode,
+    mut shader: S,
+)
+    where F: Format,
+          S: FnMut(Vector) -> F::Pixel,
+{
+    let (origin, extent) = (pixel_map.origin(), pixel_map.extent());
+    fill_spans(origin, extent, contours, fill_rule, |start_x, y, length| {
+        let row = pixel_map.horizontal_mut(start_x, y, length);
+        for (x, dest) in (start_x ..).zip(row) {
+            let center = Vector{x: x as f64 + 0.5, y: y as f64 + 0.5};
+            match blend_mode {
+                BlendMode::Source =>
+                    *dest = shader(center),
+                BlendMode::SourceOver =>
+                    format.blend_source_over(slice::from_mut(dest), shader(center)),
+                BlendMode::Destination =>
+                    (),
+            }
+        }
+    });
+}
+
+/// Find the runs of pixels whose centers are inside flattened contours,
+/// within the `extent` pixels starting at `origin`.
+///
+/// Each run is passed to `span` as its start, its row, and its length.
+fn fill_spans<S>(
+    (origin_x, origin_y): (u32, u32),
+    (extent_x, extent_y): (u32, u32),
+    contours: &[Vec<Vector>],
+    fill_rule: FillRule,
+    mut span: S,
+)
+    where S: FnMut(u32, u32, u32)
+{
+    // Edges from top to bottom, with the direction they count in.
+    // Horizontal edges never cross the center of a row.
+    let mut edges: Vec<(Vector, Vector, i32)> =
+        contours.iter()
+        .flat_map(|contour| edges(contour))
+        .filter(|(a, b)| [a.x, a.y, b.x, b.y].iter().all(|c| c.is_finite()))
+        .filter(|(a, b)| a.y != b.y)
+        .map(|(&a, &b)| if a.y < b.y { (a, b, 1) } else { (b, a, -1) })
+        .collect();
+    edges.sort_by(|e, f| total_cmp(&e.0.y, &f.0.y));
+
+    let mut next = 0;
+    let mut active: Vec<(Vector, Vector, i32)> = Vec::new();
+    let mut crossings: Vec<(f64, i32)> = Vec::new();
+
+    for y in origin_y .. origin_y + extent_y {
+
+        // Edges cross the center of a row if it lies
+        // in the half-open range from their top to their bottom.
+        let center = y as f64 + 0.5;
+        while next < edges.len() && edges[next].0.y <= center {
+            active.push(edges[next]);
+            next += 1;
+        }
+        active.retain(|&(_, b, _)| center < b.y);
+
+        crossings.clear();
+        crossings.extend(active.iter().map(|&(a, b, direction)| {
+            let x = a.x + (center - a.y) * (b.x - a.x) / (b.y - a.y);
+            (x, direction)
+        }));
+        crossings.sort_by(|c, d| total_cmp(&c.0, &d.0));
+
+        // Crossings count towards the winding number
+        // of the points to their left, so going from left to right,
+        // each crossing passed no longer counts.
+        let mut winding_number: i32 = crossings.iter().map(|c| c.1).sum();
+        for window in crossings.windows(2) {
+            let ((x0, direction), (x1, _)) = (window[0], window[1]);
+            winding_number -= direction;
+            if fill_rule.is_inside(winding_number) {
+                let (start_x, length) = pixel_span(x0, x1, origin_x, extent_x);
+                if length != 0 {
+                    span(start_x, y, length);
+                }
+            }
+        }
+
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::BlendMode;
+    use crate::Color;
+    use crate::Format;
+    use crate::Matrix;
+    use crate::Paint;
+    use crate::PathBuf;
+    use crate::PixelMap;
+    use crate::PixelMapCanvas;
+    use crate::Vector;
+    use crate::formats::Bgra8888;
+    use crate::path::FillRule;
+    use crate::path::flatten;
+    use crate::path::winding_number;
+
+    use std::slice;
+
+    const PAINT: Paint<[u8; 4]> =
+        Paint{blend_mode: BlendMode::SourceOver, pixel: [0, 0, 0x80, 0x80]};
+
+    fn square(path: &mut PathBuf, x: f64, y: f64, size: f64)
+    {
+        path.push_move(Vector{x, y});
+        path.push_linear(Vector{x: x + size, y});
+        path.push_linear(Vector{x: x + size, y: y + size});
+        path.push_linear(Vector{x, y: y + size});
+    }
+
+    #[test]
+    fn test_fill_rules()
+    {
+        // Two overlapping squares in the same direction,
+        // and a triangle within the overlap in the opposite direction.
+        let mut path = PathBuf::new();
+        square(&mut path, 1.2, 1.2, 4.0);
+        square(&mut path, 3.2, 3.7, 4.3);
+        path.push_move(Vector{x: 3.3, y: 3.8});
+        path.push_linear(Vector{x: 3.3, y: 5.1});
+        path.push_linear(Vector{x: 5.1, y: 5.1});
+        let contours = flatten(path.instructions(), 0.1);
+
+        // Pixels are drawn once, even where contours overlap.
+        let mut drawn = [0; 4];
+        Bgra8888.blend_source_over(slice::from_mut(&mut drawn), PAINT.pixel);
+
+        for &fill_rule in &[FillRule::NonZero, FillRule::EvenOdd] {
+            let mut pixel_map = PixelMap::new([0; 4], 10, 10).unwrap();
+            let mut canvas = PixelMapCanvas::new(Bgra8888, pixel_map.as_mut());
+            canvas.fill_path(Matrix::IDENTITY, path.instructions(), fill_rule, PAINT);
+
+            for (i, &pixel) in pixel_map.pixels().iter().enumerate() {
+                let center = Vector{x: (i % 10) as f64 + 0.5, y: (i / 10) as f64 + 0.5};
+                let inside = fill_rule.is_inside(winding_number(&contours, center));
+                let expected = if inside { drawn } else { [0; 4] };
+                assert_eq!(pixel, expected, "{:?} {}", fill_rule, i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shade_path()
+    {
+        // A square scaled by two, getting redder to the right.
+        let mut path = PathBuf::new();
+        square(&mut path, 0.0, 0.0, 2.0);
+        let shader = |point: Vector| Color::new(point.x as f32 / 2.0, 0.0, 0.0, 1.0);
+        let (fill_rule, blend_mode) = (FillRule::NonZero, BlendMode::Source);
+
+        let mut pixel_map = PixelMap::new([0; 4], 5, 5).unwrap();
+        let mut canvas = PixelMapCanvas::new(Bgra8888, pixel_map.as_mut());
+        let matrix = Matrix::from_scale(2.0, 2.0);
+        canvas.shade_path(matrix, path.instructions(), fill_rule, blend_mode, shader);
+        for (i, &pixel) in pixel_map.pixels().iter().enumerate() {
+            let (x, y) = (i % 5, i / 5);
+            let point = Vector{x: (x as f64 + 0.5) / 2.0, y: (y as f64 + 0.5) / 2.0};
+            let expected = if x < 4 && y < 4 { shader(point).to_pixel(Bgra8888) } else { [0; 4] };
+            assert_eq!(pixel, expected, "{}", i);
+        }
+
+        // Nothing is drawn with a matrix that cannot be inverted.
+        let mut pixel_map = PixelMap::new([0; 4], 5, 5).unwrap();
+        let mut canvas = PixelMapCanvas::new(Bgra8888, pixel_map.as_mut());
+        let matrix = Matrix::from_scale(0.0, 2.0);
+        canvas.shade_path(matrix, path.instructions(), fill_rule, blend_mode, shader);
+        assert!(pixel_map.pixels().iter().all(|&pixel| pixel == [0; 4]));
+    }
+}
@@ -0,0 +1,926 @@
+//! Signed distance fields of shapes.
+//!
+//! A signed distance field stores for each of its pixels
+//! the distance from the pixel center to the outline of a shape,
+//! positive inside the shape and negative outside.
+//! Unlike an image of the shape, a distance field can be drawn
+//! at any scale with smooth anti-aliased edges,
+//! and with effects such as outlines and glows.
+//! This makes it suitable for icons and glyphs drawn at many sizes.
+//!
+//! Single-channel fields round off sharp corners when magnified.
+//! Multi-channel fields store three distances per pixel,
+//! each to a different subset of the edges of the shape,
+//! and the median of the three keeps corners sharp.
+//! This is the technique described in Viktor Chlumský’s thesis
+//! _Shape Decomposition for Multi-channel Distance Fields_.
+
+use crate::Bounds;
+use crate::Format;
+use crate::Homography;
+use crate::Matrix;
+use crate::PathBuf;
+use crate::PixelMapMut;
+use crate::Vector;
+use crate::path::FillRule;
+use crate::path::Instruction;
+use crate::path::flatten_instruction;
+use crate::path::signed_area;
+use crate::path::winding_number;
+
+use std::mem;
+use std::slice;
+
+/// Maximum distance between a curve and the line segments
+/// that approximate it, in pixels of the distance field.
+const TOLERANCE: f64 = 0.01;
+
+/// Sine of the angle between edges above which they form a corner.
+///
+/// Edges that meet at an angle of more than about eight degrees,
+/// or that turn back on themselves, form a corner.
+const CORNER: f64 = 0.1411;
+
+// The channels of each edge of a multi-channel field, as bits.
+// Edges that meet at a corner share exactly one channel.
+const WHITE: u8 = 0b111;
+const CYAN: u8 = 0b110;
+const MAGENTA: u8 = 0b101;
+const YELLOW: u8 = 0b011;
+
+/// Distances stored in each pixel of a distance field.
+///
+/// This is implemented for `f32`, for single-channel fields,
+/// and for `[f32; 3]`, for multi-channel fields.
+pub trait Distances: Copy
+{
+    /// The signed distance that the distances represent.
+    ///
+    /// For multi-channel fields, this is the median of the channels.
+    fn signed_distance(self) -> f32;
+
+    /// Interpolate linearly between two sets of distances.
+    fn lerp(self, other: Self, t: f32) -> Self;
+}
+
+impl Distances for f32
+{
+    fn signed_distance(self) -> f32
+    {
+        self
+    }
+
+    fn lerp(self, other: Self, t: f32) -> Self
+    {
+        self + (other - self) * t
+    }
+}
+
+impl Distances for [f32; 3]
+{
+    fn signed_distance(self) -> f32
+    {
+        let [r, g, b] = self;
+        f32::max(f32::min(r, g), f32::min(f32::max(r, g), b))
+    }
+
+    fn lerp(self, other: Self, t: f32) -> Self
+    {
+        let [r0, g0, b0] = self;
+        let [r1, g1, b1] = other;
+        [r0.lerp(r1, t), g0.lerp(g1, t), b0.lerp(b1, t)]
+    }
+}
+
+/// Grid of signed distances to the outline of a shape.
+///
+/// Each pixel stores the distance from its center to the outline,
+/// in pixels of the field, positive inside the shape and negative outside.
+/// Distances are clamped to the range of the field.
+/// The field covers the shape with a margin of the range on each side,
+/// and [`DistanceField::matrix`] maps the coordinates of the shape
+/// onto the coordinates of the field.
+///
+/// Use [`generate`] and [`generate_multi_channel`] to create fields,
+/// and [`PixelMapCanvas::distance_field`] to draw them.
+///
+/// [`PixelMapCanvas::distance_field`]: `crate::PixelMapCanvas::distance_field`
+#[derive(Clone, Debug)]
+pub struct DistanceField<T>
+{
+    distances: Vec<T>,
+    extent_x: u32,
+    extent_y: u32,
+    range: f64,
+    scale: f64,
+    offset: Vector,
+}
+
+impl<T> DistanceField<T>
+{
+    /// The width and height of the field in pixels.
+    pub fn extent(&self) -> (u32, u32)
+    {
+        (self.extent_x, self.extent_y)
+    }
+
+    /// The distances of the pixels of the field, row by row.
+    pub fn distances(&self) -> &[T]
+    {
+        &self.distances
+    }
+
+    /// The largest distance stored in the field, in pixels of the field.
+    pub fn range(&self) -> f64
+    {
+        self.range
+    }
+
+    /// The matrix that maps the coordinates of the shape
+    /// onto the coordinates of the field.
+    ///
+    /// This scales and translates, but does not flip:
+    /// the first row of the field is at the top
+    /// in a coordinate system where y points down.
+    pub fn matrix(&self) -> Matrix
+    {
+        Matrix::from_translate(self.offset.x, self.offset.y) *
+            Matrix::from_scale(self.scale, self.scale)
+    }
+}
+
+impl<T> DistanceField<T>
+    where T: Distances
+{
+    /// The signed distance at a point in the coordinates of the field.
+    ///
+    /// The distances of the four nearest pixel centers are interpolated.
+    /// Beyond the outermost pixel centers,
+    /// the outermost pixels are repeated.
+    pub fn sample(&self, point: Vector) -> f32
+    {
+        if self.distances.is_empty() {
+            return -self.range as f32;
+        }
+
+        // Coordinates of the nearest pixel centers and the weight between them.
+        let neighbors = |c: f64, extent: u32| {
+            let c = c - 0.5;
+            let floor = c.floor();
+            let c0 = (floor as i64).clamp(0, extent as i64 - 1) as usize;
+            let c1 = (floor as i64 + 1).clamp(0, extent as i64 - 1) as usize;
+            (c0, c1, (c - floor) as f32)
+        };
+        let (x0, x1, wx) = neighbors(point.x, self.extent_x);
+        let (y0, y1, wy) = neighbors(point.y, self.extent_y);
+
+        let pixel = |x: usize, y: usize| self.distances[x + y * self.extent_x as usize];
+        let top = pixel(x0, y0).lerp(pixel(x1, y0), wx);
+        let bottom = pixel(x0, y1).lerp(pixel(x1, y1), wx);
+        top.lerp(bottom, wy).signed_distance()
+    }
+
+    /// The matrix that maps the coordinates of the field
+    /// onto the coordinates of the shape.
+    fn inverse_matrix(&self) -> Matrix
+    {
+        Matrix::from_scale(1.0 / self.scale, 1.0 / self.scale) *
+            Matrix::from_translate(-self.offset.x, -self.offset.y)
+    }
+}
+
+/// Generate a single-channel signed distance field of a filled path.
+///
+/// The field has `scale` pixels per unit of the path,
+/// and stores distances of up to `range` pixels from the outline.
+/// The range should be at least the width of any effects
+/// the field will be drawn with, divided by the magnification.
+/// Which points are inside the path is decided by the fill rule.
+///
+/// If the path has no line segments with finite coordinates,
+/// or the scale or range is not positive and finite,
+/// the field has no pixels.
+pub fn generate(path: &PathBuf, fill_rule: FillRule, scale: f64, range: f64)
+    -> DistanceField<f32>
+{
+    generate_with(path, fill_rule, scale, range, |shape, point| {
+        shape.signed_distance(point) as f32
+    })
+}
+
+/// Generate a multi-channel signed distance field of a filled path.
+///
+/// This is like [`generate`], but the pixels have three channels.
+/// The edges of each contour of the path are assigned two channels each,
+/// such that the edges that meet at a corner share only one channel.
+/// Each channel stores the distance to the nearest edge assigned to it,
+/// extending edges beyond their end points along their tangents,
+/// so that the median of the channels forms a sharp corner.
+/// Where the median would put a pixel center on the wrong side
+/// of the outline, the pixel stores the single-channel distance instead.
+pub fn generate_multi_channel(path: &PathBuf, fill_rule: FillRule, scale: f64, range: f64)
+    -> DistanceField<[f32; 3]>
+{
+    generate_with(path, fill_rule, scale, range, |shape, point| {
+        map_channels(shape.channels(point), |c| c as f32)
+    })
+}
+
+fn generate_with<T, D>(
+    path: &PathBuf,
+    fill_rule: FillRule,
+    scale: f64,
+    range: f64,
+    distances: D,
+) -> DistanceField<T>
+    where T: ClampTo,
+          D: Fn(&Shape, Vector) -> T,
+{
+    let empty = DistanceField{
+        distances: Vec::new(),
+        extent_x: 0,
+        extent_y: 0,
+        range: if range.is_finite() { range.abs() } else { 0.0 },
+        scale: 1.0,
+        offset: Vector{x: 0.0, y: 0.0},
+    };
+    let valid = |x: f64| x > 0.0 && x.is_finite();
+    if !valid(scale) || !valid(range) {
+        return empty;
+    }
+
+    let mut shape = Shape::new(path, scale, fill_rule);
+    let bounds = Bounds::from_points(shape.points());
+    if bounds.is_empty() {
+        return empty;
+    }
+
+    // Put the outline within the field, with a margin of the range.
+    let offset = Vector{x: range - bounds.min.x, y: range - bounds.min.y};
+    shape.translate(offset);
+    let extent_x = (bounds.max.x - bounds.min.x + 2.0 * range).ceil() as u32;
+    let extent_y = (bounds.max.y - bounds.min.y + 2.0 * range).ceil() as u32;
+
+    let clamp = range as f32;
+    let mut field = Vec::with_capacity(extent_x as usize * extent_y as usize);
+    for y in 0 .. extent_y {
+        for x in 0 .. extent_x {
+            let center = Vector{x: x as f64 + 0.5, y: y as f64 + 0.5};
+            let distance = distances(&shape, center);
+            field.push(distance.clamp_to(clamp));
+        }
+    }
+
+    DistanceField{distances: field, extent_x, extent_y, range, scale, offset}
+}
+
+/// Apply a function to each of three channels.
+fn map_channels<T, U>([r, g, b]: [T; 3], mut f: impl FnMut(T) -> U) -> [U; 3]
+{
+    [f(r), f(g), f(b)]
+}
+
+/// Clamping of distances to the range of a field.
+trait ClampTo
+{
+    fn clamp_to(self, range: f32) -> Self;
+}
+
+impl ClampTo for f32
+{
+    fn clamp_to(self, range: f32) -> Self
+    {
+        self.clamp(-range, range)
+    }
+}
+
+impl ClampTo for [f32; 3]
+{
+    fn clamp_to(self, range: f32) -> Self
+    {
+        map_channels(self, |c| c.clamp(-range, range))
+    }
+}
+
+/// Piece of a contour, flattened into a polyline.
+///
+/// The tangents are those of the curve the edge was flattened from,
+/// which the directions of the line segments only approximate.
+struct Edge
+{
+    points: Vec<Vector>,
+    start_tangent: Vector,
+    end_tangent: Vector,
+    color: u8,
+}
+
+/// The nearest point on an edge to some point.
+#[derive(Clone, Copy)]
+struct Nearest
+{
+    /// The distance to the nearest point.
+    distance: f64,
+
+    /// How far the direction to the nearest point is
+    /// from being perpendicular to the edge, from 0 to 1.
+    /// This breaks ties between edges that meet at a corner.
+    obliqueness: f64,
+
+    /// The signed distance to the edge extended along its tangents
+    /// at its end points, positive to the left of the edge.
+    pseudo_distance: f64,
+}
+
+impl Nearest
+{
+    fn is_nearer_than(self, other: Option<Self>) -> bool
+    {
+        match other {
+            None => true,
+            Some(other) =>
+                self.distance < other.distance ||
+                self.distance == other.distance &&
+                    self.obliqueness < other.obliqueness,
+        }
+    }
+}
+
+/// Outline of a path, in the coordinates of the field.
+struct Shape
+{
+    fill_rule: FillRule,
+    contours: Vec<Vec<Edge>>,
+
+    // The contours as polygons, for computing winding numbers.
+    polygons: Vec<Vec<Vector>>,
+
+    // Which side of the edges is inside: 1 for left, -1 for right.
+    orientation: f64,
+}
+
+impl Shape
+{
+    fn new(path: &PathBuf, scale: f64, fill_rule: FillRule) -> Self
+    {
+        let matrix = Matrix::from_scale(scale, scale);
+        let mut contours = Vec::new();
+        let mut contour = Vec::new();
+        let mut start = Vector{x: 0.0, y: 0.0};
+        let mut p0 = start;
+        let mut points = Vec::new();
+
+        for instruction in path.instructions() {
+            let instruction = matrix * instruction;
+            if let Instruction::Move(to) = instruction {
+                close_contour(&mut contours, &mut contour, p0, start);
+                start = to;
+                p0 = to;
+                continue;
+            }
+            points.clear();
+            points.push(p0);
+            flatten_instruction(p0, instruction, TOLERANCE, &mut points);
+            push_edge(&mut contour, &points, tangents(p0, instruction));
+            p0 = *points.last().unwrap();
+        }
+        close_contour(&mut contours, &mut contour, p0, start);
+
+        let mut polygons = Vec::with_capacity(contours.len());
+        for contour in &mut contours {
+            polygons.push(polygon(contour));
+            color_edges(contour);
+        }
+
+        let orientation = if signed_area(&polygons) < 0.0 { -1.0 } else { 1.0 };
+        Self{fill_rule, contours, polygons, orientation}
+    }
+
+    fn points(&self) -> impl Iterator<Item=Vector> + '_
+    {
+        self.polygons.iter().flatten().copied()
+    }
+
+    fn translate(&mut self, offset: Vector)
+    {
+        let points =
+            self.contours.iter_mut().flatten()
+            .flat_map(|edge| &mut edge.points)
+            .chain(self.polygons.iter_mut().flatten());
+        for point in points {
+            *point = *point + offset;
+        }
+    }
+
+    fn edges(&self) -> impl Iterator<Item=&Edge>
+    {
+        self.contours.iter().flatten()
+    }
+
+    fn is_inside(&self, point: Vector) -> bool
+    {
+        self.fill_rule.is_inside(winding_number(&self.polygons, point))
+    }
+
+    /// The exact signed distance to the outline.
+    fn signed_distance(&self, point: Vector) -> f64
+    {
+        let distance =
+            self.edges()
+            .map(|edge| nearest(edge, point).distance)
+            .fold(f64::INFINITY, f64::min);
+        if self.is_inside(point) { distance } else { -distance }
+    }
+
+    /// The signed pseudo-distances to the nearest edges of each channel.
+    fn channels(&self, point: Vector) -> [f64; 3]
+    {
+        let mut best: [Option<Nearest>; 3] = [None; 3];
+        let mut distance = f64::INFINITY;
+        for edge in self.edges() {
+            let nearest = nearest(edge, point);
+            distance = f64::min(distance, nearest.distance);
+            for (channel, best) in best.iter_mut().enumerate() {
+                if edge.color & 1 << channel != 0 && nearest.is_nearer_than(*best) {
+                    *best = Some(nearest);
+                }
+            }
+        }
+
+        let inside = self.is_inside(point);
+        let channels = map_channels(best, |nearest| match nearest {
+            Some(nearest) => nearest.pseudo_distance * self.orientation,
+            None => -distance,
+        });
+
+        // The pseudo-distances only know the side of a single edge,
+        // which is wrong for some shapes, such as self-intersecting ones.
+        // Where the median disagrees with the fill rule, fall back.
+        let median = map_channels(channels, |c| c as f32).signed_distance();
+        if (median > 0.0) != inside {
+            let distance = if inside { distance } else { -distance };
+            return [distance; 3];
+        }
+        channels
+    }
+}
+
+/// Append an edge to a contour, unless it has no length.
+///
+/// Edges with non-finite points are skipped.
+fn push_edge(contour: &mut Vec<Edge>, points: &[Vector], (start, end): (Vector, Vector))
+{
+    if !points.iter().all(|p| p.x.is_finite() && p.y.is_finite()) {
+        return;
+    }
+    let mut points = points.to_vec();
+    points.dedup();
+    if points.len() < 2 {
+        return;
+    }
+    let n = points.len();
+    let tangent = |tangent: Vector, segment: Vector| {
+        if tangent.length() > 0.0 { tangent } else { segment }
+    };
+    let start_tangent = tangent(start, points[1] - points[0]);
+    let end_tangent = tangent(end, points[n - 1] - points[n - 2]);
+    contour.push(Edge{points, start_tangent, end_tangent, color: WHITE});
+}
+
+/// The directions of an instruction starting at `p0`
+/// at its start and at its end.
+///
+/// Control points that coincide with an end point are skipped.
+/// If all points coincide, the directions are zero.
+fn tangents(p0: Vector, instruction: Instruction) -> (Vector, Vector)
+{
+    let points = match instruction {
+        Instruction::Move(p1) | Instruction::Linear(p1) => vec![p0, p1],
+        Instruction::Quadratic(p1, p2) => vec![p0, p1, p2],
+        Instruction::Cubic(p1, p2, p3) => vec![p0, p1, p2, p3],
+    };
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let start = points.iter().map(|&p| p - first).find(|d| d.length() > 0.0);
+    let end = points.iter().rev().map(|&p| last - p).find(|d| d.length() > 0.0);
+    let zero = Vector{x: 0.0, y: 0.0};
+    (start.unwrap_or(zero), end.unwrap_or(zero))
+}
+
+/// Close a contour with a straight edge, and add it to the contours.
+fn close_contour(contours: &mut Vec<Vec<Edge>>, contour: &mut Vec<Edge>, p0: Vector, start: Vector)
+{
+    if contour.is_empty() {
+        return;
+    }
+    push_edge(contour, &[p0, start], (start - p0, start - p0));
+    contours.push(mem::take(contour));
+}
+
+/// The points of a contour, without repeating the shared end points.
+fn polygon(contour: &[Edge]) -> Vec<Vector>
+{
+    let mut polygon = Vec::new();
+    for edge in contour {
+        let skip = (polygon.last() == edge.points.first()) as usize;
+        polygon.extend_from_slice(&edge.points[skip ..]);
+    }
+    polygon
+}
+
+fn is_corner(a: Vector, b: Vector) -> bool
+{
+    let (a, b) = (1.0 / a.length() * a, 1.0 / b.length() * b);
+    a.dot(b) <= 0.0 || a.cross(b).abs() > CORNER
+}
+
+/// Assign channels to the edges of a contour.
+///
+/// The edges between each pair of consecutive corners
+/// are joined into a single edge with two channels,
+/// different from those of the neighboring edges.
+/// Contours without corners keep a single edge with all channels,
+/// and contours with a single corner are divided into three edges.
+fn color_edges(contour: &mut Vec<Edge>)
+{
+    let n = contour.len();
+    let corners: Vec<usize> =
+        (0 .. n)
+        .filter(|&i| is_corner(contour[(i + n - 1) % n].end_tangent, contour[i].start_tangent))
+        .collect();
+
+    match corners.len() {
+        0 => {
+            for edge in contour {
+                edge.color = WHITE;
+            }
+        },
+
+        // Divide the contour into three edges, so that the
+        // two edges that meet at the corner share one channel.
+        1 => {
+            contour.rotate_left(corners[0]);
+            let points = polygon(contour);
+            let segments = points.len() - 1;
+            if segments < 3 {
+                return;
+            }
+            let (start_tangent, end_tangent) =
+                (contour[0].start_tangent, contour[n - 1].end_tangent);
+            let (a, b) = (segments / 3, 2 * segments / 3);
+            *contour = vec![
+                Edge{
+                    points: points[..= a].to_vec(),
+                    start_tangent,
+                    end_tangent: points[a] - points[a - 1],
+                    color: MAGENTA,
+                },
+                Edge{
+                    points: points[a ..= b].to_vec(),
+                    start_tangent: points[a + 1] - points[a],
+                    end_tangent: points[b] - points[b - 1],
+                    color: WHITE,
+                },
+                Edge{
+                    points: points[b ..].to_vec(),
+                    start_tangent: points[b + 1] - points[b],
+                    end_tangent,
+                    color: YELLOW,
+                },
+            ];
+        },
+
+        // Join the edges between consecutive corners,
+        // and cycle through the colors, making sure that
+        // the last edge differs from the first edge as well.
+        count => {
+            contour.rotate_left(corners[0]);
+            let starts: Vec<usize> = corners.iter().map(|c| c - corners[0]).collect();
+            let ends = starts[1 ..].iter().copied().chain(Some(n));
+            let colors = [CYAN, MAGENTA, YELLOW];
+            *contour = starts.iter().zip(ends).enumerate()
+                .map(|(i, (&start, end))| {
+                    let edges = &contour[start .. end];
+                    let color =
+                        if count % 3 == 1 && i == count - 1 { MAGENTA }
+                        else { colors[i % 3] };
+                    Edge{
+                        points: polygon(edges),
+                        start_tangent: edges[0].start_tangent,
+                        end_tangent: edges[edges.len() - 1].end_tangent,
+                        color,
+                    }
+                })
+                .collect();
+        },
+    }
+}
+
+/// The nearest point on an edge to the given point.
+fn nearest(edge: &Edge, point: Vector) -> Nearest
+{
+    let last = edge.points.len() - 2;
+    let mut best: Option<Nearest> = None;
+    for (i, segment) in edge.points.windows(2).enumerate() {
+        let (a, b) = (segment[0], segment[1]);
+        let direction = b - a;
+        let t = (point - a).dot(direction) / direction.dot(direction);
+        let q =
+            if t <= 0.0 { a }
+            else if t >= 1.0 { b }
+            else { a + t * direction };
+        let distance = (point - q).length();
+        let unit = 1.0 / direction.length() * direction;
+        let obliqueness =
+            if distance == 0.0 { 0.0 }
+            else { (1.0 / distance * (point - q)).dot(unit).abs() };
+
+        // Beyond the end points, the distance to the tangent line.
+        let side = unit.cross(point - a);
+        let pseudo_distance =
+            if i == 0 && t < 0.0 {
+                let tangent = edge.start_tangent;
+                (1.0 / tangent.length() * tangent).cross(point - a)
+            } else if i == last && t > 1.0 {
+                let tangent = edge.end_tangent;
+                (1.0 / tangent.length() * tangent).cross(point - b)
+            } else if side < 0.0 {
+                -distance
+            } else {
+                distance
+            };
+
+        let nearest = Nearest{distance, obliqueness, pseudo_distance};
+        if nearest.is_nearer_than(best) {
+            best = Some(nearest);
+        }
+    }
+    best.expect("Edges have at least two points")
+}
+
+/// How to draw a distance field.
+///
+/// The effects are drawn from the bottom up:
+/// first the glow, then the outline, then the fill,
+/// each blended with the [`SourceOver`] formula.
+///
+/// [`SourceOver`]: `crate::BlendMode::SourceOver`
+#[derive(Clone, Copy, Debug)]
+pub struct Style<T>
+{
+    /// The pixel the shape is filled with.
+    pub fill: T,
+
+    /// The pixel and width of an outline around the shape.
+    ///
+    /// The width is in pixels of the pixel map,
+    /// and the outline lies outside of the shape.
+    pub outline: Option<(T, f64)>,
+
+    /// The pixel and radius of a glow around the shape and its outline.
+    ///
+    /// The radius is in pixels of the pixel map,
+    /// and the glow fades out quadratically over it.
+    pub glow: Option<(T, f64)>,
+}
+
+/// Draw a distance field onto a pixel map, transformed with a matrix.
+///
+/// This implements [`PixelMapCanvas::distance_field`].
+///
+/// [`PixelMapCanvas::distance_field`]: `crate::PixelMapCanvas::distance_field`
+pub(crate) fn draw_distance_field<F, T>(
+    format: &F,
+    pixel_map: &mut PixelMapMut<F::Pixel>,
+    matrix: Matrix,
+    field: &DistanceField<T>,
+    style: Style<F::Pixel>,
+)
+    where F: Format,
+          T: Distances,
+{
+    let (field_x, field_y) = field.extent();
+    if field_x == 0 || field_y == 0 {
+        return;
+    }
+
+    // Distances are converted from pixels of the field
+    // to pixels of the pixel map, assuming uniform scaling.
+    let matrix = matrix * field.inverse_matrix();
+    let scale = (matrix.a * matrix.d - matrix.b * matrix.c).abs().sqrt();
+    let inverse = match Homography::from(matrix).inverse() {
+        Some(inverse) if scale.is_finite() => inverse,
+        _ => return,
+    };
+
+    let (origin_x, origin_y) = pixel_map.origin();
+    let (extent_x, extent_y) = pixel_map.extent();
+    let corner = |x: u32, y: u32| matrix * Vector{x: x as f64, y: y as f64};
+    let corners = [corner(0, 0), corner(field_x, 0), corner(field_x, field_y), corner(0, field_y)];
+    let bounds = Bounds::from_points(corners.iter().copied());
+    if !bounds.is_finite() {
+        return;
+    }
+    let x0 = f64::max(origin_x as f64, bounds.min.x.floor());
+    let y0 = f64::max(origin_y as f64, bounds.min.y.floor());
+    let x1 = f64::min(origin_x as f64 + extent_x as f64, bounds.max.x.ceil());
+    let y1 = f64::min(origin_y as f64 + extent_y as f64, bounds.max.y.ceil());
+    if !(x0 < x1 && y0 < y1) {
+        return;
+    }
+    let (x0, y0, x1, y1) = (x0 as u32, y0 as u32, x1 as u32, y1 as u32);
+
+    let outline_width = style.outline.map_or(0.0, |(_, width)| width.max(0.0));
+
+    for y in y0 .. y1 {
+        let row = pixel_map.horizontal_mut(x0, y, x1 - x0);
+        for (x, dest) in (x0 ..).zip(row) {
+
+            let center = Vector{x: x as f64 + 0.5, y: y as f64 + 0.5};
+            let point = match inverse.transform(center) {
+                Some(point) => point,
+                None => continue,
+            };
+            let inside =
+                point.x >= 0.0 && point.x < field_x as f64 &&
+                point.y >= 0.0 && point.y < field_y as f64;
+            if !inside {
+                continue;
+            }
+
+            // Anti-alias over one pixel centered on each edge.
+            let distance = field.sample(point) as f64 * scale;
+            let mut blend = |pixel: F::Pixel, coverage: f64| {
+                let coverage = (coverage.clamp(0.0, 1.0) * 255.0).round() as u8;
+                if coverage != 0 {
+                    format.blend_source_over_coverage(slice::from_mut(dest), pixel, coverage);
+                }
+            };
+
+            if let Some((pixel, radius)) = style.glow {
+                let distance = distance + outline_width;
+                let fade =
+                    if distance > 0.0 { 1.0 }
+                    else if radius > 0.0 { (1.0 + distance / radius).clamp(0.0, 1.0) }
+                    else { 0.0 };
+                blend(pixel, fade * fade);
+            }
+            if let Some((pixel, _)) = style.outline {
+                blend(pixel, 0.5 + distance + outline_width);
+            }
+            blend(style.fill, 0.5 + distance);
+
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use crate::PixelMap;
+    use crate::PixelMapCanvas;
+    use crate::formats::Bgra8888;
+
+    fn v(x: f64, y: f64) -> Vector
+    {
+        Vector{x, y}
+    }
+
+    fn polygon_path(points: &[Vector]) -> PathBuf
+    {
+        let mut path = PathBuf::new();
+        path.push_move(points[0]);
+        for &point in &points[1 ..] {
+            path.push_linear(point);
+        }
+        path
+    }
+
+    fn square() -> PathBuf
+    {
+        polygon_path(&[v(0.0, 0.0), v(10.0, 0.0), v(10.0, 10.0), v(0.0, 10.0)])
+    }
+
+    #[test]
+    fn test_generate()
+    {
+        let reversed =
+            polygon_path(&[v(0.0, 0.0), v(0.0, 10.0), v(10.0, 10.0), v(10.0, 0.0)]);
+        for path in &[square(), reversed] {
+            let single = generate(path, FillRule::NonZero, 1.0, 2.0);
+            let multi = generate_multi_channel(path, FillRule::NonZero, 1.0, 2.0);
+            assert_eq!(single.extent(), (14, 14));
+            assert_eq!(multi.extent(), (14, 14));
+            assert_eq!(single.matrix() * v(0.0, 0.0), v(2.0, 2.0));
+
+            let at = |x: usize, y: usize| {
+                let multi = multi.distances()[x + 14 * y].signed_distance();
+                (single.distances()[x + 14 * y], multi)
+            };
+            assert_eq!(at(7, 7), (2.0, 2.0));
+            assert_eq!(at(0, 7), (-1.5, -1.5));
+            assert_eq!(at(7, 0), (-1.5, -1.5));
+            assert_eq!(at(2, 7), (0.5, 0.5));
+            assert_eq!(at(1, 7), (-0.5, -0.5));
+            assert_eq!(at(12, 3), (-0.5, -0.5));
+
+            // Diagonally off a corner, the single-channel field
+            // has the true distance, and the multi-channel field
+            // has the distance to the extended edges.
+            let (single, multi) = at(1, 1);
+            assert!((single + 0.5 * 2f32.sqrt()).abs() < 1e-6);
+            assert_eq!(multi, -0.5);
+        }
+
+        let empty = generate(&PathBuf::new(), FillRule::NonZero, 1.0, 2.0);
+        assert_eq!(empty.extent(), (0, 0));
+        let empty = generate(&square(), FillRule::NonZero, f64::NAN, 2.0);
+        assert_eq!(empty.extent(), (0, 0));
+    }
+
+    #[test]
+    fn test_color_edges()
+    {
+        let colors = |path: &PathBuf| -> Vec<u8> {
+            let shape = Shape::new(path, 1.0, FillRule::NonZero);
+            shape.edges().map(|edge| edge.color).collect()
+        };
+
+        assert_eq!(colors(&square()), [CYAN, MAGENTA, YELLOW, MAGENTA]);
+
+        let triangle = polygon_path(&[v(0.0, 0.0), v(10.0, 0.0), v(0.0, 10.0)]);
+        assert_eq!(colors(&triangle), [CYAN, MAGENTA, YELLOW]);
+
+        // Smooth contours have no corners.
+        let mut circle = PathBuf::new();
+        circle.push_move(v(1.0, 0.0));
+        circle.push_quadratic(v(1.0, 1.0), v(0.0, 1.0));
+        circle.push_quadratic(v(-1.0, 1.0), v(-1.0, 0.0));
+        circle.push_quadratic(v(-1.0, -1.0), v(0.0, -1.0));
+        circle.push_quadratic(v(1.0, -1.0), v(1.0, 0.0));
+        assert_eq!(colors(&circle), [WHITE; 4]);
+
+        // A single corner is divided into three edges.
+        let mut teardrop = PathBuf::new();
+        teardrop.push_move(v(0.0, 0.0));
+        teardrop.push_cubic(v(10.0, -10.0), v(10.0, 10.0), v(0.0, 0.0));
+        assert_eq!(colors(&teardrop), [MAGENTA, WHITE, YELLOW]);
+    }
+
+    fn draw<T>(field: &DistanceField<T>, matrix: Matrix, style: Style<[u8; 4]>)
+        -> PixelMap<[u8; 4]>
+        where T: Distances
+    {
+        let mut pixel_map = PixelMap::new([0; 4], 40, 40).unwrap();
+        let mut canvas = PixelMapCanvas::new(Bgra8888, pixel_map.as_mut());
+        canvas.distance_field(matrix, field, style);
+        pixel_map
+    }
+
+    const WHITE_PIXEL: [u8; 4] = [0xFF; 4];
+
+    #[test]
+    fn test_draw_corners()
+    {
+        let style = Style{fill: WHITE_PIXEL, outline: None, glow: None};
+        let matrix = Matrix::from_translate(16.0, 16.0) * Matrix::from_scale(8.0, 8.0);
+        let alpha = |pixel_map: &PixelMap<[u8; 4]>, x: usize, y: usize| {
+            pixel_map.pixels()[x + 40 * y][3]
+        };
+
+        // The multi-channel field keeps the corner sharp when magnified.
+        let multi = generate_multi_channel(&square(), FillRule::NonZero, 1.0, 2.0);
+        let result = draw(&multi, matrix, style);
+        assert_eq!(alpha(&result, 16, 16), 0xFF);
+        assert_eq!(alpha(&result, 15, 15), 0);
+        assert_eq!(alpha(&result, 15, 30), 0);
+        assert_eq!(alpha(&result, 16, 30), 0xFF);
+
+        // The single-channel field rounds it off.
+        let single = generate(&square(), FillRule::NonZero, 1.0, 2.0);
+        let result = draw(&single, matrix, style);
+        assert_eq!(alpha(&result, 16, 16), 0);
+        assert_eq!(alpha(&result, 16, 30), 0xFF);
+    }
+
+    #[test]
+    fn test_draw_effects()
+    {
+        let field = generate_multi_channel(&square(), FillRule::NonZero, 1.0, 4.0);
+        let red = [0, 0, 0xFF, 0xFF];
+        let blue = [0xFF, 0, 0, 0xFF];
+        let style = Style{fill: WHITE_PIXEL, outline: Some((red, 2.0)), glow: Some((blue, 2.0))};
+        let result = draw(&field, Matrix::from_translate(10.0, 10.0), style);
+        let row: Vec<[u8; 4]> = result.pixels()[40 * 15 ..][.. 12].to_vec();
+
+        // From left to right: nothing, the glow fading in,
+        // the outline, and the fill.
+        assert_eq!(row[5], [0; 4]);
+        assert_eq!(row[6], [0xFF, 0, 0, 0x10]);
+        assert_eq!(row[7], [0xFF, 0, 0, 0x8F]);
+        assert_eq!(row[8], red);
+        assert_eq!(row[9], red);
+        assert_eq!(row[10], WHITE_PIXEL);
+        assert_eq!(row[11], WHITE_PIXEL);
+    }
+}
@@ -0,0 +1,315 @@
+use crate::BlendMode;
+use crate::Bounds;
+use crate::Canvas;
+use crate::Format;
+use crate::Matrix;
+use crate::Paint;
+use crate::Vector;
+use crate::path::Instruction;
+
+use std::fmt::Write;
+
+/// Canvas that writes an SVG document.
+///
+/// Each drawing command becomes a `<path>` element,
+/// with the matrix as its `transform` attribute
+/// and the paint as its `fill` and `fill-opacity` attributes.
+/// The format is used to find out the color of each pixel.
+/// Open the document in a web browser to inspect what was drawn;
+/// the elements appear in the order they were drawn.
+///
+/// Blend modes are mapped to the CSS `mix-blend-mode` property,
+/// and recorded in a `data-blend-mode` attribute for inspection.
+/// [`SourceOver`] is the normal blend mode of SVG.
+/// [`Destination`] draws nothing,
+/// so its elements are emitted with `display: none`.
+///
+/// Commands with points that are NaN or infinite
+/// once transformed by the matrix would produce invalid SVG,
+/// so they are replaced with an XML comment that says so.
+///
+/// Layers become `<g>` elements with an `opacity` attribute,
+/// clipped to the bounds of the layer with a `<clipPath>`,
+/// and with the blend mode mapped like for drawing commands.
+/// Layers with infinite bounds are not clipped,
+/// and layers with empty or NaN bounds are not displayed.
+///
+/// # Limitations
+///
+/// [`Source`] has no equivalent in SVG or CSS, and is drawn as normal.
+/// Where a [`PixelMapCanvas`] replaces what lies beneath the shape,
+/// the SVG document draws the shape over it instead,
+/// which looks different when the paint is not opaque.
+/// Such commands are preceded by an XML comment that says so.
+///
+/// [`Source`]: `BlendMode::Source`
+/// [`SourceOver`]: `BlendMode::SourceOver`
+/// [`Destination`]: `BlendMode::Destination`
+/// [`PixelMapCanvas`]: `crate::PixelMapCanvas`
+pub struct SvgCanvas<F>
+{
+    format: F,
+    document: String,
+
+    // The number of layers that are pushed.
+    layers: usize,
+
+    // The number of clip paths, used to give them unique IDs.
+    clip_paths: usize,
+}
+
+impl<F> SvgCanvas<F>
+    where F: Format
+{
+    /// Create a new canvas with the given size in pixels.
+    ///
+    /// The SVG document starts with the `<svg>` start tag.
+    /// The end tag is written by [`SvgCanvas::finish`].
+    pub fn new(format: F, extent_x: u32, extent_y: u32) -> Self
+    {
+        let mut document = String::new();
+        writeln!(
+            document,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" \
+                  width=\"{0}\" height=\"{1}\" \
+                  viewBox=\"0 0 {0} {1}\">",
+            extent_x,
+            extent_y,
+        ).unwrap();
+        Self{format, document, layers: 0, clip_paths: 0}
+    }
+
+    /// Finish the SVG document and return it.
+    ///
+    /// Layers that are still pushed are popped first.
+    pub fn finish(mut self) -> String
+    {
+        while self.layers > 0 {
+            self.pop_layer();
+        }
+        self.document.push_str("</svg>\n");
+        self.document
+    }
+
+    fn element(&mut self, matrix: Matrix, points: &[Vector], data: &str, paint: Paint<F::Pixel>)
+    {
+        let Matrix{a, b, c, d, tx, ty} = matrix;
+        let finite =
+            [a, b, c, d, tx, ty].iter().all(|x| x.is_finite()) &&
+            points.iter().all(|&point| {
+                let Vector{x, y} = matrix * point;
+                x.is_finite() && y.is_finite()
+            });
+        if !finite {
+            self.document.push_str("<!-- Command with non-finite coordinates -->\n");
+            return;
+        }
+
+        let [red, green, blue, alpha] = self.format.to_rgba8888(paint.pixel);
+        let (blend_mode, style) = blend_mode_style(paint.blend_mode);
+        if matches!(paint.blend_mode, BlendMode::Source) && alpha != 0xFF {
+            self.document.push_str("<!-- Translucent source drawn as source-over -->\n");
+        }
+
+        writeln!(
+            self.document,
+            "<path d=\"{}\" transform=\"matrix({} {} {} {} {} {})\" \
+                   fill=\"#{:02x}{:02x}{:02x}\" fill-opacity=\"{}\" \
+                   style=\"{}\" data-blend-mode=\"{}\"/>",
+            data,
+            a, b, c, d, tx, ty,
+            red, green, blue, alpha as f64 / 255.0,
+            style, blend_mode,
+        ).unwrap();
+    }
+}
+
+impl<F> Canvas for SvgCanvas<F>
+    where F: Format
+{
+    type Pixel = F::Pixel;
+
+    fn rectangle(
+        &mut self,
+        matrix: Matrix,
+        start: Vector,
+        extent: Vector,
+        paint: Paint<Self::Pixel>,
+    )
+    {
+        let data = format!(
+            "M {} {} h {} v {} h {} Z",
+            start.x, start.y, extent.x, extent.y, -extent.x,
+        );
+        let points = [
+            start,
+            start + Vector{x: extent.x, y: 0.0},
+            start + extent,
+            start + Vector{x: 0.0, y: extent.y},
+        ];
+        self.element(matrix, &points, &data, paint);
+    }
+
+    fn path<P: IntoIterator<Item=Instruction>>(
+        &mut self,
+        matrix: Matrix,
+        path: P,
+        paint: Paint<Self::Pixel>,
+    )
+    {
+        let mut data = String::new();
+        let mut points = Vec::new();
+        for instruction in path {
+            if data.is_empty() && !matches!(instruction, Instruction::Move(..)) {
+                // SVG paths must begin with a move,
+                // whereas our paths begin at the origin.
+                data.push_str("M 0 0");
+            }
+            if !data.is_empty() {
+                data.push(' ');
+            }
+            match instruction {
+                Instruction::Move(p) => points.push(p),
+                Instruction::Linear(p1) => points.push(p1),
+                Instruction::Quadratic(p1, p2) => points.extend_from_slice(&[p1, p2]),
+                Instruction::Cubic(p1, p2, p3) => points.extend_from_slice(&[p1, p2, p3]),
+            }
+            match instruction {
+                Instruction::Move(p) =>
+                    write!(data, "M {} {}", p.x, p.y),
+                Instruction::Linear(p1) =>
+                    write!(data, "L {} {}", p1.x, p1.y),
+                Instruction::Quadratic(p1, p2) =>
+                    write!(data, "Q {} {} {} {}", p1.x, p1.y, p2.x, p2.y),
+                Instruction::Cubic(p1, p2, p3) =>
+                    write!(data, "C {} {} {} {} {} {}",
+                           p1.x, p1.y, p2.x, p2.y, p3.x, p3.y),
+            }.unwrap();
+        }
+        self.element(matrix, &points, &data, paint);
+    }
+
+    fn push_layer(&mut self, bounds: Bounds, opacity: f32, blend_mode: BlendMode)
+    {
+        self.layers += 1;
+
+        if bounds.is_empty() {
+            self.document.push_str("<!-- Layer with empty or NaN bounds -->\n");
+            self.document.push_str("<g style=\"display: none\">\n");
+            return;
+        }
+
+        let mut clip_path = String::new();
+        if bounds.is_finite() {
+            let id = self.clip_paths;
+            self.clip_paths += 1;
+            let Bounds{min, max} = bounds;
+            writeln!(
+                self.document,
+                "<clipPath id=\"layer{}\"><rect x=\"{}\" y=\"{}\" \
+                                          width=\"{}\" height=\"{}\"/></clipPath>",
+                id, min.x, min.y, max.x - min.x, max.y - min.y,
+            ).unwrap();
+            write!(clip_path, " clip-path=\"url(#layer{})\"", id).unwrap();
+        }
+
+        let (blend_mode, style) = blend_mode_style(blend_mode);
+        writeln!(
+            self.document,
+            "<g{} opacity=\"{}\" style=\"{}\" data-blend-mode=\"{}\">",
+            clip_path, opacity.clamp(0.0, 1.0), style, blend_mode,
+        ).unwrap();
+    }
+
+    fn pop_layer(&mut self)
+    {
+        if self.layers > 0 {
+            self.layers -= 1;
+            self.document.push_str("</g>\n");
+        }
+    }
+}
+
+/// The name of the blend mode for the `data-blend-mode` attribute,
+/// and the CSS style that implements it.
+fn blend_mode_style(blend_mode: BlendMode) -> (&'static str, &'static str)
+{
+    match blend_mode {
+        BlendMode::Source => ("source", "mix-blend-mode: normal"),
+        BlendMode::SourceOver => ("source-over", "mix-blend-mode: normal"),
+        BlendMode::Destination => ("destination", "display: none"),
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use crate::formats::Bgra8888;
+
+    #[test]
+    fn test_document()
+    {
+        let paint = Paint{blend_mode: BlendMode::SourceOver, pixel: [0xFF, 0x80, 0x00, 0xFF]};
+        let mut canvas = SvgCanvas::new(Bgra8888, 100, 50);
+        canvas.rectangle(
+            Matrix::from_translate(10.0, 20.0),
+            Vector{x: 1.0, y: 2.0},
+            Vector{x: 3.0, y: 4.0},
+            paint,
+        );
+        canvas.path(
+            Matrix::IDENTITY,
+            vec![Instruction::Linear(Vector{x: 5.0, y: 6.5})],
+            Paint{blend_mode: BlendMode::Destination, ..paint},
+        );
+        canvas.path(
+            Matrix::IDENTITY,
+            vec![Instruction::Move(Vector{x: f64::NAN, y: 0.0})],
+            paint,
+        );
+        canvas.rectangle(
+            Matrix::IDENTITY,
+            Vector{x: 0.0, y: 0.0},
+            Vector{x: 1.0, y: 1.0},
+            Paint{blend_mode: BlendMode::Source, pixel: [0xFF, 0x80, 0x00, 0x80]},
+        );
+        canvas.rectangle(
+            Matrix::from_scale(1e300, 1e300),
+            Vector{x: 0.0, y: 0.0},
+            Vector{x: 1e300, y: 1e300},
+            paint,
+        );
+        canvas.pop_layer();
+        let bounds = Bounds{min: Vector{x: 1.0, y: 2.0}, max: Vector{x: 5.0, y: 4.0}};
+        canvas.push_layer(bounds, 0.5, BlendMode::SourceOver);
+        canvas.push_layer(Bounds::EMPTY, 1.0, BlendMode::Source);
+        canvas.pop_layer();
+        let document = canvas.finish();
+
+        let expected =
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"50\" viewBox=\"0 0 100 50\">\n\
+             <path d=\"M 1 2 h 3 v 4 h -3 Z\" transform=\"matrix(1 0 0 1 10 20)\" \
+                   fill=\"#0080ff\" fill-opacity=\"1\" \
+                   style=\"mix-blend-mode: normal\" data-blend-mode=\"source-over\"/>\n\
+             <path d=\"M 0 0 L 5 6.5\" transform=\"matrix(1 0 0 1 0 0)\" \
+                   fill=\"#0080ff\" fill-opacity=\"1\" \
+                   style=\"display: none\" data-blend-mode=\"destination\"/>\n\
+             <!-- Command with non-finite coordinates -->\n\
+             <!-- Translucent source drawn as source-over -->\n\
+             <path d=\"M 0 0 h 1 v 1 h -1 Z\" transform=\"matrix(1 0 0 1 0 0)\" \
+                   fill=\"#0080ff\" fill-opacity=\"0.5019607843137255\" \
+                   style=\"mix-blend-mode: normal\" data-blend-mode=\"source\"/>\n\
+             <!-- Command with non-finite coordinates -->\n\
+             <clipPath id=\"layer0\"><rect x=\"1\" y=\"2\" width=\"4\" height=\"2\"/></clipPath>\n\
+             <g clip-path=\"url(#layer0)\" opacity=\"0.5\" \
+                style=\"mix-blend-mode: normal\" data-blend-mode=\"source-over\">\n\
+             <!-- Layer with empty or NaN bounds -->\n\
+             <g style=\"display: none\">\n\
+             </g>\n\
+             </g>\n\
+             </svg>\n";
+        assert_eq!(document, expected);
+    }
+}
@@ -0,0 +1,819 @@
+//! Converting paths into triangle meshes.
+//!
+//! GPUs draw triangles, not paths.
+//! A [`Tessellator`] converts filled and stroked paths into [`Mesh`]es
+//! of triangles that cover exactly the region the path fills,
+//! optionally surrounded by a fringe of triangles that fade out
+//! to anti-alias the edges.
+//! Meshes can also be drawn on the CPU with [`PixelMapCanvas::mesh`],
+//! which selects the same pixels as [`PixelMapCanvas::fill_path`].
+//!
+//! [`PixelMapCanvas::mesh`]: `crate::PixelMapCanvas::mesh`
+//! [`PixelMapCanvas::fill_path`]: `crate::PixelMapCanvas::fill_path`
+
+use crate::BlendMode;
+use crate::Format;
+use crate::Matrix;
+use crate::Paint;
+use crate::PathBuf;
+use crate::PixelMapMut;
+use crate::Vector;
+use crate::canvas::pixel_span;
+use crate::float::total_cmp;
+use crate::image::blend_pixel;
+use crate::path::BooleanOp;
+use crate::path::FillRule;
+use crate::path::Instruction;
+use crate::path::boolean;
+use crate::path::disc;
+use crate::path::edges;
+use crate::path::flatten;
+use crate::path::flatten_instruction;
+use crate::path::push_positive;
+
+use std::collections::HashMap;
+use std::mem;
+use std::slice;
+
+/// Indexed triangle mesh.
+///
+/// Every three consecutive indices refer to
+/// the vertices of a single triangle.
+/// Triangles may have either winding direction.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh
+{
+    /// The vertices of the triangles.
+    pub vertices: Vec<Vertex>,
+
+    /// Indices into the vertices, three per triangle.
+    pub indices: Vec<u32>,
+}
+
+/// Vertex of a [`Mesh`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vertex
+{
+    /// The position of the vertex, in pixels.
+    pub position: Vector,
+
+    /// How much of the paint to draw at this vertex,
+    /// from zero (nothing) to one (all of it).
+    ///
+    /// The coverage is interpolated linearly across triangles.
+    /// Vertices of the filled region have coverage one,
+    /// and the outer vertices of the fringe have coverage zero.
+    pub coverage: f32,
+}
+
+impl Mesh
+{
+    /// Create an empty mesh, without allocating.
+    pub fn new() -> Self
+    {
+        Self{vertices: Vec::new(), indices: Vec::new()}
+    }
+
+    /// Remove all triangles, keeping the allocations.
+    pub fn clear(&mut self)
+    {
+        self.vertices.clear();
+        self.indices.clear();
+    }
+
+    /// Append a vertex and return its index.
+    fn push_vertex(&mut self, position: Vector, coverage: f32) -> u32
+    {
+        let index = self.vertices.len() as u32;
+        self.vertices.push(Vertex{position, coverage});
+        index
+    }
+}
+
+/// How to stroke a path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stroke
+{
+    /// The width of the stroke, in the coordinates of the path.
+    ///
+    /// Strokes whose width is not positive and finite are not drawn.
+    pub width: f64,
+
+    /// How to connect consecutive line segments.
+    pub join: Join,
+
+    /// How to end contours that are not closed.
+    pub cap: Cap,
+}
+
+/// How to connect consecutive line segments of a stroke.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Join
+{
+    /// Extend the outer edges until they meet.
+    ///
+    /// If the distance from the meeting point to the vertex
+    /// exceeds the given multiple of half the stroke width,
+    /// a bevel join is used instead.
+    Miter(f64),
+
+    /// Round off the corner with a circular arc.
+    Round,
+
+    /// Cut off the corner with a straight line.
+    Bevel,
+}
+
+/// How to end a contour of a stroke that is not closed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Cap
+{
+    /// End the stroke at the end point.
+    Butt,
+
+    /// Extend the stroke with a half disc around the end point.
+    Round,
+
+    /// Extend the stroke by half its width beyond the end point.
+    Square,
+}
+
+/// Converts paths into triangle meshes.
+///
+/// Paths are transformed with a matrix first,
+/// and their curves are flattened in pixel coordinates,
+/// so that meshes can be drawn without further transformation.
+///
+/// The filled region is divided into trapezoids between
+/// the vertices and intersections of the edges of the path,
+/// which handles self-intersecting paths and both fill rules.
+/// As with [`boolean`], this takes quadratic time
+/// in the number of edges.
+#[derive(Clone, Copy, Debug)]
+pub struct Tessellator
+{
+    /// Maximum distance between a curve and the line segments
+    /// that approximate it, in pixels.
+    ///
+    /// A quarter pixel is a good default.
+    pub tolerance: f64,
+
+    /// The width of the anti-aliasing fringe, in pixels.
+    ///
+    /// The fringe lies outside of the filled region,
+    /// so shapes appear to grow by about half its width.
+    /// One pixel is a good default.
+    /// If the width is zero, no fringe is generated,
+    /// and the mesh covers exactly the pixels whose centers
+    /// are inside the path.
+    pub fringe: f64,
+}
+
+impl Tessellator
+{
+    /// Append the triangles filling a path to the mesh.
+    ///
+    /// Contours are implicitly closed, as with [`flatten`].
+    pub fn fill(&self, path: &PathBuf, matrix: Matrix, fill_rule: FillRule, mesh: &mut Mesh)
+    {
+        let path: PathBuf = path.instructions().map(|i| matrix * i).collect();
+        self.fill_transformed(&path, fill_rule, mesh);
+    }
+
+    /// Append the triangles stroking a path to the mesh.
+    ///
+    /// Contours whose last point equals their first point are closed,
+    /// and joined at that point; other contours end in caps.
+    /// The stroke is computed in the coordinates of the path
+    /// and then transformed, so it is scaled by the matrix like the path.
+    /// Overlapping parts of the stroke are covered only once.
+    pub fn stroke(&self, path: &PathBuf, matrix: Matrix, stroke: Stroke, mesh: &mut Mesh)
+    {
+        if !(stroke.width > 0.0 && stroke.width.is_finite()) {
+            return;
+        }
+
+        // Curves are flattened before transforming,
+        // so the tolerance is scaled down accordingly.
+        let tolerance = self.tolerance / max_scale(matrix);
+        if tolerance.is_nan() {
+            return;
+        }
+
+        // Each piece of the stroke is a polygon with a positive direction,
+        // so the non-zero fill rule fills their union.
+        let mut pieces = PathBuf::new();
+        let mut push_piece = |polygon: &[Vector]| {
+            let polygon: Vec<Vector> = polygon.iter().map(|&p| matrix * p).collect();
+            push_positive(&mut pieces, &polygon);
+        };
+        for polyline in polylines(path, tolerance) {
+            stroke_polyline(&polyline, stroke, tolerance, &mut push_piece);
+        }
+
+        self.fill_transformed(&pieces, FillRule::NonZero, mesh);
+    }
+
+    fn fill_transformed(&self, path: &PathBuf, fill_rule: FillRule, mesh: &mut Mesh)
+    {
+        let contours = flatten(path.instructions(), self.tolerance);
+        fill_contours(&contours, fill_rule, mesh);
+
+        if self.fringe > 0.0 && self.fringe.is_finite() {
+            let empty = PathBuf::new();
+            let region = boolean(BooleanOp::Union, path, &empty, fill_rule, self.tolerance);
+            let contours = flatten(region.instructions(), self.tolerance);
+            fringe(&contours, self.fringe, mesh);
+        }
+    }
+}
+
+/// The factor by which the matrix scales lengths at most.
+fn max_scale(matrix: Matrix) -> f64
+{
+    let Matrix{a, b, c, d, ..} = matrix;
+    let sum = a * a + b * b + c * c + d * d;
+    let det = a * d - b * c;
+    let root = f64::max(sum * sum - 4.0 * det * det, 0.0).sqrt();
+    ((sum + root) / 2.0).sqrt()
+}
+
+/// Append trapezoids covering the region inside the contours.
+fn fill_contours(contours: &[Vec<Vector>], fill_rule: FillRule, mesh: &mut Mesh)
+{
+    // Edges from top to bottom, with the direction they count in,
+    // as in the winding number.
+    let mut edges: Vec<(Vector, Vector, i32)> =
+        contours.iter()
+        .flat_map(|contour| edges(contour))
+        .filter(|(a, b)| [a.x, a.y, b.x, b.y].iter().all(|c| c.is_finite()))
+        .filter(|(a, b)| a.y != b.y)
+        .map(|(&a, &b)| if a.y < b.y { (a, b, 1) } else { (b, a, -1) })
+        .collect();
+    edges.sort_by(|e, f| total_cmp(&e.0.y, &f.0.y));
+
+    // Divide the plane into horizontal slabs
+    // within which no edges start, end, or cross.
+    let mut ys: Vec<f64> = Vec::new();
+    for e in &edges {
+        ys.extend_from_slice(&[e.0.y, e.1.y]);
+    }
+    for (i, e) in edges.iter().enumerate() {
+        for f in &edges[i + 1 ..] {
+            let top = f64::max(e.0.y, f.0.y);
+            let bottom = f64::min(e.1.y, f.1.y);
+            if top >= bottom {
+                continue;
+            }
+            let d0 = x_at(*e, top) - x_at(*f, top);
+            let d1 = x_at(*e, bottom) - x_at(*f, bottom);
+            if d0 * d1 < 0.0 {
+                let y = top + (bottom - top) * d0 / (d0 - d1);
+                if top < y && y < bottom {
+                    ys.push(y);
+                }
+            }
+        }
+    }
+    ys.sort_by(total_cmp);
+    ys.dedup();
+
+    // Vertices on the boundaries between slabs are shared.
+    let mut vertices = HashMap::new();
+    let mut vertex = |mesh: &mut Mesh, position: Vector| {
+        let key = (position.x.to_bits(), position.y.to_bits());
+        *vertices.entry(key).or_insert_with(|| mesh.push_vertex(position, 1.0))
+    };
+
+    let mut next = 0;
+    let mut active: Vec<(Vector, Vector, i32)> = Vec::new();
+    let mut crossings: Vec<(f64, f64, i32)> = Vec::new();
+    for window in ys.windows(2) {
+        let (y0, y1) = (window[0], window[1]);
+        while next < edges.len() && edges[next].0.y <= y0 {
+            active.push(edges[next]);
+            next += 1;
+        }
+        active.retain(|e| y0 < e.1.y);
+
+        crossings.clear();
+        crossings.extend(active.iter().map(|&e| (x_at(e, y0), x_at(e, y1), e.2)));
+        crossings.sort_by(|c, d| total_cmp(&(c.0 + c.1), &(d.0 + d.1)));
+
+        // As in the scanline rasterizer, each crossing passed
+        // no longer counts towards the winding number.
+        // Adjacent inside regions are merged into one trapezoid.
+        let mut winding_number: i32 = crossings.iter().map(|c| c.2).sum();
+        let mut left = None;
+        for (i, &(_, _, direction)) in crossings.iter().enumerate() {
+            winding_number -= direction;
+            let inside = fill_rule.is_inside(winding_number);
+            match left {
+                None if inside => left = Some(i),
+                Some(l) if !inside => {
+                    let (l, r) = (crossings[l], crossings[i]);
+                    let top_left = Vector{x: l.0, y: y0};
+                    let top_right = Vector{x: r.0, y: y0};
+                    let bottom_right = Vector{x: r.1, y: y1};
+                    let bottom_left = Vector{x: l.1, y: y1};
+                    for &triangle in &[
+                        [top_left, top_right, bottom_right],
+                        [top_left, bottom_right, bottom_left],
+                    ] {
+                        let [p0, p1, p2] = triangle;
+                        if (p1 - p0).cross(p2 - p0) != 0.0 {
+                            for &p in &triangle {
+                                let index = vertex(mesh, p);
+                                mesh.indices.push(index);
+                            }
+                        }
+                    }
+                    left = None;
+                },
+                _ => (),
+            }
+        }
+    }
+}
+
+/// The x coordinate of an edge at the given y coordinate.
+///
+/// The end points are returned exactly,
+/// so that edges that share them meet exactly.
+fn x_at((a, b, _): (Vector, Vector, i32), y: f64) -> f64
+{
+    if y == a.y { return a.x; }
+    if y == b.y { return b.x; }
+    a.x + (y - a.y) * (b.x - a.x) / (b.y - a.y)
+}
+
+/// Append a fringe of the given width around the contours,
+/// which must have the inside on their left, as returned by [`boolean`].
+fn fringe(contours: &[Vec<Vector>], width: f64, mesh: &mut Mesh)
+{
+    for contour in contours {
+
+        // Edges with their outward normals scaled to the fringe width.
+        let outward: Vec<(Vector, Vector, Vector)> =
+            edges(contour)
+            .filter(|(a, b)| a != b)
+            .map(|(&a, &b)| (a, b, (-width / (b - a).length()) * (b - a).perpendicular()))
+            .collect();
+
+        for (i, &(a, b, normal)) in outward.iter().enumerate() {
+            let i0 = mesh.push_vertex(a, 1.0);
+            let i1 = mesh.push_vertex(b, 1.0);
+            let i2 = mesh.push_vertex(b + normal, 0.0);
+            let i3 = mesh.push_vertex(a + normal, 0.0);
+            mesh.indices.extend_from_slice(&[i0, i1, i2, i0, i2, i3]);
+
+            // Convex corners leave a gap between the fringes of their edges.
+            let (_, c, next_normal) = outward[(i + 1) % outward.len()];
+            if (b - a).cross(c - b) > 0.0 {
+                let i4 = mesh.push_vertex(b + next_normal, 0.0);
+                mesh.indices.extend_from_slice(&[i1, i2, i4]);
+            }
+        }
+
+    }
+}
+
+/// The contours of a path as open polylines,
+/// without consecutive duplicate points.
+fn polylines(path: &PathBuf, tolerance: f64) -> Vec<Vec<Vector>>
+{
+    // Contours consisting of only the point moved to draw nothing,
+    // unlike contours with segments of zero length.
+    let mut polylines = Vec::new();
+    let mut points = vec![Vector{x: 0.0, y: 0.0}];
+    let mut drawn = false;
+    for instruction in path.instructions() {
+        if let Instruction::Move(to) = instruction {
+            if drawn {
+                polylines.push(mem::take(&mut points));
+            }
+            points.clear();
+            points.push(to);
+            drawn = false;
+            continue;
+        }
+        let p0 = points[points.len() - 1];
+        flatten_instruction(p0, instruction, tolerance, &mut points);
+        points.dedup();
+        drawn = true;
+    }
+    if drawn {
+        polylines.push(points);
+    }
+    polylines
+}
+
+/// Call `piece` with polygons whose union is the stroke of the polyline.
+fn stroke_polyline(
+    polyline: &[Vector],
+    stroke: Stroke,
+    tolerance: f64,
+    piece: &mut impl FnMut(&[Vector]),
+)
+{
+    let radius = stroke.width / 2.0;
+    let round = |piece: &mut dyn FnMut(&[Vector]), center: Vector| {
+        let disc: Vec<Vector> = disc(radius, tolerance).iter().map(|&p| center + p).collect();
+        piece(&disc);
+    };
+
+    let closed = polyline.len() > 2 && polyline.first() == polyline.last();
+    let points = if closed { &polyline[.. polyline.len() - 1] } else { polyline };
+
+    if points.len() == 1 {
+        let p = points[0];
+        let (x, y) = (Vector{x: radius, y: 0.0}, Vector{x: 0.0, y: radius});
+        match stroke.cap {
+            Cap::Butt => (),
+            Cap::Round => round(piece, p),
+            Cap::Square => piece(&[p - x - y, p + x - y, p + x + y, p - x + y]),
+        }
+        return;
+    }
+
+    let direction = |a: Vector, b: Vector| (1.0 / (b - a).length()) * (b - a);
+    let segments = if closed { points.len() } else { points.len() - 1 };
+    for i in 0 .. segments {
+        let (a, b) = (points[i], points[(i + 1) % points.len()]);
+        let normal = radius * direction(a, b).perpendicular();
+        piece(&[a - normal, b - normal, b + normal, a + normal]);
+    }
+
+    // Joins at the interior points, and at the first point if closed.
+    let joins = if closed { 0 .. points.len() } else { 1 .. points.len() - 1 };
+    for i in joins {
+        let previous = points[(i + points.len() - 1) % points.len()];
+        let (p, next) = (points[i], points[(i + 1) % points.len()]);
+        let (d0, d1) = (direction(previous, p), direction(p, next));
+
+        // The corner sticks out on the side the polyline turns away from.
+        let sign = if d0.cross(d1) > 0.0 { -radius } else { radius };
+        let n0 = sign * d0.perpendicular();
+        let n1 = sign * d1.perpendicular();
+        let cos = d0.dot(d1);
+        let bevel = [p, p + n0, p + n1];
+        match stroke.join {
+            Join::Round => round(piece, p),
+            Join::Bevel => piece(&bevel),
+            Join::Miter(limit) => {
+                let ratio = (2.0 / (1.0 + cos)).sqrt();
+                if ratio <= limit {
+                    let miter = p + (1.0 / (1.0 + cos)) * (n0 + n1);
+                    piece(&[p, p + n0, miter, p + n1]);
+                } else {
+                    piece(&bevel);
+                }
+            },
+        }
+    }
+
+    if !closed {
+        let n = points.len();
+        let ends = [
+            (points[0], direction(points[1], points[0])),
+            (points[n - 1], direction(points[n - 2], points[n - 1])),
+        ];
+        for &(p, outward) in &ends {
+            let normal = radius * outward.perpendicular();
+            let extend = radius * outward;
+            match stroke.cap {
+                Cap::Butt => (),
+                Cap::Round => round(piece, p),
+                Cap::Square =>
+                    piece(&[p - normal, p + normal, p + normal + extend, p - normal + extend]),
+            }
+        }
+    }
+}
+
+/// Draw a triangle mesh onto a pixel map.
+///
+/// This implements [`PixelMapCanvas::mesh`].
+///
+/// [`PixelMapCanvas::mesh`]: `crate::PixelMapCanvas::mesh`
+pub(crate) fn draw_mesh<F>(
+    format: &F,
+    pixel_map: &mut PixelMapMut<F::Pixel>,
+    mesh: &Mesh,
+    paint: Paint<F::Pixel>,
+)
+    where F: Format
+{
+    for triangle in mesh.indices.chunks_exact(3) {
+        let vertex = |i: usize| mesh.vertices[triangle[i] as usize];
+        draw_triangle(format, pixel_map, [vertex(0), vertex(1), vertex(2)], paint);
+    }
+}
+
+fn draw_triangle<F>(
+    format: &F,
+    pixel_map: &mut PixelMapMut<F::Pixel>,
+    [v0, mut v1, mut v2]: [Vertex; 3],
+    paint: Paint<F::Pixel>,
+)
+    where F: Format
+{
+    if matches!(paint.blend_mode, BlendMode::Destination) {
+        return;
+    }
+
+    // Make the interior lie to the left of each edge.
+    let area = (v1.position - v0.position).cross(v2.position - v0.position);
+    if !(area != 0.0 && area.is_finite()) {
+        return;
+    }
+    if area < 0.0 {
+        mem::swap(&mut v1, &mut v2);
+    }
+
+    let (origin_x, origin_y) = pixel_map.origin();
+    let (extent_x, extent_y) = pixel_map.extent();
+    let xs = [v0.position.x, v1.position.x, v2.position.x];
+    let ys = [v0.position.y, v1.position.y, v2.position.y];
+    let min = |cs: [f64; 3]| f64::min(f64::min(cs[0], cs[1]), cs[2]);
+    let max = |cs: [f64; 3]| f64::max(f64::max(cs[0], cs[1]), cs[2]);
+    let (start_x, length_x) = pixel_span(min(xs), max(xs), origin_x, extent_x);
+    let (start_y, length_y) = pixel_span(min(ys), max(ys), origin_y, extent_y);
+    if length_x == 0 {
+        return;
+    }
+
+    // Each edge is opposite the vertex its edge function weighs.
+    let edges = [
+        (v1.position, v2.position),
+        (v2.position, v0.position),
+        (v0.position, v1.position),
+    ];
+    let owns_ties = [
+        owns_tie(edges[0].1 - edges[0].0),
+        owns_tie(edges[1].1 - edges[1].0),
+        owns_tie(edges[2].1 - edges[2].0),
+    ];
+    let coverages = [f64::from(v0.coverage), f64::from(v1.coverage), f64::from(v2.coverage)];
+    let uniform = coverages[0] == coverages[1] && coverages[1] == coverages[2];
+
+    for y in start_y .. start_y + length_y {
+        let row = pixel_map.horizontal_mut(start_x, y, length_x);
+        for (x, dest) in (start_x ..).zip(row) {
+            let center = Vector{x: x as f64 + 0.5, y: y as f64 + 0.5};
+            let weight = |(a, b): (Vector, Vector)| edge_function(a, b, center);
+            let weights = [weight(edges[0]), weight(edges[1]), weight(edges[2])];
+            let inside = weights.iter().zip(&owns_ties)
+                .all(|(&w, &owns_tie)| w > 0.0 || (w == 0.0 && owns_tie));
+            if !inside {
+                continue;
+            }
+
+            let coverage =
+                if uniform {
+                    coverages[0]
+                } else {
+                    let sum = weights[0] + weights[1] + weights[2];
+                    (0 .. 3).map(|i| weights[i] * coverages[i]).sum::<f64>() / sum
+                };
+            let coverage = (coverage.clamp(0.0, 1.0) * 255.0).round() as u8;
+            let dest = slice::from_mut(dest);
+            match (paint.blend_mode, coverage) {
+                (_, 0) => (),
+                (blend_mode, 255) =>
+                    blend_pixel(format, &mut dest[0], paint.pixel, blend_mode),
+                (BlendMode::Source, _) =>
+                    format.blend_source_coverage(dest, paint.pixel, coverage),
+                (BlendMode::SourceOver, _) =>
+                    format.blend_source_over_coverage(dest, paint.pixel, coverage),
+                (BlendMode::Destination, _) => (),
+            }
+        }
+    }
+}
+
+/// Twice the area of the triangle (a, b, point),
+/// positive if the point lies to the left of the edge from a to b.
+///
+/// The result for the edge from b to a is exactly the negation,
+/// so that triangles that share an edge agree on which side a point is.
+fn edge_function(a: Vector, b: Vector, point: Vector) -> f64
+{
+    if a.x < b.x || (a.x == b.x && a.y < b.y) {
+        (b - a).cross(point - a)
+    } else {
+        -(a - b).cross(point - b)
+    }
+}
+
+/// Whether pixel centers exactly on an edge with the given direction
+/// belong to the triangle to its left.
+///
+/// Like the scanline rasterizer, such pixel centers belong
+/// to the region in the positive x direction,
+/// or for horizontal edges, the positive y direction.
+fn owns_tie(direction: Vector) -> bool
+{
+    let inward = direction.perpendicular();
+    inward.x > 0.0 || (inward.x == 0.0 && inward.y > 0.0)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use crate::Canvas;
+    use crate::PixelMap;
+    use crate::PixelMapCanvas;
+    use crate::formats::Bgra8888;
+
+    use proptest::collection::vec;
+    use proptest::prop_assert;
+    use proptest::proptest;
+
+    const EXTENT: u32 = 40;
+
+    const PAINT: Paint<[u8; 4]> =
+        Paint{blend_mode: BlendMode::SourceOver, pixel: [0, 0x40, 0x80, 0x80]};
+
+    const TESSELLATOR: Tessellator = Tessellator{tolerance: 0.25, fringe: 0.0};
+
+    fn v(x: f64, y: f64) -> Vector
+    {
+        Vector{x, y}
+    }
+
+    fn polygon(points: &[Vector]) -> PathBuf
+    {
+        let mut path = PathBuf::new();
+        path.push_move(points[0]);
+        for &point in &points[1 ..] {
+            path.push_linear(point);
+        }
+        path
+    }
+
+    fn draw(f: impl FnOnce(&mut PixelMapCanvas<Bgra8888>)) -> PixelMap<[u8; 4]>
+    {
+        let mut pixel_map = PixelMap::new([0; 4], EXTENT, EXTENT).unwrap();
+        f(&mut PixelMapCanvas::new(Bgra8888, pixel_map.as_mut()));
+        pixel_map
+    }
+
+    fn draw_mesh(mesh: &Mesh) -> PixelMap<[u8; 4]>
+    {
+        draw(|canvas| canvas.mesh(mesh, PAINT))
+    }
+
+    /// The pixels at which the results differ.
+    ///
+    /// Pixels are drawn with a translucent paint,
+    /// so that pixels drawn twice differ as well.
+    fn differences(a: &PixelMap<[u8; 4]>, b: &PixelMap<[u8; 4]>) -> Vec<Vector>
+    {
+        a.pixels().iter().zip(b.pixels()).enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(i, _)| i as u32)
+            .map(|i| v((i % EXTENT) as f64 + 0.5, (i / EXTENT) as f64 + 0.5))
+            .collect()
+    }
+
+    /// Whether the point is so close to an edge
+    /// that rounding may decide on which side it lies.
+    fn near_edge(contours: &[Vec<Vector>], point: Vector) -> bool
+    {
+        contours.iter().flat_map(|contour| edges(contour)).any(|(&a, &b)| {
+            let t = ((point - a).dot(b - a) / (b - a).dot(b - a)).clamp(0.0, 1.0);
+            (a + t * (b - a) - point).length() < 1e-6
+        })
+    }
+
+    fn assert_fill_matches_scanline(path: &PathBuf, matrix: Matrix, fill_rule: FillRule)
+    {
+        let mut mesh = Mesh::new();
+        TESSELLATOR.fill(path, matrix, fill_rule, &mut mesh);
+        let expected = draw(|canvas| {
+            canvas.fill_path(matrix, path.instructions(), fill_rule, PAINT);
+        });
+        let actual = draw_mesh(&mesh);
+
+        let transformed = path.instructions().map(|i| matrix * i);
+        let contours = flatten(transformed, TESSELLATOR.tolerance);
+        for point in differences(&expected, &actual) {
+            assert!(near_edge(&contours, point), "{:?} {:?}", fill_rule, point);
+        }
+    }
+
+    #[test]
+    fn test_fill_matches_scanline()
+    {
+        // A self-intersecting star with a curved hole,
+        // which both fill rules fill differently.
+        let mut path = polygon(&[
+            v(20.3, 2.1), v(31.1, 35.7), v(2.6, 14.2), v(37.9, 14.4), v(8.8, 35.9),
+        ]);
+        path.push_move(v(17.0, 20.0));
+        path.push_quadratic(v(20.0, 14.0), v(23.0, 20.0));
+        path.push_cubic(v(23.0, 26.0), v(17.0, 26.0), v(17.0, 20.0));
+
+        let rotate = Matrix{tx: 20.0, ty: -7.0, ..Matrix::from_rotate(0.4)};
+        for &matrix in &[Matrix::IDENTITY, rotate] {
+            for &fill_rule in &[FillRule::NonZero, FillRule::EvenOdd] {
+                assert_fill_matches_scanline(&path, matrix, fill_rule);
+            }
+        }
+    }
+
+    proptest! {
+
+        #[test]
+        fn test_random_polygons_match_scanline(
+            points in vec((-5.0 .. 45.0, -5.0 .. 45.0), 3 .. 12),
+            even_odd: bool,
+        )
+        {
+            let points: Vec<Vector> = points.into_iter().map(|(x, y)| v(x, y)).collect();
+            let fill_rule = if even_odd { FillRule::EvenOdd } else { FillRule::NonZero };
+            let mut mesh = Mesh::new();
+            TESSELLATOR.fill(&polygon(&points), Matrix::IDENTITY, fill_rule, &mut mesh);
+            let expected = draw(|canvas| {
+                let path = polygon(&points);
+                canvas.fill_path(Matrix::IDENTITY, path.instructions(), fill_rule, PAINT);
+            });
+            for point in differences(&expected, &draw_mesh(&mesh)) {
+                prop_assert!(near_edge(slice::from_ref(&points), point), "{:?}", point);
+            }
+        }
+
+    }
+
+    #[test]
+    fn test_stroke()
+    {
+        let line = polygon(&[v(5.2, 10.3), v(30.6, 10.3)]);
+        let stroke = |cap| Stroke{width: 4.0, join: Join::Round, cap};
+
+        // Butt caps end at the end points, and square caps extend beyond.
+        for &(cap, extend) in &[(Cap::Butt, 0.0), (Cap::Square, 2.0)] {
+            let mut mesh = Mesh::new();
+            TESSELLATOR.stroke(&line, Matrix::IDENTITY, stroke(cap), &mut mesh);
+            let expected = draw(|canvas| {
+                let start = v(5.2 - extend, 8.3);
+                let extent = v(25.4 + 2.0 * extend, 4.0);
+                canvas.rectangle(Matrix::IDENTITY, start, extent, PAINT);
+            });
+            assert!(differences(&expected, &draw_mesh(&mesh)).is_empty(), "{:?}", cap);
+        }
+
+        // A closed square with miter joins is a square with a square hole,
+        // and the scaled stroke width is scaled as well.
+        let square = polygon(&[
+            v(3.1, 3.1), v(13.1, 3.1), v(13.1, 13.1), v(3.1, 13.1), v(3.1, 3.1),
+        ]);
+        let stroke = Stroke{width: 2.0, join: Join::Miter(4.0), cap: Cap::Butt};
+        let mut mesh = Mesh::new();
+        TESSELLATOR.stroke(&square, Matrix::from_scale(2.0, 2.0), stroke, &mut mesh);
+        let expected = draw(|canvas| {
+            let outer = polygon(&[v(4.2, 4.2), v(28.2, 4.2), v(28.2, 28.2), v(4.2, 28.2)]);
+            let inner = polygon(&[v(8.2, 8.2), v(24.2, 8.2), v(24.2, 24.2), v(8.2, 24.2)]);
+            let frame = outer.instructions().chain(inner.instructions());
+            canvas.fill_path(Matrix::IDENTITY, frame, FillRule::EvenOdd, PAINT);
+        });
+        assert!(differences(&expected, &draw_mesh(&mesh)).is_empty());
+
+        // With a bevel join, the outer corners are cut off.
+        let stroke = Stroke{join: Join::Bevel, ..stroke};
+        let mut mesh = Mesh::new();
+        TESSELLATOR.stroke(&square, Matrix::from_scale(2.0, 2.0), stroke, &mut mesh);
+        let result = draw_mesh(&mesh);
+        assert_eq!(result.pixels()[4 + 4 * EXTENT as usize], [0; 4]);
+        assert_ne!(result.pixels()[6 + 4 * EXTENT as usize], [0; 4]);
+    }
+
+    #[test]
+    fn test_fringe()
+    {
+        let square = polygon(&[v(10.0, 10.0), v(30.0, 10.0), v(30.0, 30.0), v(10.0, 30.0)]);
+        let tessellator = Tessellator{fringe: 2.0, ..TESSELLATOR};
+        let mut mesh = Mesh::new();
+        tessellator.fill(&square, Matrix::IDENTITY, FillRule::NonZero, &mut mesh);
+        assert!(mesh.vertices.iter().all(|v| v.coverage == 0.0 || v.coverage == 1.0));
+
+        let paint = Paint{blend_mode: BlendMode::Source, pixel: [0xFF; 4]};
+        let mut pixel_map = PixelMap::new([0; 4], EXTENT, EXTENT).unwrap();
+        PixelMapCanvas::new(Bgra8888, pixel_map.as_mut()).mesh(&mesh, paint);
+        let alpha = |x: usize, y: usize| pixel_map.pixels()[x + y * EXTENT as usize][3];
+
+        // The fringe fades out from the edges,
+        // and also around the corners.
+        assert_eq!(alpha(20, 20), 255);
+        assert_eq!(alpha(10, 20), 255);
+        assert_eq!(alpha(9, 20), 191);
+        assert_eq!(alpha(8, 20), 64);
+        assert_eq!(alpha(7, 20), 0);
+        assert_eq!(alpha(31, 20), 64);
+        assert!(alpha(30, 30) > 0 && alpha(30, 30) < 255);
+        assert_eq!(alpha(32, 32), 0);
+    }
+}
@@ -0,0 +1,259 @@
+use crate::Bounds;
+use crate::DisplayList;
+use crate::Format;
+use crate::Layers;
+use crate::PixelMapCanvas;
+use crate::PixelMapMut;
+use crate::Vector;
+
+use crossbeam_utils::thread;
+
+use std::panic;
+
+/// Draws display lists onto pixel maps using multiple threads.
+///
+/// The pixel map is divided into tiles with [`PixelMapMut::tiles`].
+/// Each command of the display list is assigned to
+/// the tiles its bounding box intersects (_binning_),
+/// and the tiles are then distributed over the threads,
+/// each of which replays the commands of its tiles onto them
+/// using a [`PixelMapCanvas`].
+///
+/// Tiles draw exactly the pixels within them
+/// that a [`PixelMapCanvas`] for the whole pixel map would draw,
+/// and commands are replayed in order within each tile,
+/// so the result is identical to replaying the display list
+/// onto a single [`PixelMapCanvas`].
+/// It does not depend on the number of threads or the tile size.
+/// Layers that are still pushed at the end of the display list
+/// are drawn, as by [`PixelMapCanvas::into_layers`].
+pub struct TiledRenderer
+{
+    tile_extent_x: u32,
+    tile_extent_y: u32,
+    threads: usize,
+
+    // The bins are kept across calls to reuse their allocations.
+    bins: Vec<Vec<usize>>,
+}
+
+impl TiledRenderer
+{
+    /// Create a renderer with the given tile size and number of threads.
+    ///
+    /// Tiles of 64 by 64 pixels are a good default.
+    /// If `threads` is one, no threads are spawned
+    /// and all tiles are drawn on the calling thread.
+    ///
+    /// # Panics
+    ///
+    /// If either tile extent or the number of threads is zero,
+    /// this function panics.
+    pub fn new(tile_extent_x: u32, tile_extent_y: u32, threads: usize) -> Self
+    {
+        assert!(tile_extent_x > 0 && tile_extent_y > 0, "Empty tiles");
+        assert!(threads > 0, "No threads");
+        Self{tile_extent_x, tile_extent_y, threads, bins: Vec::new()}
+    }
+
+    /// Replay the display list onto the pixel map.
+    pub fn render<F>(
+        &mut self,
+        format: F,
+        display_list: &DisplayList<F::Pixel>,
+        pixel_map: PixelMapMut<F::Pixel>,
+    )
+        where F: Format + Sync,
+              F::Pixel: Send + Sync,
+    {
+        let tiles = pixel_map.tiles(self.tile_extent_x, self.tile_extent_y);
+
+        // Assign the commands to the tiles they affect.
+        self.bins.resize_with(tiles.len(), Vec::new);
+        for bin in &mut self.bins[.. tiles.len()] {
+            bin.clear();
+        }
+        for index in 0 .. display_list.len() {
+            let bounds = display_list.command_bounds(index);
+            for (tile, bin) in tiles.iter().zip(&mut self.bins) {
+                if affects(bounds, tile) {
+                    bin.push(index);
+                }
+            }
+        }
+
+        // Each thread reuses the buffers of layers from tile to tile.
+        let draw_tile = |layers, (tile, bin): (PixelMapMut<F::Pixel>, &Vec<usize>)| {
+            let mut canvas = PixelMapCanvas::with_layers(&format, tile, layers);
+            for &index in bin {
+                display_list.replay_one(index, &mut canvas);
+            }
+            canvas.into_layers()
+        };
+
+        let work = tiles.into_iter().zip(&self.bins);
+
+        if self.threads == 1 {
+            work.fold(Layers::new(), draw_tile);
+            return;
+        }
+
+        // Deal the tiles to the threads like cards,
+        // so that each thread gets tiles from all over the pixel map.
+        let mut hands: Vec<Vec<_>> = (0 .. self.threads).map(|_| Vec::new()).collect();
+        for (i, item) in work.enumerate() {
+            hands[i % self.threads].push(item);
+        }
+
+        // Panics in the threads are passed on to the caller.
+        let draw_tile = &draw_tile;
+        let result = thread::scope(|scope| {
+            for hand in hands {
+                scope.spawn(move |_| hand.into_iter().fold(Layers::new(), draw_tile));
+            }
+        });
+        if let Err(payload) = result {
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+/// Whether a command with the given bounding box
+/// may draw any pixels within the given tile.
+fn affects<T>(bounds: Bounds, tile: &PixelMapMut<T>) -> bool
+{
+    // Paths with NaN or infinite points have bounds that are not finite,
+    // but their other line segments are still drawn.
+    // Such commands are not culled, to be on the safe side.
+    if !bounds.is_finite() {
+        return true;
+    }
+
+    let (origin_x, origin_y) = tile.origin();
+    let (extent_x, extent_y) = tile.extent();
+    let tile_bounds = Bounds{
+        min: Vector{x: origin_x as f64, y: origin_y as f64},
+        max: Vector{
+            x: origin_x as f64 + extent_x as f64,
+            y: origin_y as f64 + extent_y as f64,
+        },
+    };
+    bounds.intersects(tile_bounds)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use crate::BlendMode;
+    use crate::Canvas;
+    use crate::Matrix;
+    use crate::Paint;
+    use crate::PixelMap;
+    use crate::RecordingCanvas;
+    use crate::formats::Bgra8888;
+    use crate::path::Instruction;
+
+    use proptest::collection::vec;
+    use proptest::prop_assert_eq;
+    use proptest::proptest;
+
+    const EXTENT_X: u32 = 200;
+    const EXTENT_Y: u32 = 150;
+
+    /// Three points, a pixel, and the kind of command:
+    /// 0 for rectangles, 1 for paths,
+    /// 2 for pushing a layer, and 3 for popping a layer.
+    type Shape = ((f64, f64), (f64, f64), (f64, f64), u32, u8);
+
+    fn display_list(shapes: &[Shape]) -> DisplayList<[u8; 4]>
+    {
+        let mut canvas = RecordingCanvas::new();
+        for &((x0, y0), (x1, y1), (x2, y2), pixel, kind) in shapes {
+            let pixel = u32::to_le_bytes(pixel);
+            let blend_mode =
+                if pixel[0] % 2 == 0 { BlendMode::SourceOver }
+                else { BlendMode::Source };
+            let paint = Paint{blend_mode, pixel};
+            let matrix = Matrix::from_rotate(x2 / 100.0);
+            let p0 = Vector{x: x0, y: y0};
+            let p1 = Vector{x: x1, y: y1};
+            let p2 = Vector{x: x2, y: y2};
+            match kind {
+                0 => canvas.rectangle(Matrix::IDENTITY, p0, p1 - p0, paint),
+                1 => {
+                    let path = vec![
+                        Instruction::Move(p0),
+                        Instruction::Linear(p1),
+                        Instruction::Quadratic(p2, p0),
+                    ];
+                    canvas.path(matrix, path, paint);
+                },
+                2 => {
+                    let bounds = Bounds::from_points(vec![p0, p1]);
+                    let opacity = pixel[1] as f32 / 255.0;
+                    canvas.push_layer(bounds, opacity, blend_mode);
+                },
+                _ => canvas.pop_layer(),
+            }
+        }
+        canvas.into_display_list()
+    }
+
+    fn render_single(display_list: &DisplayList<[u8; 4]>) -> PixelMap<[u8; 4]>
+    {
+        let mut pixel_map = PixelMap::new([0; 4], EXTENT_X, EXTENT_Y).unwrap();
+        let mut canvas = PixelMapCanvas::new(Bgra8888, pixel_map.as_mut());
+        display_list.replay(&mut canvas);
+        canvas.into_layers();
+        pixel_map
+    }
+
+    fn render_tiled(display_list: &DisplayList<[u8; 4]>, tile: u32, threads: usize)
+        -> PixelMap<[u8; 4]>
+    {
+        let mut pixel_map = PixelMap::new([0; 4], EXTENT_X, EXTENT_Y).unwrap();
+        let mut renderer = TiledRenderer::new(tile, tile, threads);
+        renderer.render(Bgra8888, display_list, pixel_map.as_mut());
+        pixel_map
+    }
+
+    proptest! {
+
+        #[test]
+        fn test_identical_to_single_threaded(
+            shapes in vec(
+                (
+                    (-50.0 .. 250.0, -50.0 .. 200.0),
+                    (-50.0 .. 250.0, -50.0 .. 200.0),
+                    (-50.0 .. 250.0, -50.0 .. 200.0),
+                    0 .. u32::MAX,
+                    0u8 .. 4,
+                ),
+                0 .. 20,
+            ),
+            tile in 1u32 .. 80,
+            threads in 1usize .. 5,
+        )
+        {
+            let display_list = display_list(&shapes);
+            let single = render_single(&display_list);
+            let tiled = render_tiled(&display_list, tile, threads);
+            prop_assert_eq!(single.pixels(), tiled.pixels());
+        }
+
+    }
+
+    #[test]
+    fn test_reuse()
+    {
+        let shapes = [((10.0, 10.0), (190.0, 140.0), (0.0, 0.0), 0xFF0000FF, 0)];
+        let display_list = display_list(&shapes);
+        let mut pixel_map = PixelMap::new([0; 4], EXTENT_X, EXTENT_Y).unwrap();
+        let mut renderer = TiledRenderer::new(64, 64, 4);
+        renderer.render(Bgra8888, &display_list, pixel_map.as_mut());
+        renderer.render(Bgra8888, &DisplayList::new(), pixel_map.as_mut());
+        assert_eq!(pixel_map.pixels(), render_single(&display_list).pixels());
+    }
+}
@@ -1,5 +1,6 @@
 use np_graphics::BlendMode;
 use np_graphics::Canvas;
+use np_graphics::Color;
 use np_graphics::Matrix;
 use np_graphics::Paint;
 use np_graphics::PixelMapMut;
@@ -8,10 +9,17 @@ use np_graphics::Vector;
 use np_graphics::formats::Bgra8888;
 use np_shell::RunningApps;
 use np_shell::draw_wallpaper;
+use np_text::FontDatabase;
 use np_text::FontFile;
-use np_text::Image;
+use np_text::FontQuery;
+use np_text::Paragraph;
+use np_text::ParagraphStyle;
+use np_text::ShapingOptions;
+use np_text::Tag;
 use np_unix::Mmap;
 use std::fs::OpenOptions;
+use std::io::Error;
+use std::io::ErrorKind;
 use std::io::Result;
 use std::os::unix::io::AsRawFd;
 
@@ -26,16 +34,45 @@ fn main() -> Result<()>
         .map(|fd| libc::pollfd{fd, events: libc::POLLIN, revents: 0})
     );
 
-    let font_file = FontFile::open_mapped("/fonts/FreeSerif.ttf")?;
-    let typeface = font_file.typeface(0).unwrap();
-    let glyph_h = typeface.glyph(76).unwrap();
-    let glyph_a = typeface.glyph(69).unwrap();
-    let glyph_l = typeface.glyph(80).unwrap();
-    let glyph_o = typeface.glyph(83).unwrap();
-    println!("{:?}", glyph_h);
-    println!("{:?}", glyph_a);
-    println!("{:?}", glyph_l);
-    println!("{:?}", glyph_o);
+    let mut font_database = FontDatabase::new();
+    font_database.scan_directory("/fonts")?;
+    if font_database.faces().is_empty() {
+        return Err(Error::new(ErrorKind::NotFound, "no fonts in /fonts"));
+    }
+
+    // Each run of the text is laid out with the typeface that covers it,
+    // such as an emoji typeface for the emoji, and the runs are drawn
+    // one after another along the same baseline.
+    // Runs whose typeface cannot be read or parsed are left out.
+    let font_query = FontQuery{families: &["FreeSerif"], ..FontQuery::default()};
+    let text = "Hallo wereld \u{1F600}";
+    let font_runs = font_database.font_runs(&font_query, text);
+    let font_files: Vec<_> =
+        font_runs.iter()
+        .filter_map(|run| Some((run, FontFile::open_mapped(&run.face.path).ok()?)))
+        .collect();
+    let typefaces: Vec<_> =
+        font_files.iter()
+        .filter_map(|(run, font_file)| Some((*run, font_file.typeface(run.face.index).ok()?)))
+        .collect();
+    let paragraph_style = ParagraphStyle{
+        size: 40.0,
+        shaping: ShapingOptions{script: Tag(*b"latn"), ..ShapingOptions::default()},
+        ..ParagraphStyle::default()
+    };
+    let mut pen = 0.0;
+    let mut paragraphs = Vec::new();
+    for (run, typeface) in &typefaces {
+        let paragraph = Paragraph::layout(typeface, &text[run.range.clone()], &paragraph_style);
+        let palette = typeface.palettes().into_iter().next().map(|palette| palette.colors);
+        let advance: f64 =
+            paragraph.glyphs.iter()
+            .filter_map(|glyph| typeface.glyph(glyph.glyph_id))
+            .map(|glyph| glyph.advance_x * paragraph.scale)
+            .sum();
+        paragraphs.push((typeface, paragraph, palette.unwrap_or_default(), pen));
+        pen += advance;
+    }
 
     let fb_file =
         OpenOptions::new()
@@ -59,17 +96,18 @@ fn main() -> Result<()>
         let dur = std::time::Duration::from_secs(1);
         std::thread::sleep(dur);
 
-        let mut pixel_map = PixelMapMut::new(
+        let pixel_map = PixelMapMut::new(
             unsafe { fb_mmap.as_mut::<[u8; 4]>() },
             1024,
             1024,
             768,
         ).unwrap();
 
-        draw_wallpaper(&mut pixel_map);
-
+        let (extent_x, extent_y) = pixel_map.extent();
         let mut canvas = PixelMapCanvas::new(Bgra8888, pixel_map);
 
+        draw_wallpaper(&mut canvas, extent_x, extent_y);
+
         canvas.rectangle(
             Matrix::from_scale(2.0, 2.0),
             Vector{x: 0.0, y: 0.0},
@@ -80,28 +118,15 @@ fn main() -> Result<()>
             },
         );
 
-        let scale = 1.0 / 25.0;
-        let mut offset = 100.0;
-        for glyph in &[&glyph_h, &glyph_a, &glyph_l, &glyph_l, &glyph_o] {
-
-            let outline = match &glyph.image {
-                Image::Outline(outline) => outline,
-                _ => panic!("Expected outline glyph"),
-            };
-
-            canvas.path(
-                Matrix::IDENTITY
-                    * Matrix::from_translate(offset, 500.0)
-                    * Matrix::from_scale(1.0 * scale, -1.0 * scale),
-                outline.instructions(),
-                Paint{
-                    blend_mode: BlendMode::Source,
-                    pixel: [0xFF, 0xFF, 0xFF, 0xFF],
-                },
+        for (typeface, paragraph, palette, pen) in &paragraphs {
+            let baseline = paragraph.lines.first().map_or(0.0, |line| line.baseline);
+            paragraph.draw_color(
+                &mut canvas,
+                typeface,
+                Matrix::from_translate(100.0 + pen, 500.0 - baseline),
+                palette,
+                Color::WHITE,
             );
-
-            offset += glyph.advance_x * scale;
-
         }
 
     }
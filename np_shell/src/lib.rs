@@ -8,15 +8,29 @@ mod app_info;
 mod running_app;
 mod running_apps;
 
-use np_graphics::Blitter;
-use np_graphics::PixelMapMut;
-use np_graphics::blitters::BlendSourceBlitter;
+use np_graphics::BlendMode;
+use np_graphics::Canvas;
+use np_graphics::Matrix;
+use np_graphics::Paint;
+use np_graphics::Vector;
 
 /// Cover the entire screen with the wallpaper.
-pub fn draw_wallpaper(pixel_map: &mut PixelMapMut<[u8; 4]>)
+///
+/// The wallpaper is drawn onto any canvas,
+/// so it can also be exported with an [`SvgCanvas`].
+///
+/// [`SvgCanvas`]: `np_graphics::SvgCanvas`
+pub fn draw_wallpaper<C>(canvas: &mut C, extent_x: u32, extent_y: u32)
+    where C: Canvas<Pixel=[u8; 4]>
 {
-    let pixel = [0xC2, 0x48, 0x1D, 0xFF];
-    let blitter = BlendSourceBlitter::new(pixel);
-    let extent = pixel_map.extent();
-    blitter.rectangle(pixel_map, 0, 0, extent.0, extent.1);
+    let paint = Paint{
+        blend_mode: BlendMode::Source,
+        pixel: [0xC2, 0x48, 0x1D, 0xFF],
+    };
+    canvas.rectangle(
+        Matrix::IDENTITY,
+        Vector{x: 0.0, y: 0.0},
+        Vector{x: extent_x as f64, y: extent_y as f64},
+        paint,
+    );
 }
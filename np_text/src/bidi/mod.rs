@@ -0,0 +1,745 @@
+//! Resolving the direction of text, as described in [UAX #9].
+//!
+//! [UAX #9]: https://www.unicode.org/reports/tr9/
+
+use std::cmp::Ordering;
+use std::ops::Range;
+
+mod tables;
+
+/// The direction in which text is written.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction
+{
+    /// Left to right, as in Latin text.
+    LeftToRight,
+
+    /// Right to left, as in Arabic and Hebrew text.
+    RightToLeft,
+}
+
+/// Embedding level of text.
+///
+/// Text at even levels is written left to right,
+/// and text at odd levels is written right to left.
+/// Text at higher levels is embedded in text at lower levels.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Level(pub u8);
+
+impl Level
+{
+    /// The highest level that explicit embeddings can reach.
+    pub const MAX_DEPTH: u8 = 125;
+
+    /// The lowest level with the given direction.
+    pub fn from_direction(direction: Direction) -> Self
+    {
+        match direction {
+            Direction::LeftToRight => Self(0),
+            Direction::RightToLeft => Self(1),
+        }
+    }
+
+    /// The direction of text at this level.
+    pub fn direction(self) -> Direction
+    {
+        if self.is_rtl() { Direction::RightToLeft } else { Direction::LeftToRight }
+    }
+
+    /// Whether text at this level is written right to left.
+    pub fn is_rtl(self) -> bool
+    {
+        self.0 % 2 == 1
+    }
+}
+
+/// A paragraph of text, as separated by paragraph separators.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BidiParagraph
+{
+    /// The range of bytes of the paragraph, including its separator.
+    pub range: Range<usize>,
+
+    /// The embedding level of the paragraph.
+    pub level: Level,
+}
+
+/// The resolved embedding levels of text.
+///
+/// To resolve embedding levels, call [`BidiInfo::new`].
+#[derive(Clone, Debug)]
+pub struct BidiInfo
+{
+    /// The embedding level of each byte of the text,
+    /// before the rules for lines are applied.
+    pub levels: Vec<Level>,
+
+    /// The paragraphs of the text.
+    pub paragraphs: Vec<BidiParagraph>,
+}
+
+impl BidiInfo
+{
+    /// Resolve the embedding levels of text.
+    ///
+    /// The text is split into paragraphs at paragraph separators such as newlines.
+    /// Paragraphs are given the direction if there is one,
+    /// and otherwise take the direction of their first strong character.
+    pub fn new(text: &str, direction: Option<Direction>) -> Self
+    {
+        let mut levels = Vec::with_capacity(text.len());
+        let mut paragraphs = Vec::new();
+
+        let mut chars = Vec::new();
+        let mut classes = Vec::new();
+        let mut iter = text.char_indices().peekable();
+        let mut start = 0;
+        while let Some((offset, c)) = iter.next() {
+            chars.push(c);
+            classes.push(BidiClass::of(c));
+            if c == '\r' && iter.peek().map(|&(_, next)| next) == Some('\n') {
+                continue;
+            }
+            let end = offset + c.len_utf8();
+            if classes[classes.len() - 1] == BidiClass::B || end == text.len() {
+                let paragraph = resolve_paragraph(&chars, &classes, direction);
+                for (c, level) in chars.iter().zip(&paragraph.levels) {
+                    levels.extend(std::iter::repeat(*level).take(c.len_utf8()));
+                }
+                paragraphs.push(BidiParagraph{range: start .. end, level: paragraph.level});
+                chars.clear();
+                classes.clear();
+                start = end;
+            }
+        }
+
+        Self{levels, paragraphs}
+    }
+
+    /// The embedding level of each byte of a line of the text,
+    /// with the rules for lines applied.
+    ///
+    /// Whitespace at the end of the line, and before tabs and separators,
+    /// is put at the level of the paragraph.
+    pub fn line_levels(&self, text: &str, line: Range<usize>) -> Vec<Level>
+    {
+        let paragraph_level =
+            self.paragraphs.iter()
+            .find(|paragraph| paragraph.range.contains(&line.start))
+            .map_or(Level(0), |paragraph| paragraph.level);
+
+        let mut levels = self.levels[line.clone()].to_vec();
+        let mut trailing = true;
+        for (offset, c) in text[line.clone()].char_indices().rev() {
+            let class = BidiClass::of(c);
+            let reset = match class {
+                BidiClass::S | BidiClass::B => {
+                    trailing = true;
+                    true
+                },
+                BidiClass::Ws | BidiClass::Fsi | BidiClass::Lri |
+                BidiClass::Rli | BidiClass::Pdi => trailing,
+                _ if class.is_removed() => trailing,
+                _ => {
+                    trailing = false;
+                    false
+                },
+            };
+            if reset {
+                for level in &mut levels[offset .. offset + c.len_utf8()] {
+                    *level = paragraph_level;
+                }
+            }
+        }
+        levels
+    }
+
+    /// The embedding level of the paragraph containing the given byte.
+    pub fn paragraph_level(&self, offset: usize) -> Level
+    {
+        self.paragraphs.iter()
+            .find(|paragraph| paragraph.range.contains(&offset))
+            .map_or(Level(0), |paragraph| paragraph.level)
+    }
+}
+
+/// Put items with the given levels in visual order, from left to right.
+///
+/// This returns the logical index of each item in visual order.
+/// The levels are usually those of a line, as given by [`BidiInfo::line_levels`],
+/// but they can be of any items, such as the glyphs of the line.
+pub fn reorder_visual(levels: &[Level]) -> Vec<usize>
+{
+    let mut order = (0 .. levels.len()).collect::<Vec<_>>();
+    let highest = levels.iter().max().map_or(0, |level| level.0);
+    let lowest_odd = levels.iter().map(|level| level.0 | 1).min().unwrap_or(1);
+
+    // From the highest level down to the lowest odd level,
+    // reverse every sequence of items at that level or higher.
+    for level in (lowest_odd ..= highest).rev() {
+        let mut start = 0;
+        while start < order.len() {
+            if levels[order[start]].0 < level {
+                start += 1;
+                continue;
+            }
+            let end =
+                (start .. order.len()).find(|&i| levels[order[i]].0 < level)
+                .unwrap_or(order.len());
+            order[start .. end].reverse();
+            start = end;
+        }
+    }
+    order
+}
+
+/// The character whose glyph is the mirror image of that of the given character,
+/// such as `)` for `(`.
+///
+/// Characters such as brackets are mirrored in right-to-left text.
+pub fn mirror(c: char) -> Option<char>
+{
+    tables::MIRRORS.binary_search_by_key(&c, |&(c, _)| c).ok().map(|i| tables::MIRRORS[i].1)
+}
+
+/// Bidirectional character type.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum BidiClass
+{
+    L, R, Al,
+    En, Es, Et, An, Cs, Nsm, Bn,
+    B, S, Ws, On,
+    Lre, Lro, Rle, Rlo, Pdf, Lri, Rli, Fsi, Pdi,
+}
+
+use self::BidiClass::*;
+
+impl BidiClass
+{
+    fn of(c: char) -> Self
+    {
+        let c = c as u32;
+        let index = tables::BIDI_CLASS.binary_search_by(|&(start, end, _)| {
+            if end < c { Ordering::Less }
+            else if start > c { Ordering::Greater }
+            else { Ordering::Equal }
+        });
+        index.map_or(L, |index| tables::BIDI_CLASS[index].2)
+    }
+
+    /// Whether rule X9 removes characters of this type.
+    fn is_removed(self) -> bool
+    {
+        matches!(self, Rle | Lre | Rlo | Lro | Pdf | Bn)
+    }
+
+    fn is_isolate_initiator(self) -> bool
+    {
+        matches!(self, Lri | Rli | Fsi)
+    }
+
+    /// Whether this is a neutral or isolate formatting type, for rules N1 and N2.
+    fn is_neutral(self) -> bool
+    {
+        matches!(self, B | S | Ws | On | Lri | Rli | Fsi | Pdi)
+    }
+
+    /// The strong direction for rules N0 to N2,
+    /// in which numbers count as right to left.
+    fn strong(self) -> Option<Self>
+    {
+        match self {
+            L => Some(L),
+            R | Al | En | An => Some(R),
+            _ => None,
+        }
+    }
+}
+
+/// Embedding levels of a paragraph.
+struct ResolvedParagraph
+{
+    level: Level,
+    levels: Vec<Level>,
+}
+
+/// Apply the rules to a single paragraph.
+fn resolve_paragraph(chars: &[char], original: &[BidiClass], direction: Option<Direction>)
+    -> ResolvedParagraph
+{
+    let matching_pdi = matching_pdis(original);
+
+    // The paragraph level (P2, P3).
+    let level = match direction {
+        Some(direction) => Level::from_direction(direction),
+        None => match first_strong(original, &matching_pdi, 0 .. original.len()) {
+            Some(R) => Level(1),
+            _ => Level(0),
+        },
+    };
+
+    let (mut levels, mut classes) = explicit_levels(original, &matching_pdi, level);
+
+    for sequence in isolating_run_sequences(original, &matching_pdi, &levels) {
+        resolve_sequence(chars, original, &mut classes, &mut levels, &sequence, level);
+    }
+
+    // Removed characters take the level of the character before them.
+    for i in 0 .. levels.len() {
+        if original[i].is_removed() {
+            levels[i] = if i == 0 { level } else { levels[i - 1] };
+        }
+    }
+
+    ResolvedParagraph{level, levels}
+}
+
+/// The index of the matching PDI of every isolate initiator (BD9).
+fn matching_pdis(classes: &[BidiClass]) -> Vec<Option<usize>>
+{
+    let mut matching = vec![None; classes.len()];
+    let mut initiators = Vec::new();
+    for (i, &class) in classes.iter().enumerate() {
+        if class.is_isolate_initiator() {
+            initiators.push(i);
+        } else if class == Pdi {
+            if let Some(initiator) = initiators.pop() {
+                matching[initiator] = Some(i);
+            }
+        }
+    }
+    matching
+}
+
+/// The direction of the first strong character in the range,
+/// skipping isolates (P2).
+fn first_strong(classes: &[BidiClass], matching_pdi: &[Option<usize>], range: Range<usize>)
+    -> Option<BidiClass>
+{
+    let mut i = range.start;
+    while i < range.end {
+        match classes[i] {
+            L => return Some(L),
+            R | Al => return Some(R),
+            class if class.is_isolate_initiator() => {
+                i = matching_pdi[i].unwrap_or(range.end);
+            },
+            _ => (),
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Entry of the directional status stack.
+#[derive(Clone, Copy)]
+struct Status
+{
+    level: u8,
+    override_class: Option<BidiClass>,
+    isolate: bool,
+}
+
+/// Apply explicit embeddings, overrides, and isolates (X1 to X8).
+///
+/// This returns the level of every character,
+/// and the types of the characters after overrides.
+fn explicit_levels(original: &[BidiClass], matching_pdi: &[Option<usize>], paragraph: Level)
+    -> (Vec<Level>, Vec<BidiClass>)
+{
+    let mut levels = vec![paragraph; original.len()];
+    let mut classes = original.to_vec();
+
+    let mut stack = vec![Status{level: paragraph.0, override_class: None, isolate: false}];
+    let mut overflow_isolates = 0;
+    let mut overflow_embeddings = 0;
+    let mut valid_isolates = 0;
+
+    let next_level = |level: u8, rtl: bool| if rtl { (level + 1) | 1 } else { (level + 2) & !1 };
+
+    for (i, &class) in original.iter().enumerate() {
+        let top = stack[stack.len() - 1];
+        let overflow = overflow_isolates > 0 || overflow_embeddings > 0;
+        match class {
+            Rle | Lre | Rlo | Lro => {
+                levels[i] = Level(top.level);
+                let level = next_level(top.level, matches!(class, Rle | Rlo));
+                if level <= Level::MAX_DEPTH && !overflow {
+                    let override_class = match class {
+                        Rlo => Some(R),
+                        Lro => Some(L),
+                        _ => None,
+                    };
+                    stack.push(Status{level, override_class, isolate: false});
+                } else if overflow_isolates == 0 {
+                    overflow_embeddings += 1;
+                }
+            },
+            Rli | Lri | Fsi => {
+                levels[i] = Level(top.level);
+                if let Some(override_class) = top.override_class {
+                    classes[i] = override_class;
+                }
+                let rtl = match class {
+                    Rli => true,
+                    Lri => false,
+                    _ => {
+                        let end = matching_pdi[i].unwrap_or(original.len());
+                        first_strong(original, matching_pdi, i + 1 .. end) == Some(R)
+                    },
+                };
+                let level = next_level(top.level, rtl);
+                if level <= Level::MAX_DEPTH && !overflow {
+                    valid_isolates += 1;
+                    stack.push(Status{level, override_class: None, isolate: true});
+                } else {
+                    overflow_isolates += 1;
+                }
+            },
+            Pdi => {
+                if overflow_isolates > 0 {
+                    overflow_isolates -= 1;
+                } else if valid_isolates > 0 {
+                    overflow_embeddings = 0;
+                    while !stack[stack.len() - 1].isolate {
+                        stack.pop();
+                    }
+                    stack.pop();
+                    valid_isolates -= 1;
+                }
+                let top = stack[stack.len() - 1];
+                levels[i] = Level(top.level);
+                if let Some(override_class) = top.override_class {
+                    classes[i] = override_class;
+                }
+            },
+            Pdf => {
+                levels[i] = Level(top.level);
+                if overflow_isolates > 0 {
+                } else if overflow_embeddings > 0 {
+                    overflow_embeddings -= 1;
+                } else if !top.isolate && stack.len() >= 2 {
+                    stack.pop();
+                }
+            },
+            B => levels[i] = paragraph,
+            Bn => levels[i] = Level(top.level),
+            _ => {
+                levels[i] = Level(top.level);
+                if let Some(override_class) = top.override_class {
+                    classes[i] = override_class;
+                }
+            },
+        }
+    }
+
+    (levels, classes)
+}
+
+/// Sequences of level runs that are resolved together,
+/// as the text inside isolates does not affect the text around them (X10).
+///
+/// Characters removed by rule X9 are left out.
+fn isolating_run_sequences(
+    original: &[BidiClass],
+    matching_pdi: &[Option<usize>],
+    levels: &[Level],
+) -> Vec<Vec<usize>>
+{
+    // Split the remaining characters into runs of the same level.
+    let mut runs: Vec<Vec<usize>> = Vec::new();
+    let mut run_starting_at = vec![None; original.len()];
+    let mut previous_level = None;
+    for i in (0 .. original.len()).filter(|&i| !original[i].is_removed()) {
+        if previous_level != Some(levels[i]) {
+            run_starting_at[i] = Some(runs.len());
+            runs.push(Vec::new());
+        }
+        runs.last_mut().unwrap().push(i);
+        previous_level = Some(levels[i]);
+    }
+
+    // Join runs that end with an isolate initiator
+    // to the runs that start with the matching PDI.
+    let is_matched_pdi = |i: usize| original[i] == Pdi && matching_pdi.contains(&Some(i));
+    let mut sequences = Vec::new();
+    for run in &runs {
+        if is_matched_pdi(run[0]) {
+            continue;
+        }
+        let mut sequence = run.clone();
+        loop {
+            let last = sequence[sequence.len() - 1];
+            let next_run =
+                matching_pdi[last]
+                .filter(|_| original[last].is_isolate_initiator())
+                .and_then(|pdi| run_starting_at[pdi]);
+            match next_run {
+                Some(next_run) => sequence.extend_from_slice(&runs[next_run]),
+                None => break,
+            }
+        }
+        sequences.push(sequence);
+    }
+    sequences
+}
+
+/// Resolve weak and neutral types and implicit levels
+/// of an isolating run sequence (W1 to W7, N0 to N2, I1 and I2).
+fn resolve_sequence(
+    chars: &[char],
+    original: &[BidiClass],
+    classes: &mut [BidiClass],
+    levels: &mut [Level],
+    sequence: &[usize],
+    paragraph: Level,
+)
+{
+    let level = levels[sequence[0]];
+    let kept = |i: &usize| !original[*i].is_removed();
+    let before = (0 .. sequence[0]).rev().find(kept).map_or(paragraph, |i| levels[i]);
+    let last = sequence[sequence.len() - 1];
+    let after =
+        if original[last].is_isolate_initiator() { paragraph }
+        else { (last + 1 .. original.len()).find(kept).map_or(paragraph, |i| levels[i]) };
+    let sos = if level.max(before).is_rtl() { R } else { L };
+    let eos = if level.max(after).is_rtl() { R } else { L };
+
+    let mut types = sequence.iter().map(|&i| classes[i]).collect::<Vec<_>>();
+    let n = types.len();
+
+    // W1: non-spacing marks take the type of the character before them.
+    for k in 0 .. n {
+        if types[k] == Nsm {
+            types[k] =
+                if k == 0 { sos }
+                else if matches!(original[sequence[k - 1]], Lri | Rli | Fsi | Pdi) { On }
+                else { types[k - 1] };
+        }
+    }
+
+    // W2, W3: European numbers after Arabic letters are Arabic numbers,
+    // and Arabic letters are right to left.
+    let mut strong = sos;
+    for t in &mut types {
+        match *t {
+            L | R | Al => strong = *t,
+            En if strong == Al => *t = An,
+            _ => (),
+        }
+    }
+    for t in &mut types {
+        if *t == Al {
+            *t = R;
+        }
+    }
+
+    // W4: single separators between numbers of the same type.
+    for k in 1 .. n.saturating_sub(1) {
+        let (previous, next) = (types[k - 1], types[k + 1]);
+        if types[k] == Es && previous == En && next == En {
+            types[k] = En;
+        } else if types[k] == Cs && previous == next && matches!(previous, En | An) {
+            types[k] = previous;
+        }
+    }
+
+    // W5: terminators next to European numbers.
+    let mut k = 0;
+    while k < n {
+        if types[k] != Et {
+            k += 1;
+            continue;
+        }
+        let end = (k .. n).find(|&j| types[j] != Et).unwrap_or(n);
+        if k > 0 && types[k - 1] == En || end < n && types[end] == En {
+            for t in &mut types[k .. end] {
+                *t = En;
+            }
+        }
+        k = end;
+    }
+
+    // W6: remaining separators and terminators are neutral.
+    for t in &mut types {
+        if matches!(*t, Es | Et | Cs) {
+            *t = On;
+        }
+    }
+
+    // W7: European numbers after left-to-right text are left to right.
+    let mut strong = sos;
+    for t in &mut types {
+        match *t {
+            L | R => strong = *t,
+            En if strong == L => *t = L,
+            _ => (),
+        }
+    }
+
+    // N0: paired brackets.
+    let embedding = if level.is_rtl() { R } else { L };
+    for (open, close) in bracket_pairs(chars, sequence, &types) {
+        let inside = types[open + 1 .. close].iter().filter_map(|t| t.strong());
+        let mut found_opposite = false;
+        let mut direction = None;
+        for strong in inside {
+            if strong == embedding {
+                direction = Some(embedding);
+                break;
+            }
+            found_opposite = true;
+        }
+        if direction.is_none() && found_opposite {
+            let context = types[.. open].iter().rev().find_map(|t| t.strong()).unwrap_or(sos);
+            direction = Some(if context != embedding { context } else { embedding });
+        }
+        if let Some(direction) = direction {
+            for &bracket in &[open, close] {
+                types[bracket] = direction;
+                for k in bracket + 1 .. n {
+                    if original[sequence[k]] != Nsm {
+                        break;
+                    }
+                    types[k] = direction;
+                }
+            }
+        }
+    }
+
+    // N1, N2: neutrals between text of the same direction take that direction,
+    // and other neutrals take the embedding direction.
+    let mut k = 0;
+    while k < n {
+        if !types[k].is_neutral() {
+            k += 1;
+            continue;
+        }
+        let end = (k .. n).find(|&j| !types[j].is_neutral()).unwrap_or(n);
+        let leading = if k == 0 { Some(sos) } else { types[k - 1].strong() };
+        let trailing = if end == n { Some(eos) } else { types[end].strong() };
+        let direction = if leading == trailing { leading.unwrap_or(embedding) } else { embedding };
+        for t in &mut types[k .. end] {
+            *t = direction;
+        }
+        k = end;
+    }
+
+    // I1, I2: implicit levels.
+    for (&i, &t) in sequence.iter().zip(&types) {
+        let increase = match (levels[i].is_rtl(), t) {
+            (false, R) => 1,
+            (false, An) | (false, En) => 2,
+            (true, L) | (true, An) | (true, En) => 1,
+            _ => 0,
+        };
+        levels[i].0 += increase;
+        classes[i] = t;
+    }
+}
+
+/// Pairs of brackets within an isolating run sequence (BD16),
+/// as indices into the sequence, sorted by the opening bracket.
+fn bracket_pairs(chars: &[char], sequence: &[usize], types: &[BidiClass]) -> Vec<(usize, usize)>
+{
+    // Angle brackets have canonical equivalents, which match each other.
+    let canonical = |c| match c {
+        '\u{2329}' => '\u{3008}',
+        '\u{232A}' => '\u{3009}',
+        c => c,
+    };
+
+    let mut pairs = Vec::new();
+    let mut openings: Vec<(char, usize)> = Vec::new();
+    for (k, &i) in sequence.iter().enumerate() {
+        if types[k] != On {
+            continue;
+        }
+        let c = canonical(chars[i]);
+        if let Ok(index) = tables::BRACKETS.binary_search_by_key(&c, |&(open, _)| open) {
+            if openings.len() == 63 {
+                break;
+            }
+            openings.push((tables::BRACKETS[index].1, k));
+        } else if let Some(depth) = openings.iter().rposition(|&(close, _)| close == c) {
+            pairs.push((openings[depth].1, k));
+            openings.truncate(depth);
+        }
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    /// The levels of the characters of text, as digits.
+    fn levels(text: &str, direction: Option<Direction>) -> String
+    {
+        let info = BidiInfo::new(text, direction);
+        text.char_indices().map(|(i, _)| (b'0' + info.levels[i].0) as char).collect()
+    }
+
+    #[test]
+    fn test_classes()
+    {
+        assert_eq!(BidiClass::of('a'), L);
+        assert_eq!(BidiClass::of('\u{5D0}'), R);
+        assert_eq!(BidiClass::of('\u{627}'), Al);
+        assert_eq!(BidiClass::of('1'), En);
+        assert_eq!(BidiClass::of('\u{661}'), An);
+        assert_eq!(BidiClass::of(' '), Ws);
+        assert_eq!(BidiClass::of('\n'), B);
+        assert_eq!(BidiClass::of('\u{2067}'), Rli);
+        assert_eq!(BidiClass::of('\u{5FF}'), R);
+        assert_eq!(mirror('('), Some(')'));
+        assert_eq!(mirror('\u{2264}'), Some('\u{2265}'));
+        assert_eq!(mirror('\u{2208}'), Some('\u{220B}'));
+        assert_eq!(mirror('\u{2211}'), None);
+        assert_eq!(mirror('a'), None);
+    }
+
+    #[test]
+    fn test_levels()
+    {
+        // Hebrew is written right to left, within left-to-right text.
+        assert_eq!(levels("ab \u{5D0}\u{5D1} cd", None), "00011000");
+        assert_eq!(levels("\u{5D0}\u{5D1} cd \u{5D2}", None), "1112211");
+        assert_eq!(levels("ab cd", Some(Direction::RightToLeft)), "22222");
+
+        // Numbers are written left to right, even after Arabic letters.
+        assert_eq!(levels("\u{627} 12", None), "1122");
+        assert_eq!(levels("ab \u{5D0} 1.5", None), "00011222");
+
+        // Brackets match the text inside them.
+        assert_eq!(levels("\u{5D0} (ab) \u{5D1}", None), "11122111");
+        assert_eq!(levels("ab (\u{5D0}) cd", None), "000010000");
+
+        // Isolates do not affect the text around them.
+        assert_eq!(levels("\u{5D0} \u{2066}ab\u{2069} 1", None), "11122112");
+        assert_eq!(levels("ab \u{2067}\u{5D0} c\u{2069} d", None), "0000112000");
+
+        // Overrides force a direction, and paragraphs are resolved separately.
+        assert_eq!(levels("\u{202E}ab\u{202C}", None), "0111");
+        assert_eq!(levels("ab\n\u{5D0}b", None), "00012");
+    }
+
+    #[test]
+    fn test_line_levels_and_reordering()
+    {
+        let text = "\u{5D0}\u{5D1} cd ";
+        let info = BidiInfo::new(text, None);
+        assert_eq!(info.paragraph_level(0), Level(1));
+        let levels = info.line_levels(text, 0 .. text.len());
+        let chars = text.char_indices().map(|(i, _)| levels[i]).collect::<Vec<_>>();
+        assert_eq!(chars, [Level(1), Level(1), Level(1), Level(2), Level(2), Level(1)]);
+
+        // The trailing space ends up on the left, then the Latin letters,
+        // then the Hebrew letters from right to left.
+        assert_eq!(reorder_visual(&chars), [5, 3, 4, 2, 1, 0]);
+        assert_eq!(reorder_visual(&[Level(0), Level(1), Level(1), Level(0)]), [0, 2, 1, 3]);
+        assert_eq!(reorder_visual(&[]), []);
+    }
+}
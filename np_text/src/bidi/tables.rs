@@ -0,0 +1,420 @@
+// This file is generated by tools/unicode_tables.py
+// from the Unicode Character Database 14.0.0.
+// Do not edit it by hand.
+
+use super::BidiClass;
+use super::BidiClass::*;
+
+/// Ranges of code points with a bidirectional class other than L.
+#[rustfmt::skip]
+pub(super) const BIDI_CLASS: &[(u32, u32, BidiClass)] = &[
+    (0x0000, 0x0008, Bn), (0x0009, 0x0009, S), (0x000A, 0x000A, B), (0x000B, 0x000B, S),
+    (0x000C, 0x000C, Ws), (0x000D, 0x000D, B), (0x000E, 0x001B, Bn), (0x001C, 0x001E, B),
+    (0x001F, 0x001F, S), (0x0020, 0x0020, Ws), (0x0021, 0x0022, On), (0x0023, 0x0025, Et),
+    (0x0026, 0x002A, On), (0x002B, 0x002B, Es), (0x002C, 0x002C, Cs),
+    (0x002D, 0x002D, Es), (0x002E, 0x002F, Cs), (0x0030, 0x0039, En),
+    (0x003A, 0x003A, Cs), (0x003B, 0x0040, On), (0x005B, 0x0060, On),
+    (0x007B, 0x007E, On), (0x007F, 0x0084, Bn), (0x0085, 0x0085, B), (0x0086, 0x009F, Bn),
+    (0x00A0, 0x00A0, Cs), (0x00A1, 0x00A1, On), (0x00A2, 0x00A5, Et),
+    (0x00A6, 0x00A9, On), (0x00AB, 0x00AC, On), (0x00AD, 0x00AD, Bn),
+    (0x00AE, 0x00AF, On), (0x00B0, 0x00B1, Et), (0x00B2, 0x00B3, En),
+    (0x00B4, 0x00B4, On), (0x00B6, 0x00B8, On), (0x00B9, 0x00B9, En),
+    (0x00BB, 0x00BF, On), (0x00D7, 0x00D7, On), (0x00F7, 0x00F7, On),
+    (0x02B9, 0x02BA, On), (0x02C2, 0x02CF, On), (0x02D2, 0x02DF, On),
+    (0x02E5, 0x02ED, On), (0x02EF, 0x02FF, On), (0x0300, 0x036F, Nsm),
+    (0x0374, 0x0375, On), (0x037E, 0x037E, On), (0x0384, 0x0385, On),
+    (0x0387, 0x0387, On), (0x03F6, 0x03F6, On), (0x0483, 0x0489, Nsm),
+    (0x058A, 0x058A, On), (0x058D, 0x058E, On), (0x058F, 0x058F, Et), (0x0590, 0x0590, R),
+    (0x0591, 0x05BD, Nsm), (0x05BE, 0x05BE, R), (0x05BF, 0x05BF, Nsm),
+    (0x05C0, 0x05C0, R), (0x05C1, 0x05C2, Nsm), (0x05C3, 0x05C3, R),
+    (0x05C4, 0x05C5, Nsm), (0x05C6, 0x05C6, R), (0x05C7, 0x05C7, Nsm),
+    (0x05C8, 0x05FF, R), (0x0600, 0x0605, An), (0x0606, 0x0607, On), (0x0608, 0x0608, Al),
+    (0x0609, 0x060A, Et), (0x060B, 0x060B, Al), (0x060C, 0x060C, Cs),
+    (0x060D, 0x060D, Al), (0x060E, 0x060F, On), (0x0610, 0x061A, Nsm),
+    (0x061B, 0x064A, Al), (0x064B, 0x065F, Nsm), (0x0660, 0x0669, An),
+    (0x066A, 0x066A, Et), (0x066B, 0x066C, An), (0x066D, 0x066F, Al),
+    (0x0670, 0x0670, Nsm), (0x0671, 0x06D5, Al), (0x06D6, 0x06DC, Nsm),
+    (0x06DD, 0x06DD, An), (0x06DE, 0x06DE, On), (0x06DF, 0x06E4, Nsm),
+    (0x06E5, 0x06E6, Al), (0x06E7, 0x06E8, Nsm), (0x06E9, 0x06E9, On),
+    (0x06EA, 0x06ED, Nsm), (0x06EE, 0x06EF, Al), (0x06F0, 0x06F9, En),
+    (0x06FA, 0x0710, Al), (0x0711, 0x0711, Nsm), (0x0712, 0x072F, Al),
+    (0x0730, 0x074A, Nsm), (0x074B, 0x07A5, Al), (0x07A6, 0x07B0, Nsm),
+    (0x07B1, 0x07BF, Al), (0x07C0, 0x07EA, R), (0x07EB, 0x07F3, Nsm), (0x07F4, 0x07F5, R),
+    (0x07F6, 0x07F9, On), (0x07FA, 0x07FC, R), (0x07FD, 0x07FD, Nsm), (0x07FE, 0x0815, R),
+    (0x0816, 0x0819, Nsm), (0x081A, 0x081A, R), (0x081B, 0x0823, Nsm),
+    (0x0824, 0x0824, R), (0x0825, 0x0827, Nsm), (0x0828, 0x0828, R),
+    (0x0829, 0x082D, Nsm), (0x082E, 0x0858, R), (0x0859, 0x085B, Nsm),
+    (0x085C, 0x085F, R), (0x0860, 0x088F, Al), (0x0890, 0x0891, An), (0x0892, 0x0897, Al),
+    (0x0898, 0x089F, Nsm), (0x08A0, 0x08C9, Al), (0x08CA, 0x08E1, Nsm),
+    (0x08E2, 0x08E2, An), (0x08E3, 0x0902, Nsm), (0x093A, 0x093A, Nsm),
+    (0x093C, 0x093C, Nsm), (0x0941, 0x0948, Nsm), (0x094D, 0x094D, Nsm),
+    (0x0951, 0x0957, Nsm), (0x0962, 0x0963, Nsm), (0x0981, 0x0981, Nsm),
+    (0x09BC, 0x09BC, Nsm), (0x09C1, 0x09C4, Nsm), (0x09CD, 0x09CD, Nsm),
+    (0x09E2, 0x09E3, Nsm), (0x09F2, 0x09F3, Et), (0x09FB, 0x09FB, Et),
+    (0x09FE, 0x09FE, Nsm), (0x0A01, 0x0A02, Nsm), (0x0A3C, 0x0A3C, Nsm),
+    (0x0A41, 0x0A42, Nsm), (0x0A47, 0x0A48, Nsm), (0x0A4B, 0x0A4D, Nsm),
+    (0x0A51, 0x0A51, Nsm), (0x0A70, 0x0A71, Nsm), (0x0A75, 0x0A75, Nsm),
+    (0x0A81, 0x0A82, Nsm), (0x0ABC, 0x0ABC, Nsm), (0x0AC1, 0x0AC5, Nsm),
+    (0x0AC7, 0x0AC8, Nsm), (0x0ACD, 0x0ACD, Nsm), (0x0AE2, 0x0AE3, Nsm),
+    (0x0AF1, 0x0AF1, Et), (0x0AFA, 0x0AFF, Nsm), (0x0B01, 0x0B01, Nsm),
+    (0x0B3C, 0x0B3C, Nsm), (0x0B3F, 0x0B3F, Nsm), (0x0B41, 0x0B44, Nsm),
+    (0x0B4D, 0x0B4D, Nsm), (0x0B55, 0x0B56, Nsm), (0x0B62, 0x0B63, Nsm),
+    (0x0B82, 0x0B82, Nsm), (0x0BC0, 0x0BC0, Nsm), (0x0BCD, 0x0BCD, Nsm),
+    (0x0BF3, 0x0BF8, On), (0x0BF9, 0x0BF9, Et), (0x0BFA, 0x0BFA, On),
+    (0x0C00, 0x0C00, Nsm), (0x0C04, 0x0C04, Nsm), (0x0C3C, 0x0C3C, Nsm),
+    (0x0C3E, 0x0C40, Nsm), (0x0C46, 0x0C48, Nsm), (0x0C4A, 0x0C4D, Nsm),
+    (0x0C55, 0x0C56, Nsm), (0x0C62, 0x0C63, Nsm), (0x0C78, 0x0C7E, On),
+    (0x0C81, 0x0C81, Nsm), (0x0CBC, 0x0CBC, Nsm), (0x0CCC, 0x0CCD, Nsm),
+    (0x0CE2, 0x0CE3, Nsm), (0x0D00, 0x0D01, Nsm), (0x0D3B, 0x0D3C, Nsm),
+    (0x0D41, 0x0D44, Nsm), (0x0D4D, 0x0D4D, Nsm), (0x0D62, 0x0D63, Nsm),
+    (0x0D81, 0x0D81, Nsm), (0x0DCA, 0x0DCA, Nsm), (0x0DD2, 0x0DD4, Nsm),
+    (0x0DD6, 0x0DD6, Nsm), (0x0E31, 0x0E31, Nsm), (0x0E34, 0x0E3A, Nsm),
+    (0x0E3F, 0x0E3F, Et), (0x0E47, 0x0E4E, Nsm), (0x0EB1, 0x0EB1, Nsm),
+    (0x0EB4, 0x0EBC, Nsm), (0x0EC8, 0x0ECD, Nsm), (0x0F18, 0x0F19, Nsm),
+    (0x0F35, 0x0F35, Nsm), (0x0F37, 0x0F37, Nsm), (0x0F39, 0x0F39, Nsm),
+    (0x0F3A, 0x0F3D, On), (0x0F71, 0x0F7E, Nsm), (0x0F80, 0x0F84, Nsm),
+    (0x0F86, 0x0F87, Nsm), (0x0F8D, 0x0F97, Nsm), (0x0F99, 0x0FBC, Nsm),
+    (0x0FC6, 0x0FC6, Nsm), (0x102D, 0x1030, Nsm), (0x1032, 0x1037, Nsm),
+    (0x1039, 0x103A, Nsm), (0x103D, 0x103E, Nsm), (0x1058, 0x1059, Nsm),
+    (0x105E, 0x1060, Nsm), (0x1071, 0x1074, Nsm), (0x1082, 0x1082, Nsm),
+    (0x1085, 0x1086, Nsm), (0x108D, 0x108D, Nsm), (0x109D, 0x109D, Nsm),
+    (0x135D, 0x135F, Nsm), (0x1390, 0x1399, On), (0x1400, 0x1400, On),
+    (0x1680, 0x1680, Ws), (0x169B, 0x169C, On), (0x1712, 0x1714, Nsm),
+    (0x1732, 0x1733, Nsm), (0x1752, 0x1753, Nsm), (0x1772, 0x1773, Nsm),
+    (0x17B4, 0x17B5, Nsm), (0x17B7, 0x17BD, Nsm), (0x17C6, 0x17C6, Nsm),
+    (0x17C9, 0x17D3, Nsm), (0x17DB, 0x17DB, Et), (0x17DD, 0x17DD, Nsm),
+    (0x17F0, 0x17F9, On), (0x1800, 0x180A, On), (0x180B, 0x180D, Nsm),
+    (0x180E, 0x180E, Bn), (0x180F, 0x180F, Nsm), (0x1885, 0x1886, Nsm),
+    (0x18A9, 0x18A9, Nsm), (0x1920, 0x1922, Nsm), (0x1927, 0x1928, Nsm),
+    (0x1932, 0x1932, Nsm), (0x1939, 0x193B, Nsm), (0x1940, 0x1940, On),
+    (0x1944, 0x1945, On), (0x19DE, 0x19FF, On), (0x1A17, 0x1A18, Nsm),
+    (0x1A1B, 0x1A1B, Nsm), (0x1A56, 0x1A56, Nsm), (0x1A58, 0x1A5E, Nsm),
+    (0x1A60, 0x1A60, Nsm), (0x1A62, 0x1A62, Nsm), (0x1A65, 0x1A6C, Nsm),
+    (0x1A73, 0x1A7C, Nsm), (0x1A7F, 0x1A7F, Nsm), (0x1AB0, 0x1ACE, Nsm),
+    (0x1B00, 0x1B03, Nsm), (0x1B34, 0x1B34, Nsm), (0x1B36, 0x1B3A, Nsm),
+    (0x1B3C, 0x1B3C, Nsm), (0x1B42, 0x1B42, Nsm), (0x1B6B, 0x1B73, Nsm),
+    (0x1B80, 0x1B81, Nsm), (0x1BA2, 0x1BA5, Nsm), (0x1BA8, 0x1BA9, Nsm),
+    (0x1BAB, 0x1BAD, Nsm), (0x1BE6, 0x1BE6, Nsm), (0x1BE8, 0x1BE9, Nsm),
+    (0x1BED, 0x1BED, Nsm), (0x1BEF, 0x1BF1, Nsm), (0x1C2C, 0x1C33, Nsm),
+    (0x1C36, 0x1C37, Nsm), (0x1CD0, 0x1CD2, Nsm), (0x1CD4, 0x1CE0, Nsm),
+    (0x1CE2, 0x1CE8, Nsm), (0x1CED, 0x1CED, Nsm), (0x1CF4, 0x1CF4, Nsm),
+    (0x1CF8, 0x1CF9, Nsm), (0x1DC0, 0x1DFF, Nsm), (0x1FBD, 0x1FBD, On),
+    (0x1FBF, 0x1FC1, On), (0x1FCD, 0x1FCF, On), (0x1FDD, 0x1FDF, On),
+    (0x1FED, 0x1FEF, On), (0x1FFD, 0x1FFE, On), (0x2000, 0x200A, Ws),
+    (0x200B, 0x200D, Bn), (0x200F, 0x200F, R), (0x2010, 0x2027, On), (0x2028, 0x2028, Ws),
+    (0x2029, 0x2029, B), (0x202A, 0x202A, Lre), (0x202B, 0x202B, Rle),
+    (0x202C, 0x202C, Pdf), (0x202D, 0x202D, Lro), (0x202E, 0x202E, Rlo),
+    (0x202F, 0x202F, Cs), (0x2030, 0x2034, Et), (0x2035, 0x2043, On),
+    (0x2044, 0x2044, Cs), (0x2045, 0x205E, On), (0x205F, 0x205F, Ws),
+    (0x2060, 0x2065, Bn), (0x2066, 0x2066, Lri), (0x2067, 0x2067, Rli),
+    (0x2068, 0x2068, Fsi), (0x2069, 0x2069, Pdi), (0x206A, 0x206F, Bn),
+    (0x2070, 0x2070, En), (0x2074, 0x2079, En), (0x207A, 0x207B, Es),
+    (0x207C, 0x207E, On), (0x2080, 0x2089, En), (0x208A, 0x208B, Es),
+    (0x208C, 0x208E, On), (0x20A0, 0x20CF, Et), (0x20D0, 0x20F0, Nsm),
+    (0x2100, 0x2101, On), (0x2103, 0x2106, On), (0x2108, 0x2109, On),
+    (0x2114, 0x2114, On), (0x2116, 0x2118, On), (0x211E, 0x2123, On),
+    (0x2125, 0x2125, On), (0x2127, 0x2127, On), (0x2129, 0x2129, On),
+    (0x212E, 0x212E, Et), (0x213A, 0x213B, On), (0x2140, 0x2144, On),
+    (0x214A, 0x214D, On), (0x2150, 0x215F, On), (0x2189, 0x218B, On),
+    (0x2190, 0x2211, On), (0x2212, 0x2212, Es), (0x2213, 0x2213, Et),
+    (0x2214, 0x2335, On), (0x237B, 0x2394, On), (0x2396, 0x2426, On),
+    (0x2440, 0x244A, On), (0x2460, 0x2487, On), (0x2488, 0x249B, En),
+    (0x24EA, 0x26AB, On), (0x26AD, 0x27FF, On), (0x2900, 0x2B73, On),
+    (0x2B76, 0x2B95, On), (0x2B97, 0x2BFF, On), (0x2CE5, 0x2CEA, On),
+    (0x2CEF, 0x2CF1, Nsm), (0x2CF9, 0x2CFF, On), (0x2D7F, 0x2D7F, Nsm),
+    (0x2DE0, 0x2DFF, Nsm), (0x2E00, 0x2E5D, On), (0x2E80, 0x2E99, On),
+    (0x2E9B, 0x2EF3, On), (0x2F00, 0x2FD5, On), (0x2FF0, 0x2FFB, On),
+    (0x3000, 0x3000, Ws), (0x3001, 0x3004, On), (0x3008, 0x3020, On),
+    (0x302A, 0x302D, Nsm), (0x3030, 0x3030, On), (0x3036, 0x3037, On),
+    (0x303D, 0x303F, On), (0x3099, 0x309A, Nsm), (0x309B, 0x309C, On),
+    (0x30A0, 0x30A0, On), (0x30FB, 0x30FB, On), (0x31C0, 0x31E3, On),
+    (0x321D, 0x321E, On), (0x3250, 0x325F, On), (0x327C, 0x327E, On),
+    (0x32B1, 0x32BF, On), (0x32CC, 0x32CF, On), (0x3377, 0x337A, On),
+    (0x33DE, 0x33DF, On), (0x33FF, 0x33FF, On), (0x4DC0, 0x4DFF, On),
+    (0xA490, 0xA4C6, On), (0xA60D, 0xA60F, On), (0xA66F, 0xA672, Nsm),
+    (0xA673, 0xA673, On), (0xA674, 0xA67D, Nsm), (0xA67E, 0xA67F, On),
+    (0xA69E, 0xA69F, Nsm), (0xA6F0, 0xA6F1, Nsm), (0xA700, 0xA721, On),
+    (0xA788, 0xA788, On), (0xA802, 0xA802, Nsm), (0xA806, 0xA806, Nsm),
+    (0xA80B, 0xA80B, Nsm), (0xA825, 0xA826, Nsm), (0xA828, 0xA82B, On),
+    (0xA82C, 0xA82C, Nsm), (0xA838, 0xA839, Et), (0xA874, 0xA877, On),
+    (0xA8C4, 0xA8C5, Nsm), (0xA8E0, 0xA8F1, Nsm), (0xA8FF, 0xA8FF, Nsm),
+    (0xA926, 0xA92D, Nsm), (0xA947, 0xA951, Nsm), (0xA980, 0xA982, Nsm),
+    (0xA9B3, 0xA9B3, Nsm), (0xA9B6, 0xA9B9, Nsm), (0xA9BC, 0xA9BD, Nsm),
+    (0xA9E5, 0xA9E5, Nsm), (0xAA29, 0xAA2E, Nsm), (0xAA31, 0xAA32, Nsm),
+    (0xAA35, 0xAA36, Nsm), (0xAA43, 0xAA43, Nsm), (0xAA4C, 0xAA4C, Nsm),
+    (0xAA7C, 0xAA7C, Nsm), (0xAAB0, 0xAAB0, Nsm), (0xAAB2, 0xAAB4, Nsm),
+    (0xAAB7, 0xAAB8, Nsm), (0xAABE, 0xAABF, Nsm), (0xAAC1, 0xAAC1, Nsm),
+    (0xAAEC, 0xAAED, Nsm), (0xAAF6, 0xAAF6, Nsm), (0xAB6A, 0xAB6B, On),
+    (0xABE5, 0xABE5, Nsm), (0xABE8, 0xABE8, Nsm), (0xABED, 0xABED, Nsm),
+    (0xFB1D, 0xFB1D, R), (0xFB1E, 0xFB1E, Nsm), (0xFB1F, 0xFB28, R), (0xFB29, 0xFB29, Es),
+    (0xFB2A, 0xFB4F, R), (0xFB50, 0xFD3D, Al), (0xFD3E, 0xFD4F, On), (0xFD50, 0xFDCE, Al),
+    (0xFDCF, 0xFDCF, On), (0xFDD0, 0xFDEF, Bn), (0xFDF0, 0xFDFC, Al),
+    (0xFDFD, 0xFDFF, On), (0xFE00, 0xFE0F, Nsm), (0xFE10, 0xFE19, On),
+    (0xFE20, 0xFE2F, Nsm), (0xFE30, 0xFE4F, On), (0xFE50, 0xFE50, Cs),
+    (0xFE51, 0xFE51, On), (0xFE52, 0xFE52, Cs), (0xFE54, 0xFE54, On),
+    (0xFE55, 0xFE55, Cs), (0xFE56, 0xFE5E, On), (0xFE5F, 0xFE5F, Et),
+    (0xFE60, 0xFE61, On), (0xFE62, 0xFE63, Es), (0xFE64, 0xFE66, On),
+    (0xFE68, 0xFE68, On), (0xFE69, 0xFE6A, Et), (0xFE6B, 0xFE6B, On),
+    (0xFE70, 0xFEFE, Al), (0xFEFF, 0xFEFF, Bn), (0xFF01, 0xFF02, On),
+    (0xFF03, 0xFF05, Et), (0xFF06, 0xFF0A, On), (0xFF0B, 0xFF0B, Es),
+    (0xFF0C, 0xFF0C, Cs), (0xFF0D, 0xFF0D, Es), (0xFF0E, 0xFF0F, Cs),
+    (0xFF10, 0xFF19, En), (0xFF1A, 0xFF1A, Cs), (0xFF1B, 0xFF20, On),
+    (0xFF3B, 0xFF40, On), (0xFF5B, 0xFF65, On), (0xFFE0, 0xFFE1, Et),
+    (0xFFE2, 0xFFE4, On), (0xFFE5, 0xFFE6, Et), (0xFFE8, 0xFFEE, On),
+    (0xFFF0, 0xFFF8, Bn), (0xFFF9, 0xFFFD, On), (0xFFFE, 0xFFFF, Bn),
+    (0x10101, 0x10101, On), (0x10140, 0x1018C, On), (0x10190, 0x1019C, On),
+    (0x101A0, 0x101A0, On), (0x101FD, 0x101FD, Nsm), (0x102E0, 0x102E0, Nsm),
+    (0x102E1, 0x102FB, En), (0x10376, 0x1037A, Nsm), (0x10800, 0x1091E, R),
+    (0x1091F, 0x1091F, On), (0x10920, 0x10A00, R), (0x10A01, 0x10A03, Nsm),
+    (0x10A04, 0x10A04, R), (0x10A05, 0x10A06, Nsm), (0x10A07, 0x10A0B, R),
+    (0x10A0C, 0x10A0F, Nsm), (0x10A10, 0x10A37, R), (0x10A38, 0x10A3A, Nsm),
+    (0x10A3B, 0x10A3E, R), (0x10A3F, 0x10A3F, Nsm), (0x10A40, 0x10AE4, R),
+    (0x10AE5, 0x10AE6, Nsm), (0x10AE7, 0x10B38, R), (0x10B39, 0x10B3F, On),
+    (0x10B40, 0x10CFF, R), (0x10D00, 0x10D23, Al), (0x10D24, 0x10D27, Nsm),
+    (0x10D28, 0x10D2F, Al), (0x10D30, 0x10D39, An), (0x10D3A, 0x10D3F, Al),
+    (0x10D40, 0x10E5F, R), (0x10E60, 0x10E7E, An), (0x10E7F, 0x10EAA, R),
+    (0x10EAB, 0x10EAC, Nsm), (0x10EAD, 0x10F2F, R), (0x10F30, 0x10F45, Al),
+    (0x10F46, 0x10F50, Nsm), (0x10F51, 0x10F6F, Al), (0x10F70, 0x10F81, R),
+    (0x10F82, 0x10F85, Nsm), (0x10F86, 0x10FFF, R), (0x11001, 0x11001, Nsm),
+    (0x11038, 0x11046, Nsm), (0x11052, 0x11065, On), (0x11070, 0x11070, Nsm),
+    (0x11073, 0x11074, Nsm), (0x1107F, 0x11081, Nsm), (0x110B3, 0x110B6, Nsm),
+    (0x110B9, 0x110BA, Nsm), (0x110C2, 0x110C2, Nsm), (0x11100, 0x11102, Nsm),
+    (0x11127, 0x1112B, Nsm), (0x1112D, 0x11134, Nsm), (0x11173, 0x11173, Nsm),
+    (0x11180, 0x11181, Nsm), (0x111B6, 0x111BE, Nsm), (0x111C9, 0x111CC, Nsm),
+    (0x111CF, 0x111CF, Nsm), (0x1122F, 0x11231, Nsm), (0x11234, 0x11234, Nsm),
+    (0x11236, 0x11237, Nsm), (0x1123E, 0x1123E, Nsm), (0x112DF, 0x112DF, Nsm),
+    (0x112E3, 0x112EA, Nsm), (0x11300, 0x11301, Nsm), (0x1133B, 0x1133C, Nsm),
+    (0x11340, 0x11340, Nsm), (0x11366, 0x1136C, Nsm), (0x11370, 0x11374, Nsm),
+    (0x11438, 0x1143F, Nsm), (0x11442, 0x11444, Nsm), (0x11446, 0x11446, Nsm),
+    (0x1145E, 0x1145E, Nsm), (0x114B3, 0x114B8, Nsm), (0x114BA, 0x114BA, Nsm),
+    (0x114BF, 0x114C0, Nsm), (0x114C2, 0x114C3, Nsm), (0x115B2, 0x115B5, Nsm),
+    (0x115BC, 0x115BD, Nsm), (0x115BF, 0x115C0, Nsm), (0x115DC, 0x115DD, Nsm),
+    (0x11633, 0x1163A, Nsm), (0x1163D, 0x1163D, Nsm), (0x1163F, 0x11640, Nsm),
+    (0x11660, 0x1166C, On), (0x116AB, 0x116AB, Nsm), (0x116AD, 0x116AD, Nsm),
+    (0x116B0, 0x116B5, Nsm), (0x116B7, 0x116B7, Nsm), (0x1171D, 0x1171F, Nsm),
+    (0x11722, 0x11725, Nsm), (0x11727, 0x1172B, Nsm), (0x1182F, 0x11837, Nsm),
+    (0x11839, 0x1183A, Nsm), (0x1193B, 0x1193C, Nsm), (0x1193E, 0x1193E, Nsm),
+    (0x11943, 0x11943, Nsm), (0x119D4, 0x119D7, Nsm), (0x119DA, 0x119DB, Nsm),
+    (0x119E0, 0x119E0, Nsm), (0x11A01, 0x11A06, Nsm), (0x11A09, 0x11A0A, Nsm),
+    (0x11A33, 0x11A38, Nsm), (0x11A3B, 0x11A3E, Nsm), (0x11A47, 0x11A47, Nsm),
+    (0x11A51, 0x11A56, Nsm), (0x11A59, 0x11A5B, Nsm), (0x11A8A, 0x11A96, Nsm),
+    (0x11A98, 0x11A99, Nsm), (0x11C30, 0x11C36, Nsm), (0x11C38, 0x11C3D, Nsm),
+    (0x11C92, 0x11CA7, Nsm), (0x11CAA, 0x11CB0, Nsm), (0x11CB2, 0x11CB3, Nsm),
+    (0x11CB5, 0x11CB6, Nsm), (0x11D31, 0x11D36, Nsm), (0x11D3A, 0x11D3A, Nsm),
+    (0x11D3C, 0x11D3D, Nsm), (0x11D3F, 0x11D45, Nsm), (0x11D47, 0x11D47, Nsm),
+    (0x11D90, 0x11D91, Nsm), (0x11D95, 0x11D95, Nsm), (0x11D97, 0x11D97, Nsm),
+    (0x11EF3, 0x11EF4, Nsm), (0x11FD5, 0x11FDC, On), (0x11FDD, 0x11FE0, Et),
+    (0x11FE1, 0x11FF1, On), (0x16AF0, 0x16AF4, Nsm), (0x16B30, 0x16B36, Nsm),
+    (0x16F4F, 0x16F4F, Nsm), (0x16F8F, 0x16F92, Nsm), (0x16FE2, 0x16FE2, On),
+    (0x16FE4, 0x16FE4, Nsm), (0x1BC9D, 0x1BC9E, Nsm), (0x1BCA0, 0x1BCA3, Bn),
+    (0x1CF00, 0x1CF2D, Nsm), (0x1CF30, 0x1CF46, Nsm), (0x1D167, 0x1D169, Nsm),
+    (0x1D173, 0x1D17A, Bn), (0x1D17B, 0x1D182, Nsm), (0x1D185, 0x1D18B, Nsm),
+    (0x1D1AA, 0x1D1AD, Nsm), (0x1D1E9, 0x1D1EA, On), (0x1D200, 0x1D241, On),
+    (0x1D242, 0x1D244, Nsm), (0x1D245, 0x1D245, On), (0x1D300, 0x1D356, On),
+    (0x1D6DB, 0x1D6DB, On), (0x1D715, 0x1D715, On), (0x1D74F, 0x1D74F, On),
+    (0x1D789, 0x1D789, On), (0x1D7C3, 0x1D7C3, On), (0x1D7CE, 0x1D7FF, En),
+    (0x1DA00, 0x1DA36, Nsm), (0x1DA3B, 0x1DA6C, Nsm), (0x1DA75, 0x1DA75, Nsm),
+    (0x1DA84, 0x1DA84, Nsm), (0x1DA9B, 0x1DA9F, Nsm), (0x1DAA1, 0x1DAAF, Nsm),
+    (0x1E000, 0x1E006, Nsm), (0x1E008, 0x1E018, Nsm), (0x1E01B, 0x1E021, Nsm),
+    (0x1E023, 0x1E024, Nsm), (0x1E026, 0x1E02A, Nsm), (0x1E130, 0x1E136, Nsm),
+    (0x1E2AE, 0x1E2AE, Nsm), (0x1E2EC, 0x1E2EF, Nsm), (0x1E2FF, 0x1E2FF, Et),
+    (0x1E800, 0x1E8CF, R), (0x1E8D0, 0x1E8D6, Nsm), (0x1E8D7, 0x1E943, R),
+    (0x1E944, 0x1E94A, Nsm), (0x1E94B, 0x1EC6F, R), (0x1EC70, 0x1ECBF, Al),
+    (0x1ECC0, 0x1ECFF, R), (0x1ED00, 0x1ED4F, Al), (0x1ED50, 0x1EDFF, R),
+    (0x1EE00, 0x1EEEF, Al), (0x1EEF0, 0x1EEF1, On), (0x1EEF2, 0x1EEFF, Al),
+    (0x1EF00, 0x1EFFF, R), (0x1F000, 0x1F02B, On), (0x1F030, 0x1F093, On),
+    (0x1F0A0, 0x1F0AE, On), (0x1F0B1, 0x1F0BF, On), (0x1F0C1, 0x1F0CF, On),
+    (0x1F0D1, 0x1F0F5, On), (0x1F100, 0x1F10A, En), (0x1F10B, 0x1F10F, On),
+    (0x1F12F, 0x1F12F, On), (0x1F16A, 0x1F16F, On), (0x1F1AD, 0x1F1AD, On),
+    (0x1F260, 0x1F265, On), (0x1F300, 0x1F6D7, On), (0x1F6DD, 0x1F6EC, On),
+    (0x1F6F0, 0x1F6FC, On), (0x1F700, 0x1F773, On), (0x1F780, 0x1F7D8, On),
+    (0x1F7E0, 0x1F7EB, On), (0x1F7F0, 0x1F7F0, On), (0x1F800, 0x1F80B, On),
+    (0x1F810, 0x1F847, On), (0x1F850, 0x1F859, On), (0x1F860, 0x1F887, On),
+    (0x1F890, 0x1F8AD, On), (0x1F8B0, 0x1F8B1, On), (0x1F900, 0x1FA53, On),
+    (0x1FA60, 0x1FA6D, On), (0x1FA70, 0x1FA74, On), (0x1FA78, 0x1FA7C, On),
+    (0x1FA80, 0x1FA86, On), (0x1FA90, 0x1FAAC, On), (0x1FAB0, 0x1FABA, On),
+    (0x1FAC0, 0x1FAC5, On), (0x1FAD0, 0x1FAD9, On), (0x1FAE0, 0x1FAE7, On),
+    (0x1FAF0, 0x1FAF6, On), (0x1FB00, 0x1FB92, On), (0x1FB94, 0x1FBCA, On),
+    (0x1FBF0, 0x1FBF9, En), (0x1FFFE, 0x1FFFF, Bn), (0x2FFFE, 0x2FFFF, Bn),
+    (0x3FFFE, 0x3FFFF, Bn), (0x4FFFE, 0x4FFFF, Bn), (0x5FFFE, 0x5FFFF, Bn),
+    (0x6FFFE, 0x6FFFF, Bn), (0x7FFFE, 0x7FFFF, Bn), (0x8FFFE, 0x8FFFF, Bn),
+    (0x9FFFE, 0x9FFFF, Bn), (0xAFFFE, 0xAFFFF, Bn), (0xBFFFE, 0xBFFFF, Bn),
+    (0xCFFFE, 0xCFFFF, Bn), (0xDFFFE, 0xE00FF, Bn), (0xE0100, 0xE01EF, Nsm),
+    (0xE01F0, 0xE0FFF, Bn), (0xEFFFE, 0xEFFFF, Bn), (0xFFFFE, 0xFFFFF, Bn),
+    (0x10FFFE, 0x10FFFF, Bn),
+];
+
+/// Pairs of characters whose glyphs mirror each other, sorted by the first.
+#[rustfmt::skip]
+pub(super) const MIRRORS: &[(char, char)] = &[
+    ('\u{28}', '\u{29}'), ('\u{29}', '\u{28}'), ('\u{3C}', '\u{3E}'),
+    ('\u{3E}', '\u{3C}'), ('\u{5B}', '\u{5D}'), ('\u{5D}', '\u{5B}'),
+    ('\u{7B}', '\u{7D}'), ('\u{7D}', '\u{7B}'), ('\u{AB}', '\u{BB}'),
+    ('\u{BB}', '\u{AB}'), ('\u{F3A}', '\u{F3B}'), ('\u{F3B}', '\u{F3A}'),
+    ('\u{F3C}', '\u{F3D}'), ('\u{F3D}', '\u{F3C}'), ('\u{169B}', '\u{169C}'),
+    ('\u{169C}', '\u{169B}'), ('\u{2039}', '\u{203A}'), ('\u{203A}', '\u{2039}'),
+    ('\u{2045}', '\u{2046}'), ('\u{2046}', '\u{2045}'), ('\u{207D}', '\u{207E}'),
+    ('\u{207E}', '\u{207D}'), ('\u{208D}', '\u{208E}'), ('\u{208E}', '\u{208D}'),
+    ('\u{2208}', '\u{220B}'), ('\u{2209}', '\u{220C}'), ('\u{220A}', '\u{220D}'),
+    ('\u{220B}', '\u{2208}'), ('\u{220C}', '\u{2209}'), ('\u{220D}', '\u{220A}'),
+    ('\u{2215}', '\u{29F5}'), ('\u{221F}', '\u{2BFE}'), ('\u{2220}', '\u{29A3}'),
+    ('\u{2221}', '\u{299B}'), ('\u{2222}', '\u{29A0}'), ('\u{2224}', '\u{2AEE}'),
+    ('\u{223C}', '\u{223D}'), ('\u{223D}', '\u{223C}'), ('\u{2243}', '\u{22CD}'),
+    ('\u{2245}', '\u{224C}'), ('\u{224C}', '\u{2245}'), ('\u{2252}', '\u{2253}'),
+    ('\u{2253}', '\u{2252}'), ('\u{2254}', '\u{2255}'), ('\u{2255}', '\u{2254}'),
+    ('\u{2264}', '\u{2265}'), ('\u{2265}', '\u{2264}'), ('\u{2266}', '\u{2267}'),
+    ('\u{2267}', '\u{2266}'), ('\u{2268}', '\u{2269}'), ('\u{2269}', '\u{2268}'),
+    ('\u{226A}', '\u{226B}'), ('\u{226B}', '\u{226A}'), ('\u{226E}', '\u{226F}'),
+    ('\u{226F}', '\u{226E}'), ('\u{2270}', '\u{2271}'), ('\u{2271}', '\u{2270}'),
+    ('\u{2272}', '\u{2273}'), ('\u{2273}', '\u{2272}'), ('\u{2274}', '\u{2275}'),
+    ('\u{2275}', '\u{2274}'), ('\u{2276}', '\u{2277}'), ('\u{2277}', '\u{2276}'),
+    ('\u{2278}', '\u{2279}'), ('\u{2279}', '\u{2278}'), ('\u{227A}', '\u{227B}'),
+    ('\u{227B}', '\u{227A}'), ('\u{227C}', '\u{227D}'), ('\u{227D}', '\u{227C}'),
+    ('\u{227E}', '\u{227F}'), ('\u{227F}', '\u{227E}'), ('\u{2280}', '\u{2281}'),
+    ('\u{2281}', '\u{2280}'), ('\u{2282}', '\u{2283}'), ('\u{2283}', '\u{2282}'),
+    ('\u{2284}', '\u{2285}'), ('\u{2285}', '\u{2284}'), ('\u{2286}', '\u{2287}'),
+    ('\u{2287}', '\u{2286}'), ('\u{2288}', '\u{2289}'), ('\u{2289}', '\u{2288}'),
+    ('\u{228A}', '\u{228B}'), ('\u{228B}', '\u{228A}'), ('\u{228F}', '\u{2290}'),
+    ('\u{2290}', '\u{228F}'), ('\u{2291}', '\u{2292}'), ('\u{2292}', '\u{2291}'),
+    ('\u{2298}', '\u{29B8}'), ('\u{22A2}', '\u{22A3}'), ('\u{22A3}', '\u{22A2}'),
+    ('\u{22A6}', '\u{2ADE}'), ('\u{22A8}', '\u{2AE4}'), ('\u{22A9}', '\u{2AE3}'),
+    ('\u{22AB}', '\u{2AE5}'), ('\u{22B0}', '\u{22B1}'), ('\u{22B1}', '\u{22B0}'),
+    ('\u{22B2}', '\u{22B3}'), ('\u{22B3}', '\u{22B2}'), ('\u{22B4}', '\u{22B5}'),
+    ('\u{22B5}', '\u{22B4}'), ('\u{22B6}', '\u{22B7}'), ('\u{22B7}', '\u{22B6}'),
+    ('\u{22B8}', '\u{27DC}'), ('\u{22C9}', '\u{22CA}'), ('\u{22CA}', '\u{22C9}'),
+    ('\u{22CB}', '\u{22CC}'), ('\u{22CC}', '\u{22CB}'), ('\u{22CD}', '\u{2243}'),
+    ('\u{22D0}', '\u{22D1}'), ('\u{22D1}', '\u{22D0}'), ('\u{22D6}', '\u{22D7}'),
+    ('\u{22D7}', '\u{22D6}'), ('\u{22D8}', '\u{22D9}'), ('\u{22D9}', '\u{22D8}'),
+    ('\u{22DA}', '\u{22DB}'), ('\u{22DB}', '\u{22DA}'), ('\u{22DC}', '\u{22DD}'),
+    ('\u{22DD}', '\u{22DC}'), ('\u{22DE}', '\u{22DF}'), ('\u{22DF}', '\u{22DE}'),
+    ('\u{22E0}', '\u{22E1}'), ('\u{22E1}', '\u{22E0}'), ('\u{22E2}', '\u{22E3}'),
+    ('\u{22E3}', '\u{22E2}'), ('\u{22E4}', '\u{22E5}'), ('\u{22E5}', '\u{22E4}'),
+    ('\u{22E6}', '\u{22E7}'), ('\u{22E7}', '\u{22E6}'), ('\u{22E8}', '\u{22E9}'),
+    ('\u{22E9}', '\u{22E8}'), ('\u{22EA}', '\u{22EB}'), ('\u{22EB}', '\u{22EA}'),
+    ('\u{22EC}', '\u{22ED}'), ('\u{22ED}', '\u{22EC}'), ('\u{22F0}', '\u{22F1}'),
+    ('\u{22F1}', '\u{22F0}'), ('\u{22F2}', '\u{22FA}'), ('\u{22F3}', '\u{22FB}'),
+    ('\u{22F4}', '\u{22FC}'), ('\u{22F6}', '\u{22FD}'), ('\u{22F7}', '\u{22FE}'),
+    ('\u{22FA}', '\u{22F2}'), ('\u{22FB}', '\u{22F3}'), ('\u{22FC}', '\u{22F4}'),
+    ('\u{22FD}', '\u{22F6}'), ('\u{22FE}', '\u{22F7}'), ('\u{2308}', '\u{2309}'),
+    ('\u{2309}', '\u{2308}'), ('\u{230A}', '\u{230B}'), ('\u{230B}', '\u{230A}'),
+    ('\u{2329}', '\u{232A}'), ('\u{232A}', '\u{2329}'), ('\u{2768}', '\u{2769}'),
+    ('\u{2769}', '\u{2768}'), ('\u{276A}', '\u{276B}'), ('\u{276B}', '\u{276A}'),
+    ('\u{276C}', '\u{276D}'), ('\u{276D}', '\u{276C}'), ('\u{276E}', '\u{276F}'),
+    ('\u{276F}', '\u{276E}'), ('\u{2770}', '\u{2771}'), ('\u{2771}', '\u{2770}'),
+    ('\u{2772}', '\u{2773}'), ('\u{2773}', '\u{2772}'), ('\u{2774}', '\u{2775}'),
+    ('\u{2775}', '\u{2774}'), ('\u{27C3}', '\u{27C4}'), ('\u{27C4}', '\u{27C3}'),
+    ('\u{27C5}', '\u{27C6}'), ('\u{27C6}', '\u{27C5}'), ('\u{27C8}', '\u{27C9}'),
+    ('\u{27C9}', '\u{27C8}'), ('\u{27CB}', '\u{27CD}'), ('\u{27CD}', '\u{27CB}'),
+    ('\u{27D5}', '\u{27D6}'), ('\u{27D6}', '\u{27D5}'), ('\u{27DC}', '\u{22B8}'),
+    ('\u{27DD}', '\u{27DE}'), ('\u{27DE}', '\u{27DD}'), ('\u{27E2}', '\u{27E3}'),
+    ('\u{27E3}', '\u{27E2}'), ('\u{27E4}', '\u{27E5}'), ('\u{27E5}', '\u{27E4}'),
+    ('\u{27E6}', '\u{27E7}'), ('\u{27E7}', '\u{27E6}'), ('\u{27E8}', '\u{27E9}'),
+    ('\u{27E9}', '\u{27E8}'), ('\u{27EA}', '\u{27EB}'), ('\u{27EB}', '\u{27EA}'),
+    ('\u{27EC}', '\u{27ED}'), ('\u{27ED}', '\u{27EC}'), ('\u{27EE}', '\u{27EF}'),
+    ('\u{27EF}', '\u{27EE}'), ('\u{2983}', '\u{2984}'), ('\u{2984}', '\u{2983}'),
+    ('\u{2985}', '\u{2986}'), ('\u{2986}', '\u{2985}'), ('\u{2987}', '\u{2988}'),
+    ('\u{2988}', '\u{2987}'), ('\u{2989}', '\u{298A}'), ('\u{298A}', '\u{2989}'),
+    ('\u{298B}', '\u{298C}'), ('\u{298C}', '\u{298B}'), ('\u{298D}', '\u{2990}'),
+    ('\u{298E}', '\u{298F}'), ('\u{298F}', '\u{298E}'), ('\u{2990}', '\u{298D}'),
+    ('\u{2991}', '\u{2992}'), ('\u{2992}', '\u{2991}'), ('\u{2993}', '\u{2994}'),
+    ('\u{2994}', '\u{2993}'), ('\u{2995}', '\u{2996}'), ('\u{2996}', '\u{2995}'),
+    ('\u{2997}', '\u{2998}'), ('\u{2998}', '\u{2997}'), ('\u{299B}', '\u{2221}'),
+    ('\u{29A0}', '\u{2222}'), ('\u{29A3}', '\u{2220}'), ('\u{29A4}', '\u{29A5}'),
+    ('\u{29A5}', '\u{29A4}'), ('\u{29A8}', '\u{29A9}'), ('\u{29A9}', '\u{29A8}'),
+    ('\u{29AA}', '\u{29AB}'), ('\u{29AB}', '\u{29AA}'), ('\u{29AC}', '\u{29AD}'),
+    ('\u{29AD}', '\u{29AC}'), ('\u{29AE}', '\u{29AF}'), ('\u{29AF}', '\u{29AE}'),
+    ('\u{29B8}', '\u{2298}'), ('\u{29C0}', '\u{29C1}'), ('\u{29C1}', '\u{29C0}'),
+    ('\u{29C4}', '\u{29C5}'), ('\u{29C5}', '\u{29C4}'), ('\u{29CF}', '\u{29D0}'),
+    ('\u{29D0}', '\u{29CF}'), ('\u{29D1}', '\u{29D2}'), ('\u{29D2}', '\u{29D1}'),
+    ('\u{29D4}', '\u{29D5}'), ('\u{29D5}', '\u{29D4}'), ('\u{29D8}', '\u{29D9}'),
+    ('\u{29D9}', '\u{29D8}'), ('\u{29DA}', '\u{29DB}'), ('\u{29DB}', '\u{29DA}'),
+    ('\u{29E8}', '\u{29E9}'), ('\u{29E9}', '\u{29E8}'), ('\u{29F5}', '\u{2215}'),
+    ('\u{29F8}', '\u{29F9}'), ('\u{29F9}', '\u{29F8}'), ('\u{29FC}', '\u{29FD}'),
+    ('\u{29FD}', '\u{29FC}'), ('\u{2A2B}', '\u{2A2C}'), ('\u{2A2C}', '\u{2A2B}'),
+    ('\u{2A2D}', '\u{2A2E}'), ('\u{2A2E}', '\u{2A2D}'), ('\u{2A34}', '\u{2A35}'),
+    ('\u{2A35}', '\u{2A34}'), ('\u{2A3C}', '\u{2A3D}'), ('\u{2A3D}', '\u{2A3C}'),
+    ('\u{2A64}', '\u{2A65}'), ('\u{2A65}', '\u{2A64}'), ('\u{2A79}', '\u{2A7A}'),
+    ('\u{2A7A}', '\u{2A79}'), ('\u{2A7B}', '\u{2A7C}'), ('\u{2A7C}', '\u{2A7B}'),
+    ('\u{2A7D}', '\u{2A7E}'), ('\u{2A7E}', '\u{2A7D}'), ('\u{2A7F}', '\u{2A80}'),
+    ('\u{2A80}', '\u{2A7F}'), ('\u{2A81}', '\u{2A82}'), ('\u{2A82}', '\u{2A81}'),
+    ('\u{2A83}', '\u{2A84}'), ('\u{2A84}', '\u{2A83}'), ('\u{2A85}', '\u{2A86}'),
+    ('\u{2A86}', '\u{2A85}'), ('\u{2A87}', '\u{2A88}'), ('\u{2A88}', '\u{2A87}'),
+    ('\u{2A89}', '\u{2A8A}'), ('\u{2A8A}', '\u{2A89}'), ('\u{2A8B}', '\u{2A8C}'),
+    ('\u{2A8C}', '\u{2A8B}'), ('\u{2A8D}', '\u{2A8E}'), ('\u{2A8E}', '\u{2A8D}'),
+    ('\u{2A8F}', '\u{2A90}'), ('\u{2A90}', '\u{2A8F}'), ('\u{2A91}', '\u{2A92}'),
+    ('\u{2A92}', '\u{2A91}'), ('\u{2A93}', '\u{2A94}'), ('\u{2A94}', '\u{2A93}'),
+    ('\u{2A95}', '\u{2A96}'), ('\u{2A96}', '\u{2A95}'), ('\u{2A97}', '\u{2A98}'),
+    ('\u{2A98}', '\u{2A97}'), ('\u{2A99}', '\u{2A9A}'), ('\u{2A9A}', '\u{2A99}'),
+    ('\u{2A9B}', '\u{2A9C}'), ('\u{2A9C}', '\u{2A9B}'), ('\u{2A9D}', '\u{2A9E}'),
+    ('\u{2A9E}', '\u{2A9D}'), ('\u{2A9F}', '\u{2AA0}'), ('\u{2AA0}', '\u{2A9F}'),
+    ('\u{2AA1}', '\u{2AA2}'), ('\u{2AA2}', '\u{2AA1}'), ('\u{2AA6}', '\u{2AA7}'),
+    ('\u{2AA7}', '\u{2AA6}'), ('\u{2AA8}', '\u{2AA9}'), ('\u{2AA9}', '\u{2AA8}'),
+    ('\u{2AAA}', '\u{2AAB}'), ('\u{2AAB}', '\u{2AAA}'), ('\u{2AAC}', '\u{2AAD}'),
+    ('\u{2AAD}', '\u{2AAC}'), ('\u{2AAF}', '\u{2AB0}'), ('\u{2AB0}', '\u{2AAF}'),
+    ('\u{2AB1}', '\u{2AB2}'), ('\u{2AB2}', '\u{2AB1}'), ('\u{2AB3}', '\u{2AB4}'),
+    ('\u{2AB4}', '\u{2AB3}'), ('\u{2AB5}', '\u{2AB6}'), ('\u{2AB6}', '\u{2AB5}'),
+    ('\u{2AB7}', '\u{2AB8}'), ('\u{2AB8}', '\u{2AB7}'), ('\u{2AB9}', '\u{2ABA}'),
+    ('\u{2ABA}', '\u{2AB9}'), ('\u{2ABB}', '\u{2ABC}'), ('\u{2ABC}', '\u{2ABB}'),
+    ('\u{2ABD}', '\u{2ABE}'), ('\u{2ABE}', '\u{2ABD}'), ('\u{2ABF}', '\u{2AC0}'),
+    ('\u{2AC0}', '\u{2ABF}'), ('\u{2AC1}', '\u{2AC2}'), ('\u{2AC2}', '\u{2AC1}'),
+    ('\u{2AC3}', '\u{2AC4}'), ('\u{2AC4}', '\u{2AC3}'), ('\u{2AC5}', '\u{2AC6}'),
+    ('\u{2AC6}', '\u{2AC5}'), ('\u{2AC7}', '\u{2AC8}'), ('\u{2AC8}', '\u{2AC7}'),
+    ('\u{2AC9}', '\u{2ACA}'), ('\u{2ACA}', '\u{2AC9}'), ('\u{2ACB}', '\u{2ACC}'),
+    ('\u{2ACC}', '\u{2ACB}'), ('\u{2ACD}', '\u{2ACE}'), ('\u{2ACE}', '\u{2ACD}'),
+    ('\u{2ACF}', '\u{2AD0}'), ('\u{2AD0}', '\u{2ACF}'), ('\u{2AD1}', '\u{2AD2}'),
+    ('\u{2AD2}', '\u{2AD1}'), ('\u{2AD3}', '\u{2AD4}'), ('\u{2AD4}', '\u{2AD3}'),
+    ('\u{2AD5}', '\u{2AD6}'), ('\u{2AD6}', '\u{2AD5}'), ('\u{2ADE}', '\u{22A6}'),
+    ('\u{2AE3}', '\u{22A9}'), ('\u{2AE4}', '\u{22A8}'), ('\u{2AE5}', '\u{22AB}'),
+    ('\u{2AEC}', '\u{2AED}'), ('\u{2AED}', '\u{2AEC}'), ('\u{2AEE}', '\u{2224}'),
+    ('\u{2AF7}', '\u{2AF8}'), ('\u{2AF8}', '\u{2AF7}'), ('\u{2AF9}', '\u{2AFA}'),
+    ('\u{2AFA}', '\u{2AF9}'), ('\u{2BFE}', '\u{221F}'), ('\u{2E02}', '\u{2E03}'),
+    ('\u{2E03}', '\u{2E02}'), ('\u{2E04}', '\u{2E05}'), ('\u{2E05}', '\u{2E04}'),
+    ('\u{2E09}', '\u{2E0A}'), ('\u{2E0A}', '\u{2E09}'), ('\u{2E0C}', '\u{2E0D}'),
+    ('\u{2E0D}', '\u{2E0C}'), ('\u{2E1C}', '\u{2E1D}'), ('\u{2E1D}', '\u{2E1C}'),
+    ('\u{2E20}', '\u{2E21}'), ('\u{2E21}', '\u{2E20}'), ('\u{2E22}', '\u{2E23}'),
+    ('\u{2E23}', '\u{2E22}'), ('\u{2E24}', '\u{2E25}'), ('\u{2E25}', '\u{2E24}'),
+    ('\u{2E26}', '\u{2E27}'), ('\u{2E27}', '\u{2E26}'), ('\u{2E28}', '\u{2E29}'),
+    ('\u{2E29}', '\u{2E28}'), ('\u{2E55}', '\u{2E56}'), ('\u{2E56}', '\u{2E55}'),
+    ('\u{2E57}', '\u{2E58}'), ('\u{2E58}', '\u{2E57}'), ('\u{2E59}', '\u{2E5A}'),
+    ('\u{2E5A}', '\u{2E59}'), ('\u{2E5B}', '\u{2E5C}'), ('\u{2E5C}', '\u{2E5B}'),
+    ('\u{3008}', '\u{3009}'), ('\u{3009}', '\u{3008}'), ('\u{300A}', '\u{300B}'),
+    ('\u{300B}', '\u{300A}'), ('\u{300C}', '\u{300D}'), ('\u{300D}', '\u{300C}'),
+    ('\u{300E}', '\u{300F}'), ('\u{300F}', '\u{300E}'), ('\u{3010}', '\u{3011}'),
+    ('\u{3011}', '\u{3010}'), ('\u{3014}', '\u{3015}'), ('\u{3015}', '\u{3014}'),
+    ('\u{3016}', '\u{3017}'), ('\u{3017}', '\u{3016}'), ('\u{3018}', '\u{3019}'),
+    ('\u{3019}', '\u{3018}'), ('\u{301A}', '\u{301B}'), ('\u{301B}', '\u{301A}'),
+    ('\u{FE59}', '\u{FE5A}'), ('\u{FE5A}', '\u{FE59}'), ('\u{FE5B}', '\u{FE5C}'),
+    ('\u{FE5C}', '\u{FE5B}'), ('\u{FE5D}', '\u{FE5E}'), ('\u{FE5E}', '\u{FE5D}'),
+    ('\u{FE64}', '\u{FE65}'), ('\u{FE65}', '\u{FE64}'), ('\u{FF08}', '\u{FF09}'),
+    ('\u{FF09}', '\u{FF08}'), ('\u{FF1C}', '\u{FF1E}'), ('\u{FF1E}', '\u{FF1C}'),
+    ('\u{FF3B}', '\u{FF3D}'), ('\u{FF3D}', '\u{FF3B}'), ('\u{FF5B}', '\u{FF5D}'),
+    ('\u{FF5D}', '\u{FF5B}'), ('\u{FF5F}', '\u{FF60}'), ('\u{FF60}', '\u{FF5F}'),
+    ('\u{FF62}', '\u{FF63}'), ('\u{FF63}', '\u{FF62}'),
+];
+
+/// Pairs of opening and closing brackets, sorted by the opening bracket.
+#[rustfmt::skip]
+pub(super) const BRACKETS: &[(char, char)] = &[
+    ('\u{28}', '\u{29}'), ('\u{5B}', '\u{5D}'), ('\u{7B}', '\u{7D}'),
+    ('\u{F3A}', '\u{F3B}'), ('\u{F3C}', '\u{F3D}'), ('\u{169B}', '\u{169C}'),
+    ('\u{2045}', '\u{2046}'), ('\u{207D}', '\u{207E}'), ('\u{208D}', '\u{208E}'),
+    ('\u{2308}', '\u{2309}'), ('\u{230A}', '\u{230B}'), ('\u{2329}', '\u{232A}'),
+    ('\u{2768}', '\u{2769}'), ('\u{276A}', '\u{276B}'), ('\u{276C}', '\u{276D}'),
+    ('\u{276E}', '\u{276F}'), ('\u{2770}', '\u{2771}'), ('\u{2772}', '\u{2773}'),
+    ('\u{2774}', '\u{2775}'), ('\u{27C5}', '\u{27C6}'), ('\u{27E6}', '\u{27E7}'),
+    ('\u{27E8}', '\u{27E9}'), ('\u{27EA}', '\u{27EB}'), ('\u{27EC}', '\u{27ED}'),
+    ('\u{27EE}', '\u{27EF}'), ('\u{2983}', '\u{2984}'), ('\u{2985}', '\u{2986}'),
+    ('\u{2987}', '\u{2988}'), ('\u{2989}', '\u{298A}'), ('\u{298B}', '\u{298C}'),
+    ('\u{298D}', '\u{2990}'), ('\u{298F}', '\u{298E}'), ('\u{2991}', '\u{2992}'),
+    ('\u{2993}', '\u{2994}'), ('\u{2995}', '\u{2996}'), ('\u{2997}', '\u{2998}'),
+    ('\u{29D8}', '\u{29D9}'), ('\u{29DA}', '\u{29DB}'), ('\u{29FC}', '\u{29FD}'),
+    ('\u{2E22}', '\u{2E23}'), ('\u{2E24}', '\u{2E25}'), ('\u{2E26}', '\u{2E27}'),
+    ('\u{2E28}', '\u{2E29}'), ('\u{2E55}', '\u{2E56}'), ('\u{2E57}', '\u{2E58}'),
+    ('\u{2E59}', '\u{2E5A}'), ('\u{2E5B}', '\u{2E5C}'), ('\u{3008}', '\u{3009}'),
+    ('\u{300A}', '\u{300B}'), ('\u{300C}', '\u{300D}'), ('\u{300E}', '\u{300F}'),
+    ('\u{3010}', '\u{3011}'), ('\u{3014}', '\u{3015}'), ('\u{3016}', '\u{3017}'),
+    ('\u{3018}', '\u{3019}'), ('\u{301A}', '\u{301B}'), ('\u{FE59}', '\u{FE5A}'),
+    ('\u{FE5B}', '\u{FE5C}'), ('\u{FE5D}', '\u{FE5E}'), ('\u{FF08}', '\u{FF09}'),
+    ('\u{FF3B}', '\u{FF3D}'), ('\u{FF5B}', '\u{FF5D}'), ('\u{FF5F}', '\u{FF60}'),
+    ('\u{FF62}', '\u{FF63}'),
+];
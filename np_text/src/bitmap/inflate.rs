@@ -0,0 +1,294 @@
+//! Decompressing data in the zlib format, described in [RFC 1950]
+//! and [RFC 1951].
+//!
+//! [RFC 1950]: https://www.rfc-editor.org/rfc/rfc1950
+//! [RFC 1951]: https://www.rfc-editor.org/rfc/rfc1951
+
+/// Decompress a zlib stream.
+///
+/// Decompressing stops with [`None`] when the output grows beyond `max_len`,
+/// so that small malformed images cannot exhaust memory.
+/// The Adler-32 checksum is not verified.
+pub(super) fn zlib_decompress(data: &[u8], max_len: usize) -> Option<Vec<u8>>
+{
+    let (method, flags) = (*data.first()?, *data.get(1)?);
+    let check = u16::from_be_bytes([method, flags]) % 31;
+    if method & 0x0F != 8 || check != 0 || flags & 0x20 != 0 {
+        return None;
+    }
+    inflate(&data[2 ..], max_len)
+}
+
+/// Decompress a raw deflate stream.
+fn inflate(data: &[u8], max_len: usize) -> Option<Vec<u8>>
+{
+    let mut bits = Bits{data, position: 0};
+    let mut output = Vec::new();
+    loop {
+        let last = bits.bits(1)? == 1;
+        match bits.bits(2)? {
+            0 => stored(&mut bits, &mut output, max_len)?,
+            1 => {
+                let (literals, distances) = fixed_codes();
+                codes(&mut bits, &mut output, max_len, &literals, &distances)?;
+            },
+            2 => {
+                let (literals, distances) = dynamic_codes(&mut bits)?;
+                codes(&mut bits, &mut output, max_len, &literals, &distances)?;
+            },
+            _ => return None,
+        }
+        if last {
+            return Some(output);
+        }
+    }
+}
+
+/// Reader of the bits of a deflate stream, least significant bit first.
+struct Bits<'a>
+{
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Bits<'a>
+{
+    fn bits(&mut self, count: u32) -> Option<u32>
+    {
+        let mut value = 0;
+        for i in 0 .. count {
+            let byte = *self.data.get(self.position / 8)?;
+            let bit = (byte >> (self.position % 8)) & 1;
+            value |= (bit as u32) << i;
+            self.position += 1;
+        }
+        Some(value)
+    }
+
+    /// Skip to the start of the next byte, and read bytes from there.
+    fn bytes(&mut self, count: usize) -> Option<&'a [u8]>
+    {
+        let start = (self.position + 7) / 8;
+        let bytes = self.data.get(start .. start.checked_add(count)?)?;
+        self.position = (start + count) * 8;
+        Some(bytes)
+    }
+}
+
+/// Canonical Huffman code, given by the number of codes of each length
+/// and the symbols ordered by code.
+struct Huffman
+{
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman
+{
+    /// Create the canonical code with the given code lengths of the symbols.
+    ///
+    /// Incomplete codes are allowed, as a single distance code is,
+    /// but codes with more codes of a length than fit are not.
+    fn new(lengths: &[u8]) -> Option<Self>
+    {
+        let mut counts = [0; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut left = 1i32;
+        for &count in &counts[1 ..] {
+            left = left * 2 - count as i32;
+            if left < 0 {
+                return None;
+            }
+        }
+
+        let mut symbols = Vec::with_capacity(lengths.len());
+        for length in 1 .. 16 {
+            let of_length = (0 ..).zip(lengths).filter(|&(_, &l)| l == length);
+            symbols.extend(of_length.map(|(symbol, _)| symbol));
+        }
+        Some(Self{counts, symbols})
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Option<u16>
+    {
+        // Codes of each length follow those of the previous length,
+        // so the code is compared with the first code of each length.
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1 ..] {
+            code |= bits.bits(1)? as i32;
+            let count = count as i32;
+            if code - first < count {
+                return self.symbols.get((index + code - first) as usize).copied();
+            }
+            index += count;
+            first = (first + count) * 2;
+            code *= 2;
+        }
+        None
+    }
+}
+
+/// Copy a block that is not compressed.
+fn stored(bits: &mut Bits, output: &mut Vec<u8>, max_len: usize) -> Option<()>
+{
+    let header = bits.bytes(4)?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    let inverse = u16::from_le_bytes([header[2], header[3]]);
+    if len != !inverse || output.len() + len as usize > max_len {
+        return None;
+    }
+    output.extend_from_slice(bits.bytes(len as usize)?);
+    Some(())
+}
+
+/// The codes of blocks compressed with fixed codes.
+fn fixed_codes() -> (Huffman, Huffman)
+{
+    let mut lengths = [0; 288];
+    lengths[.. 144].fill(8);
+    lengths[144 .. 256].fill(9);
+    lengths[256 .. 280].fill(7);
+    lengths[280 ..].fill(8);
+    let literals = Huffman::new(&lengths).expect("complete code");
+    let distances = Huffman::new(&[5; 30]).expect("incomplete code");
+    (literals, distances)
+}
+
+/// Read the codes of a block compressed with dynamic codes.
+fn dynamic_codes(bits: &mut Bits) -> Option<(Huffman, Huffman)>
+{
+    const ORDER: [usize; 19] =
+        [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+    let literal_count = bits.bits(5)? as usize + 257;
+    let distance_count = bits.bits(5)? as usize + 1;
+    let length_count = bits.bits(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return None;
+    }
+
+    let mut length_lengths = [0; 19];
+    for &symbol in &ORDER[.. length_count] {
+        length_lengths[symbol] = bits.bits(3)? as u8;
+    }
+    let length_code = Huffman::new(&length_lengths)?;
+
+    // The code lengths of both codes are compressed together,
+    // with symbols that repeat lengths.
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (length, repeat) = match length_code.decode(bits)? {
+            symbol @ 0 ..= 15 => (symbol as u8, 1),
+            16 => (*lengths.last()?, 3 + bits.bits(2)?),
+            17 => (0, 3 + bits.bits(3)?),
+            _ => (0, 11 + bits.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat(length).take(repeat as usize));
+    }
+    if lengths.len() != literal_count + distance_count || lengths[256] == 0 {
+        return None;
+    }
+
+    let literals = Huffman::new(&lengths[.. literal_count])?;
+    let distances = Huffman::new(&lengths[literal_count ..])?;
+    Some((literals, distances))
+}
+
+/// Decompress a block compressed with the given codes.
+fn codes(
+    bits: &mut Bits,
+    output: &mut Vec<u8>,
+    max_len: usize,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Option<()>
+{
+    const LENGTH_BASE: [u16; 29] = [
+        3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31,
+        35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+    ];
+    const LENGTH_EXTRA: [u8; 29] = [
+        0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2,
+        3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+    ];
+    const DISTANCE_BASE: [u16; 30] = [
+        1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193,
+        257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+    ];
+    const DISTANCE_EXTRA: [u8; 30] = [
+        0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6,
+        7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13,
+    ];
+
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        if symbol < 256 {
+            if output.len() == max_len {
+                return None;
+            }
+            output.push(symbol as u8);
+            continue;
+        }
+        if symbol == 256 {
+            return Some(());
+        }
+
+        let symbol = symbol - 257;
+        let extra = bits.bits(*LENGTH_EXTRA.get(symbol)? as u32)?;
+        let length = LENGTH_BASE[symbol] as usize + extra as usize;
+        let symbol = distances.decode(bits)? as usize;
+        let extra = bits.bits(*DISTANCE_EXTRA.get(symbol)? as u32)?;
+        let distance = DISTANCE_BASE[symbol] as usize + extra as usize;
+        if distance > output.len() || output.len() + length > max_len {
+            return None;
+        }
+
+        // The copy may overlap what it produces, to repeat short sequences.
+        let start = output.len() - distance;
+        for i in 0 .. length {
+            output.push(output[start + i]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_zlib_decompress()
+    {
+        // Generated with Python's zlib.compress,
+        // which uses a stored, fixed, and dynamic block for these.
+        let stored = [
+            0x78, 0x01, 0x01, 0x05, 0x00, 0xFA, 0xFF,
+            0x68, 0x65, 0x6C, 0x6C, 0x6F, 0x06, 0x2C, 0x02, 0x15,
+        ];
+        assert_eq!(zlib_decompress(&stored, 5).as_deref(), Some(&b"hello"[..]));
+        assert_eq!(zlib_decompress(&stored, 4), None);
+
+        let fixed = [
+            0x78, 0xDA, 0xCB, 0x48, 0xCD, 0xC9, 0xC9, 0x57,
+            0xC8, 0x40, 0x27, 0x01, 0x68, 0x03, 0x08, 0xB1,
+        ];
+        let expected = &b"hello hello hello hello"[..];
+        assert_eq!(zlib_decompress(&fixed, 100).as_deref(), Some(expected));
+        assert_eq!(zlib_decompress(&fixed, 22), None);
+
+        let dynamic = [
+            0x78, 0xDA, 0x35, 0x88, 0x41, 0x09, 0x00, 0x00, 0x0C, 0x84, 0xB2,
+            0x6A, 0xFF, 0x10, 0xE3, 0x84, 0x3D, 0x04, 0x55, 0x15, 0x10, 0x27,
+            0x45, 0xD0, 0xEC, 0xBF, 0xBA, 0x82, 0x03, 0xE6, 0x14, 0x13, 0x0B,
+        ];
+        let expected = &b"bbbbaaababbbbabbbaabbaabaaaabaababbaaaabaabbababaa"[..];
+        assert_eq!(zlib_decompress(&dynamic, 100).as_deref(), Some(expected));
+
+        assert_eq!(zlib_decompress(&fixed[.. 10], 100), None);
+        assert_eq!(zlib_decompress(&[0x78, 0x9D], 100), None);
+    }
+}
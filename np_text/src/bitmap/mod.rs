@@ -0,0 +1,444 @@
+//! Loading bitmap glyphs from the strikes of the sbix, CBDT, and EBDT tables.
+//!
+//! The sbix table stores PNG images for each glyph and strike.
+//! The CBDT and EBDT tables store PNG images or packed bitmaps,
+//! located through the index subtables of the CBLC and EBLC tables.
+
+use self::png::decode_png;
+
+use crate::Bitmap;
+use crate::parse::i16_at;
+use crate::parse::offset32;
+use crate::parse::u16_at;
+use crate::parse::u32_at;
+
+use np_graphics::PixelMap;
+use std::ops::Range;
+
+mod inflate;
+mod png;
+
+/// Load the bitmap of a glyph from the strike best suited to a size.
+///
+/// Tables with color bitmaps are preferred over those with monochrome ones.
+/// If the typeface has no bitmap for the glyph,
+/// this function returns [`None`].
+pub(crate) fn glyph_bitmap(face: &ttf_parser::Face, glyph_id: u16, pixels_per_em: f64)
+    -> Option<Bitmap>
+{
+    let table = |tag| face.table_data(ttf_parser::Tag::from_bytes(tag));
+    let sbix = table(b"sbix").and_then(|sbix| {
+        sbix_bitmap(sbix, face.number_of_glyphs(), glyph_id, pixels_per_em)
+    });
+    let cbdt = || embedded_bitmap(table(b"CBLC")?, table(b"CBDT")?, glyph_id, pixels_per_em);
+    let ebdt = || embedded_bitmap(table(b"EBLC")?, table(b"EBDT")?, glyph_id, pixels_per_em);
+    sbix.or_else(cbdt).or_else(ebdt)
+}
+
+/// Pick the strike best suited to a size, given the sizes of the strikes.
+///
+/// This is the smallest strike at least as large as the size,
+/// as scaling bitmaps down looks better than scaling them up,
+/// or the largest strike if there is none.
+fn best_strike<I>(sizes: I, pixels_per_em: f64) -> Option<usize>
+    where I: IntoIterator<Item=(usize, f64)>
+{
+    let better = |size: f64, than: f64| {
+        let (large_enough, than_large_enough) = (size >= pixels_per_em, than >= pixels_per_em);
+        if large_enough != than_large_enough { large_enough }
+        else if large_enough { size < than }
+        else { size > than }
+    };
+    let mut best: Option<(usize, f64)> = None;
+    for (index, size) in sizes {
+        if best.map_or(true, |(_, best_size)| better(size, best_size)) {
+            best = Some((index, size));
+        }
+    }
+    best.map(|(index, _)| index)
+}
+
+/// Load the bitmap of a glyph from the sbix table.
+fn sbix_bitmap(sbix: &[u8], glyph_count: u16, glyph_id: u16, pixels_per_em: f64)
+    -> Option<Bitmap>
+{
+    if glyph_id >= glyph_count {
+        return None;
+    }
+
+    let strike_count = u32_at(sbix, 4)? as usize;
+    let strikes: Vec<&[u8]> =
+        (0 .. strike_count)
+        .scan((), |_, index| offset32(sbix, 8 + 4 * index))
+        .collect();
+    let sizes =
+        strikes.iter().enumerate()
+        .filter(|(_, strike)| sbix_glyph_data(strike, glyph_id).is_some())
+        .filter_map(|(index, strike)| Some((index, u16_at(strike, 0)? as f64)));
+    let strike = strikes[best_strike(sizes, pixels_per_em)?];
+
+    // Glyphs may use the image of another glyph.
+    let mut data = sbix_glyph_data(strike, glyph_id)?;
+    if data.get(4 .. 8)? == b"dupe" {
+        data = sbix_glyph_data(strike, u16_at(data, 8)?)?;
+    }
+    if data.get(4 .. 8)? != b"png " {
+        return None;
+    }
+
+    let pixels = decode_png(&data[8 ..])?;
+    let origin_x = i16_at(data, 0)? as f64;
+    let origin_y = i16_at(data, 2)? as f64;
+    Some(Bitmap{
+        left: origin_x,
+        top: origin_y + pixels.extent().1 as f64,
+        pixels_per_em: u16_at(strike, 0)? as f64,
+        is_color: true,
+        pixels,
+    })
+}
+
+/// The data of a glyph in an sbix strike,
+/// or [`None`] if the strike has no image for the glyph.
+fn sbix_glyph_data(strike: &[u8], glyph_id: u16) -> Option<&[u8]>
+{
+    let start = u32_at(strike, 4 + 4 * glyph_id as usize)? as usize;
+    let end = u32_at(strike, 8 + 4 * glyph_id as usize)? as usize;
+    if end < start + 8 {
+        return None;
+    }
+    strike.get(start .. end)
+}
+
+/// The size of a BitmapSize record in the CBLC and EBLC tables.
+const BITMAP_SIZE_LEN: usize = 48;
+
+/// Load the bitmap of a glyph from the CBDT or EBDT table,
+/// given the corresponding location table.
+fn embedded_bitmap(location: &[u8], data: &[u8], glyph_id: u16, pixels_per_em: f64)
+    -> Option<Bitmap>
+{
+    let strike_count = u32_at(location, 4)? as usize;
+    let strikes: Vec<&[u8]> =
+        (0 .. strike_count)
+        .scan((), |_, index| {
+            let start = 8 + BITMAP_SIZE_LEN * index;
+            location.get(start .. start + BITMAP_SIZE_LEN)
+        })
+        .collect();
+    let sizes =
+        strikes.iter().enumerate()
+        .filter(|(_, strike)| locate_image(location, strike, glyph_id).is_some())
+        .map(|(index, strike)| (index, strike[45] as f64));
+    let strike = strikes[best_strike(sizes, pixels_per_em)?];
+
+    let image = locate_image(location, strike, glyph_id)?;
+    let bytes = data.get(image.range)?;
+    let bit_depth = strike[46];
+    let (metrics, pixels, is_color) = match image.format {
+        1 => {
+            let metrics = GlyphMetrics::small(bytes)?;
+            (metrics, metrics.mask(bytes.get(5 ..)?, bit_depth, true)?, false)
+        },
+        2 => {
+            let metrics = GlyphMetrics::small(bytes)?;
+            (metrics, metrics.mask(bytes.get(5 ..)?, bit_depth, false)?, false)
+        },
+        5 => {
+            let metrics = image.metrics?;
+            (metrics, metrics.mask(bytes, bit_depth, false)?, false)
+        },
+        6 => {
+            let metrics = GlyphMetrics::big(bytes)?;
+            (metrics, metrics.mask(bytes.get(8 ..)?, bit_depth, true)?, false)
+        },
+        7 => {
+            let metrics = GlyphMetrics::big(bytes)?;
+            (metrics, metrics.mask(bytes.get(8 ..)?, bit_depth, false)?, false)
+        },
+        17 => (GlyphMetrics::small(bytes)?, decode_png(png_data(bytes, 5)?)?, true),
+        18 => (GlyphMetrics::big(bytes)?, decode_png(png_data(bytes, 8)?)?, true),
+        19 => (image.metrics?, decode_png(png_data(bytes, 0)?)?, true),
+        _ => return None,
+    };
+
+    Some(Bitmap{
+        left: metrics.bearing_x as f64,
+        top: metrics.bearing_y as f64,
+        pixels_per_em: strike[45] as f64,
+        is_color,
+        pixels,
+    })
+}
+
+/// Where the image of a glyph is in the CBDT or EBDT table.
+struct ImageLocation
+{
+    /// The image format, which tells how the image is stored.
+    format: u16,
+
+    /// The range of the image in the table.
+    range: Range<usize>,
+
+    /// The metrics of the glyph, for index formats that store them
+    /// instead of the images.
+    metrics: Option<GlyphMetrics>,
+}
+
+/// Find the image of a glyph in a strike,
+/// through the index subtables of the CBLC or EBLC table.
+fn locate_image(location: &[u8], strike: &[u8], glyph_id: u16) -> Option<ImageLocation>
+{
+    let array = u32_at(strike, 0)? as usize;
+    let subtable_count = u32_at(strike, 8)? as usize;
+    for index in 0 .. subtable_count {
+        let record = array + 8 * index;
+        let first = u16_at(location, record)?;
+        let last = u16_at(location, record + 2)?;
+        if glyph_id < first || glyph_id > last {
+            continue;
+        }
+
+        let subtable = location.get(array + u32_at(location, record + 4)? as usize ..)?;
+        let index_format = u16_at(subtable, 0)?;
+        let format = u16_at(subtable, 2)?;
+        let image_offset = u32_at(subtable, 4)? as usize;
+        let i = (glyph_id - first) as usize;
+        let (range, metrics) = match index_format {
+            1 => {
+                let start = u32_at(subtable, 8 + 4 * i)? as usize;
+                let end = u32_at(subtable, 12 + 4 * i)? as usize;
+                (start .. end, None)
+            },
+            2 => {
+                let size = u32_at(subtable, 8)? as usize;
+                let metrics = GlyphMetrics::big(subtable.get(12 ..)?)?;
+                (i * size .. (i + 1) * size, Some(metrics))
+            },
+            3 => {
+                let start = u16_at(subtable, 8 + 2 * i)? as usize;
+                let end = u16_at(subtable, 10 + 2 * i)? as usize;
+                (start .. end, None)
+            },
+            4 => {
+                // Sparse glyphs are given with pairs of a glyph and an offset,
+                // followed by one more pair for the end of the last image.
+                let count = u32_at(subtable, 8)? as usize;
+                let pair = (0 .. count).find(|&pair| {
+                    u16_at(subtable, 12 + 4 * pair) == Some(glyph_id)
+                })?;
+                let start = u16_at(subtable, 14 + 4 * pair)? as usize;
+                let end = u16_at(subtable, 18 + 4 * pair)? as usize;
+                (start .. end, None)
+            },
+            5 => {
+                let size = u32_at(subtable, 8)? as usize;
+                let metrics = GlyphMetrics::big(subtable.get(12 ..)?)?;
+                let count = u32_at(subtable, 20)? as usize;
+                let i = (0 .. count).find(|&i| u16_at(subtable, 24 + 2 * i) == Some(glyph_id))?;
+                (i * size .. (i + 1) * size, Some(metrics))
+            },
+            _ => return None,
+        };
+
+        // Glyphs without an image have an empty range.
+        if range.start >= range.end {
+            return None;
+        }
+        let range = image_offset.checked_add(range.start)? .. image_offset.checked_add(range.end)?;
+        return Some(ImageLocation{format, range, metrics});
+    }
+    None
+}
+
+/// The PNG image after the metrics of a glyph in the CBDT table,
+/// which is preceded by its length.
+fn png_data(bytes: &[u8], offset: usize) -> Option<&[u8]>
+{
+    let len = u32_at(bytes, offset)? as usize;
+    bytes.get(offset + 4 .. (offset + 4).checked_add(len)?)
+}
+
+/// The size and placement of a glyph bitmap, in pixels.
+#[derive(Clone, Copy, Debug)]
+struct GlyphMetrics
+{
+    width: u8,
+    height: u8,
+    bearing_x: i8,
+    bearing_y: i8,
+}
+
+impl GlyphMetrics
+{
+    /// Parse small glyph metrics, which are 5 bytes long.
+    fn small(bytes: &[u8]) -> Option<Self>
+    {
+        let bytes = bytes.get(.. 5)?;
+        Some(Self{
+            height: bytes[0],
+            width: bytes[1],
+            bearing_x: bytes[2] as i8,
+            bearing_y: bytes[3] as i8,
+        })
+    }
+
+    /// Parse big glyph metrics, which are 8 bytes long,
+    /// of which the horizontal metrics are used.
+    fn big(bytes: &[u8]) -> Option<Self>
+    {
+        bytes.get(.. 8)?;
+        Self::small(bytes)
+    }
+
+    /// Unpack a monochrome or grayscale bitmap into a mask.
+    ///
+    /// Rows of byte aligned bitmaps start at a byte,
+    /// while those of bit aligned bitmaps follow the previous row.
+    fn mask(&self, bytes: &[u8], bit_depth: u8, byte_aligned: bool)
+        -> Option<PixelMap<[u8; 4]>>
+    {
+        if !matches!(bit_depth, 1 | 2 | 4 | 8) {
+            return None;
+        }
+        let depth = bit_depth as usize;
+        let (width, height) = (self.width as usize, self.height as usize);
+        let row_bits = width * depth;
+        let stride = if byte_aligned { (row_bits + 7) / 8 * 8 } else { row_bits };
+
+        let max = (1 << depth) - 1;
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0 .. height {
+            for x in 0 .. width {
+                let bit = y * stride + x * depth;
+                let byte = *bytes.get(bit / 8)? as usize;
+                let value = (byte >> (8 - depth - bit % 8)) & max;
+                pixels.push([0, 0, 0, (value * 255 / max) as u8]);
+            }
+        }
+        PixelMap::from_pixels(pixels, self.width as u32, self.height as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use super::png::tests::RGBA;
+
+    use crate::test_font::be;
+    use crate::test_font::be32;
+
+    /// An sbix strike with the given glyph data for each glyph.
+    fn sbix_strike(pixels_per_em: u16, glyphs: &[&[u8]]) -> Vec<u8>
+    {
+        let mut offsets = vec![4 + 4 * (glyphs.len() as u32 + 1)];
+        for glyph in glyphs {
+            offsets.push(offsets.last().unwrap() + glyph.len() as u32);
+        }
+        let mut strike = be(&[pixels_per_em, 72]);
+        strike.extend_from_slice(&be32(&offsets));
+        strike.extend(glyphs.concat());
+        strike
+    }
+
+    /// A CBLC or EBLC table with a single strike
+    /// with a single index subtable of format 1.
+    fn location_table(
+        pixels_per_em: u8,
+        bit_depth: u8,
+        glyphs: (u16, u16),
+        image_format: u16,
+        offsets: &[u32],
+    ) -> Vec<u8>
+    {
+        let mut table = be(&[3, 0]);
+        table.extend_from_slice(&be32(&[1, 56, 0, 1, 0]));
+        table.extend_from_slice(&[0; 24]);
+        table.extend_from_slice(&be(&[glyphs.0, glyphs.1]));
+        table.extend_from_slice(&[pixels_per_em, pixels_per_em, bit_depth, 1]);
+        table.extend_from_slice(&be(&[glyphs.0, glyphs.1]));
+        table.extend_from_slice(&be32(&[8]));
+        table.extend_from_slice(&be(&[1, image_format]));
+        table.extend_from_slice(&be32(&[4]));
+        table.extend_from_slice(&be32(offsets));
+        table
+    }
+
+    #[test]
+    fn test_best_strike()
+    {
+        let sizes = || vec![(0, 20.0), (1, 40.0), (2, 32.0)];
+        assert_eq!(best_strike(sizes(), 16.0), Some(0));
+        assert_eq!(best_strike(sizes(), 32.0), Some(2));
+        assert_eq!(best_strike(sizes(), 33.0), Some(1));
+        assert_eq!(best_strike(sizes(), 64.0), Some(1));
+        assert_eq!(best_strike(vec![], 16.0), None);
+    }
+
+    #[test]
+    fn test_sbix()
+    {
+        // Glyph 1 is in both strikes,
+        // and glyph 2 is a duplicate of it in the larger strike.
+        let mut image = be(&[1, 0xFFFE]);
+        image.extend_from_slice(b"png ");
+        image.extend_from_slice(&RGBA);
+        let mut dupe = be(&[0, 0]);
+        dupe.extend_from_slice(b"dupe");
+        dupe.extend_from_slice(&be(&[1]));
+        let small = sbix_strike(20, &[&[], &image, &[]]);
+        let large = sbix_strike(40, &[&[], &image, &dupe]);
+        let mut sbix = be(&[1, 1]);
+        sbix.extend_from_slice(&be32(&[2, 16, 16 + small.len() as u32]));
+        sbix.extend(small);
+        sbix.extend(large);
+
+        let bitmap = sbix_bitmap(&sbix, 3, 1, 16.0).unwrap();
+        assert_eq!((bitmap.left, bitmap.top, bitmap.pixels_per_em), (1.0, 0.0, 20.0));
+        assert_eq!(bitmap.pixels.pixels()[0], [0, 0, 255, 255]);
+        assert!(bitmap.is_color);
+        assert_eq!(sbix_bitmap(&sbix, 3, 1, 30.0).unwrap().pixels_per_em, 40.0);
+        assert_eq!(sbix_bitmap(&sbix, 3, 1, 50.0).unwrap().pixels_per_em, 40.0);
+
+        let bitmap = sbix_bitmap(&sbix, 3, 2, 16.0).unwrap();
+        assert_eq!((bitmap.left, bitmap.top, bitmap.pixels_per_em), (1.0, 0.0, 40.0));
+        assert!(sbix_bitmap(&sbix, 3, 0, 16.0).is_none());
+        assert!(sbix_bitmap(&sbix, 3, 3, 16.0).is_none());
+    }
+
+    #[test]
+    fn test_ebdt()
+    {
+        // Glyph 1 is a byte aligned monochrome bitmap of 3 by 2 pixels,
+        // and glyph 2 has no bitmap.
+        let eblc = location_table(12, 1, (1, 2), 1, &[0, 7, 7]);
+        let mut ebdt = be(&[2, 0]);
+        ebdt.extend_from_slice(&[2, 3, 1, 5, 4, 0b1010_0000, 0b0100_0000]);
+
+        let bitmap = embedded_bitmap(&eblc, &ebdt, 1, 16.0).unwrap();
+        assert_eq!((bitmap.left, bitmap.top, bitmap.pixels_per_em), (1.0, 5.0, 12.0));
+        assert!(!bitmap.is_color);
+        let (on, off) = ([0, 0, 0, 255], [0, 0, 0, 0]);
+        assert_eq!(bitmap.pixels.extent(), (3, 2));
+        assert_eq!(bitmap.pixels.pixels(), [on, off, on, off, on, off]);
+        assert!(embedded_bitmap(&eblc, &ebdt, 2, 16.0).is_none());
+        assert!(embedded_bitmap(&eblc, &ebdt, 3, 16.0).is_none());
+    }
+
+    #[test]
+    fn test_cbdt()
+    {
+        // Glyph 1 is a PNG image with small metrics.
+        let cblc = location_table(109, 32, (1, 1), 17, &[0, 9 + RGBA.len() as u32]);
+        let mut cbdt = be(&[3, 0]);
+        cbdt.extend_from_slice(&[2, 2, 0, 2, 2]);
+        cbdt.extend_from_slice(&be32(&[RGBA.len() as u32]));
+        cbdt.extend_from_slice(&RGBA);
+
+        let bitmap = embedded_bitmap(&cblc, &cbdt, 1, 16.0).unwrap();
+        assert_eq!((bitmap.left, bitmap.top, bitmap.pixels_per_em), (0.0, 2.0, 109.0));
+        assert!(bitmap.is_color);
+        assert_eq!(bitmap.pixels.pixels()[3], [30, 20, 10, 0]);
+    }
+}
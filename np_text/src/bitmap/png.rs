@@ -0,0 +1,346 @@
+//! Decoding PNG images, described in the [PNG specification].
+//!
+//! All color types, bit depths, filters, and interlacing are supported.
+//! Chunk checksums are not verified, and ancillary chunks
+//! other than transparency are ignored.
+//!
+//! [PNG specification]: https://www.w3.org/TR/png/
+
+use super::inflate::zlib_decompress;
+
+use np_graphics::PixelMap;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// Images larger than this on either side are rejected,
+/// as glyphs are never that large.
+const MAX_EXTENT: u32 = 4096;
+
+/// The pixel positions of the passes of interlaced images,
+/// as the first column, first row, column step, and row step.
+const ADAM7: [(u32, u32, u32, u32); 7] = [
+    (0, 0, 8, 8), (4, 0, 8, 8), (0, 4, 4, 8), (2, 0, 4, 4),
+    (0, 2, 2, 4), (1, 0, 2, 2), (0, 1, 1, 2),
+];
+
+/// Decode a PNG image into BGRA pixels with straight alpha.
+///
+/// If the image is malformed,
+/// this function returns [`None`].
+pub(super) fn decode_png(data: &[u8]) -> Option<PixelMap<[u8; 4]>>
+{
+    if data.get(.. 8)? != SIGNATURE {
+        return None;
+    }
+
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut compressed = Vec::new();
+    let mut rest = &data[8 ..];
+    while rest.len() >= 12 {
+        let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let chunk = rest.get(8 .. 8usize.checked_add(len)?)?;
+        match &rest[4 .. 8] {
+            b"IHDR" => header = Some(Header::parse(chunk)?),
+            b"PLTE" => palette = chunk,
+            b"tRNS" => transparency = chunk,
+            b"IDAT" => compressed.extend_from_slice(chunk),
+            b"IEND" => break,
+            _ => (),
+        }
+        rest = rest.get(len + 12 ..)?;
+    }
+    let header = header?;
+
+    let passes = if header.interlaced { &ADAM7[..] } else { &[(0, 0, 1, 1)][..] };
+    let raw_len = passes.iter().map(|&pass| header.pass_len(pass)).sum();
+    let raw = zlib_decompress(&compressed, raw_len)?;
+    if raw.len() != raw_len {
+        return None;
+    }
+
+    let mut pixels = vec![[0; 4]; header.width as usize * header.height as usize];
+    let mut raw = &raw[..];
+    for &pass in passes {
+        let (filtered, rest) = raw.split_at(header.pass_len(pass));
+        raw = rest;
+        let (width, _) = header.pass_extent(pass);
+        if width == 0 {
+            continue;
+        }
+        let stride = header.stride(width);
+        let rows = unfilter(filtered, stride, header.filter_distance())?;
+        let (x0, y0, dx, dy) = pass;
+        for (row, y) in rows.chunks(stride).zip((y0 ..).step_by(dy as usize)) {
+            for (column, x) in (0 .. width).zip((x0 ..).step_by(dx as usize)) {
+                let pixel = header.pixel(row, column, palette, transparency)?;
+                pixels[(y * header.width + x) as usize] = pixel;
+            }
+        }
+    }
+
+    PixelMap::from_pixels(pixels, header.width, header.height)
+}
+
+/// The image header of a PNG image.
+struct Header
+{
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: u8,
+    interlaced: bool,
+}
+
+impl Header
+{
+    fn parse(chunk: &[u8]) -> Option<Self>
+    {
+        let chunk = chunk.get(.. 13)?;
+        let width = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        let height = u32::from_be_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+        let (bit_depth, color_type) = (chunk[8], chunk[9]);
+        let valid_depth = match color_type {
+            0 => matches!(bit_depth, 1 | 2 | 4 | 8 | 16),
+            3 => matches!(bit_depth, 1 | 2 | 4 | 8),
+            2 | 4 | 6 => matches!(bit_depth, 8 | 16),
+            _ => false,
+        };
+        let valid =
+            valid_depth &&
+            (1 ..= MAX_EXTENT).contains(&width) &&
+            (1 ..= MAX_EXTENT).contains(&height) &&
+            chunk[10] == 0 && chunk[11] == 0 && chunk[12] <= 1;
+        if !valid {
+            return None;
+        }
+        Some(Self{width, height, bit_depth, color_type, interlaced: chunk[12] == 1})
+    }
+
+    fn channels(&self) -> u32
+    {
+        match self.color_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> u32
+    {
+        self.channels() * self.bit_depth as u32
+    }
+
+    /// The number of bytes in a row of the given width, without its filter type.
+    fn stride(&self, width: u32) -> usize
+    {
+        ((width * self.bits_per_pixel() + 7) / 8) as usize
+    }
+
+    /// The number of bytes between a byte and the one it is predicted from.
+    fn filter_distance(&self) -> usize
+    {
+        ((self.bits_per_pixel() + 7) / 8) as usize
+    }
+
+    /// The width and height of a pass of the image.
+    fn pass_extent(&self, (x0, y0, dx, dy): (u32, u32, u32, u32)) -> (u32, u32)
+    {
+        let extent = |size: u32, start, step| (size.saturating_sub(start) + step - 1) / step;
+        (extent(self.width, x0, dx), extent(self.height, y0, dy))
+    }
+
+    /// The number of bytes in the filtered rows of a pass of the image.
+    ///
+    /// Passes without pixels have no rows.
+    fn pass_len(&self, pass: (u32, u32, u32, u32)) -> usize
+    {
+        match self.pass_extent(pass) {
+            (0, _) | (_, 0) => 0,
+            (width, height) => height as usize * (1 + self.stride(width)),
+        }
+    }
+
+    /// The sample with the given index in a row,
+    /// with samples smaller than a byte packed from the high bits.
+    fn sample(&self, row: &[u8], index: u32) -> Option<u16>
+    {
+        let depth = self.bit_depth as u32;
+        let bit = (index * depth) as usize;
+        match depth {
+            16 => Some(u16::from_be_bytes([*row.get(bit / 8)?, *row.get(bit / 8 + 1)?])),
+            _ => {
+                let byte = *row.get(bit / 8)? as u16;
+                let shift = 8 - depth as usize - bit % 8;
+                Some((byte >> shift) & ((1 << depth) - 1))
+            },
+        }
+    }
+
+    /// The pixel in the given column of an unfiltered row, in BGRA.
+    fn pixel(&self, row: &[u8], column: u32, palette: &[u8], transparency: &[u8])
+        -> Option<[u8; 4]>
+    {
+        let channels = self.channels();
+        let mut samples = [0; 4];
+        for channel in 0 .. channels {
+            samples[channel as usize] = self.sample(row, column * channels + channel)?;
+        }
+
+        // Transparent colors are given with samples of the bit depth.
+        let key = |index: usize| {
+            let bytes = transparency.get(2 * index .. 2 * index + 2)?;
+            Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+        };
+        let keyed = |count: usize| (0 .. count).all(|i| key(i) == Some(samples[i]));
+        let alpha = |keyed: bool| if keyed { 0 } else { 255 };
+
+        let depth = self.bit_depth as u32;
+        let scale = |sample: u16| match depth {
+            16 => (sample >> 8) as u8,
+            _ => (sample as u32 * 255 / ((1 << depth) - 1)) as u8,
+        };
+        let pixel = match self.color_type {
+            0 => {
+                let gray = scale(samples[0]);
+                [gray, gray, gray, alpha(keyed(1))]
+            },
+            2 => {
+                let (r, g, b) = (scale(samples[0]), scale(samples[1]), scale(samples[2]));
+                [b, g, r, alpha(keyed(3))]
+            },
+            3 => {
+                let index = samples[0] as usize;
+                let rgb = palette.get(3 * index .. 3 * index + 3)?;
+                [rgb[2], rgb[1], rgb[0], *transparency.get(index).unwrap_or(&255)]
+            },
+            4 => {
+                let gray = scale(samples[0]);
+                [gray, gray, gray, scale(samples[1])]
+            },
+            _ => {
+                let (r, g, b) = (scale(samples[0]), scale(samples[1]), scale(samples[2]));
+                [b, g, r, scale(samples[3])]
+            },
+        };
+        Some(pixel)
+    }
+}
+
+/// Undo the filters of rows, each preceded by its filter type.
+fn unfilter(filtered: &[u8], stride: usize, distance: usize) -> Option<Vec<u8>>
+{
+    let mut rows = Vec::with_capacity(filtered.len());
+    for (index, row) in filtered.chunks(stride + 1).enumerate() {
+        let start = rows.len();
+        let previous = index.checked_sub(1).map(|_| start - stride);
+        for (i, &byte) in row[1 ..].iter().enumerate() {
+            let a = if i >= distance { rows[start + i - distance] } else { 0 };
+            let b = previous.map_or(0, |previous| rows[previous + i]);
+            let c = match previous {
+                Some(previous) if i >= distance => rows[previous + i - distance],
+                _ => 0,
+            };
+            let prediction = match row[0] {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return None,
+            };
+            rows.push(byte.wrapping_add(prediction));
+        }
+    }
+    Some(rows)
+}
+
+/// Predict a byte from those to its left, above, and above left.
+fn paeth(a: u8, b: u8, c: u8) -> u8
+{
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc { a } else if pb <= pc { b } else { c }
+}
+
+#[cfg(test)]
+pub(super) mod tests
+{
+    use super::*;
+
+    /// An RGBA image of 2 by 2 pixels, filtered with the sub and Paeth filters.
+    ///
+    /// This and the other images were encoded with Python's zlib.
+    pub(in crate::bitmap) const RGBA: [u8; 83] = [
+        0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D,
+        0x49, 0x48, 0x44, 0x52, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02,
+        0x08, 0x06, 0x00, 0x00, 0x00, 0x72, 0xB6, 0x0D, 0x24, 0x00, 0x00, 0x00,
+        0x1A, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9C, 0x63, 0xFC, 0xCF, 0xC0, 0xF0,
+        0x9F, 0xF1, 0x3F, 0x43, 0x23, 0x0B, 0x23, 0xC3, 0x7F, 0x06, 0x2E, 0x51,
+        0xF9, 0x06, 0x00, 0x37, 0x5B, 0x05, 0x43, 0xA2, 0xE9, 0xA5, 0xA9, 0x00,
+        0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82,
+    ];
+
+    #[test]
+    fn test_rgba()
+    {
+        let image = decode_png(&RGBA).unwrap();
+        assert_eq!(image.extent(), (2, 2));
+        assert_eq!(image.pixels(), [
+            [0, 0, 255, 255], [0, 255, 0, 128],
+            [255, 0, 0, 255], [30, 20, 10, 0],
+        ]);
+        assert!(decode_png(&RGBA[.. 60]).is_none());
+    }
+
+    #[test]
+    fn test_palette()
+    {
+        // A palette of white and black, with white transparent,
+        // with one bit per pixel and the up filter.
+        let data = [
+            0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D,
+            0x49, 0x48, 0x44, 0x52, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02,
+            0x01, 0x03, 0x00, 0x00, 0x00, 0xA7, 0xBA, 0xF4, 0x59, 0x00, 0x00, 0x00,
+            0x06, 0x50, 0x4C, 0x54, 0x45, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x55,
+            0xC2, 0xD3, 0x7E, 0x00, 0x00, 0x00, 0x01, 0x74, 0x52, 0x4E, 0x53, 0x00,
+            0x40, 0xE6, 0xD8, 0x66, 0x00, 0x00, 0x00, 0x0C, 0x49, 0x44, 0x41, 0x54,
+            0x78, 0x9C, 0x63, 0x70, 0x60, 0x4A, 0x00, 0x00, 0x01, 0x28, 0x00, 0xA3,
+            0x80, 0x29, 0x06, 0xD3, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44,
+            0xAE, 0x42, 0x60, 0x82,
+        ];
+        let (white, black) = ([255, 255, 255, 0], [0, 0, 0, 255]);
+        let image = decode_png(&data).unwrap();
+        assert_eq!(image.extent(), (3, 2));
+        assert_eq!(image.pixels(), [white, black, white, black, white, black]);
+    }
+
+    #[test]
+    fn test_interlaced()
+    {
+        // Gray with 16 bits per sample, with black transparent,
+        // interlaced and with the average filter.
+        let data = [
+            0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D,
+            0x49, 0x48, 0x44, 0x52, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x03,
+            0x10, 0x00, 0x00, 0x00, 0x01, 0x54, 0xD4, 0x06, 0xB6, 0x00, 0x00, 0x00,
+            0x02, 0x74, 0x52, 0x4E, 0x53, 0x00, 0x00, 0x76, 0x93, 0xCD, 0x38, 0x00,
+            0x00, 0x00, 0x21, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9C, 0x63, 0x66, 0x60,
+            0x60, 0xFE, 0xFF, 0x9F, 0xF9, 0x3F, 0x43, 0x23, 0x03, 0x73, 0x03, 0x03,
+            0xF3, 0x81, 0xFF, 0xCC, 0x42, 0x26, 0xDF, 0x9F, 0x39, 0x30, 0x00, 0x00,
+            0x5D, 0xBC, 0x08, 0x33, 0x18, 0xB7, 0x9C, 0x3D, 0x00, 0x00, 0x00, 0x00,
+            0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82,
+        ];
+        let gray = |value| [value, value, value, 255];
+        let transparent = [0, 0, 0, 0];
+        let image = decode_png(&data).unwrap();
+        assert_eq!(image.extent(), (3, 3));
+        assert_eq!(image.pixels(), [
+            transparent, gray(0x80), gray(0xFF),
+            gray(0x12), transparent, gray(0x40),
+            gray(0xFF), gray(0x00), transparent,
+        ]);
+    }
+}
@@ -0,0 +1,120 @@
+use crate::Typeface;
+use crate::parse::u16_at;
+use crate::parse::u32_at;
+
+use std::convert::TryInto;
+
+/// Whether the character is a variation selector,
+/// which selects a variant of the preceding character.
+///
+/// These are looked up in format 14 cmap subtables.
+pub(crate) fn is_variation_selector(c: char) -> bool
+{
+    matches!(c,
+        '\u{180B}' ..= '\u{180D}' | '\u{180F}' |
+        '\u{FE00}' ..= '\u{FE0F}' |
+        '\u{E0100}' ..= '\u{E01EF}'
+    )
+}
+
+/// Map each character of a string to a glyph identifier.
+///
+/// `lookup` is called with each character and the variation selector
+/// that follows it, if any; variation selectors get no glyph of their own.
+/// Characters for which `lookup` returns [`None`] are mapped to [`Typeface::NOTDEF`].
+pub(crate) fn glyphs_for_str<F>(text: &str, mut lookup: F) -> Vec<u16>
+    where F: FnMut(char, Option<char>) -> Option<u16>
+{
+    let mut glyphs = Vec::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if is_variation_selector(c) {
+            continue;
+        }
+        let selector = chars.peek().copied().filter(|&s| is_variation_selector(s));
+        glyphs.push(lookup(c, selector).unwrap_or(Typeface::NOTDEF));
+    }
+    glyphs
+}
+
+/// Look up a character in the mixed 16-bit and 32-bit coverage
+/// (format 8) subtables of a cmap table.
+///
+/// ttf-parser does not support this format, as fonts rarely use it.
+/// Only the Unicode subtables are considered.
+pub(crate) fn mixed_coverage_glyph_index(cmap: &[u8], c: u32) -> Option<u16>
+{
+    let num_tables = u16_at(cmap, 2)?;
+    for i in 0 .. num_tables as usize {
+        let record = 4 + 8 * i;
+        let platform_id = u16_at(cmap, record)?;
+        let encoding_id = u16_at(cmap, record + 2)?;
+        let offset = u32_at(cmap, record + 4)? as usize;
+
+        let is_unicode = platform_id == 0 || (platform_id == 3 && encoding_id == 10);
+        if !is_unicode || u16_at(cmap, offset)? != 8 {
+            continue;
+        }
+
+        // The 8192-byte bitmap that follows the header says
+        // which 16-bit values start 32-bit characters,
+        // but the groups are given in 32-bit characters anyway.
+        let num_groups = u32_at(cmap, offset + 8204)?;
+        for j in 0 .. num_groups as usize {
+            let group = offset + 8208 + 12 * j;
+            let start = u32_at(cmap, group)?;
+            let end = u32_at(cmap, group + 4)?;
+            let start_glyph_id = u32_at(cmap, group + 8)?;
+            if start <= c && c <= end {
+                let glyph_id = start_glyph_id.checked_add(c - start)?;
+                return glyph_id.try_into().ok().filter(|&id| id != Typeface::NOTDEF);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_glyphs_for_str()
+    {
+        // Variation selector 16 selects a different glyph for the heart,
+        // and characters without glyphs become .notdef.
+        let lookup = |c, selector| match (c, selector) {
+            ('a', _) => Some(1),
+            ('\u{2764}', Some('\u{FE0F}')) => Some(3),
+            ('\u{2764}', _) => Some(2),
+            _ => None,
+        };
+        assert_eq!(glyphs_for_str("a\u{2764}\u{FE0F}b\u{2764}", lookup), [1, 3, 0, 2]);
+        assert_eq!(glyphs_for_str("\u{FE0F}a\u{FE0E}", lookup), [1]);
+        assert_eq!(glyphs_for_str("", lookup), []);
+    }
+
+    #[test]
+    fn test_mixed_coverage_glyph_index()
+    {
+        // A cmap table with a single format 8 subtable with two groups.
+        let mut cmap = vec![0, 0, 0, 1, 0, 0, 0, 4, 0, 0, 0, 12];
+        cmap.extend_from_slice(&[0, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        cmap.extend_from_slice(&[0; 8192]);
+        cmap.extend_from_slice(&2u32.to_be_bytes());
+        for &(start, end, glyph) in &[(0x41u32, 0x5Au32, 10u32), (0x1F600, 0x1F64F, 100)] {
+            cmap.extend_from_slice(&start.to_be_bytes());
+            cmap.extend_from_slice(&end.to_be_bytes());
+            cmap.extend_from_slice(&glyph.to_be_bytes());
+        }
+
+        assert_eq!(mixed_coverage_glyph_index(&cmap, 0x41), Some(10));
+        assert_eq!(mixed_coverage_glyph_index(&cmap, 0x5A), Some(35));
+        assert_eq!(mixed_coverage_glyph_index(&cmap, 0x1F601), Some(101));
+        assert_eq!(mixed_coverage_glyph_index(&cmap, 0x61), None);
+
+        // Truncated tables are not a problem.
+        assert_eq!(mixed_coverage_glyph_index(&cmap[.. 100], 0x41), None);
+    }
+}
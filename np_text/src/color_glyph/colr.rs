@@ -0,0 +1,437 @@
+use super::Fill;
+use super::GradientFill;
+use super::GradientShape;
+use super::Layer;
+use super::Spread;
+
+use crate::parse::i16_at;
+use crate::parse::offset24;
+use crate::parse::offset32;
+use crate::parse::u16_at;
+use crate::parse::u32_at;
+
+use np_graphics::Color;
+use np_graphics::ColorSpace;
+use np_graphics::Gradient;
+use np_graphics::Matrix;
+use np_graphics::PathBuf;
+use np_graphics::Vector;
+use np_graphics::path::FillRule;
+use np_graphics::path::intersection;
+
+use std::cmp::Ordering;
+use std::f64::consts::PI;
+
+/// The palette index that refers to the foreground color.
+const FOREGROUND: u16 = 0xFFFF;
+
+/// Paint graphs deeper than this are rejected, as they may have cycles.
+const MAX_DEPTH: u32 = 64;
+
+/// Paint graphs with more paints than this are rejected,
+/// as shared paints may make them exponentially large.
+const MAX_PAINTS: u32 = 10_000;
+
+/// The tolerance for flattening outlines that clip each other, in font units.
+const CLIP_TOLERANCE: f64 = 0.25;
+
+/// Flatten the color glyph with the given glyph identifier into layers.
+///
+/// Version 1 color glyphs are preferred over version 0 ones.
+/// The outlines of glyphs are obtained with `outline`.
+/// If the COLR table has no color glyph with the identifier,
+/// or the color glyph is malformed, this function returns [`None`].
+pub(crate) fn color_layers<F>(
+    colr: &[u8],
+    glyph_id: u16,
+    palette: &[Color],
+    foreground: Color,
+    outline: F,
+) -> Option<Vec<Layer>>
+    where F: Fn(u16) -> Option<PathBuf>
+{
+    let mut painter = Painter{
+        colr,
+        palette,
+        foreground,
+        outline,
+        paints: 0,
+        layers: Vec::new(),
+    };
+    match base_glyph_paint(colr, glyph_id) {
+        Some(paint) => painter.paint(paint, Matrix::IDENTITY, None, 0)?,
+        None => painter.layer_records(glyph_id)?,
+    }
+    Some(painter.layers)
+}
+
+/// The root paint of a version 1 color glyph.
+fn base_glyph_paint(colr: &[u8], glyph_id: u16) -> Option<&[u8]>
+{
+    if u16_at(colr, 0)? < 1 {
+        return None;
+    }
+    let list = offset32(colr, 14)?;
+    let count = u32_at(list, 0)? as usize;
+    let record = find_record(&list[4 ..], count, 6, glyph_id)?;
+    list.get(u32_at(record, 2)? as usize ..)
+}
+
+/// Find the record for a glyph among records sorted by glyph identifier,
+/// which is their first field.
+fn find_record(records: &[u8], count: usize, size: usize, glyph_id: u16) -> Option<&[u8]>
+{
+    let (mut low, mut high) = (0, count);
+    while low < high {
+        let middle = (low + high) / 2;
+        let record = records.get(middle * size .. (middle + 1) * size)?;
+        match u16_at(record, 0)?.cmp(&glyph_id) {
+            Ordering::Less => low = middle + 1,
+            Ordering::Greater => high = middle,
+            Ordering::Equal => return Some(record),
+        }
+    }
+    None
+}
+
+/// Collects the layers of a color glyph.
+struct Painter<'a, F>
+{
+    colr: &'a [u8],
+    palette: &'a [Color],
+    foreground: Color,
+    outline: F,
+    paints: u32,
+    layers: Vec<Layer>,
+}
+
+impl<'a, F> Painter<'a, F>
+    where F: Fn(u16) -> Option<PathBuf>
+{
+    /// Add the layers of a version 0 color glyph,
+    /// each of which is the outline of a glyph filled with a color.
+    fn layer_records(&mut self, glyph_id: u16) -> Option<()>
+    {
+        let count = u16_at(self.colr, 2)? as usize;
+        let records = offset32(self.colr, 4)?;
+        let layers = offset32(self.colr, 8)?;
+        let record = find_record(records, count, 6, glyph_id)?;
+        let first = u16_at(record, 2)? as usize;
+        let count = u16_at(record, 4)? as usize;
+        for layer in first .. first + count {
+            let glyph_id = u16_at(layers, 4 * layer)?;
+            let color = self.color(u16_at(layers, 4 * layer + 2)?, 1.0);
+            if let Some(outline) = (self.outline)(glyph_id) {
+                self.layers.push(Layer{outline, fill: Fill::Solid(color)});
+            }
+        }
+        Some(())
+    }
+
+    /// Add the layers of a version 1 paint.
+    ///
+    /// The paint is transformed with `matrix`,
+    /// and fills are clipped to `clip`, which is in font units.
+    /// Fills that are not clipped by any glyph would cover everything,
+    /// so they are left out.
+    fn paint(&mut self, paint: &'a [u8], matrix: Matrix, clip: Option<&PathBuf>, depth: u32)
+        -> Option<()>
+    {
+        self.paints += 1;
+        if depth > MAX_DEPTH || self.paints > MAX_PAINTS {
+            return None;
+        }
+
+        match *paint.first()? {
+            // Layers from the layer list.
+            1 => {
+                let list = offset32(self.colr, 18)?;
+                let count = *paint.get(1)? as usize;
+                let first = u32_at(paint, 2)? as usize;
+                for layer in first .. first + count {
+                    self.paint(offset32(list, 4 + 4 * layer)?, matrix, clip, depth + 1)?;
+                }
+            },
+
+            // Solid colors and gradients.
+            2 ..= 9 => if let Some(clip) = clip {
+                let fill = self.fill(paint, matrix)?;
+                let outline = clip.instructions().collect();
+                self.layers.push(Layer{outline, fill});
+            },
+
+            // Paints clipped to the outline of a glyph.
+            10 => {
+                let outline = (self.outline)(u16_at(paint, 4)?)?;
+                let outline: PathBuf =
+                    outline.instructions().map(|instruction| matrix * instruction).collect();
+                let clip = match clip {
+                    Some(clip) =>
+                        intersection(clip, &outline, FillRule::NonZero, CLIP_TOLERANCE),
+                    None => outline,
+                };
+                self.paint(offset24(paint, 1)?, matrix, Some(&clip), depth + 1)?;
+            },
+
+            // The paint of another color glyph.
+            11 => {
+                let base = base_glyph_paint(self.colr, u16_at(paint, 1)?)?;
+                self.paint(base, matrix, clip, depth + 1)?;
+            },
+
+            // Transformed paints.
+            12 ..= 31 => {
+                let transform = transform(paint)?;
+                self.paint(offset24(paint, 1)?, matrix * transform, clip, depth + 1)?;
+            },
+
+            // Composited paints, which are drawn with the source over the backdrop
+            // unless only one of them is drawn, as layers cannot express
+            // other composite modes.
+            32 => {
+                let source = offset24(paint, 1)?;
+                let backdrop = offset24(paint, 5)?;
+                let (draw_source, draw_backdrop) = match *paint.get(4)? {
+                    0 => (false, false),
+                    1 => (true, false),
+                    2 => (false, true),
+                    _ => (true, true),
+                };
+                if draw_backdrop {
+                    self.paint(backdrop, matrix, clip, depth + 1)?;
+                }
+                if draw_source {
+                    self.paint(source, matrix, clip, depth + 1)?;
+                }
+            },
+
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Parse a solid or gradient paint into a fill,
+    /// transformed with `matrix`.
+    ///
+    /// Formats come in pairs of a paint and its variable version,
+    /// which has the same fields followed by variation indices.
+    /// The variations are not applied.
+    fn fill(&self, paint: &[u8], matrix: Matrix) -> Option<Fill>
+    {
+        let format = paint[0];
+        let point = |offset| Some(Vector{x: fword(paint, offset)?, y: fword(paint, offset + 2)?});
+        let shape = match format {
+            2 | 3 => {
+                let color = self.color(u16_at(paint, 1)?, f2dot14(paint, 3)?);
+                return Some(Fill::Solid(color));
+            },
+            4 | 5 => {
+                // The colors are constant along lines parallel to p0 to p2,
+                // so the gradient ends at p1 projected onto their normal.
+                let (p0, p1, p2) = (point(4)?, point(8)?, point(12)?);
+                let normal = (p2 - p0).perpendicular();
+                let length = normal.dot(normal);
+                let end =
+                    if length == 0.0 { p1 }
+                    else { p0 + (p1 - p0).dot(normal) / length * normal };
+                GradientShape::Linear{start: p0, end}
+            },
+            6 | 7 => GradientShape::Radial{
+                start_center: point(4)?,
+                start_radius: u16_at(paint, 8)? as f64,
+                end_center: point(10)?,
+                end_radius: u16_at(paint, 14)? as f64,
+            },
+            _ => GradientShape::Sweep{
+                center: point(4)?,
+                start_angle: sweep_angle(paint, 8)?,
+                end_angle: sweep_angle(paint, 10)?,
+            },
+        };
+        let (gradient, spread) = self.color_line(offset24(paint, 1)?, format % 2 == 1)?;
+        Some(Fill::Gradient(GradientFill{shape, gradient, spread, matrix}))
+    }
+
+    /// Parse a color line into a gradient, given whether it is variable.
+    fn color_line(&self, line: &[u8], variable: bool) -> Option<(Gradient, Spread)>
+    {
+        let spread = match *line.first()? {
+            1 => Spread::Repeat,
+            2 => Spread::Reflect,
+            _ => Spread::Pad,
+        };
+        let count = u16_at(line, 1)? as usize;
+        let size = if variable { 10 } else { 6 };
+        let stops = (0 .. count)
+            .map(|index| {
+                let stop = line.get(3 + size * index ..)?;
+                let color = self.color(u16_at(stop, 2)?, f2dot14(stop, 4)?);
+                Some((f2dot14(stop, 0)? as f32, color))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some((Gradient::new(ColorSpace::Srgb, stops), spread))
+    }
+
+    /// The color with the given palette index, with its alpha scaled.
+    ///
+    /// Indices outside of the palette are transparent.
+    fn color(&self, index: u16, alpha: f64) -> Color
+    {
+        let color = match index {
+            FOREGROUND => self.foreground,
+            _ => self.palette.get(index as usize).copied().unwrap_or(Color::TRANSPARENT),
+        };
+        color.with_alpha(color.a * alpha as f32)
+    }
+}
+
+/// Parse the transformation of a transformed paint.
+///
+/// Formats come in pairs of a paint and its variable version,
+/// of which the variations are not applied.
+fn transform(paint: &[u8]) -> Option<Matrix>
+{
+    let f2 = |offset| f2dot14(paint, offset);
+    let fw = |offset| fword(paint, offset);
+    let (matrix, center) = match paint[0] & !1 {
+        12 => {
+            let affine = offset24(paint, 4)?;
+            let fixed = |offset| Some(u32_at(affine, offset)? as i32 as f64 / 65536.0);
+            let matrix = Matrix{
+                a: fixed(0)?, b: fixed(4)?,
+                c: fixed(8)?, d: fixed(12)?,
+                tx: fixed(16)?, ty: fixed(20)?,
+            };
+            (matrix, None)
+        },
+        14 => (Matrix::from_translate(fw(4)?, fw(6)?), None),
+        16 => (Matrix::from_scale(f2(4)?, f2(6)?), None),
+        18 => (Matrix::from_scale(f2(4)?, f2(6)?), Some((fw(8)?, fw(10)?))),
+        20 => (Matrix::from_scale(f2(4)?, f2(4)?), None),
+        22 => (Matrix::from_scale(f2(4)?, f2(4)?), Some((fw(6)?, fw(8)?))),
+        24 => (Matrix::from_rotate(f2(4)? * PI), None),
+        26 => (Matrix::from_rotate(f2(4)? * PI), Some((fw(6)?, fw(8)?))),
+        28 => (skew(f2(4)?, f2(6)?), None),
+        _ => (skew(f2(4)?, f2(6)?), Some((fw(8)?, fw(10)?))),
+    };
+    match center {
+        Some((x, y)) =>
+            Some(Matrix::from_translate(x, y) * matrix * Matrix::from_translate(-x, -y)),
+        None => Some(matrix),
+    }
+}
+
+/// The matrix that skews by angles in half turns,
+/// which turn the y axis and the x axis counterclockwise.
+fn skew(x_angle: f64, y_angle: f64) -> Matrix
+{
+    let (x, y) = ((x_angle * PI).tan(), (y_angle * PI).tan());
+    Matrix{a: 1.0, b: y, c: -x, d: 1.0, tx: 0.0, ty: 0.0}
+}
+
+/// The angle of a sweep gradient in degrees,
+/// which is stored in half turns offset by half a turn.
+fn sweep_angle(paint: &[u8], offset: usize) -> Option<f64>
+{
+    Some((f2dot14(paint, offset)? + 1.0) * 180.0)
+}
+
+fn fword(data: &[u8], offset: usize) -> Option<f64>
+{
+    i16_at(data, offset).map(f64::from)
+}
+
+fn f2dot14(data: &[u8], offset: usize) -> Option<f64>
+{
+    i16_at(data, offset).map(|value| value as f64 / 16384.0)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use crate::test_font::be;
+
+    use np_graphics::path::Instruction;
+
+    /// The outline of every glyph is a square of 10 font units.
+    fn outline(_glyph_id: u16) -> Option<PathBuf>
+    {
+        let mut path = PathBuf::new();
+        path.push_move(Vector{x: 0.0, y: 0.0});
+        path.push_linear(Vector{x: 10.0, y: 0.0});
+        path.push_linear(Vector{x: 10.0, y: 10.0});
+        path.push_linear(Vector{x: 0.0, y: 10.0});
+        Some(path)
+    }
+
+    fn first_point(layer: &Layer) -> Vector
+    {
+        match layer.outline.instructions().next() {
+            Some(Instruction::Move(point)) => point,
+            _ => panic!("outline does not start with a move"),
+        }
+    }
+
+    const RED: Color = Color::new(1.0, 0.0, 0.0, 1.0);
+    const BLUE: Color = Color::new(0.0, 0.0, 1.0, 1.0);
+
+    #[test]
+    fn test_version_0()
+    {
+        // Glyph 5 is glyph 1 in red and glyph 2 in the foreground color.
+        let mut colr = be(&[0, 1, 0, 14, 0, 20, 2]);
+        colr.extend_from_slice(&be(&[5, 0, 2]));
+        colr.extend_from_slice(&be(&[1, 0, 2, FOREGROUND]));
+
+        let layers = color_layers(&colr, 5, &[RED], Color::BLACK, outline).unwrap();
+        assert_eq!(layers.len(), 2);
+        assert!(matches!(layers[0].fill, Fill::Solid(color) if color == RED));
+        assert!(matches!(layers[1].fill, Fill::Solid(color) if color == Color::BLACK));
+        assert!(color_layers(&colr, 4, &[RED], Color::BLACK, outline).is_none());
+    }
+
+    #[test]
+    fn test_version_1()
+    {
+        // Glyph 7 has two layers: glyph 1 in half transparent blue,
+        // and glyph 2 translated and filled with a linear gradient.
+        // Glyph 8 is a color glyph that contains itself.
+        let mut colr = be(&[1, 0, 0, 0, 0, 0, 0, 0, 34, 0, 62]);
+        colr.extend_from_slice(&[0; 12]);
+        colr.extend_from_slice(&be(&[0, 2, 7, 0, 16, 8, 0, 22]));
+        colr.extend_from_slice(&[1, 2, 0, 0, 0, 0]);
+        colr.extend_from_slice(&[11, 0, 8]);
+        colr.extend_from_slice(&[0; 3]);
+        colr.extend_from_slice(&be(&[0, 2, 0, 12, 0, 23]));
+        colr.extend_from_slice(&[10, 0, 0, 6, 0, 1]);
+        colr.extend_from_slice(&[2, 0, 1, 0x20, 0]);
+        colr.extend_from_slice(&[14, 0, 0, 8, 0, 100, 0, 0]);
+        colr.extend_from_slice(&[10, 0, 0, 6, 0, 2]);
+        colr.extend_from_slice(&[4, 0, 0, 16]);
+        colr.extend_from_slice(&be(&[0, 0, 10, 0, 0, 10]));
+        colr.extend_from_slice(&[0, 0, 2]);
+        colr.extend_from_slice(&be(&[0, 0, 0x4000, 0x4000, 1, 0x4000]));
+
+        let palette = [RED, BLUE];
+        let layers = color_layers(&colr, 7, &palette, Color::BLACK, outline).unwrap();
+        assert_eq!(layers.len(), 2);
+
+        let half_blue = BLUE.with_alpha(0.5);
+        assert!(matches!(layers[0].fill, Fill::Solid(color) if color == half_blue));
+        assert_eq!(first_point(&layers[0]), Vector{x: 0.0, y: 0.0});
+
+        assert_eq!(first_point(&layers[1]), Vector{x: 100.0, y: 0.0});
+        let fill = &layers[1].fill;
+        assert!(matches!(fill, Fill::Gradient(GradientFill{
+            shape: GradientShape::Linear{end: Vector{x, ..}, ..},
+            spread: Spread::Pad,
+            ..
+        }) if *x == 10.0));
+        assert_eq!(fill.color_at(Vector{x: 100.0, y: 5.0}), RED);
+        assert_eq!(fill.color_at(Vector{x: 110.0, y: 5.0}), BLUE);
+
+        assert!(color_layers(&colr, 8, &palette, Color::BLACK, outline).is_none());
+    }
+}
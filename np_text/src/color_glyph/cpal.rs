@@ -0,0 +1,77 @@
+use super::Palette;
+
+use crate::parse::u16_at;
+use crate::parse::u32_at;
+
+use np_graphics::Color;
+
+/// Read the palettes of the CPAL table.
+///
+/// Palettes whose colors are out of bounds are left out.
+pub(crate) fn palettes(cpal: &[u8]) -> Vec<Palette>
+{
+    read_palettes(cpal).unwrap_or_default()
+}
+
+fn read_palettes(cpal: &[u8]) -> Option<Vec<Palette>>
+{
+    let version = u16_at(cpal, 0)?;
+    let entry_count = u16_at(cpal, 2)? as usize;
+    let palette_count = u16_at(cpal, 4)? as usize;
+    let records = cpal.get(u32_at(cpal, 8)? as usize ..)?;
+
+    // Version 1 says which backgrounds each palette is meant for.
+    let types = match version {
+        0 => None,
+        _ => match u32_at(cpal, 12 + 2 * palette_count)? {
+            0 => None,
+            offset => cpal.get(offset as usize ..),
+        },
+    };
+
+    let palettes = (0 .. palette_count).filter_map(|index| {
+        let first = u16_at(cpal, 12 + 2 * index)? as usize;
+        let colors = (first .. first + entry_count)
+            .map(|record| {
+                let bgra = records.get(4 * record .. 4 * record + 4)?;
+                Some(Color::from_rgba8888([bgra[2], bgra[1], bgra[0], bgra[3]]))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let flags = types.and_then(|types| u32_at(types, 4 * index)).unwrap_or(0);
+        Some(Palette{
+            colors,
+            for_light_background: flags & 1 != 0,
+            for_dark_background: flags & 2 != 0,
+        })
+    });
+    Some(palettes.collect())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_palettes()
+    {
+        // Two palettes of two colors that share a color record,
+        // of which the second is meant for dark backgrounds.
+        let mut cpal = vec![0, 1, 0, 2, 0, 2, 0, 3, 0, 0, 0, 36, 0, 0, 0, 1];
+        cpal.extend_from_slice(&[0, 0, 0, 28, 0, 0, 0, 0, 0, 0, 0, 0]);
+        cpal.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 2]);
+        cpal.extend_from_slice(&[0, 0, 255, 255, 255, 0, 0, 128, 255, 255, 255, 255]);
+
+        let red = Color::from_rgba8888([255, 0, 0, 255]);
+        let blue = Color::from_rgba8888([0, 0, 255, 128]);
+        let palette = |colors, for_dark_background| {
+            Palette{colors, for_light_background: false, for_dark_background}
+        };
+        assert_eq!(palettes(&cpal), [
+            palette(vec![red, blue], false),
+            palette(vec![blue, Color::WHITE], true),
+        ]);
+        assert_eq!(palettes(&cpal[.. 44]), [palette(vec![red, blue], false)]);
+        assert_eq!(palettes(&cpal[.. 10]), []);
+    }
+}
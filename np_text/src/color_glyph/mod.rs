@@ -0,0 +1,328 @@
+//! Color glyphs made of layers, from the COLR and CPAL tables.
+//!
+//! Version 0 of the COLR table gives each color glyph
+//! as outlines of other glyphs filled with palette colors.
+//! Version 1 gives a graph of paints with gradients and transformations,
+//! which is flattened into the same kind of layers.
+
+pub(crate) use self::colr::color_layers;
+pub(crate) use self::cpal::palettes;
+
+use np_graphics::BlendMode;
+use np_graphics::Color;
+use np_graphics::Format;
+use np_graphics::Gradient;
+use np_graphics::Homography;
+use np_graphics::Matrix;
+use np_graphics::PathBuf;
+use np_graphics::PixelMapCanvas;
+use np_graphics::Vector;
+use np_graphics::path::FillRule;
+
+mod colr;
+mod cpal;
+
+/// Layer of a color glyph, which is an outline filled with a paint.
+///
+/// The outline is in font units, with y pointing up,
+/// like the outlines of other glyphs.
+#[derive(Debug)]
+pub struct Layer
+{
+    /// The outline of the layer.
+    pub outline: PathBuf,
+
+    /// What the outline is filled with.
+    pub fill: Fill,
+}
+
+impl Layer
+{
+    /// Fill the outline of the layer onto a canvas,
+    /// transformed with the given matrix.
+    ///
+    /// The layer is drawn over what lies beneath it,
+    /// with the color of each pixel given by [`Fill::color_at`].
+    pub fn draw<F>(&self, canvas: &mut PixelMapCanvas<F>, matrix: Matrix)
+        where F: Format
+    {
+        canvas.shade_path(
+            matrix,
+            self.outline.instructions(),
+            FillRule::NonZero,
+            BlendMode::SourceOver,
+            |point| self.fill.color_at(point),
+        );
+    }
+}
+
+/// What the outline of a layer is filled with.
+#[derive(Clone, Debug)]
+pub enum Fill
+{
+    /// A single color.
+    Solid(Color),
+
+    /// Colors that vary across the layer.
+    Gradient(GradientFill),
+}
+
+impl Fill
+{
+    /// The color of the fill at a point, in font units.
+    pub fn color_at(&self, point: Vector) -> Color
+    {
+        match self {
+            Self::Solid(color) => *color,
+            Self::Gradient(gradient) => gradient.color_at(point),
+        }
+    }
+}
+
+/// Fill with colors that vary across the layer.
+#[derive(Clone, Debug)]
+pub struct GradientFill
+{
+    /// Where the positions of the gradient are.
+    pub shape: GradientShape,
+
+    /// The colors at positions of the gradient,
+    /// with zero at the start of the shape and one at its end.
+    pub gradient: Gradient,
+
+    /// The colors before the start and after the end of the shape.
+    pub spread: Spread,
+
+    /// The transformation from the space of the shape to font units.
+    pub matrix: Matrix,
+}
+
+impl GradientFill
+{
+    /// The color of the fill at a point, in font units.
+    ///
+    /// Points for which the shape has no position are transparent,
+    /// such as those outside both circles of some radial gradients.
+    pub fn color_at(&self, point: Vector) -> Color
+    {
+        let point = Homography::from(self.matrix).inverse()
+            .and_then(|inverse| inverse.transform(point));
+        match point.and_then(|point| self.shape.position(point)) {
+            Some(position) => self.gradient.at(self.spread.apply(position) as f32),
+            None => Color::TRANSPARENT,
+        }
+    }
+}
+
+/// Where the positions of a gradient are.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientShape
+{
+    /// Positions along the line from `start` to `end`,
+    /// which are the same on lines perpendicular to it.
+    Linear
+    {
+        /// The point at position zero.
+        start: Vector,
+
+        /// The point at position one.
+        end: Vector,
+    },
+
+    /// Positions on the circles between two circles,
+    /// with the centers and radii changing linearly between them.
+    ///
+    /// Where circles overlap, the one with the greater position is used.
+    Radial
+    {
+        /// The center of the circle at position zero.
+        start_center: Vector,
+
+        /// The radius of the circle at position zero.
+        start_radius: f64,
+
+        /// The center of the circle at position one.
+        end_center: Vector,
+
+        /// The radius of the circle at position one.
+        end_radius: f64,
+    },
+
+    /// Positions around a center, by angle.
+    ///
+    /// Angles are in degrees, counterclockwise from the positive x axis.
+    Sweep
+    {
+        /// The point that the positions are around.
+        center: Vector,
+
+        /// The angle at position zero.
+        start_angle: f64,
+
+        /// The angle at position one.
+        end_angle: f64,
+    },
+}
+
+impl GradientShape
+{
+    /// The position of the gradient at a point in the space of the shape.
+    fn position(&self, point: Vector) -> Option<f64>
+    {
+        match *self {
+            Self::Linear{start, end} => {
+                let direction = end - start;
+                let length = direction.dot(direction);
+                if length == 0.0 {
+                    return None;
+                }
+                Some((point - start).dot(direction) / length)
+            },
+
+            Self::Radial{start_center, start_radius, end_center, end_radius} => {
+                // Solve |point - center(t)| = radius(t) for the greatest t
+                // with a radius that is not negative.
+                let center = end_center - start_center;
+                let radius = end_radius - start_radius;
+                let point = point - start_center;
+                let a = center.dot(center) - radius * radius;
+                let b = point.dot(center) + start_radius * radius;
+                let c = point.dot(point) - start_radius * start_radius;
+                let valid = |t: f64| start_radius + t * radius >= 0.0;
+                if a.abs() < 1e-9 {
+                    let t = c / (2.0 * b);
+                    return Some(t).filter(|&t| t.is_finite() && valid(t));
+                }
+                let discriminant = b * b - a * c;
+                if discriminant < 0.0 {
+                    return None;
+                }
+                let (t0, t1) = ((b + discriminant.sqrt()) / a, (b - discriminant.sqrt()) / a);
+                [t0.max(t1), t0.min(t1)].iter().copied().find(|&t| valid(t))
+            },
+
+            Self::Sweep{center, start_angle, end_angle} => {
+                let Vector{x, y} = point - center;
+                let angle = y.atan2(x).to_degrees().rem_euclid(360.0);
+                if start_angle == end_angle {
+                    return Some(if angle < start_angle { 0.0 } else { 1.0 });
+                }
+                Some((angle - start_angle) / (end_angle - start_angle))
+            },
+        }
+    }
+}
+
+/// The colors of a gradient before its start and after its end.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Spread
+{
+    /// The colors at the start and end continue.
+    Pad,
+
+    /// The gradient repeats.
+    Repeat,
+
+    /// The gradient repeats, reversed every other time.
+    Reflect,
+}
+
+impl Spread
+{
+    fn apply(self, position: f64) -> f64
+    {
+        match self {
+            Self::Pad => position,
+            Self::Repeat => position - position.floor(),
+            Self::Reflect => {
+                let position = position.rem_euclid(2.0);
+                if position > 1.0 { 2.0 - position } else { position }
+            },
+        }
+    }
+}
+
+/// Palette of colors for color glyphs.
+///
+/// Typefaces with color glyphs have at least one palette,
+/// and may have others, for example for dark backgrounds.
+/// The colors are referred to by their index in the palette,
+/// so palettes of a typeface have the same number of colors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette
+{
+    /// The colors of the palette.
+    pub colors: Vec<Color>,
+
+    /// Whether the palette is meant for light backgrounds.
+    pub for_light_background: bool,
+
+    /// Whether the palette is meant for dark backgrounds.
+    pub for_dark_background: bool,
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use np_graphics::ColorSpace;
+
+    #[test]
+    fn test_gradient_positions()
+    {
+        let linear = GradientShape::Linear{
+            start: Vector{x: 0.0, y: 0.0},
+            end: Vector{x: 10.0, y: 0.0},
+        };
+        assert_eq!(linear.position(Vector{x: 5.0, y: 7.0}), Some(0.5));
+
+        let radial = GradientShape::Radial{
+            start_center: Vector{x: 0.0, y: 0.0},
+            start_radius: 0.0,
+            end_center: Vector{x: 0.0, y: 0.0},
+            end_radius: 10.0,
+        };
+        assert_eq!(radial.position(Vector{x: 0.0, y: -5.0}), Some(0.5));
+        assert_eq!(radial.position(Vector{x: 20.0, y: 0.0}), Some(2.0));
+
+        // Circles that grow to the right cover only half of the plane.
+        let cone = GradientShape::Radial{
+            start_center: Vector{x: 0.0, y: 0.0},
+            start_radius: 0.0,
+            end_center: Vector{x: 10.0, y: 0.0},
+            end_radius: 5.0,
+        };
+        assert_eq!(cone.position(Vector{x: -5.0, y: 0.0}), None);
+
+        let sweep = GradientShape::Sweep{
+            center: Vector{x: 0.0, y: 0.0},
+            start_angle: 0.0,
+            end_angle: 180.0,
+        };
+        assert_eq!(sweep.position(Vector{x: 0.0, y: 1.0}), Some(0.5));
+        assert_eq!(sweep.position(Vector{x: 0.0, y: -1.0}), Some(1.5));
+    }
+
+    #[test]
+    fn test_color_at()
+    {
+        let (black, white) = (Color::BLACK, Color::WHITE);
+        let mut fill = GradientFill{
+            shape: GradientShape::Linear{
+                start: Vector{x: 0.0, y: 0.0},
+                end: Vector{x: 1.0, y: 0.0},
+            },
+            gradient: Gradient::new(ColorSpace::Srgb, vec![(0.0, black), (1.0, white)]),
+            spread: Spread::Pad,
+            matrix: Matrix::from_scale(10.0, 10.0),
+        };
+        assert_eq!(fill.color_at(Vector{x: 10.0, y: 3.0}), white);
+        assert_eq!(fill.color_at(Vector{x: 15.0, y: 3.0}), white);
+        fill.spread = Spread::Repeat;
+        assert_eq!(fill.color_at(Vector{x: 10.0, y: 3.0}), black);
+        fill.spread = Spread::Reflect;
+        assert_eq!(fill.color_at(Vector{x: 20.0, y: 3.0}), black);
+        assert_eq!(fill.color_at(Vector{x: -10.0, y: 3.0}), white);
+    }
+}
@@ -0,0 +1,547 @@
+use crate::FontFile;
+use crate::grapheme_indices;
+use crate::name::names;
+
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Collection of typefaces found in font files.
+///
+/// The database records what each typeface is called and looks like,
+/// and which characters it has glyphs for, without keeping the files open.
+/// To use a typeface found in the database, open [`FaceInfo::path`]
+/// with [`FontFile::open_mapped`] and call [`FontFile::typeface`]
+/// with [`FaceInfo::index`].
+#[derive(Clone, Debug, Default)]
+pub struct FontDatabase
+{
+    faces: Vec<FaceInfo>,
+}
+
+/// What a typeface in a [`FontDatabase`] is called and looks like.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FaceInfo
+{
+    /// The path of the font file that contains the typeface.
+    pub path: PathBuf,
+
+    /// The index of the typeface within the font file.
+    pub index: u32,
+
+    /// The names of the family of the typeface.
+    ///
+    /// These are the typographic family names from the name table,
+    /// followed by the legacy family names, with English names first.
+    /// Legacy family names of styles other than the four basic ones
+    /// include the style, such as "DejaVu Sans Condensed".
+    pub families: Vec<String>,
+
+    /// The weight of the typeface, from 1 to 1000, where 400 is normal.
+    pub weight: u16,
+
+    /// The width of the typeface, from 1 (ultra-condensed)
+    /// to 9 (ultra-expanded), where 5 is normal.
+    pub width: u16,
+
+    /// Whether the typeface is upright or slanted.
+    pub style: FontStyle,
+
+    /// The characters the typeface has glyphs for.
+    pub coverage: Coverage,
+}
+
+/// Whether a typeface is upright or slanted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FontStyle
+{
+    /// Upright.
+    Normal,
+
+    /// Slanted, with cursive letterforms.
+    Italic,
+
+    /// Slanted, with the letterforms of the upright style.
+    Oblique,
+}
+
+/// The characters a typeface has glyphs for.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Coverage
+{
+    /// Sorted, disjoint and non-adjacent ranges of code points.
+    ranges: Vec<Range<u32>>,
+}
+
+/// What typeface to look for in a [`FontDatabase`].
+///
+/// Matching follows the font matching algorithm of CSS:
+/// the first family with any typefaces is chosen,
+/// and among its typefaces the closest width is preferred over
+/// the closest style, which is preferred over the closest weight.
+#[derive(Clone, Copy, Debug)]
+pub struct FontQuery<'a>
+{
+    /// The families to look for, in order of preference.
+    ///
+    /// Family names are compared case-insensitively.
+    pub families: &'a [&'a str],
+
+    /// The weight to look for, from 1 to 1000, where 400 is normal.
+    pub weight: u16,
+
+    /// The width to look for, from 1 to 9, where 5 is normal.
+    pub width: u16,
+
+    /// The style to look for.
+    pub style: FontStyle,
+}
+
+/// Run of text that is to be rendered with one typeface.
+///
+/// To obtain runs, call [`FontDatabase::font_runs`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontRun<'a>
+{
+    /// The byte offsets of the run in the text.
+    pub range: Range<usize>,
+
+    /// The typeface to render the run with.
+    pub face: &'a FaceInfo,
+}
+
+impl FontDatabase
+{
+    /// Create an empty database.
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// The typefaces in the database, in the order in which they were added.
+    pub fn faces(&self) -> &[FaceInfo]
+    {
+        &self.faces
+    }
+
+    /// Add the typefaces of the font files in a directory and its subdirectories.
+    ///
+    /// Files with the extensions `ttf`, `otf`, `ttc` and `otc` are read.
+    /// Subdirectories and files that cannot be read are skipped,
+    /// as are typefaces that cannot be parsed.
+    /// If the directory itself cannot be read, this function returns [`Err`].
+    pub fn scan_directory<P>(&mut self, path: P) -> io::Result<()>
+        where P: AsRef<Path>
+    {
+        let mut entries =
+            fs::read_dir(path)?
+            .collect::<io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => {
+                    let _ = self.scan_directory(&path);
+                    continue;
+                },
+                Ok(_) => (),
+                Err(_) => continue,
+            }
+            let extension = path.extension().and_then(|extension| extension.to_str());
+            let is_font_file = extension.map_or(false, |extension| {
+                ["ttf", "otf", "ttc", "otc"].iter()
+                    .any(|font| extension.eq_ignore_ascii_case(font))
+            });
+            if is_font_file {
+                let _ = self.add_file(path);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add the typefaces of a font file.
+    ///
+    /// Typefaces that cannot be parsed are skipped.
+    /// This function returns the number of typefaces that were added.
+    pub fn add_file<P>(&mut self, path: P) -> io::Result<usize>
+        where P: AsRef<Path>
+    {
+        let path = path.as_ref();
+        let font_file = FontFile::open_mapped(path)?;
+        let data = font_file.data();
+        let count = ttf_parser::fonts_in_collection(data).unwrap_or(1);
+
+        let before = self.faces.len();
+        for index in 0 .. count {
+            if let Ok(face) = ttf_parser::Face::from_slice(data, index) {
+                self.faces.push(face_info(path, index, &face));
+            }
+        }
+        Ok(self.faces.len() - before)
+    }
+
+    /// The typeface that best matches the query.
+    ///
+    /// If none of the families of the query are in the database,
+    /// this function returns [`None`].
+    pub fn query(&self, query: &FontQuery) -> Option<&FaceInfo>
+    {
+        query.families.iter()
+            .find_map(|family| self.best_match(query, |face| face.has_family(family)))
+    }
+
+    /// The typefaces to try in turn for each character.
+    ///
+    /// The chain starts with the best match of each family of the query,
+    /// followed by the best match of every other family in the database,
+    /// so that characters the requested families lack can still be rendered.
+    pub fn fallback_chain(&self, query: &FontQuery) -> Vec<&FaceInfo>
+    {
+        let mut chain: Vec<&FaceInfo> = Vec::new();
+        let mut families: Vec<&str> = Vec::new();
+        let requested = query.families.iter().copied();
+        let others = self.faces.iter().filter_map(|face| face.families.first());
+        for family in requested.chain(others.map(String::as_str)) {
+            if families.iter().any(|other| other.eq_ignore_ascii_case(family)) {
+                continue;
+            }
+            families.push(family);
+            let face = self.best_match(query, |face| face.has_family(family));
+            if let Some(face) = face {
+                if !chain.iter().any(|other| std::ptr::eq(*other, face)) {
+                    chain.push(face);
+                }
+            }
+        }
+        chain
+    }
+
+    /// Split text into runs that are each rendered with one typeface.
+    ///
+    /// Each grapheme is given the first typeface of the [fallback chain]
+    /// that covers all of its characters, apart from variation selectors
+    /// and joiners, which typefaces need not have glyphs for.
+    /// If no typeface covers a whole grapheme, the first typeface
+    /// that covers its first character is used instead,
+    /// and if no typeface covers even that, the first typeface of the chain.
+    /// If the database is empty, this function returns no runs.
+    ///
+    /// [fallback chain]: `FontDatabase::fallback_chain`
+    pub fn font_runs<'a>(&'a self, query: &FontQuery, text: &str) -> Vec<FontRun<'a>>
+    {
+        let chain = self.fallback_chain(query);
+        let primary = match chain.first() {
+            Some(primary) => *primary,
+            None => return Vec::new(),
+        };
+
+        let mut runs: Vec<FontRun> = Vec::new();
+        for (offset, grapheme) in grapheme_indices(text) {
+            let mut chars = grapheme.chars().filter(|&c| !is_default_ignorable(c));
+            let face =
+                chain.iter()
+                .find(|face| chars.clone().all(|c| face.coverage.contains(c)))
+                .or_else(|| {
+                    let first = chars.next()?;
+                    chain.iter().find(|face| face.coverage.contains(first))
+                })
+                .map_or(primary, |face| *face);
+
+            let range = offset .. offset + grapheme.len();
+            match runs.last_mut() {
+                Some(run) if std::ptr::eq(run.face, face) => run.range.end = range.end,
+                _ => runs.push(FontRun{range, face}),
+            }
+        }
+        runs
+    }
+
+    /// The typeface among those accepted by the filter that best matches
+    /// the width, style and weight of the query.
+    fn best_match<F>(&self, query: &FontQuery, filter: F) -> Option<&FaceInfo>
+        where F: Fn(&FaceInfo) -> bool
+    {
+        let mut candidates: Vec<&FaceInfo> =
+            self.faces.iter()
+            .filter(|face| filter(face))
+            .collect();
+
+        let widths = candidates.iter().map(|face| face.width);
+        let width = nearest(widths, query.width, query.width <= 5)?;
+        candidates.retain(|face| face.width == width);
+
+        let style =
+            style_preference(query.style).iter()
+            .copied()
+            .find(|&style| candidates.iter().any(|face| face.style == style))?;
+        candidates.retain(|face| face.style == style);
+
+        let weights = candidates.iter().map(|face| face.weight);
+        let weight = nearest_weight(weights, query.weight)?;
+        candidates.into_iter().find(|face| face.weight == weight)
+    }
+}
+
+impl FaceInfo
+{
+    /// Whether the typeface belongs to the family,
+    /// compared case-insensitively.
+    pub fn has_family(&self, family: &str) -> bool
+    {
+        self.families.iter().any(|other| other.eq_ignore_ascii_case(family))
+    }
+}
+
+impl Coverage
+{
+    /// The coverage of the given code points, in any order.
+    pub fn from_code_points<I>(code_points: I) -> Self
+        where I: IntoIterator<Item=u32>
+    {
+        let mut code_points: Vec<u32> = code_points.into_iter().collect();
+        code_points.sort_unstable();
+
+        let mut ranges: Vec<Range<u32>> = Vec::new();
+        for code_point in code_points {
+            match ranges.last_mut() {
+                Some(range) if range.end >= code_point =>
+                    range.end = range.end.max(code_point + 1),
+                _ => ranges.push(code_point .. code_point + 1),
+            }
+        }
+        Self{ranges}
+    }
+
+    /// Whether the typeface has a glyph for the character.
+    pub fn contains(&self, c: char) -> bool
+    {
+        let c = c as u32;
+        let index = self.ranges.partition_point(|range| range.end <= c);
+        self.ranges.get(index).map_or(false, |range| range.start <= c)
+    }
+
+    /// The number of characters the typeface has glyphs for.
+    pub fn len(&self) -> usize
+    {
+        self.ranges.iter().map(|range| range.len()).sum()
+    }
+
+    /// Whether the typeface has glyphs for no characters.
+    pub fn is_empty(&self) -> bool
+    {
+        self.ranges.is_empty()
+    }
+}
+
+impl<'a> Default for FontQuery<'a>
+{
+    fn default() -> Self
+    {
+        Self{families: &[], weight: 400, width: 5, style: FontStyle::Normal}
+    }
+}
+
+/// Read what a typeface is called and looks like.
+fn face_info(path: &Path, index: u32, face: &ttf_parser::Face) -> FaceInfo
+{
+    let style =
+        if face.is_italic() { FontStyle::Italic }
+        else if face.is_oblique() { FontStyle::Oblique }
+        else { FontStyle::Normal };
+
+    // Characters mapped to the .notdef glyph are not covered.
+    let mut code_points = Vec::new();
+    for subtable in face.character_mapping_subtables() {
+        if subtable.is_unicode() {
+            subtable.codepoints(|code_point| code_points.push(code_point));
+        }
+    }
+    let coverage = Coverage::from_code_points(
+        code_points.into_iter()
+        .filter(|&code_point| {
+            char::from_u32(code_point)
+            .and_then(|c| face.glyph_index(c))
+            .map_or(false, |glyph_id| glyph_id.0 != 0)
+        })
+    );
+
+    FaceInfo{
+        path: path.to_owned(),
+        index,
+        families: family_names(face),
+        weight: face.weight().to_number(),
+        width: face.width().to_number(),
+        style,
+        coverage,
+    }
+}
+
+/// The typographic and legacy family names from the name table.
+fn family_names(face: &ttf_parser::Face) -> Vec<String>
+{
+    use ttf_parser::name_id;
+
+    let mut families: Vec<String> = Vec::new();
+    for &name_id in &[name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY] {
+        for name in names(face, name_id) {
+            if !families.iter().any(|family| family.eq_ignore_ascii_case(&name)) {
+                families.push(name);
+            }
+        }
+    }
+    families
+}
+
+/// Whether a character is invisible and may lack a glyph,
+/// such as a variation selector or a joiner.
+fn is_default_ignorable(c: char) -> bool
+{
+    matches!(c, '\u{200C}' | '\u{200D}' | '\u{FE00}' ..= '\u{FE0F}' | '\u{E0100}' ..= '\u{E01EF}')
+}
+
+/// The order in which styles are tried when looking for a style.
+fn style_preference(style: FontStyle) -> [FontStyle; 3]
+{
+    use FontStyle::*;
+
+    match style {
+        Normal => [Normal, Oblique, Italic],
+        Italic => [Italic, Oblique, Normal],
+        Oblique => [Oblique, Italic, Normal],
+    }
+}
+
+/// The value closest to the desired one,
+/// looking first below it if `below_first` and above it otherwise.
+fn nearest<I>(values: I, desired: u16, below_first: bool) -> Option<u16>
+    where I: Iterator<Item=u16> + Clone
+{
+    let below = values.clone().filter(|&value| value <= desired).max();
+    let above = values.filter(|&value| value >= desired).min();
+    if below_first { below.or(above) } else { above.or(below) }
+}
+
+/// The weight closest to the desired one, as chosen by CSS.
+///
+/// Desired weights from 400 to 500 first look for heavier weights up to 500,
+/// then for lighter weights, and then for heavier weights above 500.
+fn nearest_weight<I>(weights: I, desired: u16) -> Option<u16>
+    where I: Iterator<Item=u16> + Clone
+{
+    if !(400 ..= 500).contains(&desired) {
+        return nearest(weights, desired, desired < 400);
+    }
+    weights.clone().filter(|&weight| weight >= desired && weight <= 500).min()
+        .or_else(|| weights.clone().filter(|&weight| weight < desired).max())
+        .or_else(|| weights.filter(|&weight| weight > 500).min())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn face(family: &str, weight: u16, width: u16, style: FontStyle, chars: &str) -> FaceInfo
+    {
+        FaceInfo{
+            path: PathBuf::from(format!("/fonts/{}-{}-{}.ttf", family, weight, width)),
+            index: 0,
+            families: vec![family.to_owned()],
+            weight,
+            width,
+            style,
+            coverage: Coverage::from_code_points(chars.chars().map(|c| c as u32)),
+        }
+    }
+
+    #[test]
+    fn test_coverage()
+    {
+        let coverage = Coverage::from_code_points(vec![5, 3, 4, 9, 4, 10]);
+        assert_eq!(coverage.ranges, [3 .. 6, 9 .. 11]);
+        assert_eq!(coverage.len(), 5);
+        let contains = |c| coverage.contains(char::from_u32(c).unwrap());
+        assert!(!contains(2) && contains(3) && contains(5) && !contains(6));
+        assert!(contains(10) && !contains(11));
+    }
+
+    #[test]
+    fn test_query()
+    {
+        use FontStyle::*;
+
+        let faces = vec![
+            face("Sans", 300, 5, Normal, ""),
+            face("Sans", 500, 5, Normal, ""),
+            face("Sans", 700, 5, Normal, ""),
+            face("Sans", 400, 5, Oblique, ""),
+            face("Sans", 400, 3, Italic, ""),
+            face("Sans", 400, 7, Normal, ""),
+            face("Serif", 400, 5, Normal, ""),
+        ];
+        let database = FontDatabase{faces};
+        let find = |families: &[&str], weight, width, style| {
+            let query = FontQuery{families, weight, width, style};
+            database.query(&query).map(|face| (face.weight, face.width, face.style))
+        };
+
+        // Weights from 400 to 500 look up to 500 first, then down.
+        assert_eq!(find(&["sans"], 400, 5, Normal), Some((500, 5, Normal)));
+        assert_eq!(find(&["Sans"], 450, 5, Normal), Some((500, 5, Normal)));
+        assert_eq!(find(&["Sans"], 550, 5, Normal), Some((700, 5, Normal)));
+        assert_eq!(find(&["Sans"], 350, 5, Normal), Some((300, 5, Normal)));
+        assert_eq!(find(&["Sans"], 200, 5, Normal), Some((300, 5, Normal)));
+
+        // Italic falls back to oblique before normal,
+        // but widths take precedence over styles.
+        assert_eq!(find(&["Sans"], 400, 5, Italic), Some((400, 5, Oblique)));
+        assert_eq!(find(&["Sans"], 400, 4, Normal), Some((400, 3, Italic)));
+        assert_eq!(find(&["Sans"], 400, 6, Normal), Some((400, 7, Normal)));
+
+        assert_eq!(find(&["Mono", "Serif"], 700, 5, Normal), Some((400, 5, Normal)));
+        assert_eq!(find(&["Mono"], 400, 5, Normal), None);
+    }
+
+    #[test]
+    fn test_font_runs()
+    {
+        use FontStyle::*;
+
+        let faces = vec![
+            face("Latin", 400, 5, Normal, "abc \u{301}"),
+            face("Greek", 400, 5, Normal, "αβγ \u{301}"),
+            face("Marks", 400, 5, Normal, "b\u{308}"),
+        ];
+        let database = FontDatabase{faces};
+        let query = FontQuery{families: &["Latin"], ..FontQuery::default()};
+        let families = |text| {
+            database.font_runs(&query, text).into_iter()
+                .map(|run| (run.range, run.face.families[0].as_str()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(families("ab αβ c"), [
+            (0 .. 3, "Latin"),
+            (3 .. 7, "Greek"),
+            (7 .. 9, "Latin"),
+        ]);
+
+        // Graphemes stay together, in the first typeface
+        // that covers them entirely or else covers their first character.
+        assert_eq!(families("ab\u{308}a\u{308}"), [
+            (0 .. 1, "Latin"),
+            (1 .. 4, "Marks"),
+            (4 .. 7, "Latin"),
+        ]);
+
+        // Variation selectors need not be covered,
+        // and characters no typeface covers use the primary typeface.
+        assert_eq!(families("α\u{FE0E}\u{4E00}"), [(0 .. 5, "Greek"), (5 .. 8, "Latin")]);
+
+        assert_eq!(FontDatabase::new().font_runs(&query, "abc"), []);
+    }
+}
@@ -58,7 +58,7 @@ impl FontFile
         Ok(Self{inner: Inner::Mmap(mmap)})
     }
 
-    fn data(&self) -> &[u8]
+    pub (crate) fn data(&self) -> &[u8]
     {
         match &self.inner {
             Inner::Vec(vec) =>
@@ -83,7 +83,6 @@ impl FontFile
     /// this function returns [`Err`].
     pub fn typeface(&self, index: u32) -> Result<Typeface>
     {
-        let inner = ttf_parser::Face::from_slice(self.data(), index)?;
-        Ok(Typeface{inner})
+        Typeface::from_slice(self.data(), index)
     }
 }
@@ -1,21 +1,49 @@
+use crate::Layer;
+
+use np_graphics::Bounds;
 use np_graphics::PathBuf;
+use np_graphics::PixelMap;
+
+use std::fmt;
 
 /// Information about a glyph in a typeface.
 ///
 /// A glyph is a visual representation of a character in a typeface.
 /// This structure contains information parsed from a typeface.
-/// To obtain a glyph, call [`Typeface::glyph`] after obtaining a typeface.
+/// To obtain a glyph, call [`Typeface::glyph`], [`Typeface::glyph_at_size`],
+/// or [`Typeface::color_glyph`] after obtaining a typeface.
+/// Measurements are in font units, with y pointing up;
+/// see [`Metrics::scale`] for converting them to pixels.
 ///
 /// [`Typeface::glyph`]: `crate::Typeface::glyph`
+/// [`Typeface::glyph_at_size`]: `crate::Typeface::glyph_at_size`
+/// [`Typeface::color_glyph`]: `crate::Typeface::color_glyph`
+/// [`Metrics::scale`]: `crate::Metrics::scale`
 #[derive(Debug)]
 pub struct Glyph
 {
     /// The horizontal advance of the glyph.
     pub advance_x: f64,
 
+    /// The vertical advance of the glyph, for vertical text.
+    ///
+    /// This is [`None`] if the typeface has no vertical metrics.
+    pub advance_y: Option<f64>,
+
+    /// The distance from the origin of the glyph
+    /// to the left edge of its bounding box.
+    pub left_side_bearing: f64,
+
+    /// The bounding box of the image of the glyph.
+    ///
+    /// This is [`Bounds::EMPTY`] for glyphs without an outline,
+    /// such as spaces.
+    pub bounds: Bounds,
+
     /// What the glyph looks like.
     ///
-    /// Whether this is a bitmap or an outline depends on the typeface;
+    /// Whether this is a bitmap or an outline depends on the typeface
+    /// and on how the glyph was obtained;
     /// this crate does not perform a conversion
     /// from one representation into the other.
     pub image: Image,
@@ -25,13 +53,63 @@ pub struct Glyph
 #[derive(Debug)]
 pub enum Image
 {
-    /// Bitmap image of a glyph.
-    ///
-    /// This is not yet implemented,
-    /// hence the use of `Infallible`.
-    Bitmap(std::convert::Infallible),
+    /// Bitmap image of a glyph,
+    /// drawn for a particular size.
+    Bitmap(Bitmap),
+
+    /// Color image of a glyph,
+    /// given by layers drawn from bottom to top.
+    Layers(Vec<Layer>),
 
     /// Vector image of a glyph,
     /// given by its outline path.
+    ///
+    /// The outline is in font units, with y pointing up.
+    /// To draw glyphs at many sizes, generate a distance field
+    /// of the outline with [`np_graphics::sdf`].
     Outline(PathBuf),
 }
+
+/// Bitmap image of a glyph, such as an emoji.
+///
+/// Bitmaps are stored in strikes, each for a number of pixels per em.
+/// Unlike outlines, their measurements are in pixels, with y pointing up;
+/// to draw a bitmap at another size, scale it by the ratio of the sizes.
+pub struct Bitmap
+{
+    /// The pixels of the bitmap in BGRA order, with straight alpha,
+    /// with the top row first.
+    ///
+    /// Monochrome and grayscale bitmaps are black,
+    /// with the coverage of each pixel in its alpha.
+    pub pixels: PixelMap<[u8; 4]>,
+
+    /// The distance from the origin of the glyph
+    /// to the left edge of the bitmap.
+    pub left: f64,
+
+    /// The distance from the origin of the glyph
+    /// to the top edge of the bitmap.
+    pub top: f64,
+
+    /// The number of pixels per em of the strike the bitmap is from.
+    pub pixels_per_em: f64,
+
+    /// Whether the bitmap has colors, as emoji do,
+    /// instead of being a mask to fill with a color.
+    pub is_color: bool,
+}
+
+impl fmt::Debug for Bitmap
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_struct("Bitmap")
+            .field("extent", &self.pixels.extent())
+            .field("left", &self.left)
+            .field("top", &self.top)
+            .field("pixels_per_em", &self.pixels_per_em)
+            .field("is_color", &self.is_color)
+            .finish()
+    }
+}
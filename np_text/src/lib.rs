@@ -2,12 +2,38 @@
 
 #![warn(missing_docs)]
 
+pub use self::bidi::*;
+pub use self::color_glyph::*;
 pub use self::error::*;
+pub use self::font_database::*;
 pub use self::font_file::*;
 pub use self::glyph::*;
+pub use self::line_break::*;
+pub use self::metrics::*;
+pub use self::paragraph::*;
+pub use self::segment::*;
+pub use self::shape::*;
+pub use self::tag::*;
 pub use self::typeface::*;
+pub use self::variation::*;
 
+mod bidi;
+mod bitmap;
+mod cmap;
+mod color_glyph;
 mod error;
+mod font_database;
 mod font_file;
 mod glyph;
+mod line_break;
+mod metrics;
+mod name;
+mod paragraph;
+mod parse;
+mod segment;
+mod shape;
+mod tag;
+#[cfg(test)]
+mod test_font;
 mod typeface;
+mod variation;
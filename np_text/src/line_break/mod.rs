@@ -0,0 +1,423 @@
+//! Finding line break opportunities, as described in [UAX #14].
+//!
+//! [UAX #14]: https://www.unicode.org/reports/tr14/
+
+use std::cmp::Ordering;
+use std::iter::FusedIterator;
+use std::str::CharIndices;
+
+mod tables;
+
+/// A position in text where a line may or must be broken.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LineBreak
+{
+    /// The byte offset into the text at which the next line starts.
+    pub offset: usize,
+
+    /// Whether the line must be broken here,
+    /// as after a newline and at the end of the text.
+    pub mandatory: bool,
+}
+
+/// Iterator over the line break opportunities in text.
+///
+/// To obtain this iterator, call [`line_breaks`].
+#[derive(Clone, Debug)]
+pub struct LineBreaks<'a>
+{
+    text: &'a str,
+    chars: CharIndices<'a>,
+    state: Option<State>,
+}
+
+/// Find the positions in text where lines may be broken.
+///
+/// This implements the default line breaking algorithm of UAX #14,
+/// with the tailoring of numbers in its example 7 that its test data also uses,
+/// and without the tailorings for dictionary-based scripts such as Thai,
+/// whose words are only separated where they are separated by spaces.
+/// The end of non-empty text is a mandatory break.
+pub fn line_breaks(text: &str) -> LineBreaks<'_>
+{
+    LineBreaks{text, chars: text.char_indices(), state: None}
+}
+
+impl<'a> Iterator for LineBreaks<'a>
+{
+    type Item = LineBreak;
+
+    fn next(&mut self) -> Option<LineBreak>
+    {
+        for (offset, c) in &mut self.chars {
+            let class = Class::of(c);
+            match &mut self.state {
+                None => self.state = Some(State::new(class)),
+                Some(state) => match state.advance(class) {
+                    Some(mandatory) => return Some(LineBreak{offset, mandatory}),
+                    None => continue,
+                },
+            }
+        }
+        // Once the end is reached, there is no state left
+        // so that the final break is reported once.
+        self.state.take().map(|_| LineBreak{offset: self.text.len(), mandatory: true})
+    }
+}
+
+impl<'a> FusedIterator for LineBreaks<'a>
+{
+}
+
+/// Line breaking class of a character, as resolved by rule LB1.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Class
+{
+    Bk, Cr, Lf, Cm, Nl, Wj, Zw, Gl, Sp, Zwj,
+    B2, Ba, Bb, Hy, Cb, Cl, Cp, Ex, In, Ns, Op, Qu,
+    Is, Nu, Po, Pr, Sy, Al, Eb, Em, H2, H3, Hl, Id,
+    Jl, Jv, Jt, Ri,
+}
+
+use self::Class::*;
+
+impl Class
+{
+    fn of(c: char) -> Self
+    {
+        let c = c as u32;
+        let index = tables::LINE_BREAK.binary_search_by(|&(start, end, _)| {
+            if end < c { Ordering::Less }
+            else if start > c { Ordering::Greater }
+            else { Ordering::Equal }
+        });
+        index.map_or(Al, |index| tables::LINE_BREAK[index].2)
+    }
+
+    fn is_hard_break(self) -> bool
+    {
+        matches!(self, Bk | Cr | Lf | Nl)
+    }
+}
+
+/// What the rules need to know about the text before a position.
+#[derive(Clone, Copy, Debug)]
+struct State
+{
+    /// The class of the previous character,
+    /// where combining marks take the class of their base (LB9).
+    before: Class,
+
+    /// The class of the character before that.
+    before_that: Option<Class>,
+
+    /// The class of the last character that was not a space.
+    before_spaces: Class,
+
+    /// Whether the previous character was a zero width joiner.
+    after_zwj: bool,
+
+    /// Whether the previous character is an odd regional indicator,
+    /// which pairs up with the next.
+    odd_regional_indicator: bool,
+
+    /// Whether the previous characters are a number,
+    /// a digit followed by digits and separators.
+    in_number: bool,
+
+    /// Whether the previous characters are a number followed by closing punctuation.
+    closed_number: bool,
+}
+
+impl State
+{
+    fn new(class: Class) -> Self
+    {
+        let resolved = if matches!(class, Cm | Zwj) { Al } else { class };
+        Self{
+            before: resolved,
+            before_that: None,
+            before_spaces: resolved,
+            after_zwj: class == Zwj,
+            odd_regional_indicator: class == Ri,
+            in_number: resolved == Nu,
+            closed_number: false,
+        }
+    }
+
+    /// Move past the next character.
+    ///
+    /// If there is a break opportunity before the character,
+    /// this returns whether the break is mandatory.
+    fn advance(&mut self, class: Class) -> Option<bool>
+    {
+        let before = self.before;
+
+        // Combining marks are part of the character before them (LB9).
+        if matches!(class, Cm | Zwj) && !before.is_hard_break() &&
+            !matches!(before, Sp | Zw) {
+            self.after_zwj = class == Zwj;
+            return None;
+        }
+
+        let decision = self.decide(class);
+
+        let resolved = if matches!(class, Cm | Zwj) { Al } else { class };
+        self.odd_regional_indicator =
+            resolved == Ri && !(before == Ri && self.odd_regional_indicator);
+        self.closed_number = self.in_number && matches!(resolved, Cl | Cp);
+        self.in_number = resolved == Nu || self.in_number && matches!(resolved, Sy | Is);
+        self.before_that = Some(before);
+        self.before = resolved;
+        if resolved != Sp {
+            self.before_spaces = resolved;
+        }
+        self.after_zwj = class == Zwj;
+
+        match decision {
+            Decision::Mandatory => Some(true),
+            Decision::Allowed => Some(false),
+            Decision::Prohibited => None,
+        }
+    }
+
+    /// Apply the rules to the position before a character.
+    fn decide(&self, class: Class) -> Decision
+    {
+        use self::Decision::*;
+
+        let before = self.before;
+        let before_spaces = self.before_spaces;
+
+        // Hard line breaks (LB4, LB5, LB6).
+        match (before, class) {
+            (Cr, Lf) => return Prohibited,
+            (Bk, _) | (Cr, _) | (Lf, _) | (Nl, _) => return Mandatory,
+            (_, Bk) | (_, Cr) | (_, Lf) | (_, Nl) => return Prohibited,
+            _ => (),
+        }
+
+        // Spaces and zero width characters (LB7, LB8, LB8a).
+        if matches!(class, Sp | Zw) {
+            return Prohibited;
+        }
+        if before_spaces == Zw && matches!(before, Sp | Zw) {
+            return Allowed;
+        }
+        if self.after_zwj {
+            return Prohibited;
+        }
+
+        // Remaining combining marks are alphabetic (LB10).
+        let class = if matches!(class, Cm | Zwj) { Al } else { class };
+
+        // Word joiners and non-breaking characters (LB11, LB12, LB12a).
+        if class == Wj || before == Wj || before == Gl {
+            return Prohibited;
+        }
+        if class == Gl && !matches!(before, Sp | Ba | Hy) {
+            return Prohibited;
+        }
+
+        // Closing and opening punctuation, possibly with spaces in between
+        // (LB13, LB14, LB15, LB16, LB17).
+        if matches!(class, Cl | Cp | Ex | Is | Sy) {
+            return Prohibited;
+        }
+        if before_spaces == Op && matches!(before, Sp | Op) {
+            return Prohibited;
+        }
+        let after_spaces = |classes: &[Class]| {
+            classes.contains(&before_spaces) && (before == Sp || before == before_spaces)
+        };
+        if class == Op && after_spaces(&[Qu]) ||
+            class == Ns && after_spaces(&[Cl, Cp]) ||
+            class == B2 && after_spaces(&[B2]) {
+            return Prohibited;
+        }
+
+        // Spaces (LB18).
+        if before == Sp {
+            return Allowed;
+        }
+
+        // Quotation marks and contingent breaks (LB19, LB20).
+        if class == Qu || before == Qu {
+            return Prohibited;
+        }
+        if class == Cb || before == Cb {
+            return Allowed;
+        }
+
+        // Hyphens and other non-starters (LB21, LB21a, LB21b, LB22).
+        if matches!(class, Ba | Hy | Ns) || before == Bb {
+            return Prohibited;
+        }
+        if self.before_that == Some(Hl) && matches!(before, Hy | Ba) {
+            return Prohibited;
+        }
+        if before == Sy && class == Hl || class == In {
+            return Prohibited;
+        }
+
+        // Numbers, with the tailoring of LB25 in example 7 of UAX #14.
+        // Prefixes and postfixes stay with any opening punctuation after them,
+        // not only with that of a number, as the character after it is not known yet.
+        if self.in_number && matches!(class, Nu | Sy | Is | Cl | Cp | Pr | Po) ||
+            self.closed_number && matches!(class, Pr | Po) {
+            return Prohibited;
+        }
+
+        // Letters, prefixes, and Korean syllables (LB23 to LB29).
+        // The exception for East Asian wide opening punctuation in LB30
+        // is not made, as it rarely follows letters directly.
+        let prohibited = matches!((before, class),
+            (Al, Nu) | (Hl, Nu) | (Nu, Al) | (Nu, Hl) |
+            (Pr, Id) | (Pr, Eb) | (Pr, Em) | (Id, Po) | (Eb, Po) | (Em, Po) |
+            (Pr, Al) | (Pr, Hl) | (Po, Al) | (Po, Hl) |
+            (Al, Pr) | (Al, Po) | (Hl, Pr) | (Hl, Po) |
+            (Po, Op) | (Po, Nu) | (Pr, Op) | (Pr, Nu) | (Hy, Nu) |
+            (Jl, Jl) | (Jl, Jv) | (Jl, H2) | (Jl, H3) |
+            (Jv, Jv) | (Jv, Jt) | (H2, Jv) | (H2, Jt) |
+            (Jt, Jt) | (H3, Jt) |
+            (Jl, Po) | (Jv, Po) | (Jt, Po) | (H2, Po) | (H3, Po) |
+            (Pr, Jl) | (Pr, Jv) | (Pr, Jt) | (Pr, H2) | (Pr, H3) |
+            (Al, Al) | (Al, Hl) | (Hl, Al) | (Hl, Hl) |
+            (Is, Al) | (Is, Hl) |
+            (Al, Op) | (Hl, Op) | (Nu, Op) | (Cp, Al) | (Cp, Hl) | (Cp, Nu)
+        );
+        if prohibited {
+            return Prohibited;
+        }
+
+        // Flags and emoji modifiers (LB30a, LB30b).
+        if before == Ri && class == Ri && self.odd_regional_indicator {
+            return Prohibited;
+        }
+        if before == Eb && class == Em {
+            return Prohibited;
+        }
+
+        // Everywhere else (LB31).
+        Allowed
+    }
+}
+
+/// Whether the rules allow a break before a character.
+#[derive(Clone, Copy, Debug)]
+enum Decision
+{
+    Mandatory,
+    Allowed,
+    Prohibited,
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn breaks(text: &str) -> Vec<(usize, bool)>
+    {
+        line_breaks(text).map(|b| (b.offset, b.mandatory)).collect()
+    }
+
+    #[test]
+    fn test_classes()
+    {
+        assert_eq!(Class::of('a'), Al);
+        assert_eq!(Class::of(' '), Sp);
+        assert_eq!(Class::of('\n'), Lf);
+        assert_eq!(Class::of('\u{301}'), Cm);
+        assert_eq!(Class::of('('), Op);
+        assert_eq!(Class::of(')'), Cp);
+        assert_eq!(Class::of('7'), Nu);
+        assert_eq!(Class::of('$'), Pr);
+        assert_eq!(Class::of('\u{5D0}'), Hl);
+        assert_eq!(Class::of('\u{4E00}'), Id);
+        assert_eq!(Class::of('\u{3041}'), Ns);
+        assert_eq!(Class::of('\u{AC00}'), H2);
+        assert_eq!(Class::of('\u{AC01}'), H3);
+        assert_eq!(Class::of('\u{1F1E6}'), Ri);
+        assert_eq!(Class::of('\u{10FFFF}'), Al);
+    }
+
+    #[test]
+    fn test_line_breaks()
+    {
+        assert_eq!(breaks(""), []);
+        assert_eq!(breaks("Hallo"), [(5, true)]);
+        assert_eq!(breaks("Hallo wereld"), [(6, false), (12, true)]);
+        assert_eq!(breaks("a  b\r\nc\n"), [(3, false), (6, true), (8, true)]);
+
+        // Punctuation stays with the word before or after it.
+        assert_eq!(breaks("(\"hi\"), you!"), [(8, false), (12, true)]);
+        assert_eq!(breaks("well-known"), [(5, false), (10, true)]);
+        assert_eq!(breaks("$3.50 (ok)"), [(6, false), (10, true)]);
+        assert_eq!(breaks("(12)%"), [(5, true)]);
+        assert_eq!(breaks("(a)%"), [(3, false), (4, true)]);
+
+        // Combining marks and non-breaking spaces do not give opportunities.
+        assert_eq!(breaks("e\u{301} f"), [(4, false), (5, true)]);
+        assert_eq!(breaks("10\u{A0}km"), [(6, true)]);
+
+        // Ideographs can be broken between, but not before small kana.
+        assert_eq!(breaks("\u{65E5}\u{672C}\u{3063}"), [(3, false), (9, true)]);
+
+        // Flags are pairs of regional indicators.
+        let flags = "\u{1F1F3}\u{1F1F1}\u{1F1EA}\u{1F1FA}";
+        assert_eq!(breaks(flags), [(8, false), (16, true)]);
+    }
+
+    /// Run a line of LineBreakTest.txt, with its comment.
+    ///
+    /// The test data is that of Unicode 10.0, older than the tables, so the breaks are updated
+    /// for rules LB8a and LB22, which Unicode 11.0 extended
+    /// to characters after a zero width joiner and before inseparables of any class.
+    /// The comment gives the class of each character and the rule for each position.
+    fn check(case: &str, comment: &str)
+    {
+        let tokens: Vec<&str> = comment.split_whitespace().collect();
+        let classes: Vec<&str> = tokens.iter()
+            .filter(|token| token.starts_with('(') && token.ends_with(')'))
+            .cloned()
+            .collect();
+        let rules: Vec<&str> =
+            tokens.iter().filter(|token| token.starts_with('[')).cloned().collect();
+
+        let mut text = String::new();
+        let mut expected = Vec::new();
+        for (index, token) in case.split_whitespace().enumerate() {
+            let position = index / 2;
+            let inside = position > 0 && position < classes.len();
+            let extended = inside && (
+                classes[position - 1].starts_with("(ZWJ") ||
+                rules[position] == "[999.0]" && classes[position] == "(IN)"
+            );
+            match token {
+                "÷" if position == 0 || extended => (),
+                "÷" => expected.push(text.len()),
+                "×" => (),
+                _ => {
+                    let c = u32::from_str_radix(token, 16).ok().and_then(std::char::from_u32);
+                    text.push(c.expect("code point"));
+                },
+            }
+        }
+        let offsets: Vec<usize> = line_breaks(&text).map(|b| b.offset).collect();
+        assert_eq!(offsets, expected, "{} # {}", case, comment);
+    }
+
+    #[test]
+    fn test_line_break_test()
+    {
+        let data = include_str!("../../tests/data/LineBreakTest.txt");
+        let cases = data.lines().filter_map(|line| line.split_once('#'));
+        let mut count = 0;
+        for (case, comment) in cases.filter(|(case, _)| !case.is_empty()) {
+            check(case, comment);
+            count += 1;
+        }
+        assert_eq!(count, 7282);
+    }
+}
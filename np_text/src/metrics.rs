@@ -0,0 +1,223 @@
+/// Measurements of a typeface as a whole.
+///
+/// To obtain metrics, call [`Typeface::metrics`].
+/// All measurements except [`Metrics::italic_angle`] are in font units,
+/// with y pointing up and the baseline at zero;
+/// use [`Metrics::scale`] to convert them to pixels.
+///
+/// Typefaces give the ascender, descender and line gap both in the hhea table
+/// and in the OS/2 table, which has typographic and Windows variants of them.
+/// These are not exposed separately; instead, one set is chosen
+/// the way most text layout does, so that lines are spaced as elsewhere.
+///
+/// [`Typeface::metrics`]: `crate::Typeface::metrics`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Metrics
+{
+    /// The number of font units per em.
+    ///
+    /// If the typeface specifies an invalid value,
+    /// this is 1000, which is common for PostScript outlines.
+    pub units_per_em: f64,
+
+    /// The distance from the baseline to the top of a line.
+    ///
+    /// If the typeface has an OS/2 table, this is its typographic ascender
+    /// if the typeface sets the `USE_TYPO_METRICS` flag,
+    /// and its Windows ascender otherwise.
+    /// Without an OS/2 table, this is the ascender of the hhea table.
+    pub ascender: f64,
+
+    /// The distance from the baseline to the bottom of a line,
+    /// which is usually negative.
+    ///
+    /// This is taken from the same table as [`Metrics::ascender`].
+    pub descender: f64,
+
+    /// The additional distance between consecutive lines.
+    ///
+    /// This is the typographic line gap of the OS/2 table
+    /// if the typeface sets the `USE_TYPO_METRICS` flag,
+    /// and the line gap of the hhea table otherwise,
+    /// since the OS/2 table has no Windows line gap.
+    pub line_gap: f64,
+
+    /// The height of lowercase letters such as “x”, if specified.
+    pub x_height: Option<f64>,
+
+    /// The height of capital letters such as “H”, if specified.
+    pub cap_height: Option<f64>,
+
+    /// Where to draw underlines, if specified.
+    pub underline: Option<LineMetrics>,
+
+    /// Where to draw strikeouts, if specified.
+    pub strikeout: Option<LineMetrics>,
+
+    /// The angle of upright strokes, in degrees counterclockwise from vertical.
+    ///
+    /// This is negative for typefaces that lean to the right,
+    /// and zero if the typeface does not specify it.
+    pub italic_angle: f64,
+}
+
+/// Position and thickness of underlines and strikeouts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineMetrics
+{
+    /// The distance from the baseline to the top of the line.
+    pub position: f64,
+
+    /// The thickness of the line.
+    pub thickness: f64,
+}
+
+impl Metrics
+{
+    /// The factor that converts font units to pixels,
+    /// for text of the given size.
+    ///
+    /// Multiply the measurements of the typeface
+    /// and those of its glyphs by this factor.
+    pub fn scale(&self, pixels_per_em: f64) -> f64
+    {
+        pixels_per_em / self.units_per_em
+    }
+
+    /// The distance between the baselines of consecutive lines.
+    pub fn line_height(&self) -> f64
+    {
+        self.ascender - self.descender + self.line_gap
+    }
+}
+
+impl From<ttf_parser::LineMetrics> for LineMetrics
+{
+    fn from(other: ttf_parser::LineMetrics) -> Self
+    {
+        Self{position: other.position as f64, thickness: other.thickness as f64}
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use crate::test_font::be;
+    use crate::test_font::font_file;
+    use crate::test_font::typeface;
+
+    use np_graphics::Bounds;
+    use np_graphics::Vector;
+
+    /// An hhea table with the given ascender, descender and line gap,
+    /// for two glyphs.
+    fn hhea(ascender: i16, descender: i16, line_gap: i16) -> Vec<u8>
+    {
+        let mut hhea = be(&[1, 0, ascender as u16, descender as u16, line_gap as u16]);
+        hhea.resize(34, 0);
+        hhea.extend(be(&[2]));
+        hhea
+    }
+
+    /// An OS/2 table of version 4 whose typographic metrics
+    /// differ from its Windows metrics.
+    fn os2(use_typo_metrics: bool) -> Vec<u8>
+    {
+        let mut os2 = be(&[4]);
+        os2.resize(26, 0);
+        os2.extend(be(&[50, 300]));
+        os2.resize(62, 0);
+        os2.extend(be(&[if use_typo_metrics { 0x80 } else { 0 }, 0, 0]));
+        os2.extend(be(&[750, -250i16 as u16, 100, 900, 300, 0, 0, 0, 0, 500, 700, 0, 0, 0]));
+        os2
+    }
+
+    #[test]
+    fn test_metrics()
+    {
+        // Version 3 of the post table, with an italic angle of -12.5°.
+        let mut post = be(&[3, 0, 0xFFF3, 0x8000, -100i16 as u16, 50]);
+        post.resize(32, 0);
+
+        let data = font_file(&[50, 50], &[
+            (b"OS/2", os2(true)),
+            (b"hhea", hhea(800, -200, 20)),
+            (b"post", post),
+        ]);
+        let metrics = typeface(&data).metrics();
+        assert_eq!(metrics, Metrics{
+            units_per_em: 1000.0,
+            ascender: 750.0,
+            descender: -250.0,
+            line_gap: 100.0,
+            x_height: Some(500.0),
+            cap_height: Some(700.0),
+            underline: Some(LineMetrics{position: -100.0, thickness: 50.0}),
+            strikeout: Some(LineMetrics{position: 300.0, thickness: 50.0}),
+            italic_angle: -12.5,
+        });
+        assert_eq!(metrics.scale(20.0), 0.02);
+        assert_eq!(metrics.line_height(), 1100.0);
+
+        // Without USE_TYPO_METRICS, the Windows metrics are used,
+        // with the line gap of the hhea table.
+        let data = font_file(&[50, 50], &[(b"OS/2", os2(false)), (b"hhea", hhea(800, -200, 20))]);
+        let metrics = typeface(&data).metrics();
+        assert_eq!((metrics.ascender, metrics.descender, metrics.line_gap), (900.0, -300.0, 20.0));
+
+        // Without an OS/2 table, the hhea metrics are used.
+        let data = font_file(&[50, 50], &[(b"hhea", hhea(800, -200, 20))]);
+        assert_eq!(typeface(&data).metrics(), Metrics{
+            units_per_em: 1000.0,
+            ascender: 800.0,
+            descender: -200.0,
+            line_gap: 20.0,
+            x_height: None,
+            cap_height: None,
+            underline: None,
+            strikeout: None,
+            italic_angle: 0.0,
+        });
+    }
+
+    #[test]
+    fn test_glyph_metrics()
+    {
+        // Typefaces without vertical metrics have no vertical advances.
+        let data = font_file(&[50], &[]);
+        assert_eq!(typeface(&data).glyph(0).unwrap().advance_y, None);
+
+        // Glyph 1 is a square of 50 units, 10 units right of its origin.
+        let mut glyf = be(&[1, 10, 0, 60, 50, 3, 0]);
+        glyf.extend_from_slice(&[1, 1, 1, 1]);
+        glyf.extend(be(&[10, 50, 0, -50i16 as u16, 0, 0, 50, 0]));
+        let loca = be(&[0, 0, 17]);
+        let hmtx = be(&[50, 0, 70, 10]);
+        let mut vhea = be(&[1, 0x1000, 500, -500i16 as u16]);
+        vhea.resize(34, 0);
+        vhea.extend(be(&[2]));
+        let vmtx = be(&[1000, 0, 1200, 100]);
+
+        let data = font_file(&[50, 70], &[
+            (b"glyf", glyf),
+            (b"hmtx", hmtx),
+            (b"loca", loca),
+            (b"vhea", vhea),
+            (b"vmtx", vmtx),
+        ]);
+        let typeface = typeface(&data);
+        let glyph = typeface.glyph(1).unwrap();
+        assert_eq!(glyph.advance_x, 70.0);
+        assert_eq!(glyph.advance_y, Some(1200.0));
+        assert_eq!(glyph.left_side_bearing, 10.0);
+        let bounds = Bounds{min: Vector{x: 10.0, y: 0.0}, max: Vector{x: 60.0, y: 50.0}};
+        assert_eq!(glyph.bounds, bounds);
+
+        // Glyph 0 has no outline.
+        let glyph = typeface.glyph(0).unwrap();
+        assert_eq!((glyph.advance_x, glyph.advance_y), (50.0, Some(1000.0)));
+        assert_eq!((glyph.left_side_bearing, glyph.bounds), (0.0, Bounds::EMPTY));
+    }
+}
@@ -0,0 +1,54 @@
+//! Reading strings from the name table.
+
+use ttf_parser::Face;
+use ttf_parser::Name;
+use ttf_parser::PlatformId;
+
+/// The strings of the name table with the given name identifier,
+/// with English strings first.
+///
+/// Strings in encodings other than UTF-16, and ASCII on the Macintosh platform,
+/// are left out, as are duplicates and empty strings.
+pub(crate) fn names(face: &Face, name_id: u16) -> Vec<String>
+{
+    let mut names: Vec<(bool, String)> =
+        face.names()
+        .filter(|name| name.name_id() == name_id)
+        .filter_map(|name| Some((!is_english(&name), decode_name(&name)?)))
+        .collect();
+    names.sort_by_key(|&(not_english, _)| not_english);
+
+    let mut result: Vec<String> = Vec::new();
+    for (_, name) in names {
+        if !name.is_empty() && !result.contains(&name) {
+            result.push(name);
+        }
+    }
+    result
+}
+
+/// Whether the name is in American English,
+/// or in English on the Macintosh platform.
+fn is_english(name: &Name) -> bool
+{
+    match name.platform_id() {
+        PlatformId::Windows => name.language_id() == 0x0409,
+        PlatformId::Macintosh => name.language_id() == 0,
+        _ => false,
+    }
+}
+
+/// Decode a name that is in UTF-16, or in ASCII on the Macintosh platform.
+fn decode_name(name: &Name) -> Option<String>
+{
+    let bytes = name.name();
+    if name.is_unicode() {
+        let units = bytes.chunks_exact(2).map(|unit| u16::from_be_bytes([unit[0], unit[1]]));
+        char::decode_utf16(units).collect::<Result<String, _>>().ok()
+    } else if name.platform_id() == PlatformId::Macintosh && name.encoding_id() == 0 {
+        // Mac Roman agrees with ASCII on the first half of its characters.
+        bytes.iter().map(|&byte| Some(byte as char).filter(char::is_ascii)).collect()
+    } else {
+        None
+    }
+}
@@ -0,0 +1,769 @@
+use crate::BidiInfo;
+use crate::Direction;
+use crate::Image;
+use crate::Metrics;
+use crate::ShapedGlyph;
+use crate::ShapingOptions;
+use crate::Typeface;
+use crate::line_breaks;
+use crate::reorder_visual;
+
+use np_graphics::BlendMode;
+use np_graphics::Bounds;
+use np_graphics::Canvas;
+use np_graphics::Color;
+use np_graphics::Format;
+use np_graphics::Matrix;
+use np_graphics::Paint;
+use np_graphics::PixelMapCanvas;
+use np_graphics::Vector;
+use np_graphics::path::FillRule;
+
+use std::ops::Range;
+
+/// How lines are placed horizontally within a paragraph.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Alignment
+{
+    /// Lines start at the start edge, which is the left edge
+    /// for left-to-right paragraphs and the right edge for right-to-left paragraphs.
+    Start,
+
+    /// Lines end at the end edge, opposite the start edge.
+    End,
+
+    /// Lines are centered between the edges.
+    Center,
+
+    /// Lines are stretched to fill the width by widening their spaces,
+    /// except for the last line of each paragraph, which starts at the start edge.
+    Justify,
+}
+
+/// How to lay out a paragraph.
+#[derive(Clone, Copy, Debug)]
+pub struct ParagraphStyle<'a>
+{
+    /// The size of the text, in pixels per em.
+    pub size: f64,
+
+    /// The width at which lines are wrapped, in pixels.
+    ///
+    /// If this is infinite, lines are only broken at newlines,
+    /// and they are aligned within the widest line.
+    pub max_width: f64,
+
+    /// How lines are placed horizontally.
+    pub alignment: Alignment,
+
+    /// The distance between the baselines of consecutive lines,
+    /// as a multiple of the line height of the typeface.
+    pub line_height: f64,
+
+    /// The maximum number of lines; text after the last line is left out.
+    pub max_lines: Option<usize>,
+
+    /// The direction of the paragraphs.
+    ///
+    /// If [`None`], each paragraph takes the direction of its first letter,
+    /// as determined by [`BidiInfo::new`].
+    pub direction: Option<Direction>,
+
+    /// How the text is shaped.
+    ///
+    /// The direction of the shaping options is ignored,
+    /// as each run of text is shaped in its own direction.
+    pub shaping: ShapingOptions<'a>,
+}
+
+impl<'a> Default for ParagraphStyle<'a>
+{
+    fn default() -> Self
+    {
+        Self{
+            size: 16.0,
+            max_width: f64::INFINITY,
+            alignment: Alignment::Start,
+            line_height: 1.0,
+            max_lines: None,
+            direction: None,
+            shaping: ShapingOptions::default(),
+        }
+    }
+}
+
+/// Text laid out in lines.
+///
+/// To lay out text, call [`Paragraph::layout`].
+/// Measurements are in pixels, with y pointing down
+/// and the top of the first line at zero.
+#[derive(Clone, Debug)]
+pub struct Paragraph
+{
+    /// The lines, from top to bottom.
+    pub lines: Vec<Line>,
+
+    /// The glyphs of all lines, in the order of the lines,
+    /// and from left to right within each line.
+    pub glyphs: Vec<PositionedGlyph>,
+
+    /// The union of the bounds of the lines.
+    pub bounds: Bounds,
+
+    /// Whether text was left out because of [`ParagraphStyle::max_lines`].
+    pub truncated: bool,
+
+    /// The factor that converts font units to pixels.
+    pub scale: f64,
+}
+
+/// A line of a paragraph.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Line
+{
+    /// The range of bytes of the text on the line,
+    /// including trailing spaces and newlines.
+    pub text: Range<usize>,
+
+    /// The range of [`Paragraph::glyphs`] on the line.
+    pub glyphs: Range<usize>,
+
+    /// The box of the line, which is as high as the line height
+    /// and as wide as the text on it without trailing spaces.
+    pub bounds: Bounds,
+
+    /// The vertical position of the baseline.
+    pub baseline: f64,
+
+    /// The direction of the paragraph the line is part of.
+    pub direction: Direction,
+
+    /// For each character on the line, in the order of the text,
+    /// the index of the character from left to right.
+    ///
+    /// This is what moving a cursor through the text visually is based on.
+    pub logical_to_visual: Vec<usize>,
+
+    /// For each character on the line, from left to right,
+    /// the index of the character in the order of the text.
+    pub visual_to_logical: Vec<usize>,
+}
+
+/// A glyph placed within a paragraph.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PositionedGlyph
+{
+    /// The glyph identifier.
+    pub glyph_id: u16,
+
+    /// The byte offset into the text of the first character
+    /// the glyph was made from.
+    pub cluster: usize,
+
+    /// Where the origin of the glyph is placed, in pixels.
+    pub position: Vector,
+}
+
+impl Paragraph
+{
+    /// Lay out text with the given typeface.
+    ///
+    /// Lines are broken at newlines, and wrapped at the break opportunities
+    /// found by [`line_breaks`] so that they fit [`ParagraphStyle::max_width`].
+    /// Words that are too wide to fit on a line of their own overflow it.
+    ///
+    /// Text with both directions is split into runs of either direction,
+    /// which are shaped separately and put in visual order on each line,
+    /// as described in [UAX #9](https://www.unicode.org/reports/tr9/).
+    pub fn layout(typeface: &Typeface, text: &str, style: &ParagraphStyle) -> Self
+    {
+        let metrics = typeface.metrics();
+        layout_with(text, style, &metrics, |run, direction| {
+            typeface.shape(run, &ShapingOptions{direction, ..style.shaping})
+        })
+    }
+
+    /// Draw the outlines of the glyphs onto a canvas.
+    ///
+    /// The glyphs are looked up in the typeface the paragraph was laid out with,
+    /// and transformed with the given matrix after being placed.
+    /// Glyphs without an outline, such as bitmap glyphs, are skipped.
+    /// To draw color glyphs in color, use [`Paragraph::draw_color`].
+    pub fn draw<C>(
+        &self,
+        canvas: &mut C,
+        typeface: &Typeface,
+        matrix: Matrix,
+        paint: Paint<C::Pixel>,
+    ) where C: Canvas
+    {
+        for positioned in &self.glyphs {
+            let glyph = match typeface.glyph(positioned.glyph_id) {
+                Some(glyph) => glyph,
+                None => continue,
+            };
+            let outline = match &glyph.image {
+                Image::Outline(outline) => outline,
+                Image::Bitmap(_) | Image::Layers(_) => continue,
+            };
+            canvas.path(matrix * self.glyph_matrix(positioned), outline.instructions(), paint);
+        }
+    }
+
+    /// Fill the glyphs onto a pixel map canvas, drawing color glyphs in color.
+    ///
+    /// Like with [`Paragraph::draw`], the glyphs are looked up in the typeface
+    /// and transformed with the given matrix after being placed.
+    /// Color glyphs, such as emoji, are drawn with their layers
+    /// from [`Typeface::color_glyph`] with the given palette.
+    /// Other glyphs with an outline are filled with the foreground color.
+    pub fn draw_color<F>(
+        &self,
+        canvas: &mut PixelMapCanvas<F>,
+        typeface: &Typeface,
+        matrix: Matrix,
+        palette: &[Color],
+        foreground: Color,
+    ) where F: Format
+    {
+        for positioned in &self.glyphs {
+            let glyph_id = positioned.glyph_id;
+            let glyph =
+                typeface.color_glyph(glyph_id, palette, foreground)
+                .or_else(|| typeface.glyph(glyph_id));
+            let glyph = match glyph {
+                Some(glyph) => glyph,
+                None => continue,
+            };
+            let matrix = matrix * self.glyph_matrix(positioned);
+            match &glyph.image {
+                Image::Layers(layers) => {
+                    for layer in layers {
+                        layer.draw(canvas, matrix);
+                    }
+                },
+                Image::Outline(outline) =>
+                    canvas.shade_path(
+                        matrix,
+                        outline.instructions(),
+                        FillRule::NonZero,
+                        BlendMode::SourceOver,
+                        |_| foreground,
+                    ),
+                Image::Bitmap(_) =>
+                    (),
+            }
+        }
+    }
+
+    /// The transformation from the font units of a glyph to the paragraph.
+    fn glyph_matrix(&self, glyph: &PositionedGlyph) -> Matrix
+    {
+        let Vector{x, y} = glyph.position;
+        Matrix::from_translate(x, y) * Matrix::from_scale(self.scale, -self.scale)
+    }
+}
+
+/// Lay out text, shaping each run of text of the same level with `shape`.
+///
+/// Like [`Typeface::shape`], `shape` gives glyphs in visual order.
+fn layout_with<F>(text: &str, style: &ParagraphStyle, metrics: &Metrics, mut shape: F)
+    -> Paragraph
+    where F: FnMut(&str, Direction) -> Vec<ShapedGlyph>
+{
+    let scale = metrics.scale(style.size);
+    let line_height = metrics.line_height() * scale * style.line_height;
+    let content_height = (metrics.ascender - metrics.descender) * scale;
+    let mut layout = Layout{
+        style,
+        scale,
+        line_height,
+        ascent: metrics.ascender * scale + (line_height - content_height) / 2.0,
+        bidi: BidiInfo::new(text, style.direction),
+        lines: Vec::new(),
+        glyphs: Vec::new(),
+        truncated: false,
+    };
+
+    let mut start = 0;
+    let mut soft_breaks = Vec::new();
+    for line_break in line_breaks(text) {
+        if !line_break.mandatory {
+            soft_breaks.push(line_break.offset);
+            continue;
+        }
+        let end = line_break.offset;
+        let content_end = start + text[start .. end].trim_end_matches(is_newline).len();
+
+        // Shape each run, with the glyphs kept in the order of the text for now.
+        let mut glyphs = Vec::new();
+        let mut run_start = start;
+        while run_start < content_end {
+            let level = layout.bidi.levels[run_start];
+            let run_end =
+                (run_start .. content_end).find(|&i| layout.bidi.levels[i] != level)
+                .unwrap_or(content_end);
+            let mut run = shape(&text[run_start .. run_end], level.direction());
+            if level.is_rtl() {
+                run.reverse();
+            }
+            for glyph in &mut run {
+                glyph.cluster += run_start;
+            }
+            glyphs.append(&mut run);
+            run_start = run_end;
+        }
+        if !layout.wrap(text, start .. end, &soft_breaks, &glyphs) {
+            break;
+        }
+        soft_breaks.clear();
+        start = end;
+    }
+
+    layout.finish(text)
+}
+
+/// State of the layout of a paragraph.
+struct Layout<'a, 'b>
+{
+    style: &'a ParagraphStyle<'b>,
+    scale: f64,
+    line_height: f64,
+    ascent: f64,
+    bidi: BidiInfo,
+    lines: Vec<PendingLine>,
+    glyphs: Vec<PositionedGlyph>,
+    truncated: bool,
+}
+
+/// A line that has yet to be aligned.
+struct PendingLine
+{
+    line: Line,
+
+    /// The end of the text on the line without trailing spaces.
+    visible_end: usize,
+
+    /// Whether the line may be stretched when justifying.
+    justify: bool,
+}
+
+impl<'a, 'b> Layout<'a, 'b>
+{
+    /// Break the text between two newlines into lines,
+    /// given its break opportunities and its glyphs in the order of the text.
+    ///
+    /// This returns false if no more lines fit.
+    fn wrap(
+        &mut self,
+        text: &str,
+        range: Range<usize>,
+        breaks: &[usize],
+        glyphs: &[ShapedGlyph],
+    ) -> bool
+    {
+        // The pen position before each glyph and after the last.
+        let mut pens = Vec::with_capacity(glyphs.len() + 1);
+        pens.push(0.0);
+        for glyph in glyphs {
+            pens.push(pens[pens.len() - 1] + glyph.advance_x * self.scale);
+        }
+        let width = |start: usize, end: usize| {
+            let visible_end = start + text[start .. end].trim_end().len();
+            pens[glyph_index(glyphs, visible_end)] - pens[glyph_index(glyphs, start)]
+        };
+
+        // Take as many break opportunities on each line as fit.
+        let mut start = range.start;
+        let mut fitting = None;
+        for &offset in breaks {
+            if let Some(end) = fitting {
+                if width(start, offset) > self.style.max_width {
+                    if !self.push_line(text, start .. end, glyphs, &pens, true) {
+                        return false;
+                    }
+                    start = end;
+                }
+            }
+            fitting = Some(offset);
+        }
+        if let Some(end) = fitting {
+            if width(start, range.end) > self.style.max_width {
+                if !self.push_line(text, start .. end, glyphs, &pens, true) {
+                    return false;
+                }
+                start = end;
+            }
+        }
+        self.push_line(text, start .. range.end, glyphs, &pens, false)
+    }
+
+    /// Add a line with the glyphs for the given range of text,
+    /// putting them in visual order.
+    ///
+    /// This returns false if the line does not fit.
+    fn push_line(
+        &mut self,
+        text: &str,
+        range: Range<usize>,
+        glyphs: &[ShapedGlyph],
+        pens: &[f64],
+        justify: bool,
+    ) -> bool
+    {
+        if self.style.max_lines.map_or(false, |max_lines| self.lines.len() >= max_lines) {
+            self.truncated = true;
+            return false;
+        }
+
+        let visible_end = range.start + text[range.clone()].trim_end().len();
+        let first = glyph_index(glyphs, range.start);
+        let last = glyph_index(glyphs, range.end);
+        let visible_last = glyph_index(glyphs, visible_end);
+
+        let levels = self.bidi.line_levels(text, range.clone());
+        let direction = self.bidi.paragraph_level(range.start).direction();
+
+        // The visible text starts at zero, so trailing spaces,
+        // which are on the left in right-to-left paragraphs, may be left of it.
+        let top = self.lines.len() as f64 * self.line_height;
+        let baseline = top + self.ascent;
+        let mut pen = match direction {
+            Direction::LeftToRight => 0.0,
+            Direction::RightToLeft => pens[visible_last] - pens[last],
+        };
+        let glyph_levels =
+            glyphs[first .. last].iter()
+            .map(|glyph| levels[glyph.cluster - range.start])
+            .collect::<Vec<_>>();
+        let start = self.glyphs.len();
+        for i in reorder_visual(&glyph_levels) {
+            let glyph = &glyphs[first + i];
+            self.glyphs.push(PositionedGlyph{
+                glyph_id: glyph.glyph_id,
+                cluster: glyph.cluster,
+                position: Vector{
+                    x: pen + glyph.offset_x * self.scale,
+                    y: baseline - glyph.offset_y * self.scale,
+                },
+            });
+            pen += glyph.advance_x * self.scale;
+        }
+
+        let char_levels =
+            text[range.clone()].char_indices()
+            .map(|(offset, _)| levels[offset])
+            .collect::<Vec<_>>();
+        let visual_to_logical = reorder_visual(&char_levels);
+        let mut logical_to_visual = vec![0; visual_to_logical.len()];
+        for (visual, &logical) in visual_to_logical.iter().enumerate() {
+            logical_to_visual[logical] = visual;
+        }
+
+        let bounds = Bounds{
+            min: Vector{x: 0.0, y: top},
+            max: Vector{x: pens[visible_last] - pens[first], y: top + self.line_height},
+        };
+        self.lines.push(PendingLine{
+            line: Line{
+                text: range,
+                glyphs: start .. self.glyphs.len(),
+                bounds,
+                baseline,
+                direction,
+                logical_to_visual,
+                visual_to_logical,
+            },
+            visible_end,
+            justify,
+        });
+        true
+    }
+
+    /// Align the lines.
+    fn finish(mut self, text: &str) -> Paragraph
+    {
+        let widest = self.lines.iter().map(|line| line.line.bounds.max.x).fold(0.0, f64::max);
+        let max_width = self.style.max_width;
+        let width = if max_width.is_finite() { max_width } else { widest };
+
+        let mut bounds = Bounds::EMPTY;
+        let mut lines = Vec::with_capacity(self.lines.len());
+        for PendingLine{mut line, visible_end, justify} in self.lines {
+            let free = (width - line.bounds.max.x).max(0.0);
+            let (start, end) = match line.direction {
+                Direction::LeftToRight => (0.0, free),
+                Direction::RightToLeft => (free, 0.0),
+            };
+            let glyphs = &mut self.glyphs[line.glyphs.clone()];
+            match self.style.alignment {
+                Alignment::Start => shift(glyphs, &mut line.bounds, start),
+                Alignment::End => shift(glyphs, &mut line.bounds, end),
+                Alignment::Center => shift(glyphs, &mut line.bounds, free / 2.0),
+                Alignment::Justify if justify => {
+                    stretch(text, glyphs, visible_end, &mut line.bounds, free);
+                },
+                Alignment::Justify => shift(glyphs, &mut line.bounds, start),
+            }
+            bounds = bounds.union(line.bounds);
+            lines.push(line);
+        }
+
+        Paragraph{
+            lines,
+            glyphs: self.glyphs,
+            bounds,
+            truncated: self.truncated,
+            scale: self.scale,
+        }
+    }
+}
+
+/// Move the glyphs of a line to the right.
+fn shift(glyphs: &mut [PositionedGlyph], bounds: &mut Bounds, dx: f64)
+{
+    for glyph in glyphs {
+        glyph.position.x += dx;
+    }
+    bounds.min.x += dx;
+    bounds.max.x += dx;
+}
+
+/// Widen the spaces of a line, other than trailing spaces,
+/// which are those at or after the end of the visible text.
+fn stretch(
+    text: &str,
+    glyphs: &mut [PositionedGlyph],
+    visible_end: usize,
+    bounds: &mut Bounds,
+    free: f64,
+)
+{
+    let is_space = |glyph: &PositionedGlyph| {
+        glyph.cluster < visible_end &&
+            text[glyph.cluster ..].chars().next().map_or(false, char::is_whitespace)
+    };
+    let spaces = glyphs.iter().filter(|glyph| is_space(glyph)).count();
+    if spaces == 0 {
+        return;
+    }
+    let extra = free / spaces as f64;
+    let mut dx = 0.0;
+    for glyph in glyphs {
+        glyph.position.x += dx;
+        if is_space(glyph) {
+            dx += extra;
+        }
+    }
+    bounds.max.x += free;
+}
+
+/// The index of the first glyph made from text at or after the offset.
+fn glyph_index(glyphs: &[ShapedGlyph], offset: usize) -> usize
+{
+    glyphs.partition_point(|glyph| glyph.cluster < offset)
+}
+
+fn is_newline(c: char) -> bool
+{
+    matches!(c, '\n' | '\u{B}' | '\u{C}' | '\r' | '\u{85}' | '\u{2028}' | '\u{2029}')
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use crate::test_font::be;
+    use crate::test_font::font_file;
+    use crate::test_font::typeface;
+
+    use np_graphics::PixelMap;
+    use np_graphics::formats::Bgra8888;
+
+    /// Lay out text with glyphs that are 10 pixels wide and lines 20 pixels high.
+    fn layout(text: &str, style: &ParagraphStyle) -> Paragraph
+    {
+        let metrics = Metrics{
+            units_per_em: 100.0,
+            ascender: 80.0,
+            descender: -20.0,
+            line_gap: 0.0,
+            x_height: None,
+            cap_height: None,
+            underline: None,
+            strikeout: None,
+            italic_angle: 0.0,
+        };
+        let style = ParagraphStyle{size: 20.0, ..*style};
+        layout_with(text, &style, &metrics, |run, direction| {
+            let mut glyphs = run.char_indices().map(|(cluster, c)| ShapedGlyph{
+                glyph_id: c as u16,
+                cluster,
+                advance_x: 50.0,
+                advance_y: 0.0,
+                offset_x: 0.0,
+                offset_y: 0.0,
+            }).collect::<Vec<_>>();
+            if direction == Direction::RightToLeft {
+                glyphs.reverse();
+            }
+            glyphs
+        })
+    }
+
+    fn line_texts<'a>(text: &'a str, paragraph: &Paragraph) -> Vec<&'a str>
+    {
+        paragraph.lines.iter().map(|line| &text[line.text.clone()]).collect()
+    }
+
+    #[test]
+    fn test_wrapping()
+    {
+        let text = "one two three\nfour  fivesixseven";
+        let style = ParagraphStyle{max_width: 75.0, ..ParagraphStyle::default()};
+        let paragraph = layout(text, &style);
+        assert_eq!(
+            line_texts(text, &paragraph),
+            ["one two ", "three\n", "four  ", "fivesixseven"],
+        );
+
+        // Trailing spaces do not count towards the width,
+        // but words that do not fit overflow.
+        let widths = paragraph.lines.iter().map(|line| line.bounds.max.x).collect::<Vec<_>>();
+        assert_eq!(widths, [70.0, 50.0, 40.0, 120.0]);
+        let baselines = paragraph.lines.iter().map(|line| line.baseline).collect::<Vec<_>>();
+        assert_eq!(baselines, [16.0, 36.0, 56.0, 76.0]);
+        assert_eq!(paragraph.bounds.max.y, 80.0);
+
+        // Glyphs are placed on their lines.
+        let line = &paragraph.lines[1];
+        let glyph = paragraph.glyphs[line.glyphs.start];
+        assert_eq!((glyph.cluster, glyph.position), (8, Vector{x: 0.0, y: 36.0}));
+        assert_eq!(paragraph.glyphs.len(), text.len() - 1);
+        assert!(!paragraph.truncated);
+
+        // Without a maximum width, lines are only broken at newlines.
+        let paragraph = layout(text, &ParagraphStyle::default());
+        assert_eq!(line_texts(text, &paragraph), ["one two three\n", "four  fivesixseven"]);
+    }
+
+    #[test]
+    fn test_alignment()
+    {
+        let text = "ab cd ef";
+        let style = ParagraphStyle{max_width: 60.0, ..ParagraphStyle::default()};
+        let start_of_lines = |alignment| {
+            let paragraph = layout(text, &ParagraphStyle{alignment, ..style});
+            paragraph.lines.iter()
+                .map(|line| paragraph.glyphs[line.glyphs.start].position.x)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(start_of_lines(Alignment::Start), [0.0, 0.0]);
+        assert_eq!(start_of_lines(Alignment::End), [10.0, 40.0]);
+        assert_eq!(start_of_lines(Alignment::Center), [5.0, 20.0]);
+
+        // Justified lines stretch their inner spaces, but not the last line.
+        let paragraph = layout(text, &ParagraphStyle{alignment: Alignment::Justify, ..style});
+        let positions =
+            paragraph.glyphs.iter().map(|glyph| glyph.position.x).collect::<Vec<_>>();
+        assert_eq!(positions, [0.0, 10.0, 20.0, 40.0, 50.0, 60.0, 0.0, 10.0]);
+        assert_eq!(paragraph.lines[0].bounds.max.x, 60.0);
+    }
+
+    #[test]
+    fn test_line_height_and_max_lines()
+    {
+        let text = "a b c d";
+        let style = ParagraphStyle{
+            max_width: 10.0,
+            line_height: 1.5,
+            max_lines: Some(2),
+            ..ParagraphStyle::default()
+        };
+        let paragraph = layout(text, &style);
+        assert_eq!(line_texts(text, &paragraph), ["a ", "b "]);
+        assert!(paragraph.truncated);
+
+        // The extra space is divided above and below the text.
+        let baselines = paragraph.lines.iter().map(|line| line.baseline).collect::<Vec<_>>();
+        assert_eq!(baselines, [21.0, 51.0]);
+        assert_eq!(paragraph.bounds.max.y, 60.0);
+
+        assert!(layout("", &style).lines.is_empty());
+    }
+
+    #[test]
+    fn test_bidi()
+    {
+        // Hebrew within left-to-right text is reversed.
+        let text = "ab \u{5D0}\u{5D1}\u{5D2} cd";
+        let paragraph = layout(text, &ParagraphStyle::default());
+        let clusters = paragraph.glyphs.iter().map(|glyph| glyph.cluster).collect::<Vec<_>>();
+        assert_eq!(clusters, [0, 1, 2, 7, 5, 3, 9, 10, 11]);
+        let positions =
+            paragraph.glyphs.iter().map(|glyph| glyph.position.x).collect::<Vec<_>>();
+        assert_eq!(positions, [0.0, 10.0, 20.0, 30.0, 40.0, 50.0, 60.0, 70.0, 80.0]);
+        let line = &paragraph.lines[0];
+        assert_eq!(line.direction, Direction::LeftToRight);
+        assert_eq!(line.visual_to_logical, [0, 1, 2, 5, 4, 3, 6, 7, 8]);
+        assert_eq!(line.logical_to_visual, [0, 1, 2, 5, 4, 3, 6, 7, 8]);
+
+        // Right-to-left paragraphs start at the right edge,
+        // with trailing spaces on the left.
+        let text = "\u{5D0}\u{5D1} ab ";
+        let style = ParagraphStyle{max_width: 100.0, ..ParagraphStyle::default()};
+        let paragraph = layout(text, &style);
+        let clusters = paragraph.glyphs.iter().map(|glyph| glyph.cluster).collect::<Vec<_>>();
+        assert_eq!(clusters, [7, 5, 6, 4, 2, 0]);
+        let positions =
+            paragraph.glyphs.iter().map(|glyph| glyph.position.x).collect::<Vec<_>>();
+        assert_eq!(positions, [40.0, 50.0, 60.0, 70.0, 80.0, 90.0]);
+        let line = &paragraph.lines[0];
+        assert_eq!(line.direction, Direction::RightToLeft);
+        assert_eq!((line.bounds.min.x, line.bounds.max.x), (50.0, 100.0));
+        assert_eq!(line.visual_to_logical, [5, 3, 4, 2, 1, 0]);
+        assert_eq!(line.logical_to_visual, [5, 4, 3, 1, 2, 0]);
+
+        // The direction can be given, rather than detected.
+        let style = ParagraphStyle{direction: Some(Direction::LeftToRight), ..style};
+        let paragraph = layout(text, &style);
+        let clusters = paragraph.glyphs.iter().map(|glyph| glyph.cluster).collect::<Vec<_>>();
+        assert_eq!(clusters, [2, 0, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_draw_color()
+    {
+        // Glyph 1 is a square of 50 units, and glyph 2 is the same square
+        // as a color glyph with the first color of the palette.
+        let mut glyf = be(&[1, 0, 0, 50, 50, 3, 0]);
+        glyf.extend_from_slice(&[1, 1, 1, 1]);
+        glyf.extend(be(&[0, 50, 0, -50i16 as u16, 0, 0, 50, 0]));
+        let loca = be(&[0, 0, 17, 17]);
+        let colr = be(&[0, 1, 0, 14, 0, 20, 1, 2, 0, 1, 1, 0]);
+        let mut cpal = be(&[0, 1, 1, 1, 0, 14, 0]);
+        cpal.extend_from_slice(&[0, 0, 0xFF, 0xFF]);
+        let data = font_file(&[50, 50, 50], &[
+            (b"COLR", colr),
+            (b"CPAL", cpal),
+            (b"glyf", glyf),
+            (b"loca", loca),
+        ]);
+        let typeface = typeface(&data);
+        let palette = &typeface.palettes()[0].colors;
+        assert_eq!(palette, &[Color::new(1.0, 0.0, 0.0, 1.0)]);
+
+        // The glyphs are 10 pixels wide, and stand on the baseline at 16 pixels.
+        let paragraph = layout("\u{2}\u{1}", &ParagraphStyle::default());
+        let mut pixel_map = PixelMap::new([0; 4], 20, 20).unwrap();
+        let mut canvas = PixelMapCanvas::new(Bgra8888, pixel_map.as_mut());
+        let foreground = Color::new(0.0, 0.0, 1.0, 1.0);
+        paragraph.draw_color(&mut canvas, &typeface, Matrix::IDENTITY, palette, foreground);
+        let pixel = |x: usize, y: usize| pixel_map.pixels()[y * 20 + x];
+        assert_eq!(pixel(5, 10), [0, 0, 0xFF, 0xFF]);
+        assert_eq!(pixel(15, 10), [0xFF, 0, 0, 0xFF]);
+        assert_eq!(pixel(5, 5), [0; 4]);
+        assert_eq!(pixel(15, 17), [0; 4]);
+    }
+}
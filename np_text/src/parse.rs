@@ -0,0 +1,58 @@
+//! Reading big-endian values from font tables.
+//!
+//! All functions return [`None`] if the data is too short,
+//! so that malformed fonts are handled without panicking.
+
+pub(crate) fn u16_at(data: &[u8], offset: usize) -> Option<u16>
+{
+    let bytes = data.get(offset .. offset.checked_add(2)?)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+pub(crate) fn i16_at(data: &[u8], offset: usize) -> Option<i16>
+{
+    u16_at(data, offset).map(|value| value as i16)
+}
+
+pub(crate) fn u32_at(data: &[u8], offset: usize) -> Option<u32>
+{
+    let bytes = data.get(offset .. offset.checked_add(4)?)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// The data at the 16-bit offset stored at `offset`,
+/// relative to the start of `data`.
+///
+/// Null offsets refer to nothing.
+pub(crate) fn offset16(data: &[u8], offset: usize) -> Option<&[u8]>
+{
+    match u16_at(data, offset)? {
+        0 => None,
+        target => data.get(target as usize ..),
+    }
+}
+
+/// The data at the 24-bit offset stored at `offset`,
+/// relative to the start of `data`.
+///
+/// Null offsets refer to nothing.
+pub(crate) fn offset24(data: &[u8], offset: usize) -> Option<&[u8]>
+{
+    let bytes = data.get(offset .. offset.checked_add(3)?)?;
+    match u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]) {
+        0 => None,
+        target => data.get(target as usize ..),
+    }
+}
+
+/// The data at the 32-bit offset stored at `offset`,
+/// relative to the start of `data`.
+///
+/// Null offsets refer to nothing.
+pub(crate) fn offset32(data: &[u8], offset: usize) -> Option<&[u8]>
+{
+    match u32_at(data, offset)? {
+        0 => None,
+        target => data.get(target as usize ..),
+    }
+}
@@ -0,0 +1,252 @@
+use super::Rules;
+use super::Segments;
+use super::lookup;
+use super::next_boundary;
+use super::previous_boundary;
+
+use std::iter::FusedIterator;
+
+mod tables;
+
+/// Iterator over the graphemes of text, with their byte offsets.
+///
+/// To obtain this iterator, call [`grapheme_indices`].
+#[derive(Clone, Debug)]
+pub struct GraphemeIndices<'a>
+{
+    segments: Segments<'a, State>,
+}
+
+/// Split text into graphemes, which are what users perceive as characters.
+///
+/// Graphemes are the extended grapheme clusters of UAX #29,
+/// such as a letter followed by combining marks, a Hangul syllable,
+/// a flag, or a sequence of emoji joined by zero width joiners.
+/// Cursors move over graphemes, and deleting removes whole graphemes.
+pub fn grapheme_indices(text: &str) -> GraphemeIndices<'_>
+{
+    GraphemeIndices{segments: Segments::new(text)}
+}
+
+/// The byte offset of the end of the grapheme that contains the byte offset,
+/// or of the next grapheme if the byte offset is at its start.
+///
+/// This looks at the text from its start,
+/// so it takes time proportional to the offset.
+pub fn next_grapheme_boundary(text: &str, offset: usize) -> usize
+{
+    next_boundary::<State>(text, offset)
+}
+
+/// The byte offset of the start of the grapheme that contains the byte offset,
+/// or of the previous grapheme if the byte offset is at its start.
+///
+/// This looks at the text from its start,
+/// so it takes time proportional to the offset.
+pub fn previous_grapheme_boundary(text: &str, offset: usize) -> usize
+{
+    previous_boundary::<State>(text, offset)
+}
+
+impl<'a> Iterator for GraphemeIndices<'a>
+{
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<(usize, &'a str)>
+    {
+        self.segments.next()
+    }
+}
+
+impl<'a> FusedIterator for GraphemeIndices<'a>
+{
+}
+
+/// Whether the character is extended pictographic, such as most emoji.
+pub(super) fn is_extended_pictographic(c: char) -> bool
+{
+    Class::of(c) == Class::ExtendedPictographic
+}
+
+/// Grapheme cluster break class of a character.
+///
+/// Extended pictographic characters, which all have the class Other,
+/// get a class of their own.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Class
+{
+    Cr, Lf, Control, Extend, Zwj, RegionalIndicator, Prepend, SpacingMark,
+    L, V, T, Lv, Lvt, ExtendedPictographic, Other,
+}
+
+use self::Class::*;
+
+impl Class
+{
+    fn of(c: char) -> Self
+    {
+        lookup(tables::GRAPHEME_CLUSTER_BREAK, c, Other)
+    }
+}
+
+/// What the rules need to know about the text before a position.
+#[derive(Clone, Copy, Debug)]
+struct State
+{
+    /// The class of the previous character.
+    before: Class,
+
+    /// Whether the previous character is an odd regional indicator,
+    /// which pairs up with the next.
+    odd_regional_indicator: bool,
+
+    /// Whether the previous characters are an extended pictographic character
+    /// followed by any number of extending characters.
+    after_pictographic: bool,
+
+    /// Whether the previous characters are those followed by a zero width joiner.
+    after_emoji_zwj: bool,
+}
+
+impl Rules for State
+{
+    fn new(c: char) -> Self
+    {
+        let mut state = Self{
+            before: Other,
+            odd_regional_indicator: false,
+            after_pictographic: false,
+            after_emoji_zwj: false,
+        };
+        state.update(Class::of(c));
+        state
+    }
+
+    fn advance(&mut self, c: char, _rest: &str) -> bool
+    {
+        let class = Class::of(c);
+        let boundary = self.decide(class);
+        self.update(class);
+        boundary
+    }
+}
+
+impl State
+{
+    /// Apply the rules to the position before a character.
+    fn decide(&self, class: Class) -> bool
+    {
+        let before = self.before;
+
+        // Line breaks and controls (GB3, GB4, GB5).
+        if before == Cr && class == Lf {
+            return false;
+        }
+        if matches!(before, Control | Cr | Lf) || matches!(class, Control | Cr | Lf) {
+            return true;
+        }
+
+        // Hangul syllables (GB6, GB7, GB8).
+        let hangul = matches!((before, class),
+            (L, L) | (L, V) | (L, Lv) | (L, Lvt) |
+            (Lv, V) | (Lv, T) | (V, V) | (V, T) |
+            (Lvt, T) | (T, T)
+        );
+        if hangul {
+            return false;
+        }
+
+        // Extending characters, spacing marks, and prepended characters
+        // (GB9, GB9a, GB9b).
+        if matches!(class, Extend | Zwj | SpacingMark) || before == Prepend {
+            return false;
+        }
+
+        // Emoji sequences (GB11).
+        if self.after_emoji_zwj && class == ExtendedPictographic {
+            return false;
+        }
+
+        // Flags (GB12, GB13).
+        if before == RegionalIndicator && class == RegionalIndicator {
+            return !self.odd_regional_indicator;
+        }
+
+        // Everywhere else (GB999).
+        true
+    }
+
+    fn update(&mut self, class: Class)
+    {
+        self.odd_regional_indicator =
+            class == RegionalIndicator &&
+            !(self.before == RegionalIndicator && self.odd_regional_indicator);
+        self.after_emoji_zwj = class == Zwj && self.after_pictographic;
+        self.after_pictographic =
+            class == ExtendedPictographic || class == Extend && self.after_pictographic;
+        self.before = class;
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use super::super::check;
+    use super::super::check_file;
+
+    fn starts(text: &str) -> Vec<usize>
+    {
+        grapheme_indices(text).map(|(start, _)| start).collect()
+    }
+
+    #[test]
+    fn test_classes()
+    {
+        assert_eq!(Class::of('a'), Other);
+        assert_eq!(Class::of('\r'), Cr);
+        assert_eq!(Class::of('\u{301}'), Extend);
+        assert_eq!(Class::of('\u{200D}'), Zwj);
+        assert_eq!(Class::of('\u{1F3FB}'), Extend);
+        assert_eq!(Class::of('\u{1F600}'), ExtendedPictographic);
+        assert_eq!(Class::of('\u{AC00}'), Lv);
+        assert_eq!(Class::of('\u{AC01}'), Lvt);
+        assert_eq!(Class::of('\u{903}'), SpacingMark);
+    }
+
+    #[test]
+    fn test_graphemes()
+    {
+        check(starts, "");
+        check(starts, "÷ 0061 ÷ 0062 ÷");
+        check(starts, "÷ 000D × 000A ÷ 0061 ÷ 000A ÷ 0308 ÷");
+        check(starts, "÷ 0061 × 0308 × 0301 ÷ 0020 ÷ 0001 ÷ 0308 ÷");
+        check(starts, "÷ 1100 × 1161 × 11A8 ÷ AC00 × 11A8 ÷ AC01 ÷ 1161 ÷");
+        check(starts, "÷ 0915 × 093F ÷ 0600 × 0661 ÷");
+        check(starts, "÷ 1F1E6 × 1F1E8 ÷ 1F1E6 × 1F1E8 ÷ 1F1E6 ÷");
+        check(starts, "÷ 0061 ÷ 1F1E6 × 1F1E8 ÷ 1F1E6 × 0308 ÷");
+        check(starts, "÷ 1F468 × 200D × 1F469 × 200D × 1F467 ÷ 1F44D × 1F3FB ÷");
+        check(starts, "÷ 1F6D1 × 0308 × 200D × 1F6D1 ÷ 0061 × 200D ÷ 1F6D1 ÷");
+    }
+
+    #[test]
+    fn test_grapheme_break_test()
+    {
+        let data = include_str!("../../tests/data/GraphemeBreakTest.txt");
+        assert_eq!(check_file(starts, data), 602);
+    }
+
+    #[test]
+    fn test_boundaries()
+    {
+        let text = "e\u{301}\u{1F1F3}\u{1F1F1}x";
+        assert_eq!(next_grapheme_boundary(text, 0), 3);
+        assert_eq!(next_grapheme_boundary(text, 1), 3);
+        assert_eq!(next_grapheme_boundary(text, 3), 11);
+        assert_eq!(next_grapheme_boundary(text, 12), 12);
+        assert_eq!(previous_grapheme_boundary(text, 11), 3);
+        assert_eq!(previous_grapheme_boundary(text, 7), 3);
+        assert_eq!(previous_grapheme_boundary(text, 3), 0);
+        assert_eq!(previous_grapheme_boundary(text, 0), 0);
+    }
+}
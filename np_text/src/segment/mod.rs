@@ -0,0 +1,153 @@
+//! Splitting text into graphemes, words, and sentences,
+//! as described in [UAX #29].
+//!
+//! [UAX #29]: https://www.unicode.org/reports/tr29/
+
+pub use self::grapheme::*;
+pub use self::sentence::*;
+pub use self::word::*;
+
+use std::cmp::Ordering;
+use std::iter::FusedIterator;
+use std::mem;
+use std::str::CharIndices;
+
+mod grapheme;
+mod sentence;
+mod word;
+
+/// Rules that decide where the boundaries between segments are.
+trait Rules
+{
+    /// The state after the first character.
+    fn new(c: char) -> Self;
+
+    /// Move past the next character, given the text after it.
+    ///
+    /// This returns whether there is a boundary before the character.
+    fn advance(&mut self, c: char, rest: &str) -> bool;
+}
+
+/// Iterator over the segments of text, with their byte offsets.
+#[derive(Clone, Debug)]
+struct Segments<'a, R>
+{
+    text: &'a str,
+    chars: CharIndices<'a>,
+    start: usize,
+    rules: Option<R>,
+}
+
+impl<'a, R> Segments<'a, R>
+{
+    fn new(text: &'a str) -> Self
+    {
+        Self{text, chars: text.char_indices(), start: 0, rules: None}
+    }
+}
+
+impl<'a, R> Iterator for Segments<'a, R>
+    where R: Rules
+{
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<(usize, &'a str)>
+    {
+        for (offset, c) in &mut self.chars {
+            let rest = &self.text[offset + c.len_utf8() ..];
+            match &mut self.rules {
+                None => self.rules = Some(R::new(c)),
+                Some(rules) => if rules.advance(c, rest) {
+                    let start = mem::replace(&mut self.start, offset);
+                    return Some((start, &self.text[start .. offset]));
+                },
+            }
+        }
+        if self.start == self.text.len() {
+            return None;
+        }
+        let start = mem::replace(&mut self.start, self.text.len());
+        Some((start, &self.text[start ..]))
+    }
+}
+
+impl<'a, R> FusedIterator for Segments<'a, R>
+    where R: Rules
+{
+}
+
+/// The first boundary after the given byte offset,
+/// or the end of the text if there is none.
+fn next_boundary<R>(text: &str, offset: usize) -> usize
+    where R: Rules
+{
+    Segments::<R>::new(text)
+        .map(|(start, segment)| start + segment.len())
+        .find(|&end| end > offset)
+        .unwrap_or(text.len())
+}
+
+/// The last boundary before the given byte offset,
+/// or the start of the text if there is none.
+fn previous_boundary<R>(text: &str, offset: usize) -> usize
+    where R: Rules
+{
+    Segments::<R>::new(text)
+        .map(|(start, _)| start)
+        .take_while(|&start| start < offset)
+        .last()
+        .unwrap_or(0)
+}
+
+/// Look up the class of a character in a table of ranges.
+fn lookup<T>(table: &[(u32, u32, T)], c: char, default: T) -> T
+    where T: Copy
+{
+    let c = c as u32;
+    let index = table.binary_search_by(|&(start, end, _)| {
+        if end < c { Ordering::Less }
+        else if start > c { Ordering::Greater }
+        else { Ordering::Equal }
+    });
+    index.map_or(default, |index| table[index].2)
+}
+
+/// Check segmentation against a test case in the format of the test files
+/// that accompany UAX #29, such as `÷ 0061 × 0308 ÷ 0020 ÷`,
+/// in which `÷` marks a boundary and `×` marks the absence of one.
+///
+/// The segmentation is given as the start of each segment.
+#[cfg(test)]
+fn check<F>(starts: F, case: &str)
+    where F: Fn(&str) -> Vec<usize>
+{
+    let mut text = String::new();
+    let mut boundaries = Vec::new();
+    for token in case.split_whitespace() {
+        match token {
+            "÷" => boundaries.push(text.len()),
+            "×" => (),
+            _ => {
+                let c = u32::from_str_radix(token, 16).ok().and_then(std::char::from_u32);
+                text.push(c.expect("code point"));
+            },
+        }
+    }
+    boundaries.pop();
+    assert_eq!(starts(&text), boundaries, "{}", case);
+}
+
+/// Check segmentation against every test case in a test file
+/// that accompanies UAX #29, returning the number of test cases.
+#[cfg(test)]
+fn check_file<F>(starts: F, data: &str) -> usize
+    where F: Fn(&str) -> Vec<usize>
+{
+    let cases = data.lines().filter_map(|line| line.split('#').next());
+    let mut count = 0;
+    for case in cases.filter(|case| !case.trim().is_empty()) {
+        check(&starts, case);
+        count += 1;
+    }
+    count
+}
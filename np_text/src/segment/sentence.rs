@@ -0,0 +1,230 @@
+use super::Rules;
+use super::Segments;
+use super::lookup;
+
+use std::iter::FusedIterator;
+
+mod tables;
+
+/// Iterator over the sentences of text, with their byte offsets.
+///
+/// To obtain this iterator, call [`sentence_indices`].
+#[derive(Clone, Debug)]
+pub struct SentenceIndices<'a>
+{
+    segments: Segments<'a, State>,
+}
+
+/// Split text into sentences.
+///
+/// Sentences include the spaces after them, and end at paragraph separators.
+/// Full stops followed by a lowercase letter, as in `etc. and`,
+/// do not end sentences, but those after abbreviations such as `Mr.`
+/// do, as recognizing abbreviations requires dictionaries.
+pub fn sentence_indices(text: &str) -> SentenceIndices<'_>
+{
+    SentenceIndices{segments: Segments::new(text)}
+}
+
+impl<'a> Iterator for SentenceIndices<'a>
+{
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<(usize, &'a str)>
+    {
+        self.segments.next()
+    }
+}
+
+impl<'a> FusedIterator for SentenceIndices<'a>
+{
+}
+
+/// Sentence break class of a character.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Class
+{
+    Cr, Lf, Extend, Sep, Format, Sp, Lower, Upper, OLetter,
+    Numeric, ATerm, SContinue, STerm, Close, Other,
+}
+
+use self::Class::*;
+
+impl Class
+{
+    fn of(c: char) -> Self
+    {
+        lookup(tables::SENTENCE_BREAK, c, Other)
+    }
+
+    fn is_paragraph_separator(self) -> bool
+    {
+        matches!(self, Sep | Cr | Lf)
+    }
+}
+
+/// What the rules need to know about the text before a position.
+///
+/// Extending and format characters are part of the character before them,
+/// unless that is a paragraph separator (SB5).
+#[derive(Clone, Copy, Debug)]
+struct State
+{
+    /// The class of the previous character.
+    before: Class,
+
+    /// The class of the character before that.
+    before_that: Option<Class>,
+
+    /// The terminator that the previous characters follow,
+    /// possibly with closing punctuation and spaces in between.
+    terminator: Option<Class>,
+
+    /// Whether there are spaces after that terminator.
+    spaces: bool,
+}
+
+impl Rules for State
+{
+    fn new(c: char) -> Self
+    {
+        let class = Class::of(c);
+        let mut state = Self{before: class, before_that: None, terminator: None, spaces: false};
+        state.update_terminator(class);
+        state
+    }
+
+    fn advance(&mut self, c: char, rest: &str) -> bool
+    {
+        let class = Class::of(c);
+        let boundary = self.decide(class, rest);
+        if !matches!(class, Extend | Format) || self.before.is_paragraph_separator() {
+            self.update_terminator(class);
+            self.before_that = Some(self.before);
+            self.before = class;
+        }
+        boundary
+    }
+}
+
+impl State
+{
+    /// Apply the rules to the position before a character.
+    fn decide(&self, class: Class, rest: &str) -> bool
+    {
+        let before = self.before;
+
+        // Paragraph separators and ignored characters (SB3, SB4, SB5).
+        if before == Cr && class == Lf {
+            return false;
+        }
+        if before.is_paragraph_separator() {
+            return true;
+        }
+        if matches!(class, Extend | Format) {
+            return false;
+        }
+
+        // Full stops in numbers and abbreviations (SB6, SB7).
+        if before == ATerm && class == Numeric {
+            return false;
+        }
+        let after_letter = matches!(self.before_that, Some(Upper) | Some(Lower));
+        if after_letter && before == ATerm && class == Upper {
+            return false;
+        }
+
+        let terminator = match self.terminator {
+            Some(terminator) => terminator,
+            None => return false,
+        };
+
+        // Full stops followed by lowercase letters (SB8).
+        if terminator == ATerm {
+            let next =
+                Some(class).into_iter().chain(rest.chars().map(Class::of))
+                .find(|&class| {
+                    matches!(class, OLetter | Upper | Lower | ATerm | STerm) ||
+                        class.is_paragraph_separator()
+                });
+            if next == Some(Lower) {
+                return false;
+            }
+        }
+
+        // Punctuation and spaces after terminators (SB8a, SB9, SB10).
+        if matches!(class, SContinue | ATerm | STerm) ||
+            !self.spaces && class == Close ||
+            class == Sp || class.is_paragraph_separator() {
+            return false;
+        }
+
+        // Sentences end after terminators (SB11),
+        // and nowhere else (SB998).
+        true
+    }
+
+    /// Keep track of terminators followed by closing punctuation and spaces.
+    fn update_terminator(&mut self, class: Class)
+    {
+        match class {
+            ATerm | STerm => {
+                self.terminator = Some(class);
+                self.spaces = false;
+            },
+            Close if self.terminator.is_some() && !self.spaces => (),
+            Sp if self.terminator.is_some() => self.spaces = true,
+            _ => {
+                self.terminator = None;
+                self.spaces = false;
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use super::super::check;
+    use super::super::check_file;
+
+    fn starts(text: &str) -> Vec<usize>
+    {
+        sentence_indices(text).map(|(start, _)| start).collect()
+    }
+
+    #[test]
+    fn test_classes()
+    {
+        assert_eq!(Class::of('a'), Lower);
+        assert_eq!(Class::of('A'), Upper);
+        assert_eq!(Class::of('.'), ATerm);
+        assert_eq!(Class::of('?'), STerm);
+        assert_eq!(Class::of(','), SContinue);
+        assert_eq!(Class::of(')'), Close);
+        assert_eq!(Class::of('\u{2029}'), Sep);
+        assert_eq!(Class::of('\u{5D0}'), OLetter);
+    }
+
+    #[test]
+    fn test_sentences()
+    {
+        check(starts, "");
+        check(starts, "÷ 0048 × 0065 × 002E × 0020 ÷ 0057 × 0065 × 002E ÷");
+        check(starts, "÷ 0065 × 0074 × 0063 × 002E × 0020 × 0064 ÷");
+        check(starts, "÷ 0033 × 002E × 0034 × 0020 × 0055 × 002E × 0053 × 002E ÷");
+        check(starts, "÷ 0061 × 003F × 0022 × 0020 ÷ 0042 × 0021 × 0021 ÷");
+        check(starts, "÷ 0061 × 002E × 002C × 0020 × 0062 ÷");
+        check(starts, "÷ 0061 × 000D × 000A ÷ 0062 × 2029 ÷ 0063 ÷");
+        check(starts, "÷ 0061 × 002E × 0308 × 0020 ÷ 0042 ÷");
+        check(starts, "÷ 05D0 × 002E × 0029 × 0020 × 000A ÷ 05D1 ÷");
+    }
+
+    #[test]
+    fn test_sentence_break_test()
+    {
+        let data = include_str!("../../tests/data/SentenceBreakTest.txt");
+        assert_eq!(check_file(starts, data), 502);
+    }
+}
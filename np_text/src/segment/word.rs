@@ -0,0 +1,280 @@
+use super::Rules;
+use super::Segments;
+use super::is_extended_pictographic;
+use super::lookup;
+use super::next_boundary;
+use super::previous_boundary;
+
+use std::iter::FusedIterator;
+
+mod tables;
+
+/// Iterator over the words of text and what is between them,
+/// with their byte offsets.
+///
+/// To obtain this iterator, call [`word_indices`].
+#[derive(Clone, Debug)]
+pub struct WordIndices<'a>
+{
+    segments: Segments<'a, State>,
+}
+
+/// Split text at word boundaries.
+///
+/// Words such as `can't` and numbers such as `3.14` are kept together.
+/// The spaces and punctuation between words are segments too,
+/// so the segments together make up the text;
+/// to find only the words, look for segments with letters or digits.
+/// Words in scripts that are not separated by spaces, such as Thai,
+/// are not separated, as that requires dictionaries.
+pub fn word_indices(text: &str) -> WordIndices<'_>
+{
+    WordIndices{segments: Segments::new(text)}
+}
+
+/// The byte offset of the first word boundary after the byte offset,
+/// or the end of the text if there is none.
+///
+/// This looks at the text from its start,
+/// so it takes time proportional to the offset.
+pub fn next_word_boundary(text: &str, offset: usize) -> usize
+{
+    next_boundary::<State>(text, offset)
+}
+
+/// The byte offset of the last word boundary before the byte offset,
+/// or the start of the text if there is none.
+///
+/// This looks at the text from its start,
+/// so it takes time proportional to the offset.
+pub fn previous_word_boundary(text: &str, offset: usize) -> usize
+{
+    previous_boundary::<State>(text, offset)
+}
+
+impl<'a> Iterator for WordIndices<'a>
+{
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<(usize, &'a str)>
+    {
+        self.segments.next()
+    }
+}
+
+impl<'a> FusedIterator for WordIndices<'a>
+{
+}
+
+/// Word break class of a character.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Class
+{
+    Cr, Lf, Newline, Extend, Zwj, RegionalIndicator, Format, Katakana,
+    HebrewLetter, ALetter, SingleQuote, DoubleQuote, MidNumLet, MidLetter,
+    MidNum, Numeric, ExtendNumLet, WSegSpace, Other,
+}
+
+use self::Class::*;
+
+impl Class
+{
+    fn of(c: char) -> Self
+    {
+        lookup(tables::WORD_BREAK, c, Other)
+    }
+
+    /// Whether characters of this class are part of the character before them
+    /// (WB4).
+    fn is_ignored(self) -> bool
+    {
+        matches!(self, Extend | Format | Zwj)
+    }
+
+    fn is_letter(self) -> bool
+    {
+        matches!(self, ALetter | HebrewLetter)
+    }
+
+    fn is_mid_letter(self) -> bool
+    {
+        matches!(self, MidLetter | MidNumLet | SingleQuote)
+    }
+
+    fn is_mid_num(self) -> bool
+    {
+        matches!(self, MidNum | MidNumLet | SingleQuote)
+    }
+}
+
+/// The class of the next character that is not ignored.
+fn next_class(rest: &str) -> Option<Class>
+{
+    rest.chars().map(Class::of).find(|class| !class.is_ignored())
+}
+
+/// What the rules need to know about the text before a position.
+#[derive(Clone, Copy, Debug)]
+struct State
+{
+    /// The class of the previous character.
+    previous: Class,
+
+    /// The class of the previous character that was not ignored.
+    before: Class,
+
+    /// The class of the character that was not ignored before that.
+    before_that: Option<Class>,
+
+    /// Whether the previous character that was not ignored
+    /// is an odd regional indicator, which pairs up with the next.
+    odd_regional_indicator: bool,
+}
+
+impl Rules for State
+{
+    fn new(c: char) -> Self
+    {
+        let class = Class::of(c);
+        Self{
+            previous: class,
+            before: class,
+            before_that: None,
+            odd_regional_indicator: class == RegionalIndicator,
+        }
+    }
+
+    fn advance(&mut self, c: char, rest: &str) -> bool
+    {
+        let class = Class::of(c);
+        let boundary = self.decide(c, class, rest);
+
+        // Ignored characters are only part of a character before them
+        // that is not a line break.
+        if !class.is_ignored() || matches!(self.before, Cr | Lf | Newline) {
+            self.odd_regional_indicator =
+                class == RegionalIndicator &&
+                !(self.before == RegionalIndicator && self.odd_regional_indicator);
+            self.before_that = Some(self.before);
+            self.before = class;
+        }
+        self.previous = class;
+        boundary
+    }
+}
+
+impl State
+{
+    /// Apply the rules to the position before a character.
+    fn decide(&self, c: char, class: Class, rest: &str) -> bool
+    {
+        let previous = self.previous;
+
+        // Line breaks (WB3, WB3a, WB3b).
+        if previous == Cr && class == Lf {
+            return false;
+        }
+        if matches!(previous, Cr | Lf | Newline) || matches!(class, Cr | Lf | Newline) {
+            return true;
+        }
+
+        // Emoji sequences, spaces, and ignored characters (WB3c, WB3d, WB4).
+        if previous == Zwj && is_extended_pictographic(c) ||
+            previous == WSegSpace && class == WSegSpace ||
+            class.is_ignored() {
+            return false;
+        }
+
+        let before = self.before;
+        let before_that = self.before_that.unwrap_or(Other);
+        let next = || next_class(rest).unwrap_or(Other);
+        let prohibited =
+            // Letters, possibly with punctuation in between (WB5 to WB7c).
+            before.is_letter() && class.is_letter() ||
+            before.is_letter() && class.is_mid_letter() && next().is_letter() ||
+            before_that.is_letter() && before.is_mid_letter() && class.is_letter() ||
+            before == HebrewLetter && class == SingleQuote ||
+            before == HebrewLetter && class == DoubleQuote && next() == HebrewLetter ||
+            before_that == HebrewLetter && before == DoubleQuote && class == HebrewLetter ||
+
+            // Numbers, possibly with punctuation in between (WB8 to WB12).
+            before == Numeric && class == Numeric ||
+            before.is_letter() && class == Numeric ||
+            before == Numeric && class.is_letter() ||
+            before_that == Numeric && before.is_mid_num() && class == Numeric ||
+            before == Numeric && class.is_mid_num() && next() == Numeric ||
+
+            // Katakana and connectors (WB13, WB13a, WB13b).
+            before == Katakana && class == Katakana ||
+            matches!(before, ALetter | HebrewLetter | Numeric | Katakana | ExtendNumLet) &&
+                class == ExtendNumLet ||
+            before == ExtendNumLet &&
+                matches!(class, ALetter | HebrewLetter | Numeric | Katakana) ||
+
+            // Flags (WB15, WB16).
+            before == RegionalIndicator && class == RegionalIndicator &&
+                self.odd_regional_indicator;
+
+        // Everywhere else (WB999).
+        !prohibited
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use super::super::check;
+    use super::super::check_file;
+
+    fn starts(text: &str) -> Vec<usize>
+    {
+        word_indices(text).map(|(start, _)| start).collect()
+    }
+
+    #[test]
+    fn test_classes()
+    {
+        assert_eq!(Class::of('a'), ALetter);
+        assert_eq!(Class::of('\u{5D0}'), HebrewLetter);
+        assert_eq!(Class::of('\''), SingleQuote);
+        assert_eq!(Class::of('.'), MidNumLet);
+        assert_eq!(Class::of(','), MidNum);
+        assert_eq!(Class::of('_'), ExtendNumLet);
+        assert_eq!(Class::of(' '), WSegSpace);
+        assert_eq!(Class::of('\u{30A2}'), Katakana);
+        assert_eq!(Class::of('\u{4E00}'), Other);
+    }
+
+    #[test]
+    fn test_words()
+    {
+        check(starts, "");
+        check(starts, "÷ 0063 × 0061 × 006E × 0027 × 0074 ÷ 0020 ÷ 0064 × 006F ÷ 002E ÷");
+        check(starts, "÷ 0033 × 002E × 0031 × 0034 ÷ 0020 × 0020 ÷ 0031 × 002C × 0030 ÷ 002C ÷");
+        check(starts, "÷ 0061 × 0062 ÷ 002E ÷ 0020 ÷ 0061 × 002E × 0062 ÷");
+        check(starts, "÷ 0061 × 0308 × 0062 ÷ 000D × 000A ÷ 0308 ÷ 0061 ÷");
+        check(starts, "÷ 05D0 × 0027 ÷ 0020 ÷ 05D0 × 0022 × 05D1 ÷");
+        check(starts, "÷ 30A2 × 30A2 ÷ 0061 × 005F × 0031 ÷ 4E00 ÷ 4E00 ÷");
+        check(starts, "÷ 1F1E6 × 1F1E8 ÷ 1F1E6 ÷ 0061 × 200D × 1F6D1 ÷");
+        check(starts, "÷ 0020 × 200D × 1F6D1 ÷ 0020 ÷ 1F6D1 ÷");
+    }
+
+    #[test]
+    fn test_word_break_test()
+    {
+        let data = include_str!("../../tests/data/WordBreakTest.txt");
+        assert_eq!(check_file(starts, data), 1823);
+    }
+
+    #[test]
+    fn test_boundaries()
+    {
+        let text = "one, two";
+        assert_eq!(next_word_boundary(text, 0), 3);
+        assert_eq!(next_word_boundary(text, 3), 4);
+        assert_eq!(next_word_boundary(text, 6), 8);
+        assert_eq!(previous_word_boundary(text, 8), 5);
+        assert_eq!(previous_word_boundary(text, 5), 4);
+    }
+}
@@ -0,0 +1,281 @@
+//! Applying glyph positioning lookups.
+
+use super::GlyphInfo;
+use super::layout::Gdef;
+use super::layout::Lookup;
+use super::layout::MARK;
+use super::layout::class;
+use super::layout::coverage_index;
+use super::next_glyph;
+use super::previous_glyph;
+
+use crate::parse::i16_at;
+use crate::parse::offset16;
+use crate::parse::u16_at;
+
+/// Lookup type of extension subtables in GPOS.
+pub(crate) const EXTENSION: u16 = 9;
+
+/// Apply a positioning lookup to every glyph in turn.
+///
+/// Cursive attachment and contextual lookups are not supported yet,
+/// and are ignored.
+pub(crate) fn apply_lookup(gdef: Gdef, lookup: &Lookup, glyphs: &mut [GlyphInfo])
+{
+    let mut i = 0;
+    while i < glyphs.len() {
+        if gdef.skips(lookup, glyphs[i].glyph_id) {
+            i += 1;
+            continue;
+        }
+        let next =
+            lookup.subtables.iter()
+            .find_map(|&subtable| apply_subtable(gdef, lookup, subtable, glyphs, i));
+        i = next.unwrap_or(i + 1);
+    }
+}
+
+/// Apply a subtable at the given glyph.
+///
+/// If the subtable applies, this returns the index
+/// of the next glyph to apply the lookup to.
+fn apply_subtable(
+    gdef: Gdef,
+    lookup: &Lookup,
+    subtable: &[u8],
+    glyphs: &mut [GlyphInfo],
+    i: usize,
+) -> Option<usize>
+{
+    let format = u16_at(subtable, 0)?;
+    let coverage = offset16(subtable, 2)?;
+    let index = coverage_index(coverage, glyphs[i].glyph_id)? as usize;
+
+    match (lookup.kind, format) {
+
+        // Single adjustment, the same for all glyphs.
+        (1, 1) => {
+            let value_format = u16_at(subtable, 4)?;
+            Value::read(subtable, 6, value_format)?.apply(&mut glyphs[i]);
+            Some(i + 1)
+        },
+
+        // Single adjustment, for each glyph.
+        (1, 2) => {
+            let value_format = u16_at(subtable, 4)?;
+            if index >= u16_at(subtable, 6)? as usize {
+                return None;
+            }
+            let offset = 8 + index * Value::size(value_format);
+            Value::read(subtable, offset, value_format)?.apply(&mut glyphs[i]);
+            Some(i + 1)
+        },
+
+        // Pair adjustment, for each pair of glyphs.
+        (2, 1) => {
+            let j = next_glyph(gdef, lookup, glyphs, i)?;
+            let (format1, format2) = (u16_at(subtable, 4)?, u16_at(subtable, 6)?);
+            if index >= u16_at(subtable, 8)? as usize {
+                return None;
+            }
+            let pair_set = offset16(subtable, 10 + 2 * index)?;
+            let record_size = 2 + Value::size(format1) + Value::size(format2);
+            let count = u16_at(pair_set, 0)? as usize;
+            let record = (0 .. count)
+                .map(|k| 2 + k * record_size)
+                .find(|&record| u16_at(pair_set, record) == Some(glyphs[j].glyph_id))?;
+            apply_pair(pair_set, record + 2, (format1, format2), glyphs, i, j)
+        },
+
+        // Pair adjustment, for each pair of glyph classes.
+        (2, 2) => {
+            let j = next_glyph(gdef, lookup, glyphs, i)?;
+            let (format1, format2) = (u16_at(subtable, 4)?, u16_at(subtable, 6)?);
+            let class1 = class(offset16(subtable, 8)?, glyphs[i].glyph_id) as usize;
+            let class2 = class(offset16(subtable, 10)?, glyphs[j].glyph_id) as usize;
+            let count1 = u16_at(subtable, 12)? as usize;
+            let count2 = u16_at(subtable, 14)? as usize;
+            if class1 >= count1 || class2 >= count2 {
+                return None;
+            }
+            let record_size = Value::size(format1) + Value::size(format2);
+            let record = 16 + (class1 * count2 + class2) * record_size;
+            apply_pair(subtable, record, (format1, format2), glyphs, i, j)
+        },
+
+        // Mark-to-base attachment.
+        (4, 1) => {
+            let base = preceding_base(gdef, glyphs, i)?;
+            let base_anchor = attachment_anchor(subtable, glyphs[base].glyph_id, index)?;
+            attach(subtable, index, base_anchor, glyphs, i, base)
+        },
+
+        // Mark-to-ligature attachment, to the last component of the ligature.
+        (5, 1) => {
+            let ligature = preceding_base(gdef, glyphs, i)?;
+            let ligature_coverage = offset16(subtable, 4)?;
+            let ligature_index = coverage_index(ligature_coverage, glyphs[ligature].glyph_id)?;
+            let class_count = u16_at(subtable, 6)? as usize;
+            let ligature_array = offset16(subtable, 10)?;
+            if ligature_index >= u16_at(ligature_array, 0)? {
+                return None;
+            }
+            let ligature_attach = offset16(ligature_array, 2 + 2 * ligature_index as usize)?;
+            let components = u16_at(ligature_attach, 0)? as usize;
+            let mark_class = mark_record(subtable, index)?.0 as usize;
+            let record = 2 + ((components.checked_sub(1)?) * class_count + mark_class) * 2;
+            let ligature_anchor = anchor(offset16(ligature_attach, record)?)?;
+            attach(subtable, index, ligature_anchor, glyphs, i, ligature)
+        },
+
+        // Mark-to-mark attachment, to the preceding mark.
+        (6, 1) => {
+            let base = previous_glyph(gdef, lookup, glyphs, i)?;
+            if gdef.glyph_class(glyphs[base].glyph_id) != MARK {
+                return None;
+            }
+            let base_anchor = attachment_anchor(subtable, glyphs[base].glyph_id, index)?;
+            attach(subtable, index, base_anchor, glyphs, i, base)
+        },
+
+        _ => None,
+
+    }
+}
+
+/// The index of the last glyph before `i` that is not a mark,
+/// which marks attach to regardless of the lookup flags.
+fn preceding_base(gdef: Gdef, glyphs: &[GlyphInfo], i: usize) -> Option<usize>
+{
+    (0 .. i).rev().find(|&j| gdef.glyph_class(glyphs[j].glyph_id) != MARK)
+}
+
+/// Apply the values of a pair adjustment record
+/// to both glyphs of the pair.
+///
+/// This returns the index of the second glyph,
+/// or the glyph after it if the second glyph was adjusted.
+fn apply_pair(
+    data: &[u8],
+    offset: usize,
+    (format1, format2): (u16, u16),
+    glyphs: &mut [GlyphInfo],
+    i: usize,
+    j: usize,
+) -> Option<usize>
+{
+    let value1 = Value::read(data, offset, format1)?;
+    let value2 = Value::read(data, offset + Value::size(format1), format2)?;
+    value1.apply(&mut glyphs[i]);
+    value2.apply(&mut glyphs[j]);
+    Some(if format2 == 0 { j } else { j + 1 })
+}
+
+/// The anchor on the base glyph, or the preceding mark for mark-to-mark,
+/// for the class of the mark with the given coverage index.
+///
+/// Mark-to-base and mark-to-mark subtables have the same structure.
+fn attachment_anchor(subtable: &[u8], base_glyph_id: u16, mark_index: usize)
+    -> Option<(i32, i32)>
+{
+    let base_index = coverage_index(offset16(subtable, 4)?, base_glyph_id)? as usize;
+    let class_count = u16_at(subtable, 6)? as usize;
+    let base_array = offset16(subtable, 10)?;
+    if base_index >= u16_at(base_array, 0)? as usize {
+        return None;
+    }
+    let mark_class = mark_record(subtable, mark_index)?.0 as usize;
+    if mark_class >= class_count {
+        return None;
+    }
+    let record = 2 + (base_index * class_count + mark_class) * 2;
+    anchor(offset16(base_array, record)?)
+}
+
+/// The class and the anchor of the mark with the given coverage index.
+fn mark_record(subtable: &[u8], mark_index: usize) -> Option<(u16, &[u8])>
+{
+    let mark_array = offset16(subtable, 8)?;
+    if mark_index >= u16_at(mark_array, 0)? as usize {
+        return None;
+    }
+    let record = 2 + 4 * mark_index;
+    Some((u16_at(mark_array, record)?, offset16(mark_array, record + 2)?))
+}
+
+/// Attach a mark so that its anchor coincides with the given anchor
+/// on the glyph it attaches to.
+///
+/// The mark is positioned once all lookups have been applied,
+/// as they may still change the advances of the glyphs in between.
+/// Attached marks do not advance the glyphs after them.
+fn attach(
+    subtable: &[u8],
+    mark_index: usize,
+    (base_x, base_y): (i32, i32),
+    glyphs: &mut [GlyphInfo],
+    i: usize,
+    base: usize,
+) -> Option<usize>
+{
+    let (mark_x, mark_y) = anchor(mark_record(subtable, mark_index)?.1)?;
+    let mark = &mut glyphs[i];
+    mark.attachment = Some((base, base_x - mark_x, base_y - mark_y));
+    mark.advance_x = 0;
+    mark.advance_y = 0;
+    Some(i + 1)
+}
+
+/// The coordinates of an anchor point.
+///
+/// Contour points and device tables of other formats are ignored.
+fn anchor(anchor: &[u8]) -> Option<(i32, i32)>
+{
+    let x = i16_at(anchor, 2)?;
+    let y = i16_at(anchor, 4)?;
+    Some((x as i32, y as i32))
+}
+
+/// Adjustments to the position and advance of a glyph.
+#[derive(Clone, Copy, Default)]
+struct Value
+{
+    x_placement: i32,
+    y_placement: i32,
+    x_advance: i32,
+    y_advance: i32,
+}
+
+impl Value
+{
+    /// The size of a value record with the given format in bytes.
+    fn size(format: u16) -> usize
+    {
+        2 * (format & 0xFF).count_ones() as usize
+    }
+
+    /// Read a value record with the given format.
+    ///
+    /// Device tables are ignored, as glyphs are not hinted.
+    fn read(data: &[u8], offset: usize, format: u16) -> Option<Self>
+    {
+        let mut fields = [0; 4];
+        let mut offset = offset;
+        for (bit, field) in fields.iter_mut().enumerate() {
+            if format & 1 << bit != 0 {
+                *field = i16_at(data, offset)? as i32;
+                offset += 2;
+            }
+        }
+        let [x_placement, y_placement, x_advance, y_advance] = fields;
+        Some(Self{x_placement, y_placement, x_advance, y_advance})
+    }
+
+    fn apply(self, glyph: &mut GlyphInfo)
+    {
+        glyph.offset_x += self.x_placement;
+        glyph.offset_y += self.y_placement;
+        glyph.advance_x += self.x_advance;
+        glyph.advance_y += self.y_advance;
+    }
+}
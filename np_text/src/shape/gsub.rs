@@ -0,0 +1,152 @@
+//! Applying glyph substitution lookups.
+
+use super::GlyphInfo;
+use super::layout::Gdef;
+use super::layout::Lookup;
+use super::layout::coverage_index;
+use super::next_glyph;
+
+use crate::parse::i16_at;
+use crate::parse::offset16;
+use crate::parse::u16_at;
+
+/// Lookup type of extension subtables in GSUB.
+pub(crate) const EXTENSION: u16 = 7;
+
+/// Apply a substitution lookup to every glyph in turn.
+///
+/// Contextual and reverse chaining lookups are not supported yet,
+/// and are ignored.
+pub(crate) fn apply_lookup(gdef: Gdef, lookup: &Lookup, glyphs: &mut Vec<GlyphInfo>)
+{
+    let mut i = 0;
+    while i < glyphs.len() {
+        if gdef.skips(lookup, glyphs[i].glyph_id) {
+            i += 1;
+            continue;
+        }
+        let next =
+            lookup.subtables.iter()
+            .find_map(|&subtable| apply_subtable(gdef, lookup, subtable, glyphs, i));
+        i = next.unwrap_or(i + 1);
+    }
+}
+
+/// Apply a subtable at the given glyph.
+///
+/// If the subtable applies, this returns the index of the glyph
+/// after those that were substituted.
+fn apply_subtable(
+    gdef: Gdef,
+    lookup: &Lookup,
+    subtable: &[u8],
+    glyphs: &mut Vec<GlyphInfo>,
+    i: usize,
+) -> Option<usize>
+{
+    let format = u16_at(subtable, 0)?;
+    let coverage = offset16(subtable, 2)?;
+    let index = coverage_index(coverage, glyphs[i].glyph_id)? as usize;
+
+    match (lookup.kind, format) {
+
+        // Single substitution by adding a delta.
+        (1, 1) => {
+            let delta = i16_at(subtable, 4)?;
+            glyphs[i].glyph_id = glyphs[i].glyph_id.wrapping_add(delta as u16);
+            Some(i + 1)
+        },
+
+        // Single substitution from an array.
+        (1, 2) => {
+            glyphs[i].glyph_id = array_item(subtable, 4, index)?;
+            Some(i + 1)
+        },
+
+        // Multiple substitution, which decomposes a glyph.
+        (2, 1) => {
+            let sequence = array_offset(subtable, 4, index)?;
+            let count = u16_at(sequence, 0)? as usize;
+            let replacement = (0 .. count)
+                .map(|j| u16_at(sequence, 2 + 2 * j))
+                .collect::<Option<Vec<_>>>()?;
+            let cluster = glyphs[i].cluster;
+            glyphs.splice(i ..= i, replacement.into_iter().map(|glyph_id| {
+                GlyphInfo{glyph_id, cluster, ..GlyphInfo::default()}
+            }));
+            Some(i + count)
+        },
+
+        // Alternate substitution, of which the first alternate is used.
+        (3, 1) => {
+            let alternates = array_offset(subtable, 4, index)?;
+            glyphs[i].glyph_id = array_item(alternates, 0, 0)?;
+            Some(i + 1)
+        },
+
+        // Ligature substitution, trying each ligature in order.
+        (4, 1) => {
+            let ligatures = array_offset(subtable, 4, index)?;
+            let count = u16_at(ligatures, 0)? as usize;
+            (0 .. count).find_map(|j| {
+                let ligature = offset16(ligatures, 2 + 2 * j)?;
+                apply_ligature(gdef, lookup, ligature, glyphs, i)
+            })
+        },
+
+        _ => None,
+
+    }
+}
+
+fn apply_ligature(
+    gdef: Gdef,
+    lookup: &Lookup,
+    ligature: &[u8],
+    glyphs: &mut Vec<GlyphInfo>,
+    i: usize,
+) -> Option<usize>
+{
+    let ligature_glyph_id = u16_at(ligature, 0)?;
+    let count = u16_at(ligature, 2)? as usize;
+
+    // The components after the first must follow,
+    // possibly with glyphs the lookup skips in between.
+    let mut components = vec![i];
+    for j in 1 .. count {
+        let next = next_glyph(gdef, lookup, glyphs, components[j - 1])?;
+        if glyphs[next].glyph_id != u16_at(ligature, 4 + 2 * (j - 1))? {
+            return None;
+        }
+        components.push(next);
+    }
+
+    // The ligature takes the place of the first component,
+    // and the glyphs that were skipped remain after it.
+    let cluster = components.iter().map(|&j| glyphs[j].cluster).min()?;
+    glyphs[i] = GlyphInfo{glyph_id: ligature_glyph_id, cluster, ..GlyphInfo::default()};
+    for &j in components[1 ..].iter().rev() {
+        glyphs.remove(j);
+    }
+    Some(i + 1)
+}
+
+/// The item at the given index of an array of 16-bit values,
+/// preceded by its length at `offset`.
+fn array_item(data: &[u8], offset: usize, index: usize) -> Option<u16>
+{
+    if index >= u16_at(data, offset)? as usize {
+        return None;
+    }
+    u16_at(data, offset + 2 + 2 * index)
+}
+
+/// Follow the offset at the given index of an array of offsets,
+/// preceded by its length at `offset`.
+fn array_offset(data: &[u8], offset: usize, index: usize) -> Option<&[u8]>
+{
+    if index >= u16_at(data, offset)? as usize {
+        return None;
+    }
+    offset16(data, offset + 2 + 2 * index)
+}
@@ -0,0 +1,275 @@
+//! Parts of the OpenType layout tables shared by GSUB and GPOS,
+//! and the glyph classes from GDEF that lookups depend on.
+
+use crate::Tag;
+use crate::parse::offset16;
+use crate::parse::offset32;
+use crate::parse::u16_at;
+
+use std::convert::TryInto;
+
+/// GDEF glyph class of mark glyphs.
+pub(crate) const MARK: u16 = 3;
+
+const IGNORE_BASE_GLYPHS: u16 = 0x0002;
+const IGNORE_LIGATURES: u16 = 0x0004;
+const IGNORE_MARKS: u16 = 0x0008;
+const USE_MARK_FILTERING_SET: u16 = 0x0010;
+const MARK_ATTACHMENT_TYPE: u16 = 0xFF00;
+
+/// Index of a glyph in a coverage table.
+pub(crate) fn coverage_index(coverage: &[u8], glyph_id: u16) -> Option<u16>
+{
+    match u16_at(coverage, 0)? {
+        1 => {
+            let count = u16_at(coverage, 2)?;
+            let (mut low, mut high) = (0, count);
+            while low < high {
+                let middle = low + (high - low) / 2;
+                let other = u16_at(coverage, 4 + 2 * middle as usize)?;
+                if other == glyph_id { return Some(middle); }
+                if other < glyph_id { low = middle + 1; } else { high = middle; }
+            }
+            None
+        },
+        2 => {
+            let count = u16_at(coverage, 2)?;
+            for i in 0 .. count as usize {
+                let record = 4 + 6 * i;
+                let start = u16_at(coverage, record)?;
+                let end = u16_at(coverage, record + 2)?;
+                if start <= glyph_id && glyph_id <= end {
+                    let start_index = u16_at(coverage, record + 4)?;
+                    return start_index.checked_add(glyph_id - start);
+                }
+            }
+            None
+        },
+        _ => None,
+    }
+}
+
+/// Class of a glyph in a class definition table.
+///
+/// Glyphs that are not listed are in class zero.
+pub(crate) fn class(class_def: &[u8], glyph_id: u16) -> u16
+{
+    let class = || match u16_at(class_def, 0)? {
+        1 => {
+            let start = u16_at(class_def, 2)?;
+            let count = u16_at(class_def, 4)?;
+            let index = glyph_id.checked_sub(start)?;
+            if index >= count { return None; }
+            u16_at(class_def, 6 + 2 * index as usize)
+        },
+        2 => {
+            let count = u16_at(class_def, 2)?;
+            (0 .. count as usize).find_map(|i| {
+                let record = 4 + 6 * i;
+                let start = u16_at(class_def, record)?;
+                let end = u16_at(class_def, record + 2)?;
+                if start <= glyph_id && glyph_id <= end { u16_at(class_def, record + 4) }
+                else { None }
+            })
+        },
+        _ => None,
+    };
+    class().unwrap_or(0)
+}
+
+/// Glyph definition table.
+#[derive(Clone, Copy)]
+pub(crate) struct Gdef<'a>
+{
+    data: &'a [u8],
+}
+
+impl<'a> Gdef<'a>
+{
+    /// Wrap the GDEF table, which may be missing.
+    pub(crate) fn new(data: Option<&'a [u8]>) -> Self
+    {
+        Self{data: data.unwrap_or(&[])}
+    }
+
+    /// The class of a glyph: base, ligature, mark, or component.
+    ///
+    /// Glyphs without a class are treated as bases by lookups.
+    pub(crate) fn glyph_class(&self, glyph_id: u16) -> u16
+    {
+        offset16(self.data, 4).map_or(0, |class_def| class(class_def, glyph_id))
+    }
+
+    fn mark_attachment_class(&self, glyph_id: u16) -> u16
+    {
+        offset16(self.data, 10).map_or(0, |class_def| class(class_def, glyph_id))
+    }
+
+    fn is_in_mark_glyph_set(&self, glyph_id: u16, set: u16) -> bool
+    {
+        let is_in_set = || {
+            if u16_at(self.data, 2)? < 2 { return None; }
+            let sets = offset16(self.data, 12)?;
+            if set >= u16_at(sets, 2)? { return None; }
+            let coverage = offset32(sets, 4 + 4 * set as usize)?;
+            coverage_index(coverage, glyph_id)
+        };
+        is_in_set().is_some()
+    }
+
+    /// Whether a lookup skips over the glyph,
+    /// as requested by its lookup flags.
+    pub(crate) fn skips(&self, lookup: &Lookup, glyph_id: u16) -> bool
+    {
+        let flag = lookup.flag;
+        match self.glyph_class(glyph_id) {
+            1 => flag & IGNORE_BASE_GLYPHS != 0,
+            2 => flag & IGNORE_LIGATURES != 0,
+            MARK => {
+                if flag & IGNORE_MARKS != 0 {
+                    return true;
+                }
+                if let Some(set) = lookup.mark_filtering_set {
+                    return !self.is_in_mark_glyph_set(glyph_id, set);
+                }
+                let attachment_type = (flag & MARK_ATTACHMENT_TYPE) >> 8;
+                attachment_type != 0 && self.mark_attachment_class(glyph_id) != attachment_type
+            },
+            _ => false,
+        }
+    }
+}
+
+/// A GSUB or GPOS table.
+#[derive(Clone, Copy)]
+pub(crate) struct LayoutTable<'a>
+{
+    data: &'a [u8],
+}
+
+/// A lookup of a GSUB or GPOS table, with extensions resolved.
+pub(crate) struct Lookup<'a>
+{
+    pub(crate) kind: u16,
+    pub(crate) flag: u16,
+    pub(crate) mark_filtering_set: Option<u16>,
+    pub(crate) subtables: Vec<&'a [u8]>,
+}
+
+impl<'a> LayoutTable<'a>
+{
+    /// Wrap a GSUB or GPOS table, which may be missing.
+    pub(crate) fn new(data: Option<&'a [u8]>) -> Self
+    {
+        Self{data: data.unwrap_or(&[])}
+    }
+
+    /// The indices of the lookups of the enabled features
+    /// for the given script and language, in the order they apply.
+    ///
+    /// Scripts the table does not know fall back to the default script,
+    /// and languages it does not know fall back to the default language.
+    pub(crate) fn lookup_indices(
+        &self,
+        script: Tag,
+        language: Option<Tag>,
+        enabled: impl Fn(Tag) -> bool,
+    ) -> Vec<u16>
+    {
+        let mut indices = Vec::new();
+        self.collect_lookup_indices(script, language, enabled, &mut indices);
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
+    fn collect_lookup_indices(
+        &self,
+        script: Tag,
+        language: Option<Tag>,
+        enabled: impl Fn(Tag) -> bool,
+        indices: &mut Vec<u16>,
+    ) -> Option<()>
+    {
+        let scripts = offset16(self.data, 4)?;
+        let script =
+            [script, Tag::DEFAULT, Tag(*b"dflt"), Tag(*b"latn")].iter()
+            .find_map(|&tag| find_record(scripts, 0, tag))?;
+
+        let lang_sys =
+            language.and_then(|language| find_record(script, 2, language))
+            .or_else(|| offset16(script, 0))?;
+
+        let features = offset16(self.data, 6)?;
+        let mut push_feature = |index: u16, required: bool| -> Option<()> {
+            let record = 2 + 6 * index as usize;
+            let tag = Tag(features.get(record .. record + 4)?.try_into().ok()?);
+            if required || enabled(tag) {
+                let feature = offset16(features, record + 4)?;
+                let count = u16_at(feature, 2)?;
+                for i in 0 .. count as usize {
+                    indices.push(u16_at(feature, 4 + 2 * i)?);
+                }
+            }
+            Some(())
+        };
+
+        let required = u16_at(lang_sys, 2)?;
+        if required != 0xFFFF {
+            push_feature(required, true);
+        }
+        let count = u16_at(lang_sys, 4)?;
+        for i in 0 .. count as usize {
+            push_feature(u16_at(lang_sys, 6 + 2 * i)?, false);
+        }
+        Some(())
+    }
+
+    /// The lookup with the given index.
+    ///
+    /// Extension subtables, which have the given lookup type,
+    /// are replaced by the subtables they refer to.
+    pub(crate) fn lookup(&self, index: u16, extension: u16) -> Option<Lookup<'a>>
+    {
+        let lookups = offset16(self.data, 8)?;
+        if index >= u16_at(lookups, 0)? {
+            return None;
+        }
+        let lookup = offset16(lookups, 2 + 2 * index as usize)?;
+        let mut kind = u16_at(lookup, 0)?;
+        let flag = u16_at(lookup, 2)?;
+        let count = u16_at(lookup, 4)? as usize;
+        let mark_filtering_set =
+            if flag & USE_MARK_FILTERING_SET != 0 { u16_at(lookup, 6 + 2 * count) }
+            else { None };
+
+        let mut subtables = Vec::with_capacity(count);
+        for i in 0 .. count {
+            let mut subtable = offset16(lookup, 6 + 2 * i)?;
+            if kind == extension {
+                subtable = offset32(subtable, 4)?;
+            }
+            subtables.push(subtable);
+        }
+        if kind == extension {
+            let first = offset16(lookup, 6)?;
+            kind = u16_at(first, 2)?;
+        }
+
+        Some(Lookup{kind, flag, mark_filtering_set, subtables})
+    }
+}
+
+/// Find the record with the given tag in a list of records
+/// of tags and 16-bit offsets relative to the list, and follow it.
+///
+/// The records are preceded by their count at `count_offset`.
+fn find_record(list: &[u8], count_offset: usize, tag: Tag) -> Option<&[u8]>
+{
+    let count = u16_at(list, count_offset)?;
+    (0 .. count as usize).find_map(|i| {
+        let record = count_offset + 2 + 6 * i;
+        if list.get(record .. record + 4)? != tag.0 { return None; }
+        offset16(list, record + 4)
+    })
+}
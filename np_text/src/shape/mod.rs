@@ -0,0 +1,496 @@
+//! Turning text into positioned glyphs.
+//!
+//! Shaping maps characters to glyphs, applies the glyph substitutions
+//! of the GSUB table, such as ligatures, and the glyph positioning
+//! of the GPOS table, such as kerning and mark attachment.
+//! Typefaces without a GPOS table are kerned with their kern table.
+
+use self::layout::Gdef;
+use self::layout::LayoutTable;
+use self::layout::Lookup;
+use self::layout::MARK;
+
+use crate::Direction;
+use crate::Tag;
+use crate::Typeface;
+use crate::cmap::is_variation_selector;
+use crate::mirror;
+
+mod gpos;
+mod gsub;
+mod layout;
+
+/// Options that affect how text is shaped.
+#[derive(Clone, Copy, Debug)]
+pub struct ShapingOptions<'a>
+{
+    /// The OpenType script tag of the text, such as `latn`.
+    ///
+    /// Typefaces often enable fewer features for the default script
+    /// than for specific scripts, so this is worth setting.
+    pub script: Tag,
+
+    /// The OpenType language system tag of the text, such as `NLD `.
+    ///
+    /// If [`None`], the default language system of the script is used.
+    pub language: Option<Tag>,
+
+    /// Features to enable or disable,
+    /// in addition to [`ShapingOptions::DEFAULT_FEATURES`].
+    ///
+    /// Later entries take precedence over earlier entries.
+    pub features: &'a [(Tag, bool)],
+
+    /// The direction of the text.
+    ///
+    /// Right-to-left text has its brackets and other characters mirrored,
+    /// and its glyphs are given from left to right, which is the reverse of
+    /// the order of the characters they are made from.
+    /// Text with mixed directions is to be split into runs first,
+    /// as is done by [`Paragraph::layout`][`crate::Paragraph::layout`].
+    pub direction: Direction,
+}
+
+impl<'a> ShapingOptions<'a>
+{
+    /// Features that are enabled unless disabled explicitly.
+    ///
+    /// Contextual and chaining contextual lookups
+    /// (GSUB lookup types 5 and 6, GPOS lookup types 7 and 8) are not supported,
+    /// and are ignored, as are reverse chaining substitutions and cursive attachment.
+    /// `calt`, which is made up of such lookups, is therefore not enabled by default,
+    /// and enabling it only applies whatever other lookups it has.
+    pub const DEFAULT_FEATURES: [Tag; 8] = [
+        Tag(*b"ccmp"), Tag(*b"locl"), Tag(*b"rlig"), Tag(*b"liga"),
+        Tag(*b"clig"), Tag(*b"kern"), Tag(*b"mark"), Tag(*b"mkmk"),
+    ];
+
+    /// Whether the given feature is enabled.
+    pub fn is_enabled(&self, feature: Tag) -> bool
+    {
+        self.features.iter().rev()
+            .find(|&&(tag, _)| tag == feature)
+            .map_or_else(|| Self::DEFAULT_FEATURES.contains(&feature), |&(_, on)| on)
+    }
+}
+
+impl<'a> Default for ShapingOptions<'a>
+{
+    fn default() -> Self
+    {
+        Self{
+            script: Tag::DEFAULT,
+            language: None,
+            features: &[],
+            direction: Direction::LeftToRight,
+        }
+    }
+}
+
+/// A glyph that results from shaping text.
+///
+/// All measurements are in font units.
+/// Each glyph is drawn at the pen position plus its offset,
+/// after which the pen position moves by its advance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShapedGlyph
+{
+    /// The glyph identifier.
+    pub glyph_id: u16,
+
+    /// The byte offset into the text of the first character
+    /// the glyph was made from.
+    ///
+    /// Ligatures have the cluster of their first character,
+    /// and glyphs made by decomposing a character share its cluster.
+    pub cluster: usize,
+
+    /// How far the pen position moves horizontally.
+    pub advance_x: f64,
+
+    /// How far the pen position moves vertically.
+    pub advance_y: f64,
+
+    /// Horizontal displacement from the pen position.
+    pub offset_x: f64,
+
+    /// Vertical displacement from the pen position.
+    pub offset_y: f64,
+}
+
+/// A glyph during shaping.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct GlyphInfo
+{
+    pub(crate) glyph_id: u16,
+    pub(crate) cluster: usize,
+    pub(crate) advance_x: i32,
+    pub(crate) advance_y: i32,
+    pub(crate) offset_x: i32,
+    pub(crate) offset_y: i32,
+
+    /// The glyph a mark is attached to, with the difference
+    /// between the anchors of the glyph and the mark.
+    pub(crate) attachment: Option<(usize, i32, i32)>,
+}
+
+/// The index of the next glyph after `i` that the lookup does not skip.
+pub(crate) fn next_glyph(gdef: Gdef, lookup: &Lookup, glyphs: &[GlyphInfo], i: usize)
+    -> Option<usize>
+{
+    (i + 1 .. glyphs.len()).find(|&j| !gdef.skips(lookup, glyphs[j].glyph_id))
+}
+
+/// The index of the last glyph before `i` that the lookup does not skip.
+pub(crate) fn previous_glyph(gdef: Gdef, lookup: &Lookup, glyphs: &[GlyphInfo], i: usize)
+    -> Option<usize>
+{
+    (0 .. i).rev().find(|&j| !gdef.skips(lookup, glyphs[j].glyph_id))
+}
+
+/// Shape text with the given typeface.
+pub(crate) fn shape_text(typeface: &Typeface, text: &str, options: &ShapingOptions)
+    -> Vec<ShapedGlyph>
+{
+    let face = &typeface.inner;
+    let table = |tag| face.table_data(ttf_parser::Tag::from_bytes(tag));
+
+    let rtl = options.direction == Direction::RightToLeft;
+    let mut glyphs = Vec::with_capacity(text.len());
+    let mut chars = text.char_indices().peekable();
+    while let Some((cluster, c)) = chars.next() {
+        if is_variation_selector(c) {
+            continue;
+        }
+        let c = if rtl { mirror(c).unwrap_or(c) } else { c };
+        let selector = chars.peek().map(|&(_, s)| s).filter(|&s| is_variation_selector(s));
+        let glyph_id = match selector {
+            Some(selector) => typeface.glyph_variation_index(c, selector),
+            None => typeface.glyph_index(c),
+        };
+        let glyph_id = glyph_id.unwrap_or(Typeface::NOTDEF);
+        glyphs.push(GlyphInfo{glyph_id, cluster, ..GlyphInfo::default()});
+    }
+
+    let gdef = Gdef::new(table(b"GDEF"));
+    let gsub = LayoutTable::new(table(b"GSUB"));
+    let gpos = LayoutTable::new(table(b"GPOS"));
+    let advance = |glyph_id| {
+        face.glyph_hor_advance(ttf_parser::GlyphId(glyph_id)).map_or(0, i32::from)
+    };
+    let has_gpos_kerning = options.is_enabled(Tag(*b"kern")) &&
+        !gpos.lookup_indices(options.script, options.language, |tag| tag == Tag(*b"kern"))
+        .is_empty();
+
+    substitute(gdef, gsub, options, &mut glyphs);
+    for glyph in &mut glyphs {
+        glyph.advance_x = advance(glyph.glyph_id);
+    }
+    position(gdef, gpos, options, &mut glyphs);
+    if options.is_enabled(Tag(*b"kern")) && !has_gpos_kerning {
+        kern(typeface, gdef, rtl, &mut glyphs);
+    }
+    resolve_attachments(rtl, &mut glyphs);
+    if rtl {
+        glyphs.reverse();
+    }
+
+    glyphs.iter().map(|glyph| ShapedGlyph{
+        glyph_id: glyph.glyph_id,
+        cluster: glyph.cluster,
+        advance_x: glyph.advance_x as f64,
+        advance_y: glyph.advance_y as f64,
+        offset_x: glyph.offset_x as f64,
+        offset_y: glyph.offset_y as f64,
+    }).collect()
+}
+
+/// Apply the substitution lookups of the enabled features, in order.
+fn substitute(
+    gdef: Gdef,
+    gsub: LayoutTable,
+    options: &ShapingOptions,
+    glyphs: &mut Vec<GlyphInfo>,
+)
+{
+    let enabled = |tag| options.is_enabled(tag);
+    let indices = gsub.lookup_indices(options.script, options.language, enabled);
+    for index in indices {
+        if let Some(lookup) = gsub.lookup(index, gsub::EXTENSION) {
+            gsub::apply_lookup(gdef, &lookup, glyphs);
+        }
+    }
+}
+
+/// Apply the positioning lookups of the enabled features, in order.
+fn position(gdef: Gdef, gpos: LayoutTable, options: &ShapingOptions, glyphs: &mut [GlyphInfo])
+{
+    let enabled = |tag| options.is_enabled(tag);
+    let indices = gpos.lookup_indices(options.script, options.language, enabled);
+    for index in indices {
+        if let Some(lookup) = gpos.lookup(index, gpos::EXTENSION) {
+            gpos::apply_lookup(gdef, &lookup, glyphs);
+        }
+    }
+}
+
+/// Kern adjacent glyphs with the legacy kern table.
+///
+/// Only horizontal pair kerning is supported.
+/// Pairs are looked up from left to right,
+/// which for right-to-left text is from the last glyph to the first.
+fn kern(typeface: &Typeface, gdef: Gdef, rtl: bool, glyphs: &mut [GlyphInfo])
+{
+    let subtables =
+        typeface.inner.kerning_subtables()
+        .filter(|subtable| subtable.is_horizontal() && !subtable.is_variable())
+        .filter(|subtable| !subtable.has_cross_stream() && !subtable.has_state_machine())
+        .collect::<Vec<_>>();
+    if subtables.is_empty() {
+        return;
+    }
+
+    let is_base = |glyph: &GlyphInfo| gdef.glyph_class(glyph.glyph_id) != MARK;
+    let bases = (0 .. glyphs.len()).filter(|&i| is_base(&glyphs[i])).collect::<Vec<_>>();
+    for pair in bases.windows(2) {
+        let (left, right) = if rtl { (pair[1], pair[0]) } else { (pair[0], pair[1]) };
+        let right_glyph = ttf_parser::GlyphId(glyphs[right].glyph_id);
+        let left_glyph = ttf_parser::GlyphId(glyphs[left].glyph_id);
+        let kerning: i32 =
+            subtables.iter()
+            .filter_map(|subtable| subtable.glyphs_kerning(left_glyph, right_glyph))
+            .map(i32::from)
+            .sum();
+        glyphs[left].advance_x += kerning;
+    }
+}
+
+/// Position attached marks relative to the glyphs they are attached to,
+/// now that the advances of the glyphs in between are final.
+///
+/// The glyphs are still in logical order, so for right-to-left text
+/// the glyphs in between come after the mark when drawn.
+fn resolve_attachments(rtl: bool, glyphs: &mut [GlyphInfo])
+{
+    for i in 0 .. glyphs.len() {
+        if let Some((base, x, y)) = glyphs[i].attachment {
+            let between: i32 =
+                if rtl { glyphs[base + 1 ..= i].iter().map(|glyph| glyph.advance_x).sum() }
+                else { -glyphs[base .. i].iter().map(|glyph| glyph.advance_x).sum::<i32>() };
+            let base = glyphs[base];
+            glyphs[i].offset_x = base.offset_x + x + between;
+            glyphs[i].offset_y = base.offset_y + y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use crate::test_font::be;
+    use crate::test_font::font_file;
+    use crate::test_font::typeface;
+
+    const F: u16 = 1;
+    const I: u16 = 2;
+    const FI: u16 = 3;
+    const A: u16 = 4;
+    const V: u16 = 5;
+    const M: u16 = 6;
+
+    /// A GSUB or GPOS table with a single feature for the default script,
+    /// which consists of a single lookup with a single subtable.
+    fn layout_table(feature: Tag, kind: u16, flag: u16, subtable: &[u8]) -> Vec<u8>
+    {
+        let mut table = be(&[1, 0, 10, 30, 44]);
+        table.extend_from_slice(&be(&[1]));
+        table.extend_from_slice(&Tag::DEFAULT.0);
+        table.extend_from_slice(&be(&[8, 4, 0, 0, 0xFFFF, 1, 0]));
+        table.extend_from_slice(&be(&[1]));
+        table.extend_from_slice(&feature.0);
+        table.extend_from_slice(&be(&[8, 0, 1, 0]));
+        table.extend_from_slice(&be(&[1, 4, kind, flag, 1, 8]));
+        table.extend_from_slice(subtable);
+        table
+    }
+
+    /// A GDEF table in which only the given glyph is a mark.
+    fn gdef_table(mark: u16) -> Vec<u8>
+    {
+        be(&[1, 0, 12, 0, 0, 0, 2, 1, mark, mark, MARK])
+    }
+
+    fn glyph_infos(glyph_ids: &[u16], advance_x: i32) -> Vec<GlyphInfo>
+    {
+        glyph_ids.iter().enumerate()
+            .map(|(cluster, &glyph_id)| {
+                GlyphInfo{glyph_id, cluster, advance_x, ..GlyphInfo::default()}
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ligature()
+    {
+        // The ligature lookup ignores marks,
+        // so the mark between the components does not prevent the ligature.
+        let subtable = be(&[1, 8, 1, 14, 1, 1, F, 1, 4, FI, 2, I]);
+        let gsub = layout_table(Tag(*b"liga"), 4, 0x0008, &subtable);
+        let gdef = gdef_table(M);
+        let (gdef, gsub) = (Gdef::new(Some(&gdef)), LayoutTable::new(Some(&gsub)));
+
+        let mut glyphs = glyph_infos(&[F, M, I, F, F], 0);
+        substitute(gdef, gsub, &ShapingOptions::default(), &mut glyphs);
+        let result = glyphs.iter().map(|g| (g.glyph_id, g.cluster)).collect::<Vec<_>>();
+        assert_eq!(result, [(FI, 0), (M, 1), (F, 3), (F, 4)]);
+
+        // Disabled features are not applied.
+        let features = [(Tag(*b"liga"), false)];
+        let options = ShapingOptions{features: &features, ..Default::default()};
+        let mut glyphs = glyph_infos(&[F, I], 0);
+        substitute(gdef, gsub, &options, &mut glyphs);
+        assert_eq!(glyphs.iter().map(|g| g.glyph_id).collect::<Vec<_>>(), [F, I]);
+    }
+
+    #[test]
+    fn test_pair_adjustment()
+    {
+        // A and V are kerned by -80 units, when A comes first.
+        let subtable = be(&[1, 12, 4, 0, 1, 18, 1, 1, A, 1, V, -80i16 as u16]);
+        let gpos = layout_table(Tag(*b"kern"), 2, 0, &subtable);
+        let gpos = LayoutTable::new(Some(&gpos));
+
+        let mut glyphs = glyph_infos(&[A, V, A, A], 1000);
+        position(Gdef::new(None), gpos, &ShapingOptions::default(), &mut glyphs);
+        let advances = glyphs.iter().map(|g| g.advance_x).collect::<Vec<_>>();
+        assert_eq!(advances, [920, 1000, 1000, 1000]);
+    }
+
+    #[test]
+    fn test_mark_to_base()
+    {
+        // The anchor of the mark at (50, 0)
+        // attaches to the anchor of the base at (300, 500).
+        let subtable = be(&[
+            1, 12, 18, 1, 24, 36,
+            1, 1, M,
+            1, 1, A,
+            1, 0, 6, 1, 50, 0,
+            1, 4, 1, 300, 500,
+        ]);
+        let gpos = layout_table(Tag(*b"mark"), 4, 0, &subtable);
+        let gdef = gdef_table(M);
+        let (gdef, gpos) = (Gdef::new(Some(&gdef)), LayoutTable::new(Some(&gpos)));
+
+        let mut glyphs = glyph_infos(&[A, M, V, M], 600);
+        position(gdef, gpos, &ShapingOptions::default(), &mut glyphs);
+        let mut rtl_glyphs = glyphs.clone();
+        resolve_attachments(false, &mut glyphs);
+        let mark = glyphs[1];
+        assert_eq!((mark.offset_x, mark.offset_y, mark.advance_x), (-350, 500, 0));
+
+        // In right-to-left text the mark is drawn before the base,
+        // but has no advance, so it is offset by the anchors only.
+        resolve_attachments(true, &mut rtl_glyphs);
+        assert_eq!((rtl_glyphs[1].offset_x, rtl_glyphs[1].offset_y), (250, 500));
+
+        // Marks on bases without an anchor stay where they are.
+        let mark = glyphs[3];
+        assert_eq!((mark.offset_x, mark.offset_y, mark.advance_x), (0, 0, 600));
+    }
+
+    #[test]
+    fn test_mark_to_ligature()
+    {
+        // The anchor of the mark at (50, 0) attaches to the anchor
+        // of the second and last component of the ligature at (700, 450).
+        let subtable = be(&[
+            1, 12, 18, 1, 24, 36,
+            1, 1, M,
+            1, 1, FI,
+            1, 0, 6, 1, 50, 0,
+            1, 4, 2, 6, 12, 1, 100, 400, 1, 700, 450,
+        ]);
+        let gpos = layout_table(Tag(*b"mark"), 5, 0, &subtable);
+        let gdef = gdef_table(M);
+        let (gdef, gpos) = (Gdef::new(Some(&gdef)), LayoutTable::new(Some(&gpos)));
+
+        let mut glyphs = glyph_infos(&[FI, M], 1000);
+        position(gdef, gpos, &ShapingOptions::default(), &mut glyphs);
+        resolve_attachments(false, &mut glyphs);
+        let mark = glyphs[1];
+        assert_eq!((mark.offset_x, mark.offset_y, mark.advance_x), (-350, 450, 0));
+    }
+
+    #[test]
+    fn test_mark_to_mark()
+    {
+        // The anchor of the mark at (50, 0)
+        // attaches to the anchor of the preceding mark at (20, 200).
+        let subtable = be(&[
+            1, 12, 18, 1, 24, 36,
+            1, 1, M,
+            1, 1, M,
+            1, 0, 6, 1, 50, 0,
+            1, 4, 1, 20, 200,
+        ]);
+        let gpos = layout_table(Tag(*b"mkmk"), 6, 0, &subtable);
+        let gdef = gdef_table(M);
+        let (gdef, gpos) = (Gdef::new(Some(&gdef)), LayoutTable::new(Some(&gpos)));
+
+        let mut glyphs = glyph_infos(&[A, M, M], 0);
+        glyphs[1].offset_x = 100;
+        position(gdef, gpos, &ShapingOptions::default(), &mut glyphs);
+        resolve_attachments(false, &mut glyphs);
+
+        // The first mark follows a base, not a mark, so it does not attach.
+        assert_eq!(glyphs[1].attachment, None);
+        let mark = glyphs[2];
+        assert_eq!(mark.attachment, Some((1, -30, 200)));
+        assert_eq!((mark.offset_x, mark.offset_y), (70, 200));
+    }
+
+    #[test]
+    fn test_kern_table()
+    {
+        // A and V are kerned by -80 units in a format 0 subtable,
+        // and the characters A and V map to them in a format 6 subtable.
+        let kern_table = be(&[0, 1, 0, 20, 0x0001, 1, 6, 0, 0, A, V, -80i16 as u16]);
+        let mut cmap = be(&[0, 1, 3, 1, 0, 12, 6, 54, 0, 'A' as u16, 22]);
+        cmap.extend(be(&[A, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, V]));
+        let tables = [(b"cmap", cmap.clone()), (b"kern", kern_table.clone())];
+        let data = font_file(&[1000; 7], &tables);
+        let legacy = typeface(&data);
+        let gdef = gdef_table(M);
+        let gdef = Gdef::new(Some(&gdef));
+
+        // Marks are skipped, and the left glyph of each pair is kerned,
+        // which for right-to-left text comes last.
+        let mut glyphs = glyph_infos(&[A, M, V, A], 1000);
+        kern(&legacy, gdef, false, &mut glyphs);
+        let advances = glyphs.iter().map(|g| g.advance_x).collect::<Vec<_>>();
+        assert_eq!(advances, [920, 1000, 1000, 1000]);
+        let mut glyphs = glyph_infos(&[V, M, A], 1000);
+        kern(&legacy, gdef, true, &mut glyphs);
+        let advances = glyphs.iter().map(|g| g.advance_x).collect::<Vec<_>>();
+        assert_eq!(advances, [1000, 1000, 920]);
+
+        // The kern table is used only if the GPOS table does not kern,
+        // and only if kerning is enabled.
+        let shape = |typeface: &Typeface, features| {
+            let options = ShapingOptions{features, ..Default::default()};
+            typeface.shape("AV", &options).iter().map(|g| g.advance_x).collect::<Vec<_>>()
+        };
+        let disabled = [(Tag(*b"kern"), false)];
+        assert_eq!(shape(&legacy, &[]), [920.0, 1000.0]);
+        assert_eq!(shape(&legacy, &disabled), [1000.0, 1000.0]);
+
+        let subtable = be(&[1, 12, 4, 0, 1, 18, 1, 1, A, 1, V, -50i16 as u16]);
+        let gpos = layout_table(Tag(*b"kern"), 2, 0, &subtable);
+        let tables = [(b"GPOS", gpos), (b"cmap", cmap), (b"kern", kern_table)];
+        let data = font_file(&[1000; 7], &tables);
+        assert_eq!(shape(&typeface(&data), &[]), [950.0, 1000.0]);
+    }
+}
@@ -0,0 +1,34 @@
+use std::fmt;
+
+/// Four-byte identifier used throughout OpenType,
+/// for scripts, languages, features, and variation axes.
+///
+/// Tags shorter than four characters are padded with spaces,
+/// as in `Tag(*b"NLD ")`.
+#[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Tag(pub [u8; 4]);
+
+impl Tag
+{
+    /// The tag of the default script and language system.
+    pub const DEFAULT: Self = Self(*b"DFLT");
+}
+
+impl fmt::Debug for Tag
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "Tag({})", self)
+    }
+}
+
+impl fmt::Display for Tag
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        for &byte in &self.0 {
+            write!(f, "{}", byte as char)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+//! Building minimal font files for tests.
+
+use crate::Typeface;
+
+/// Serialize 16-bit values, as found in font tables.
+pub(crate) fn be(values: &[u16]) -> Vec<u8>
+{
+    values.iter().flat_map(|value| value.to_be_bytes().to_vec()).collect()
+}
+
+/// Serialize 32-bit values, as found in font tables.
+pub(crate) fn be32(values: &[u32]) -> Vec<u8>
+{
+    values.iter().flat_map(|value| value.to_be_bytes().to_vec()).collect()
+}
+
+/// Font file with the given tables, and with head, hhea, maxp, and hmtx
+/// tables for glyphs with the given advances and 1000 units per em.
+///
+/// Glyph offsets in the loca table are in the short format.
+/// Given tables replace the generated tables with the same tag.
+pub(crate) fn font_file(advances: &[u16], tables: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8>
+{
+    let glyph_count = advances.len() as u16;
+    let mut head = be(&[1, 0, 1, 0, 0, 0, 0x5F0F, 0x3CF5, 0, 1000]);
+    head.resize(54, 0);
+    let mut hhea = be(&[1, 0, 800, -200i16 as u16]);
+    hhea.resize(34, 0);
+    hhea.extend(be(&[glyph_count]));
+    let maxp = be(&[0, 0x5000, glyph_count]);
+    let hmtx = advances.iter().flat_map(|&advance| be(&[advance, 0])).collect();
+
+    let mut tables: Vec<(&[u8; 4], Vec<u8>)> =
+        vec![(b"head", head), (b"hhea", hhea), (b"maxp", maxp), (b"hmtx", hmtx)]
+        .into_iter()
+        .filter(|(tag, _)| tables.iter().all(|(given, _)| given != tag))
+        .chain(tables.iter().cloned())
+        .collect();
+    tables.sort_by_key(|&(tag, _)| *tag);
+
+    let mut file = be(&[1, 0, tables.len() as u16, 0, 0, 0]);
+    let mut offset = file.len() + 16 * tables.len();
+    for (tag, data) in &tables {
+        file.extend_from_slice(*tag);
+        for &value in &[0, offset, data.len()] {
+            file.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        offset += (data.len() + 3) & !3;
+    }
+    for (_, data) in &tables {
+        file.extend_from_slice(data);
+        file.resize((file.len() + 3) & !3, 0);
+    }
+    file
+}
+
+/// Parse a font file built by [`font_file`].
+pub(crate) fn typeface(font_file: &[u8]) -> Typeface<'_>
+{
+    Typeface::from_slice(font_file, 0).unwrap()
+}
@@ -1,8 +1,32 @@
 use crate::Glyph;
 use crate::Image;
+use crate::LineMetrics;
+use crate::Metrics;
+use crate::NamedInstance;
+use crate::Palette;
+use crate::ShapedGlyph;
+use crate::Result;
+use crate::ShapingOptions;
+use crate::Tag;
+use crate::VariationAxis;
+use crate::bitmap::glyph_bitmap;
+use crate::cmap::glyphs_for_str;
+use crate::cmap::mixed_coverage_glyph_index;
+use crate::color_glyph::color_layers;
+use crate::color_glyph::palettes;
+use crate::name::names;
+use crate::shape::shape_text;
+use crate::variation::axis_value;
+use crate::variation::instance_records;
+use crate::variation::normalized_coordinate;
+use crate::variation::remove_avar;
+use crate::variation::table_tags;
 
+use np_graphics::Bounds;
+use np_graphics::Color;
 use np_graphics::PathBuf;
 use np_graphics::Vector;
+use np_graphics::path::Instruction;
 
 /// Information about a typeface, including its glyphs.
 ///
@@ -12,27 +36,298 @@ use np_graphics::Vector;
 pub struct Typeface<'a>
 {
     pub (crate) inner: ttf_parser::Face<'a>,
+
+    /// The tags of the tables in the table directory of the typeface.
+    tables: Vec<ttf_parser::Tag>,
 }
 
 impl<'a> Typeface<'a>
 {
+    /// Parse the typeface at the given index of the data of a font file.
+    pub (crate) fn from_slice(data: &'a [u8], index: u32) -> Result<Self>
+    {
+        let inner = ttf_parser::Face::from_slice(data, index)?;
+        Ok(Self{inner, tables: table_tags(data, index)})
+    }
+
+    /// The glyph identifier of the `.notdef` glyph.
+    ///
+    /// Every typeface has this glyph, usually drawn as an empty box,
+    /// and it stands in for characters the typeface has no glyph for.
+    pub const NOTDEF: u16 = 0;
+
+    /// Glyph identifier for the given character.
+    ///
+    /// All Unicode subtables of the cmap table are searched.
+    /// If the typeface has no glyph for the character,
+    /// this function returns [`None`].
+    pub fn glyph_index(&self, c: char) -> Option<u16>
+    {
+        if let Some(glyph_id) = self.inner.glyph_index(c) {
+            return Some(glyph_id.0);
+        }
+        let cmap = self.inner.table_data(ttf_parser::Tag::from_bytes(b"cmap"))?;
+        mixed_coverage_glyph_index(cmap, c as u32)
+    }
+
+    /// Glyph identifier for the given character
+    /// followed by the given variation selector.
+    ///
+    /// Variation sequences are looked up in the format 14 cmap subtable.
+    /// If the typeface does not define the variation sequence,
+    /// or defines it to use the default glyph,
+    /// this function returns the same as [`Typeface::glyph_index`].
+    pub fn glyph_variation_index(&self, c: char, selector: char) -> Option<u16>
+    {
+        use ttf_parser::cmap::Format;
+        use ttf_parser::cmap::GlyphVariationResult;
+
+        let variation =
+            self.inner.character_mapping_subtables()
+            .filter(|subtable| subtable.format() == Format::UnicodeVariationSequences)
+            .find_map(|subtable| subtable.glyph_variation_index(c, selector));
+        match variation {
+            Some(GlyphVariationResult::Found(glyph_id)) => Some(glyph_id.0),
+            _ => self.glyph_index(c),
+        }
+    }
+
+    /// Glyph identifiers for the characters of a string.
+    ///
+    /// Characters followed by a variation selector
+    /// are looked up with [`Typeface::glyph_variation_index`],
+    /// and the variation selectors themselves get no glyph.
+    /// Characters the typeface has no glyph for get [`Typeface::NOTDEF`],
+    /// so that every string can be rendered.
+    pub fn glyphs_for_str(&self, text: &str) -> Vec<u16>
+    {
+        glyphs_for_str(text, |c, selector| match selector {
+            Some(selector) => self.glyph_variation_index(c, selector),
+            None => self.glyph_index(c),
+        })
+    }
+
+    /// Shape text into positioned glyphs.
+    ///
+    /// Unlike [`Typeface::glyphs_for_str`], this applies the substitutions
+    /// and positioning of the enabled OpenType features,
+    /// such as ligatures, kerning, and mark attachment.
+    /// The text is laid out in a single line, in the direction of the options,
+    /// and the glyphs are given from left to right.
+    /// Text with both directions is laid out by [`Paragraph::layout`](crate::Paragraph::layout).
+    pub fn shape(&self, text: &str, options: &ShapingOptions) -> Vec<ShapedGlyph>
+    {
+        shape_text(self, text, options)
+    }
+
+    /// Measurements of the typeface as a whole.
+    pub fn metrics(&self) -> Metrics
+    {
+        let face = &self.inner;
+        Metrics{
+            units_per_em: face.units_per_em().map_or(1000.0, f64::from),
+            ascender: face.ascender() as f64,
+            descender: face.descender() as f64,
+            line_gap: face.line_gap() as f64,
+            x_height: face.x_height().map(f64::from),
+            cap_height: face.capital_height().map(f64::from),
+            underline: face.underline_metrics().map(LineMetrics::from),
+            strikeout: face.strikeout_metrics().map(LineMetrics::from),
+            italic_angle: face.italic_angle().map_or(0.0, f64::from),
+        }
+    }
+
     /// Glyph with the given glyph identifier.
     ///
+    /// The outline and advances are those of the instance
+    /// chosen with [`Typeface::set_variations`].
+    /// Advances vary only if the typeface has an HVAR table,
+    /// and the left side bearing of a varied glyph is its leftmost extent.
+    ///
     /// If the glyph cannot be parsed,
     /// this function returns [`None`].
     pub fn glyph(&self, glyph_id: u16) -> Option<Glyph>
     {
         let glyph_id = ttf_parser::GlyphId(glyph_id);
         let advance_x = self.inner.glyph_hor_advance(glyph_id)? as f64;
-        let image = self.glyph_image(glyph_id)?;
-        Some(Glyph{advance_x, image})
+        let advance_y = self.inner.glyph_ver_advance(glyph_id).map(f64::from);
+        let (image, bounds) = self.glyph_image(glyph_id)?;
+        let left_side_bearing =
+            Some(glyph_id)
+            .filter(|_| !self.inner.has_non_default_variation_coordinates())
+            .and_then(|glyph_id| self.inner.glyph_hor_side_bearing(glyph_id))
+            .map(f64::from)
+            .unwrap_or(if bounds.is_empty() { 0.0 } else { bounds.min.x });
+        Some(Glyph{advance_x, advance_y, left_side_bearing, bounds, image})
+    }
+
+    /// Glyph with the given glyph identifier, for drawing at the given size.
+    ///
+    /// Glyphs that have a bitmap for exactly the given number of pixels per em,
+    /// and glyphs that have a bitmap but no outline, such as most emoji,
+    /// get the bitmap from the strike best suited to the size;
+    /// see [`Bitmap`](crate::Bitmap).
+    /// The bounds of bitmap glyphs are those of the bitmap, in font units.
+    /// Other glyphs are the same as those of [`Typeface::glyph`].
+    pub fn glyph_at_size(&self, glyph_id: u16, pixels_per_em: f64) -> Option<Glyph>
+    {
+        let glyph = self.glyph(glyph_id);
+        let has_outline = glyph.as_ref().map_or(false, |glyph| !glyph.bounds.is_empty());
+        let bitmap = match glyph_bitmap(&self.inner, glyph_id, pixels_per_em) {
+            Some(bitmap) if !has_outline || bitmap.pixels_per_em == pixels_per_em =>
+                bitmap,
+            _ => return glyph,
+        };
+
+        let id = ttf_parser::GlyphId(glyph_id);
+        let scale = self.metrics().units_per_em / bitmap.pixels_per_em;
+        let (width, height) = bitmap.pixels.extent();
+        let bounds = Bounds{
+            min: scale * Vector{x: bitmap.left, y: bitmap.top - height as f64},
+            max: scale * Vector{x: bitmap.left + width as f64, y: bitmap.top},
+        };
+        Some(Glyph{
+            advance_x: self.inner.glyph_hor_advance(id)? as f64,
+            advance_y: self.inner.glyph_ver_advance(id).map(f64::from),
+            left_side_bearing: bounds.min.x,
+            bounds,
+            image: Image::Bitmap(bitmap),
+        })
+    }
+
+    /// Color glyph with the given glyph identifier, drawn with a palette.
+    ///
+    /// Color glyphs, such as emoji, are made of layers of outlines
+    /// filled with colors and gradients from the COLR table.
+    /// The colors are given by their index in the palette,
+    /// which is usually one of [`Typeface::palettes`];
+    /// colors outside of the palette are transparent.
+    /// Some layers use the foreground color, such as that of the text.
+    /// If the typeface has no color glyph with the identifier,
+    /// this function returns [`None`],
+    /// and [`Typeface::glyph`] gives the glyph in a single color.
+    pub fn color_glyph(&self, glyph_id: u16, palette: &[Color], foreground: Color)
+        -> Option<Glyph>
+    {
+        let colr = self.inner.table_data(ttf_parser::Tag::from_bytes(b"COLR"))?;
+        let outline = |glyph_id| match self.glyph_image(ttf_parser::GlyphId(glyph_id))? {
+            (Image::Outline(outline), _) => Some(outline),
+            _ => None,
+        };
+        let layers = color_layers(colr, glyph_id, palette, foreground, outline)?;
+
+        let points =
+            layers.iter()
+            .flat_map(|layer| layer.outline.instructions())
+            .flat_map(|instruction| match instruction {
+                Instruction::Move(p0) | Instruction::Linear(p0) => vec![p0],
+                Instruction::Quadratic(p0, p1) => vec![p0, p1],
+                Instruction::Cubic(p0, p1, p2) => vec![p0, p1, p2],
+            });
+        let bounds = Bounds::from_points(points);
+        let id = ttf_parser::GlyphId(glyph_id);
+        Some(Glyph{
+            advance_x: self.inner.glyph_hor_advance(id)? as f64,
+            advance_y: self.inner.glyph_ver_advance(id).map(f64::from),
+            left_side_bearing: if bounds.is_empty() { 0.0 } else { bounds.min.x },
+            bounds,
+            image: Image::Layers(layers),
+        })
+    }
+
+    /// The palettes for color glyphs, from the CPAL table.
+    ///
+    /// The first palette is the default.
+    /// Typefaces without color glyphs have no palettes.
+    pub fn palettes(&self) -> Vec<Palette>
+    {
+        let cpal = self.inner.table_data(ttf_parser::Tag::from_bytes(b"CPAL"));
+        cpal.map(palettes).unwrap_or_default()
+    }
+
+    /// The axes along which the design of the typeface varies, from the fvar table.
+    ///
+    /// Typefaces that are not variable have no axes.
+    pub fn variation_axes(&self) -> Vec<VariationAxis>
+    {
+        self.inner.variation_axes()
+            .map(|axis| VariationAxis{
+                tag: Tag(axis.tag.to_bytes()),
+                name: names(&self.inner, axis.name_id).into_iter().next(),
+                min_value: axis.min_value as f64,
+                default_value: axis.def_value as f64,
+                max_value: axis.max_value as f64,
+                hidden: axis.hidden,
+            })
+            .collect()
+    }
+
+    /// The instances of the typeface that it gives names to, from the fvar table.
+    ///
+    /// To use a named instance, pass its coordinates to [`Typeface::set_variations`].
+    /// Typefaces that are not variable have no named instances.
+    pub fn named_instances(&self) -> Vec<NamedInstance>
+    {
+        let fvar = match self.inner.table_data(ttf_parser::Tag::from_bytes(b"fvar")) {
+            Some(fvar) => fvar,
+            None => return Vec::new(),
+        };
+        let tags: Vec<Tag> =
+            self.inner.variation_axes()
+            .map(|axis| Tag(axis.tag.to_bytes()))
+            .collect();
+        instance_records(fvar).into_iter()
+            .map(|record| NamedInstance{
+                name: names(&self.inner, record.subfamily_name_id).into_iter().next(),
+                coordinates: tags.iter().copied().zip(record.coordinates).collect(),
+            })
+            .collect()
+    }
+
+    /// Choose the instance of a variable typeface, by values on its axes.
+    ///
+    /// Axes that are not given take their default values,
+    /// and values outside of the range of an axis are clamped.
+    /// This affects the glyphs and metrics of the typeface,
+    /// but the positioning from the GPOS table during shaping
+    /// remains that of the default instance.
+    /// Typefaces that are not variable are not affected.
+    ///
+    /// Only the first 32 axes of a typeface can be varied;
+    /// values for axes after those are ignored.
+    pub fn set_variations(&mut self, variations: &[(Tag, f64)])
+    {
+        let avar = self.inner.table_data(ttf_parser::Tag::from_bytes(b"avar"));
+        if avar.is_some() {
+            remove_avar(&mut self.inner, &self.tables);
+        }
+
+        let axes: Vec<_> = self.inner.variation_axes().collect();
+        for (index, axis) in axes.iter().enumerate() {
+            let value =
+                variations.iter().rev()
+                .find(|(tag, _)| tag.0 == axis.tag.to_bytes())
+                .map_or(axis.def_value as f64, |&(_, value)| value);
+            let coordinate = normalized_coordinate(axis, index, avar, value);
+            self.inner.set_variation(axis.tag, axis_value(axis, coordinate));
+        }
     }
 
-    fn glyph_image(&self, glyph_id: ttf_parser::GlyphId) -> Option<Image>
+    fn glyph_image(&self, glyph_id: ttf_parser::GlyphId) -> Option<(Image, Bounds)>
     {
+        // Glyphs such as spaces have no outline,
+        // for which ttf-parser returns None as if parsing failed.
         let mut builder = OutlineBuilder::new();
-        self.inner.outline_glyph(glyph_id, &mut builder)?;
-        Some(Image::Outline(builder.path))
+        let rect = self.inner.outline_glyph(glyph_id, &mut builder);
+        let bounds = match rect {
+            Some(rect) => Bounds{
+                min: Vector{x: rect.x_min as f64, y: rect.y_min as f64},
+                max: Vector{x: rect.x_max as f64, y: rect.y_max as f64},
+            },
+            None if builder.path.words().is_empty() => Bounds::EMPTY,
+            None => return None,
+        };
+        Some((Image::Outline(builder.path), bounds))
     }
 }
 
@@ -0,0 +1,355 @@
+//! Variable typefaces, whose design varies along axes such as weight.
+//!
+//! The fvar table lists the axes and the named instances,
+//! and the avar table maps the positions on the axes nonlinearly.
+//! The variations themselves are applied by ttf-parser,
+//! to outlines from the gvar and CFF2 tables and advances from the HVAR table.
+
+use crate::Tag;
+use crate::parse::i16_at;
+use crate::parse::u16_at;
+use crate::parse::u32_at;
+
+use std::ops::DerefMut;
+
+/// Axis along which the design of a variable typeface varies.
+///
+/// To obtain the axes of a typeface, call [`Typeface::variation_axes`].
+///
+/// [`Typeface::variation_axes`]: `crate::Typeface::variation_axes`
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariationAxis
+{
+    /// The tag of the axis, such as `wght` for weight or `wdth` for width.
+    pub tag: Tag,
+
+    /// The name of the axis from the name table, preferably in English.
+    pub name: Option<String>,
+
+    /// The least value on the axis.
+    pub min_value: f64,
+
+    /// The value on the axis of the default instance.
+    pub default_value: f64,
+
+    /// The greatest value on the axis.
+    pub max_value: f64,
+
+    /// Whether the typeface asks for the axis not to be shown to users.
+    pub hidden: bool,
+}
+
+/// Instance of a variable typeface that the typeface gives a name to,
+/// such as "Bold" or "Light Condensed".
+///
+/// To obtain the named instances of a typeface,
+/// call [`Typeface::named_instances`].
+///
+/// [`Typeface::named_instances`]: `crate::Typeface::named_instances`
+#[derive(Clone, Debug, PartialEq)]
+pub struct NamedInstance
+{
+    /// The name of the instance from the name table, preferably in English.
+    pub name: Option<String>,
+
+    /// The value on each axis, in the order of the axes,
+    /// as accepted by [`Typeface::set_variations`].
+    ///
+    /// [`Typeface::set_variations`]: `crate::Typeface::set_variations`
+    pub coordinates: Vec<(Tag, f64)>,
+}
+
+/// Named instance as stored in the fvar table.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct InstanceRecord
+{
+    pub subfamily_name_id: u16,
+    pub coordinates: Vec<f64>,
+}
+
+/// Read the named instances of the fvar table.
+///
+/// Instances that are out of bounds are left out.
+pub(crate) fn instance_records(fvar: &[u8]) -> Vec<InstanceRecord>
+{
+    read_instance_records(fvar).unwrap_or_default()
+}
+
+fn read_instance_records(fvar: &[u8]) -> Option<Vec<InstanceRecord>>
+{
+    let axes_offset = u16_at(fvar, 4)? as usize;
+    let axis_count = u16_at(fvar, 8)? as usize;
+    let axis_size = u16_at(fvar, 10)? as usize;
+    let instance_count = u16_at(fvar, 12)? as usize;
+    let instance_size = u16_at(fvar, 14)? as usize;
+    if instance_size < 4 + 4 * axis_count {
+        return None;
+    }
+
+    let instances = axes_offset + axis_count * axis_size;
+    let records = (0 .. instance_count).filter_map(|index| {
+        let offset = instances + index * instance_size;
+        let coordinates = (0 .. axis_count)
+            .map(|axis| Some(u32_at(fvar, offset + 4 + 4 * axis)? as i32 as f64 / 65536.0))
+            .collect::<Option<_>>()?;
+        Some(InstanceRecord{subfamily_name_id: u16_at(fvar, offset)?, coordinates})
+    });
+    Some(records.collect())
+}
+
+/// The position on an axis in normalized coordinates,
+/// from -1 at its least value through 0 at its default to 1 at its greatest,
+/// in units of 1/16384 and mapped by the avar table, if any.
+pub(crate) fn normalized_coordinate(
+    axis: &ttf_parser::VariationAxis,
+    axis_index: usize,
+    avar: Option<&[u8]>,
+    value: f64,
+) -> i16
+{
+    let (min, default, max) =
+        (axis.min_value as f64, axis.def_value as f64, axis.max_value as f64);
+    let value = value.clamp(min, max);
+    let normalized =
+        if value < default { (value - default) / (default - min) }
+        else if value > default { (value - default) / (max - default) }
+        else { 0.0 };
+    let coordinate = (normalized * 16384.0).round() as i16;
+    avar.and_then(|avar| map_coordinate(avar, axis_index, coordinate)).unwrap_or(coordinate)
+}
+
+/// Map a normalized coordinate with the segment map of an axis in the avar table.
+fn map_coordinate(avar: &[u8], axis_index: usize, coordinate: i16) -> Option<i16>
+{
+    if u16_at(avar, 0)? != 1 || axis_index >= u16_at(avar, 6)? as usize {
+        return None;
+    }
+    let mut offset = 8;
+    for _ in 0 .. axis_index {
+        offset += 2 + 4 * u16_at(avar, offset)? as usize;
+    }
+
+    let count = u16_at(avar, offset)? as usize;
+    let pair = |index: usize| {
+        let from = i16_at(avar, offset + 2 + 4 * index)? as f64;
+        let to = i16_at(avar, offset + 4 + 4 * index)? as f64;
+        Some((from, to))
+    };
+    let value = coordinate as f64;
+
+    // Values outside of the map are shifted like the nearest end of the map.
+    let (first, last) = match count {
+        0 => return Some(coordinate),
+        _ => (pair(0)?, pair(count - 1)?),
+    };
+    let mapped = if value <= first.0 {
+        value - first.0 + first.1
+    } else if value >= last.0 {
+        value - last.0 + last.1
+    } else {
+        let mut index = 1;
+        while pair(index)?.0 < value {
+            index += 1;
+        }
+        let (start, end) = (pair(index - 1)?, pair(index)?);
+        match end.0 - start.0 {
+            width if width > 0.0 => start.1 + (value - start.0) * (end.1 - start.1) / width,
+            _ => end.1,
+        }
+    };
+    Some(mapped.round().clamp(-16384.0, 16384.0) as i16)
+}
+
+/// The value on an axis that ttf-parser normalizes to the coordinate.
+///
+/// ttf-parser truncates normalized coordinates to units of 1/16384,
+/// so the value is half a unit further from the default.
+pub(crate) fn axis_value(axis: &ttf_parser::VariationAxis, coordinate: i16) -> f32
+{
+    let (min, default, max) =
+        (axis.min_value as f64, axis.def_value as f64, axis.max_value as f64);
+    let normalized = (coordinate as f64 + 0.5 * (coordinate as f64).signum()) / 16384.0;
+    let value =
+        if coordinate < 0 { default + normalized * (default - min) }
+        else if coordinate > 0 { default + normalized * (max - default) }
+        else { default };
+    value as f32
+}
+
+/// The tags of the tables of the typeface at an index of a font file,
+/// in the order of its table directory.
+///
+/// If the table directory is out of bounds, there are no tags.
+pub(crate) fn table_tags(data: &[u8], index: u32) -> Vec<ttf_parser::Tag>
+{
+    read_table_tags(data, index).unwrap_or_default()
+}
+
+fn read_table_tags(data: &[u8], index: u32) -> Option<Vec<ttf_parser::Tag>>
+{
+    // Font collections start with the offsets of their typefaces.
+    let offset = match data.get(.. 4)? {
+        b"ttcf" => u32_at(data, 12 + 4 * index as usize)? as usize,
+        _ => 0,
+    };
+    let count = u16_at(data, offset + 4)? as usize;
+    (0 .. count)
+        .map(|record| Some(ttf_parser::Tag(u32_at(data, offset + 12 + 16 * record)?)))
+        .collect()
+}
+
+/// Remove the avar table from a face, resetting its variations.
+///
+/// ttf-parser 0.12 maps all coordinates with the avar table
+/// every time one of them is set, so coordinates set earlier are mapped again.
+/// Without the table, coordinates are mapped once by [`normalized_coordinate`].
+///
+/// The face is parsed again from the other tables in `tags`,
+/// which are those of [`table_tags`].
+pub(crate) fn remove_avar(face: &mut ttf_parser::Face, tags: &[ttf_parser::Tag])
+{
+    let avar = ttf_parser::Tag::from_bytes(b"avar");
+    let tables: Vec<_> =
+        tags.iter()
+        .filter(|&&tag| tag != avar)
+        .map(|&tag| Ok((tag, face.table_data(tag))))
+        .collect();
+    if let Ok(tables) = ttf_parser::FaceTables::from_table_provider(tables.into_iter()) {
+        *face.deref_mut() = tables;
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use crate::Typeface;
+    use crate::test_font::be;
+    use crate::test_font::font_file;
+    use crate::test_font::typeface;
+
+    #[test]
+    fn test_instance_records()
+    {
+        // Two axes of 20 bytes at offset 16,
+        // and two instances of 14 bytes with PostScript names.
+        let mut fvar = be(&[1, 0, 16, 2, 2, 20, 2, 14]);
+        fvar.extend(vec![0; 40]);
+        fvar.extend(be(&[256, 0, 400, 0, 100, 0x8000, 300]));
+        fvar.extend(be(&[257, 0, 700, 0, 0xFFFF, 0, 301]));
+
+        let regular = InstanceRecord{subfamily_name_id: 256, coordinates: vec![400.0, 100.5]};
+        let bold = InstanceRecord{subfamily_name_id: 257, coordinates: vec![700.0, -1.0]};
+        let records = instance_records(&fvar);
+        assert_eq!(records, [regular, bold]);
+        assert_eq!(instance_records(&fvar[.. fvar.len() - 4]), records[.. 1]);
+    }
+
+    #[test]
+    fn test_normalized_coordinate()
+    {
+        let axis = ttf_parser::VariationAxis{
+            tag: ttf_parser::Tag::from_bytes(b"wght"),
+            min_value: 100.0,
+            def_value: 400.0,
+            max_value: 900.0,
+            name_id: 256,
+            hidden: false,
+        };
+        let normalize = |avar, value| normalized_coordinate(&axis, 1, avar, value);
+        assert_eq!(normalize(None, 250.0), -8192);
+        assert_eq!(normalize(None, 400.0), 0);
+        assert_eq!(normalize(None, 650.0), 8192);
+        assert_eq!(normalize(None, 1000.0), 16384);
+
+        // The second axis maps 0.5 to 0.25.
+        let mut avar = be(&[1, 0, 0, 2, 0]);
+        avar.extend(be(&[4, 0xC000, 0xC000, 0, 0, 0x2000, 0x1000, 0x4000, 0x4000]));
+        assert_eq!(normalize(Some(&avar), 250.0), -8192);
+        assert_eq!(normalize(Some(&avar), 650.0), 4096);
+        assert_eq!(normalize(Some(&avar), 525.0), 2048);
+        assert_eq!(normalize(Some(&avar), 775.0), 10240);
+        assert_eq!(normalize(Some(&avar[.. 20]), 650.0), 8192);
+
+        // ttf-parser truncates the coordinates of these values
+        // to the same coordinates.
+        let value = |coordinate| axis_value(&axis, coordinate);
+        assert_eq!(value(0), 400.0);
+        assert!(value(8192) > 650.0 && value(8192) < 650.02);
+        assert!(value(-8192) < 250.0 && value(-8192) > 249.99);
+        assert!(value(16384) > 900.0);
+    }
+
+    #[test]
+    fn test_table_tags()
+    {
+        let data = font_file(&[50], &[(b"avar", be(&[1, 0, 0, 0]))]);
+        let tags = |data: &[u8], index| -> Vec<[u8; 4]> {
+            table_tags(data, index).iter().map(|tag| tag.to_bytes()).collect()
+        };
+        let expected = [*b"avar", *b"head", *b"hhea", *b"hmtx", *b"maxp"];
+        assert_eq!(tags(&data, 0), expected);
+
+        // A collection of two typefaces at the same offset.
+        let mut collection = b"ttcf".to_vec();
+        collection.extend(be(&[1, 0, 0, 2, 0, 20, 0, 20]));
+        collection.extend(&data);
+        assert_eq!(tags(&collection, 1), expected);
+        assert!(tags(&collection, 2).is_empty());
+        assert!(tags(&data[.. 40], 0).is_empty());
+    }
+
+    #[test]
+    fn test_variations()
+    {
+        // One weight axis from 100 through 400 to 900,
+        // with the avar table mapping 650 to a quarter of the way to 900.
+        let mut fvar = be(&[1, 0, 16, 2, 1, 20, 0, 8]);
+        fvar.extend_from_slice(b"wght");
+        fvar.extend(be(&[100, 0, 400, 0, 900, 0, 0, 256]));
+        let mut avar = be(&[1, 0, 0, 1]);
+        avar.extend(be(&[4, 0xC000, 0xC000, 0, 0, 0x2000, 0x1000, 0x4000, 0x4000]));
+
+        // Glyph 1 is a square of 50 units,
+        // whose right side and advance move 50 units to the right at 900.
+        let mut glyf = be(&[1, 0, 0, 50, 50, 3, 0]);
+        glyf.extend_from_slice(&[1, 1, 1, 1]);
+        glyf.extend(be(&[0, 50, 0, -50i16 as u16, 0, 0, 50, 0]));
+        let loca = be(&[0, 0, 17]);
+        let mut gvar = be(&[1, 0, 1, 0, 0, 0, 2, 0, 0, 26, 0, 0, 10]);
+        gvar.extend(be(&[1, 10, 10, 0x8000, 0x4000]));
+        gvar.extend_from_slice(&[7, 0, 50, 50, 0, 0, 50, 0, 0, 0x87]);
+        let mut hvar = be(&[1, 0, 0, 20, 0, 0, 0, 0, 0, 0]);
+        hvar.extend(be(&[1, 0, 12, 1, 0, 22, 1, 1, 0, 0x4000, 0x4000, 2, 0, 1, 0]));
+        hvar.extend_from_slice(&[0, 50]);
+
+        let data = font_file(&[50, 50], &[
+            (b"HVAR", hvar),
+            (b"avar", avar),
+            (b"fvar", fvar),
+            (b"glyf", glyf),
+            (b"gvar", gvar),
+            (b"loca", loca),
+        ]);
+        let mut typeface = typeface(&data);
+        let right = |typeface: &Typeface| {
+            let glyph = typeface.glyph(1).unwrap();
+            (glyph.bounds.max.x, glyph.advance_x)
+        };
+        assert_eq!(right(&typeface), (50.0, 50.0));
+
+        let weight = |value| [(Tag(*b"wght"), value)];
+        typeface.set_variations(&weight(900.0));
+        assert_eq!(right(&typeface), (100.0, 100.0));
+
+        // Setting the variations again does not map them twice.
+        for _ in 0 .. 2 {
+            typeface.set_variations(&weight(650.0));
+            assert_eq!(right(&typeface), (62.0, 63.0));
+        }
+
+        typeface.set_variations(&[]);
+        assert_eq!(right(&typeface), (50.0, 50.0));
+    }
+}
@@ -0,0 +1,239 @@
+#!/usr/bin/env python3
+"""Generate the Unicode property tables of np_text.
+
+Run this from the root of the repository:
+
+    python3 tools/unicode_tables.py
+
+The tables are derived from the files of the Unicode Character Database in tools/ucd,
+laid out as in the UCD.zip of the Unicode version in UNICODE_VERSION.
+To move to another version, replace the files and update UNICODE_VERSION.
+"""
+
+import os
+import re
+
+MAX_CODE_POINT = 0x10FFFF
+
+UNICODE_VERSION = '14.0.0'
+
+UCD = os.path.join(os.path.dirname(__file__), 'ucd')
+
+
+# --- Reading the UCD ----------------------------------------------------------
+
+def read_fields(path):
+    """The fields of each data line of a UCD file, without comments,
+    checking that the header of the file names UNICODE_VERSION."""
+    with open(os.path.join(UCD, path), encoding='utf-8') as file:
+        lines = file.read().splitlines()
+    data = [line.split('#')[0].strip() for line in lines]
+    header = lines[:[bool(line) for line in data].index(True)]
+    version = UNICODE_VERSION[:-2] if UNICODE_VERSION.endswith('.0') else UNICODE_VERSION
+    assert any(re.search(r'\b{}\b'.format(re.escape(version)), line) for line in header), path
+    return [[field.strip() for field in line.split(';')] for line in data if line]
+
+
+def code_points(field):
+    """The inclusive range of code points in a field such as 0000..001F."""
+    start, _, end = field.partition('..')
+    return int(start, 16), int(end or start, 16)
+
+
+def read_property(path, field=1):
+    """The value of a property for each code point, as listed in a UCD file,
+    with the defaults given by its @missing lines and None where there are none."""
+    values = [None] * (MAX_CODE_POINT + 1)
+    with open(os.path.join(UCD, path), encoding='utf-8') as file:
+        for line in file:
+            match = re.match(r'# @missing: ([0-9A-F.]+); (\S+)', line)
+            if match:
+                start, end = code_points(match.group(1))
+                values[start:end + 1] = [match.group(2)] * (end - start + 1)
+    for fields in read_fields(path):
+        start, end = code_points(fields[0])
+        values[start:end + 1] = [fields[field]] * (end - start + 1)
+    return values
+
+
+# --- Line breaking (UAX #14) -------------------------------------------------
+
+# The Rust name and the UCD name of each line breaking class,
+# with AI, SG and XX resolved to AL and CJ to NS as by rule LB1.
+LINE_BREAK = [
+    ('Bk', 'BK'), ('Cr', 'CR'), ('Lf', 'LF'), ('Cm', 'CM'), ('Nl', 'NL'),
+    ('Wj', 'WJ'), ('Zw', 'ZW'), ('Gl', 'GL'), ('Sp', 'SP'), ('Zwj', 'ZWJ'),
+    ('B2', 'B2'), ('Ba', 'BA'), ('Bb', 'BB'), ('Hy', 'HY'), ('Cb', 'CB'),
+    ('Cl', 'CL'), ('Cp', 'CP'), ('Ex', 'EX'), ('In', 'IN'), ('Ns', 'NS'),
+    ('Op', 'OP'), ('Qu', 'QU'), ('Is', 'IS'), ('Nu', 'NU'), ('Po', 'PO'),
+    ('Pr', 'PR'), ('Sy', 'SY'), ('Al', 'AL'), ('Eb', 'EB'), ('Em', 'EM'),
+    ('H2', 'H2'), ('H3', 'H3'), ('Hl', 'HL'), ('Id', 'ID'), ('Jl', 'JL'),
+    ('Jv', 'JV'), ('Jt', 'JT'), ('Ri', 'RI'),
+    ('Al', 'AI'), ('Al', 'SG'), ('Al', 'XX'), ('Ns', 'CJ'),
+]
+
+
+def line_break_table():
+    """Ranges of code points with a line breaking class other than AL.
+
+    SA is resolved to CM for combining marks and to AL otherwise, as by rule LB1,
+    since the line breaker does not use dictionaries.
+    """
+    names = {value: name for name, value in LINE_BREAK}
+    categories = read_property('extracted/DerivedGeneralCategory.txt')
+    classes = []
+    for value, category in zip(read_property('LineBreak.txt'), categories):
+        if value == 'SA':
+            classes.append('Cm' if category in ('Mn', 'Mc') else 'Al')
+        else:
+            classes.append(names[value])
+    return compress(classes, default='Al')
+
+
+# --- Bidirectional text (UAX #9) ---------------------------------------------
+
+def bidi_class_table():
+    """Ranges of code points with a bidirectional class other than L,
+    in the spelling of the Rust enum."""
+    values = read_property('extracted/DerivedBidiClass.txt')
+    classes = [value[0] + value[1:].lower() for value in values]
+    return compress(classes, default='L')
+
+
+def mirror_table():
+    """Pairs of characters whose glyphs mirror each other (Bidi_Mirroring_Glyph)."""
+    pairs = read_fields('BidiMirroring.txt')
+    return sorted((int(first, 16), int(second, 16)) for first, second in pairs)
+
+
+def bracket_table():
+    """Pairs of opening and closing brackets (Bidi_Paired_Bracket)."""
+    pairs = read_fields('BidiBrackets.txt')
+    return sorted(
+        (int(opening, 16), int(closing, 16))
+        for opening, closing, kind in pairs if kind == 'o'
+    )
+
+
+# --- Text segmentation (UAX #29) ---------------------------------------------
+
+# The Rust name and the UCD name of each value, with the values of Extended_Pictographic,
+# which extended pictographic characters all have in place of Other, last.
+GRAPHEME_CLUSTER_BREAK = [
+    ('Cr', 'CR'), ('Lf', 'LF'), ('Control', 'Control'), ('Extend', 'Extend'), ('Zwj', 'ZWJ'),
+    ('RegionalIndicator', 'Regional_Indicator'), ('Prepend', 'Prepend'),
+    ('SpacingMark', 'SpacingMark'), ('L', 'L'), ('V', 'V'), ('T', 'T'),
+    ('Lv', 'LV'), ('Lvt', 'LVT'), ('Other', 'Other'),
+    ('ExtendedPictographic', 'Extended_Pictographic'),
+]
+
+WORD_BREAK = [
+    ('Cr', 'CR'), ('Lf', 'LF'), ('Newline', 'Newline'), ('Extend', 'Extend'), ('Zwj', 'ZWJ'),
+    ('RegionalIndicator', 'Regional_Indicator'), ('Format', 'Format'),
+    ('Katakana', 'Katakana'), ('HebrewLetter', 'Hebrew_Letter'), ('ALetter', 'ALetter'),
+    ('SingleQuote', 'Single_Quote'), ('DoubleQuote', 'Double_Quote'),
+    ('MidNumLet', 'MidNumLet'), ('MidLetter', 'MidLetter'), ('MidNum', 'MidNum'),
+    ('Numeric', 'Numeric'), ('ExtendNumLet', 'ExtendNumLet'), ('WSegSpace', 'WSegSpace'),
+    ('Other', 'Other'),
+]
+
+SENTENCE_BREAK = [
+    ('Cr', 'CR'), ('Lf', 'LF'), ('Extend', 'Extend'), ('Sep', 'Sep'), ('Format', 'Format'),
+    ('Sp', 'Sp'), ('Lower', 'Lower'), ('Upper', 'Upper'), ('OLetter', 'OLetter'),
+    ('Numeric', 'Numeric'), ('ATerm', 'ATerm'), ('SContinue', 'SContinue'),
+    ('STerm', 'STerm'), ('Close', 'Close'), ('Other', 'Other'),
+]
+
+
+def segmentation_table(path, values, pictographic=False):
+    """Ranges of code points with a value other than Other,
+    given the UCD file and the Rust name and the UCD name of each value."""
+    names = dict((value, name) for name, value in values)
+    classes = [names[value] for value in read_property(path)]
+    if pictographic:
+        for cp, value in enumerate(read_property('emoji/emoji-data.txt')):
+            if value == 'Extended_Pictographic':
+                assert classes[cp] == 'Other', (hex(cp), classes[cp])
+                classes[cp] = names[value]
+    return compress(classes, default='Other')
+
+
+# --- Output -------------------------------------------------------------------
+
+def compress(values, default):
+    """Turn a value per code point into ranges of equal values,
+    leaving out those with the default value."""
+    result = []
+    start = 0
+    for cp in range(1, len(values) + 1):
+        if cp == len(values) or values[cp] != values[start]:
+            if values[start] != default:
+                result.append((start, cp - 1, values[start]))
+            start = cp
+    return result
+
+
+def write_entries(file, entries):
+    line = '   '
+    for entry in entries:
+        if len(line) + len(entry) + 2 > 90:
+            file.write(line + '\n')
+            line = '   '
+        line += ' ' + entry + ','
+    file.write(line + '\n')
+
+
+def write_tables(path, enum, tables):
+    """Write a module with tables of ranges of code points with a value,
+    given as (name, doc, ranges), and tables of pairs of code points,
+    given as (name, doc, pairs)."""
+    with open(path, 'w') as file:
+        file.write('// This file is generated by tools/unicode_tables.py\n')
+        file.write('// from the Unicode Character Database {}.\n'.format(UNICODE_VERSION))
+        file.write('// Do not edit it by hand.\n\n')
+        file.write('use super::{0};\nuse super::{0}::*;\n'.format(enum))
+        for name, doc, table in tables:
+            file.write('\n/// {}\n'.format(doc))
+            file.write('#[rustfmt::skip]\n')
+            if len(table[0]) == 3:
+                file.write('pub(super) const {}: &[(u32, u32, {})] = &[\n'.format(name, enum))
+                write_entries(file, ['(0x{:04X}, 0x{:04X}, {})'.format(*entry) for entry in table])
+            else:
+                file.write('pub(super) const {}: &[(char, char)] = &[\n'.format(name))
+                pair = "('\\u{{{:X}}}', '\\u{{{:X}}}')"
+                write_entries(file, [pair.format(*entry) for entry in table])
+            file.write('];\n')
+
+
+def main():
+    write_tables('np_text/src/line_break/tables.rs', 'Class', [
+        ('LINE_BREAK', 'Ranges of code points with a line breaking class other than AL.',
+            line_break_table()),
+    ])
+    write_tables('np_text/src/bidi/tables.rs', 'BidiClass', [
+        ('BIDI_CLASS', 'Ranges of code points with a bidirectional class other than L.',
+            bidi_class_table()),
+        ('MIRRORS', 'Pairs of characters whose glyphs mirror each other, sorted by the first.',
+            mirror_table()),
+        ('BRACKETS', 'Pairs of opening and closing brackets, sorted by the opening bracket.',
+            bracket_table()),
+    ])
+    write_tables('np_text/src/segment/grapheme/tables.rs', 'Class', [
+        ('GRAPHEME_CLUSTER_BREAK',
+            'Ranges of code points with a grapheme cluster break class other than Other.',
+            segmentation_table('auxiliary/GraphemeBreakProperty.txt', GRAPHEME_CLUSTER_BREAK,
+                pictographic=True)),
+    ])
+    write_tables('np_text/src/segment/word/tables.rs', 'Class', [
+        ('WORD_BREAK', 'Ranges of code points with a word break class other than Other.',
+            segmentation_table('auxiliary/WordBreakProperty.txt', WORD_BREAK)),
+    ])
+    write_tables('np_text/src/segment/sentence/tables.rs', 'Class', [
+        ('SENTENCE_BREAK',
+            'Ranges of code points with a sentence break class other than Other.',
+            segmentation_table('auxiliary/SentenceBreakProperty.txt', SENTENCE_BREAK)),
+    ])
+
+
+if __name__ == '__main__':
+    main()